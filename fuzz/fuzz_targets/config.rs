@@ -0,0 +1,40 @@
+#![no_main]
+
+use doclink_checker::{Config, LinkAnalyzer};
+use libfuzzer_sys::fuzz_target;
+use std::path::PathBuf;
+
+// The input is a `.doclink.toml`; every profile it defines is applied too.
+fuzz_target!(|data: &[u8]| {
+    let Ok(content) = std::str::from_utf8(data) else {
+        return;
+    };
+    let Ok(config) = Config::parse(content) else {
+        return;
+    };
+
+    let mut configs = vec![config.clone()];
+    configs.extend(
+        config
+            .profile
+            .keys()
+            .filter_map(|name| config.with_profile(name).ok()),
+    );
+    // Feed each value back in through the environment as well.
+    if let Ok(table) = toml::Table::try_from(&config) {
+        let vars = table.iter().map(|(key, value)| {
+            let value = match value {
+                toml::Value::String(text) => text.clone(),
+                other => other.to_string(),
+            };
+            (format!("DOCLINK_{}", key.to_uppercase()), value)
+        });
+        if let Ok(config) = config.apply_env(vars) {
+            configs.push(config);
+        }
+    }
+
+    for config in &configs {
+        let _ = LinkAnalyzer::with_config(PathBuf::from("docs"), config);
+    }
+});
@@ -0,0 +1,53 @@
+#![no_main]
+
+use doclink_checker::fix::{self, LinkStyle, PathStyle};
+use doclink_checker::vfs::MemoryFileSystem;
+use doclink_checker::{file_url_path, is_absolute_os_path, split_fragment, LinkAnalyzer, LinkKind};
+use libfuzzer_sys::fuzz_target;
+use std::path::{Path, PathBuf};
+
+// The input is both a document in a small tree and, line by line, a list of
+// link targets resolved from it.
+fuzz_target!(|data: &[u8]| {
+    let Ok(input) = std::str::from_utf8(data) else {
+        return;
+    };
+
+    let mut fs = MemoryFileSystem::new();
+    fs.insert("docs/README.md", input);
+    fs.insert(
+        "docs/guide.md",
+        "# Guide\n\n## Install\n\n<h2 id=\"setup\">Setup</h2>\n\n[Home](./README.md)\n",
+    );
+    fs.insert("docs/sub/page.md", "[Up](../guide.md#install)\n");
+
+    let mut analyzer = LinkAnalyzer::new(PathBuf::from("docs")).with_filesystem(fs);
+    if analyzer.analyze_directory().is_err() {
+        return;
+    }
+    analyzer.find_broken_links();
+    analyzer.find_orphaned_documents();
+    analyzer.suggested_fixes();
+    for style in [
+        PathStyle::DotPrefixed,
+        PathStyle::Plain,
+        PathStyle::RootRelative,
+    ] {
+        analyzer.normalize_fixes(style);
+    }
+    analyzer.anchor_fixes(|_| None);
+
+    let document = Path::new("docs/README.md");
+    for target in input.lines() {
+        LinkKind::classify(target);
+        split_fragment(target);
+        is_absolute_os_path(target);
+        file_url_path(target);
+        analyzer.resolve(document, target);
+        analyzer.resolve(Path::new("docs/sub/page.md"), target);
+    }
+
+    for style in [LinkStyle::Inline, LinkStyle::Reference] {
+        fix::convert_style(input, style);
+    }
+});
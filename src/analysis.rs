@@ -0,0 +1,91 @@
+use crate::graph::LinkGraph;
+use crate::{BrokenLink, MarkdownLink};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Immutable results of one analysis, taken with `LinkAnalyzer::snapshot`.
+/// Clones share the same data, so a snapshot can be handed to other threads
+/// while the analyzer keeps rescanning.
+#[derive(Debug, Clone)]
+pub struct Analysis {
+    inner: Arc<AnalysisData>,
+}
+
+#[derive(Debug)]
+struct AnalysisData {
+    base_path: PathBuf,
+    documents: BTreeMap<PathBuf, Vec<MarkdownLink>>,
+    graph: LinkGraph,
+    backlinks: BTreeMap<PathBuf, BTreeSet<PathBuf>>,
+    broken_links: Vec<BrokenLink>,
+    orphaned_documents: Vec<PathBuf>,
+}
+
+impl Analysis {
+    pub(crate) fn new(
+        base_path: PathBuf,
+        documents: BTreeMap<PathBuf, Vec<MarkdownLink>>,
+        graph: LinkGraph,
+        broken_links: Vec<BrokenLink>,
+        orphaned_documents: Vec<PathBuf>,
+    ) -> Self {
+        let mut backlinks: BTreeMap<PathBuf, BTreeSet<PathBuf>> = BTreeMap::new();
+        for (from, targets) in &graph.edges {
+            for to in targets {
+                backlinks
+                    .entry(to.clone())
+                    .or_default()
+                    .insert(from.clone());
+            }
+        }
+
+        Self {
+            inner: Arc::new(AnalysisData {
+                base_path,
+                documents,
+                graph,
+                backlinks,
+                broken_links,
+                orphaned_documents,
+            }),
+        }
+    }
+
+    pub fn base_path(&self) -> &Path {
+        &self.inner.base_path
+    }
+
+    pub fn documents(&self) -> impl Iterator<Item = &PathBuf> {
+        self.inner.documents.keys()
+    }
+
+    pub fn links(&self, document: &Path) -> &[MarkdownLink] {
+        self.inner
+            .documents
+            .get(document)
+            .map_or(&[], Vec::as_slice)
+    }
+
+    /// Documents linking to `document`.
+    pub fn backlinks(&self, document: &Path) -> impl Iterator<Item = &PathBuf> {
+        self.inner.backlinks.get(document).into_iter().flatten()
+    }
+
+    pub fn graph(&self) -> &LinkGraph {
+        &self.inner.graph
+    }
+
+    pub fn broken_links(&self) -> &[BrokenLink] {
+        &self.inner.broken_links
+    }
+
+    pub fn orphaned_documents(&self) -> &[PathBuf] {
+        &self.inner.orphaned_documents
+    }
+
+    /// Whether both values are clones of the same snapshot.
+    pub fn ptr_eq(&self, other: &Analysis) -> bool {
+        Arc::ptr_eq(&self.inner, &other.inner)
+    }
+}
@@ -0,0 +1,2616 @@
+#[cfg(feature = "git")]
+use crate::git::PathChange;
+use crate::gitignore::GitignoreRules;
+use crate::graph::LinkGraph;
+use crate::limits::ReadLimits;
+use crate::observer::AnalysisObserver;
+use crate::vfs::{FileSystem, FollowingFileSystem, OsFileSystem};
+use crate::{
+    anchors, compile_directive, front_matter, include, normalize_path, split_fragment, Analysis,
+    CancellationToken, Cancelled, Config, Flavor, LinkKind, MarkdownLink, Resolution,
+    UnicodeNormalization,
+};
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use regex::Regex;
+#[cfg(feature = "git")]
+use std::collections::BTreeSet;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// File names of the documents at the top of a tree readers start from: a
+/// README, a static site's index, a GitHub wiki's home page and an mdBook
+/// table of contents.
+const DEFAULT_ROOT_NAMES: [&str; 5] = [
+    "README.md",
+    "readme.md",
+    "index.md",
+    "Home.md",
+    "SUMMARY.md",
+];
+
+#[derive(Debug)]
+pub struct LinkAnalyzer {
+    pub(crate) documents: HashMap<PathBuf, Vec<MarkdownLink>>,
+    pub(crate) commented: HashMap<PathBuf, Vec<MarkdownLink>>,
+    /// In an Azure DevOps wiki, the pages each `.order` file lists, as links.
+    pub(crate) order_files: HashMap<PathBuf, Vec<MarkdownLink>>,
+    /// `document_key` of every link target to where it is linked from.
+    pub(crate) inbound: HashMap<PathBuf, Vec<InboundLink>>,
+    pub(crate) base_path: PathBuf,
+    pub(crate) assumed_present: GlobSet,
+    pub(crate) exclude: GlobSet,
+    pub(crate) entry_points: Vec<PathBuf>,
+    pub(crate) root_names: Vec<String>,
+    pub(crate) root_globs: GlobSet,
+    pub(crate) base_url: String,
+    pub(crate) extensionless_candidates: Vec<String>,
+    pub(crate) flavor: Flavor,
+    pub(crate) wiki_links: bool,
+    pub(crate) code_links: bool,
+    pub(crate) heading_styles: anchors::HeadingStyles,
+    pub(crate) slugger: anchors::Slugger,
+    pub(crate) version_anchors: bool,
+    pub(crate) comment_directives: Vec<Regex>,
+    pub(crate) ignore_targets: Vec<Regex>,
+    pub(crate) sequences: BTreeMap<String, Vec<String>>,
+    /// Number of links skipped by `ignore_targets`, per analyzed document.
+    pub(crate) ignored_targets: HashMap<PathBuf, usize>,
+    /// Lowercased file name to every file with that name, shortest path
+    /// first, for wiki links and moved-file suggestions.
+    pub(crate) file_index: HashMap<String, Vec<PathBuf>>,
+    /// Headings of the scanned documents and of link targets checked since.
+    pub(crate) headings: anchors::HeadingIndex,
+    /// Titles of the scanned documents that have one.
+    pub(crate) titles: HashMap<PathBuf, String>,
+    /// Front matter fields of the scanned documents that have any.
+    pub(crate) front_matter: HashMap<PathBuf, BTreeMap<String, String>>,
+    pub(crate) follow_symlinks: bool,
+    /// Skip what `.gitignore` files list while walking.
+    pub(crate) gitignore: bool,
+    /// Other paths the last scan reached each file under, through symlinks.
+    pub(crate) aliases: HashMap<PathBuf, Vec<PathBuf>>,
+    /// The document each of those paths, normalized, is an alias of.
+    pub(crate) alias_of: HashMap<PathBuf, PathBuf>,
+    pub(crate) unicode_normalization: UnicodeNormalization,
+    /// Walked paths, normalized, by their `unicode_normalization` form, for
+    /// those the two differ.
+    pub(crate) spellings: HashMap<PathBuf, PathBuf>,
+    pub(crate) read_limits: ReadLimits,
+    /// Documents the last scan skipped as too large or binary, with why.
+    pub(crate) skipped_files: Vec<(PathBuf, String)>,
+    pub(crate) fs: Box<dyn FileSystem>,
+    pub(crate) observers: Vec<Box<dyn AnalysisObserver>>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct BrokenLink {
+    pub link: MarkdownLink,
+    pub reason: String,
+    /// The existing file the link most likely meant: one with the same name
+    /// elsewhere, or a similar one.
+    pub suggestion: Option<PathBuf>,
+}
+
+impl BrokenLink {
+    /// A finding for `link` without a suggestion.
+    pub fn new(link: MarkdownLink, reason: impl Into<String>) -> Self {
+        Self {
+            link,
+            reason: reason.into(),
+            suggestion: None,
+        }
+    }
+}
+
+/// Where an analyzed document links to a file, from `LinkAnalyzer::inbound_links`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InboundLink {
+    pub source: PathBuf,
+    pub line_number: usize,
+}
+
+/// What a broken link is missing. Links sharing one are usually fixed together.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[non_exhaustive]
+pub enum RootCause {
+    /// The outermost missing directory under the analyzed root, e.g. after a
+    /// whole section was removed or renamed.
+    MissingDirectory(PathBuf),
+    MissingFile(PathBuf),
+    MissingAnchor {
+        document: PathBuf,
+        anchor: String,
+    },
+    /// Anything else, such as external links, grouped by their reason.
+    Other(String),
+}
+
+/// Broken links with the same root cause.
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct CauseGroup {
+    pub cause: RootCause,
+    pub broken_links: Vec<BrokenLink>,
+}
+
+/// Broken links in documents sharing a value of a front matter field, such
+/// as one `owner`.
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct FieldGroup {
+    /// `None` for documents without the field.
+    pub value: Option<String>,
+    pub broken_links: Vec<BrokenLink>,
+}
+
+/// Caps on how many broken links are collected, to keep reports on
+/// pathological documents bounded.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct FindingLimits {
+    pub per_file: Option<usize>,
+    pub total: Option<usize>,
+}
+
+/// Broken links collected under `FindingLimits`.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[non_exhaustive]
+pub struct LimitedFindings {
+    /// Ordered by document and line.
+    pub broken_links: Vec<BrokenLink>,
+    /// Documents with more broken links than `per_file` allows.
+    pub truncated_files: Vec<PathBuf>,
+    /// Collection stopped early because `total` was exceeded.
+    pub aborted: bool,
+    /// Collection stopped early because the cancellation token fired.
+    pub cancelled: bool,
+}
+
+impl FindingLimits {
+    pub fn is_unlimited(&self) -> bool {
+        self.per_file.is_none() && self.total.is_none()
+    }
+
+    /// Applies the limits to findings that were collected without them.
+    pub fn apply(&self, mut broken_links: Vec<BrokenLink>) -> LimitedFindings {
+        broken_links.sort_by(|a, b| {
+            (&a.link.file_path, a.link.line_number).cmp(&(&b.link.file_path, b.link.line_number))
+        });
+
+        let mut findings = LimitedFindings::default();
+        let mut in_file = 0;
+        for broken_link in broken_links {
+            if findings
+                .broken_links
+                .last()
+                .map(|last| &last.link.file_path)
+                != Some(&broken_link.link.file_path)
+            {
+                in_file = 0;
+            }
+            if !findings.push(broken_link, &mut in_file, self) {
+                break;
+            }
+        }
+        findings
+    }
+}
+
+impl LimitedFindings {
+    /// Records a finding that is the `in_file`-th of its document, returning
+    /// `false` once the total limit is exceeded and collection should stop.
+    pub(crate) fn push(
+        &mut self,
+        broken_link: BrokenLink,
+        in_file: &mut usize,
+        limits: &FindingLimits,
+    ) -> bool {
+        if limits
+            .total
+            .is_some_and(|total| self.broken_links.len() >= total)
+        {
+            self.aborted = true;
+            return false;
+        }
+        *in_file += 1;
+        if limits.per_file.is_some_and(|per_file| *in_file > per_file) {
+            if self.truncated_files.last() != Some(&broken_link.link.file_path) {
+                self.truncated_files.push(broken_link.link.file_path);
+            }
+            return true;
+        }
+        self.broken_links.push(broken_link);
+        true
+    }
+}
+
+impl LinkAnalyzer {
+    pub fn new(base_path: PathBuf) -> Self {
+        Self {
+            documents: HashMap::new(),
+            commented: HashMap::new(),
+            order_files: HashMap::new(),
+            inbound: HashMap::new(),
+            base_path,
+            assumed_present: GlobSet::empty(),
+            exclude: GlobSet::empty(),
+            entry_points: Vec::new(),
+            root_names: DEFAULT_ROOT_NAMES.map(str::to_string).to_vec(),
+            root_globs: GlobSet::empty(),
+            base_url: String::new(),
+            extensionless_candidates: Vec::new(),
+            flavor: Flavor::default(),
+            wiki_links: false,
+            code_links: false,
+            heading_styles: anchors::HeadingStyles::default(),
+            slugger: anchors::Slugger::default(),
+            version_anchors: false,
+            comment_directives: Vec::new(),
+            ignore_targets: Vec::new(),
+            sequences: BTreeMap::new(),
+            ignored_targets: HashMap::new(),
+            file_index: HashMap::new(),
+            headings: anchors::HeadingIndex::default(),
+            titles: HashMap::new(),
+            front_matter: HashMap::new(),
+            follow_symlinks: false,
+            gitignore: true,
+            aliases: HashMap::new(),
+            alias_of: HashMap::new(),
+            unicode_normalization: UnicodeNormalization::default(),
+            spellings: HashMap::new(),
+            read_limits: ReadLimits::default(),
+            skipped_files: Vec::new(),
+            fs: Box::new(OsFileSystem),
+            observers: Vec::new(),
+        }
+    }
+
+    pub fn with_filesystem(mut self, fs: impl FileSystem + 'static) -> Self {
+        self.fs = Box::new(fs);
+        self
+    }
+
+    pub fn with_observer(mut self, observer: impl AnalysisObserver + 'static) -> Self {
+        self.observers.push(Box::new(observer));
+        self
+    }
+
+    pub fn with_config(
+        base_path: PathBuf,
+        config: &Config,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        let mut builder = GlobSetBuilder::new();
+        for pattern in &config.assume_present {
+            builder.add(Glob::new(pattern)?);
+        }
+
+        let mut exclude = GlobSetBuilder::new();
+        for pattern in &config.exclude {
+            exclude.add(Glob::new(pattern)?);
+            // `vendor/**` also names `vendor` itself, so it isn't walked at all.
+            if let Some(dir) = pattern.strip_suffix("/**") {
+                exclude.add(Glob::new(dir)?);
+            }
+        }
+
+        let flavor = Flavor::parse(&config.flavor)?;
+
+        let mut root_globs = GlobSetBuilder::new();
+        for pattern in &config.root_globs {
+            root_globs.add(Glob::new(pattern)?);
+        }
+
+        let comment_directives = config
+            .comment_directives
+            .iter()
+            .map(|pattern| compile_directive(pattern))
+            .collect::<Result<_, _>>()?;
+
+        let ignore_targets = config
+            .ignore_targets
+            .iter()
+            .map(|pattern| {
+                Regex::new(pattern)
+                    .map_err(|e| format!("Invalid `ignore_targets` pattern `{}`: {}", pattern, e))
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self {
+            assumed_present: builder.build()?,
+            exclude: exclude.build()?,
+            entry_points: config.entry_points.iter().map(PathBuf::from).collect(),
+            root_names: if config.root_names.is_empty() {
+                DEFAULT_ROOT_NAMES.map(str::to_string).to_vec()
+            } else {
+                config.root_names.clone()
+            },
+            root_globs: root_globs.build()?,
+            comment_directives,
+            ignore_targets,
+            base_url: config.base_url.trim_end_matches('/').to_string(),
+            extensionless_candidates: config.extensionless_candidates.clone(),
+            flavor,
+            wiki_links: config.wiki_links || flavor == Flavor::GithubWiki,
+            code_links: config.code_links,
+            heading_styles: anchors::HeadingStyles::parse(&config.heading_styles)?,
+            slugger: anchors::Slugger::parse(&config.slugger)?,
+            version_anchors: config.version_anchors,
+            sequences: config.sequences.clone(),
+            read_limits: ReadLimits::from_config(config),
+            follow_symlinks: config.follow_symlinks,
+            gitignore: !config.no_gitignore,
+            unicode_normalization: UnicodeNormalization::parse(&config.unicode_normalization)?,
+            fs: if config.follow_symlinks {
+                Box::new(FollowingFileSystem)
+            } else {
+                Box::new(OsFileSystem)
+            },
+            ..Self::new(base_path)
+        })
+    }
+
+    pub fn analyze_directory(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        self.analyze_directory_with(&CancellationToken::new())
+    }
+
+    /// Like `analyze_directory`, but checks `cancel` before each document and
+    /// fails with `Cancelled` once it is cancelled. Documents scanned until
+    /// then stay in the analyzer.
+    pub fn analyze_directory_with(
+        &mut self,
+        cancel: &CancellationToken,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        cancel.check()?;
+        let gitignore = self
+            .gitignore
+            .then(|| GitignoreRules::new(self.fs.as_ref(), &self.base_path));
+        let mut files = self.fs.files_skipping(&self.base_path, &|path, is_dir| {
+            self.is_excluded(path)
+                || gitignore
+                    .as_ref()
+                    .is_some_and(|rules| rules.is_ignored(path, is_dir))
+        })?;
+        self.ignored_targets.clear();
+        self.skipped_files.clear();
+        self.aliases.clear();
+        self.alias_of.clear();
+        if self.follow_symlinks {
+            // A file reached under several paths is analyzed once, under the
+            // path it really has if that was walked, else the shortest one.
+            let base = self.fs.canonicalize(&self.base_path).ok();
+            let mut walked: Vec<(PathBuf, Option<PathBuf>)> = files
+                .into_iter()
+                .map(|path| {
+                    let canonical = self.fs.canonicalize(&path).ok();
+                    (path, canonical)
+                })
+                .collect();
+            walked.sort_by_key(|(path, canonical)| {
+                let physical =
+                    base.as_ref()
+                        .zip(canonical.as_ref())
+                        .is_some_and(|(base, canonical)| {
+                            path.strip_prefix(&self.base_path).is_ok_and(|relative| {
+                                normalize_path(&base.join(relative)) == *canonical
+                            })
+                        });
+                (!physical, path.components().count(), path.clone())
+            });
+
+            let mut primaries: HashMap<PathBuf, PathBuf> = HashMap::new();
+            files = Vec::new();
+            for (path, canonical) in walked {
+                let Some(canonical) = canonical else {
+                    files.push(path);
+                    continue;
+                };
+                match primaries.get(&canonical) {
+                    Some(primary) => {
+                        self.alias_of.insert(normalize_path(&path), primary.clone());
+                        self.aliases.entry(primary.clone()).or_default().push(path);
+                    }
+                    None => {
+                        primaries.insert(canonical, path.clone());
+                        files.push(path);
+                    }
+                }
+            }
+        }
+        self.spellings.clear();
+        if self.unicode_normalization != UnicodeNormalization::None {
+            for path in files.iter().chain(self.aliases.values().flatten()) {
+                let normalized = normalize_path(path);
+                let folded = self.unicode_normalization.apply_path(&normalized);
+                if folded != normalized {
+                    self.spellings.insert(folded, normalized);
+                }
+            }
+        }
+        self.documents.clear();
+        self.commented.clear();
+        self.inbound.clear();
+        self.file_index.clear();
+        self.headings.clear();
+        self.titles.clear();
+        self.front_matter.clear();
+        self.order_files.clear();
+        for path in &files {
+            if let Some(name) = self.index_name(path) {
+                self.file_index.entry(name).or_default().push(path.clone());
+            }
+        }
+        for paths in self.file_index.values_mut() {
+            paths.sort_by_key(|path| (path.components().count(), path.clone()));
+        }
+
+        let mut total_bytes = 0;
+        for path in files {
+            let path = path.as_path();
+
+            if path.extension().and_then(|s| s.to_str()) == Some("md") {
+                cancel.check()?;
+                let stamp = self.file_stamp(path);
+                let content = match self.read_document(path) {
+                    Ok(content) => content,
+                    Err(e) if e.kind() == io::ErrorKind::InvalidData => {
+                        self.skipped_files.push((path.to_path_buf(), e.to_string()));
+                        continue;
+                    }
+                    Err(e) => return Err(e.into()),
+                };
+                total_bytes += content.len() as u64;
+                if let Some(max) = self
+                    .read_limits
+                    .max_total_bytes
+                    .filter(|max| total_bytes > *max)
+                {
+                    return Err(format!(
+                        "Documents add up to more than max_total_bytes ({}) at {}",
+                        max,
+                        path.display()
+                    )
+                    .into());
+                }
+                self.index_document(path, &content, stamp);
+            } else if self.is_order_file(path) {
+                let content = self.read_document(path)?;
+                self.index_order_file(path, &content);
+            }
+        }
+        Ok(())
+    }
+
+    /// Lowercased, normalized file name of `path` in `file_index`.
+    fn index_name(&self, path: &Path) -> Option<String> {
+        let name = path.file_name()?.to_string_lossy();
+        Some(self.unicode_normalization.apply(&name).to_lowercase())
+    }
+
+    fn is_order_file(&self, path: &Path) -> bool {
+        self.flavor == Flavor::AzureDevopsWiki
+            && path.file_name().is_some_and(|name| name == ".order")
+    }
+
+    fn index_order_file(&mut self, path: &Path, content: &str) {
+        let entries = content
+            .lines()
+            .enumerate()
+            .map(|(index, line)| (index + 1, line.trim()))
+            .filter(|(_, page)| !page.is_empty())
+            .map(|(line_number, page)| MarkdownLink {
+                text: page.to_string(),
+                target: page.to_string(),
+                line_number,
+                file_path: path.to_path_buf(),
+            })
+            .collect();
+        self.order_files.insert(path.to_path_buf(), entries);
+    }
+
+    /// Re-reads one document, e.g. after an editor saved it, replacing its
+    /// links and headings from the last scan. Other documents are not read,
+    /// so with a heading index from `load_heading_index` its links can be
+    /// checked without scanning the tree.
+    pub fn analyze_document(&mut self, path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+        let stamp = self.file_stamp(path);
+        let content = self.read_document(path)?;
+        self.index_document(path, &content, stamp);
+        Ok(())
+    }
+
+    /// Brings the analysis up to date after the files at `paths`, as walked
+    /// from the base path, were created, changed or removed. Only those files,
+    /// and the files under changed directories, are read. Returns the
+    /// documents whose findings may have changed, sorted: the changed
+    /// documents and every document linking to a changed file.
+    pub fn reanalyze(
+        &mut self,
+        paths: &[PathBuf],
+    ) -> Result<Vec<PathBuf>, Box<dyn std::error::Error>> {
+        let gitignore = self
+            .gitignore
+            .then(|| GitignoreRules::new(self.fs.as_ref(), &self.base_path));
+        let walked = |path: &Path| {
+            let relative = path.strip_prefix(&self.base_path).unwrap_or(path);
+            !relative
+                .ancestors()
+                .filter(|ancestor| !ancestor.as_os_str().is_empty())
+                .any(|ancestor| {
+                    let path = self.base_path.join(ancestor);
+                    self.is_excluded(&path)
+                        || gitignore
+                            .as_ref()
+                            .is_some_and(|rules| rules.is_ignored(&path, ancestor != relative))
+                })
+        };
+        // A directory moved in or out changes every file under it.
+        let mut expanded = Vec::new();
+        for path in paths {
+            expanded.push(path.clone());
+            expanded.extend(self.fs.files(path).unwrap_or_default());
+            expanded.extend(
+                self.documents
+                    .keys()
+                    .chain(self.order_files.keys())
+                    .filter(|known| known.starts_with(path))
+                    .cloned(),
+            );
+        }
+        expanded.sort();
+        expanded.dedup();
+        let paths: Vec<&PathBuf> = expanded.iter().filter(|path| walked(path)).collect();
+
+        let mut affected = Vec::new();
+        for path in paths {
+            let exists = self.fs.exists(path);
+            if let Some(name) = self.index_name(path) {
+                let indexed = self.file_index.entry(name).or_default();
+                indexed.retain(|indexed| indexed != path);
+                if exists {
+                    indexed.push(path.clone());
+                    indexed.sort_by_key(|path| (path.components().count(), path.clone()));
+                }
+            }
+
+            if path.extension().and_then(|s| s.to_str()) == Some("md") {
+                self.skipped_files.retain(|(skipped, _)| skipped != path);
+                match self.read_document(path) {
+                    Ok(content) => {
+                        let stamp = self.file_stamp(path);
+                        self.index_document(path, &content, stamp);
+                        affected.push(path.clone());
+                    }
+                    Err(e) if e.kind() == io::ErrorKind::NotFound => self.remove_document(path),
+                    Err(e) if e.kind() == io::ErrorKind::InvalidData => {
+                        self.remove_document(path);
+                        self.skipped_files.push((path.clone(), e.to_string()));
+                    }
+                    Err(e) => return Err(e.into()),
+                }
+            } else if self.is_order_file(path) {
+                match self.read_document(path) {
+                    Ok(content) => {
+                        self.index_order_file(path, &content);
+                        affected.push(path.clone());
+                    }
+                    Err(e) if e.kind() == io::ErrorKind::NotFound => {
+                        self.order_files.remove(path);
+                    }
+                    Err(e) => return Err(e.into()),
+                }
+            }
+            affected.extend(
+                self.inbound_links(path)
+                    .iter()
+                    .map(|inbound| inbound.source.clone()),
+            );
+        }
+        affected.sort();
+        affected.dedup();
+        Ok(affected)
+    }
+
+    /// Forgets the document at `path`, e.g. after it was deleted. Links to
+    /// it stay indexed, now as links to a missing file.
+    pub fn remove_document(&mut self, path: &Path) {
+        self.documents.remove(path);
+        self.commented.remove(path);
+        self.ignored_targets.remove(path);
+        self.titles.remove(path);
+        self.front_matter.remove(path);
+        self.headings.remove(&self.document_key(path));
+        for sources in self.inbound.values_mut() {
+            sources.retain(|inbound| inbound.source != path);
+        }
+    }
+
+    /// Broken links in the analyzed document at `path`.
+    pub fn find_broken_links_in(&self, path: &Path) -> Vec<BrokenLink> {
+        let Some(links) = self
+            .documents
+            .get(path)
+            .or_else(|| self.order_files.get(path))
+        else {
+            return Vec::new();
+        };
+        links
+            .iter()
+            .filter_map(|link| self.broken_link(link, self.resolve(path, &link.target)))
+            .collect()
+    }
+
+    /// `link` as a broken link if `resolution` failed, with the file its
+    /// suggested replacement points to, under the base path as walked.
+    fn broken_link(&self, link: &MarkdownLink, resolution: Resolution) -> Option<BrokenLink> {
+        let suggestion = resolution.suggestion.map(|target| {
+            let (path, _) = split_fragment(&target);
+            let path = normalize_path(&self.document_dir(&link.file_path).join(path));
+            match path.strip_prefix(normalize_path(&self.base_path)) {
+                Ok(relative) => self.base_path.join(relative),
+                Err(_) => path,
+            }
+        });
+        Some(BrokenLink {
+            link: link.clone(),
+            reason: resolution.error?,
+            suggestion,
+        })
+    }
+
+    /// Saves the heading index of the last scan and of the link targets
+    /// checked since, for `load_heading_index` in a later process.
+    pub fn save_heading_index(&self, path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+        self.headings.save(path, self.heading_styles, self.slugger)
+    }
+
+    /// Loads a heading index saved by `save_heading_index`, e.g. when an
+    /// editor restarts, so anchors can be checked without parsing the tree
+    /// again. Saved headings are only used while their file's size and
+    /// modification time are unchanged; others are parsed afresh. The next
+    /// `analyze_directory` replaces them all. Returns how many documents were
+    /// loaded: none if `path` is missing, unreadable, or was saved with other
+    /// `heading_styles` or `slugger`.
+    pub fn load_heading_index(&mut self, path: &Path) -> usize {
+        self.headings.load(path, self.heading_styles, self.slugger)
+    }
+
+    /// Records the links, commented-out links and headings of a document
+    /// read with `stamp`.
+    fn index_document(&mut self, path: &Path, content: &str, stamp: Option<anchors::FileStamp>) {
+        let key = self.document_key(path);
+        let headings =
+            anchors::DocumentHeadings::parse_with(content, self.heading_styles, self.slugger);
+        let fields = front_matter::fields(content);
+        match anchors::document_title(&fields, &headings.headings) {
+            Some(title) => self.titles.insert(path.to_path_buf(), title),
+            None => self.titles.remove(path),
+        };
+        if fields.is_empty() {
+            self.front_matter.remove(path);
+        } else {
+            self.front_matter.insert(path.to_path_buf(), fields);
+        }
+        self.headings.insert(key, headings, stamp);
+        let mut links = Self::extract_links(content);
+        if self.wiki_links {
+            links.extend(Self::extract_wiki_links(content));
+            if self.code_links {
+                links.extend(Self::extract_code_wiki_links(content));
+            }
+        }
+        if self.code_links {
+            links.extend(Self::extract_code_links(content));
+        }
+        links.extend(
+            include::extract_directives(content)
+                .into_iter()
+                .map(|(directive, line)| (directive.clone(), directive, line)),
+        );
+        if !self.comment_directives.is_empty() {
+            links.extend(Self::extract_directive_links(
+                content,
+                &self.comment_directives,
+            ));
+        }
+        links.sort_by_key(|(_, _, line_number)| *line_number);
+        let before = links.len();
+        links.retain(|(_, target, _)| !self.is_ignored_target(target));
+        self.ignored_targets
+            .insert(path.to_path_buf(), before - links.len());
+
+        let markdown_links: Vec<MarkdownLink> = links
+            .into_iter()
+            .map(|(text, target, line_number)| MarkdownLink {
+                text,
+                target,
+                line_number,
+                file_path: path.to_path_buf(),
+            })
+            .collect();
+
+        let commented_links = Self::extract_commented_links(content)
+            .into_iter()
+            .filter(|(_, target, _)| !self.is_ignored_target(target))
+            .map(|(text, target, line_number)| MarkdownLink {
+                text,
+                target,
+                line_number,
+                file_path: path.to_path_buf(),
+            })
+            .collect();
+        self.commented.insert(path.to_path_buf(), commented_links);
+
+        for observer in &self.observers {
+            for link in &markdown_links {
+                observer.on_link_found(link);
+            }
+            observer.on_file_scanned(path, &markdown_links);
+        }
+
+        if self.documents.contains_key(path) {
+            for sources in self.inbound.values_mut() {
+                sources.retain(|inbound| inbound.source != path);
+            }
+        }
+        for link in &markdown_links {
+            if let Some(key) = self.link_key(path, &link.target) {
+                self.inbound.entry(key).or_default().push(InboundLink {
+                    source: path.to_path_buf(),
+                    line_number: link.line_number,
+                });
+            }
+        }
+        self.documents.insert(path.to_path_buf(), markdown_links);
+    }
+
+    /// What a link points at in the inbound index: the file it resolves to,
+    /// else where it would be. `None` for same-document links and for
+    /// external links outside `base_url`.
+    fn link_key(&self, file_path: &Path, target: &str) -> Option<PathBuf> {
+        match LinkKind::classify(target) {
+            LinkKind::SameDocument => None,
+            LinkKind::Internal => {
+                // Without the fragment, so no headings are read mid-scan.
+                let (path, _) = split_fragment(target);
+                self.resolve(file_path, path).resolved.or_else(|| {
+                    self.resolve_target(file_path, path)
+                        .map(|candidate| self.document_key(&candidate))
+                })
+            }
+            _ => self.resolve(file_path, target).resolved,
+        }
+    }
+
+    /// Every link from an analyzed document to `target`, however it is
+    /// spelled, from an index built during the scan. Missing targets are
+    /// included, under the path the link names.
+    pub fn inbound_links(&self, target: &Path) -> &[InboundLink] {
+        self.inbound
+            .get(&self.document_key(target))
+            .map_or(&[], Vec::as_slice)
+    }
+
+    /// Captures the current documents together with the derived link graph,
+    /// broken links and orphans. The snapshot is unaffected by later calls to
+    /// `analyze_directory`.
+    pub fn snapshot(&self) -> Analysis {
+        let mut broken_links = self.find_broken_links();
+        broken_links.sort_by(|a, b| {
+            (&a.link.file_path, a.link.line_number).cmp(&(&b.link.file_path, b.link.line_number))
+        });
+        let mut orphaned_documents = self.find_orphaned_documents();
+        orphaned_documents.sort();
+
+        Analysis::new(
+            self.base_path.clone(),
+            self.documents
+                .iter()
+                .map(|(path, links)| (path.clone(), links.clone()))
+                .collect(),
+            self.link_graph(),
+            broken_links,
+            orphaned_documents,
+        )
+    }
+
+    /// Paths of all analyzed documents, sorted.
+    pub fn document_paths(&self) -> Vec<PathBuf> {
+        let mut paths: Vec<PathBuf> = self.documents.keys().cloned().collect();
+        paths.sort();
+        paths
+    }
+
+    /// Title of a scanned document: the `title` in its front matter, or else
+    /// its first level-1 heading.
+    pub fn document_title(&self, path: &Path) -> Option<&str> {
+        self.titles.get(path).map(String::as_str)
+    }
+
+    /// How reports name a document: its title followed by its path relative
+    /// to the analyzed directory, e.g. `Getting Started (guides/start.md)`,
+    /// or just the path when it has no title.
+    pub fn display_name(&self, path: &Path) -> String {
+        let relative = path.strip_prefix(&self.base_path).unwrap_or(path).display();
+        match self.document_title(path) {
+            Some(title) => format!("{} ({})", title, relative),
+            None => relative.to_string(),
+        }
+    }
+
+    /// Files under the analyzed directory the last scan did not check, with
+    /// why, sorted: files that aren't Markdown, and documents skipped as too
+    /// large or binary. Files matching `exclude` or `.gitignore` aren't listed.
+    pub fn excluded_files(&self) -> Vec<(PathBuf, String)> {
+        let mut excluded: Vec<(PathBuf, String)> = self
+            .file_index
+            .values()
+            .flatten()
+            .filter(|path| {
+                !self.documents.contains_key(*path)
+                    && !self
+                        .skipped_files
+                        .iter()
+                        .any(|(skipped, _)| skipped == *path)
+            })
+            .map(|path| (path.clone(), "Not a Markdown (.md) file".to_string()))
+            .collect();
+        excluded.extend(self.skipped_files.iter().cloned());
+        excluded.sort();
+        excluded
+    }
+
+    pub fn find_broken_links(&self) -> Vec<BrokenLink> {
+        self.find_broken_links_with(&CancellationToken::new())
+            .unwrap_or_default()
+    }
+
+    /// Like `find_broken_links`, but stops with `Cancelled` once `cancel` is
+    /// cancelled; it is checked before each document.
+    pub fn find_broken_links_with(
+        &self,
+        cancel: &CancellationToken,
+    ) -> Result<Vec<BrokenLink>, Cancelled> {
+        let mut broken_links = Vec::new();
+
+        for (file_path, links) in self.link_sources() {
+            cancel.check()?;
+            for link in links {
+                if let Some(broken_link) =
+                    self.broken_link(link, self.resolve(file_path, &link.target))
+                {
+                    for observer in &self.observers {
+                        observer.on_broken_link(&broken_link);
+                    }
+                    broken_links.push(broken_link);
+                }
+            }
+        }
+
+        Ok(broken_links)
+    }
+
+    /// Like `find_broken_links`, but stops resolving a document's links once
+    /// it has more than `per_file` broken ones, and stops the whole scan once
+    /// there are more than `total` or `cancel` is cancelled. Documents are
+    /// scanned in path order.
+    pub fn find_broken_links_limited(
+        &self,
+        limits: &FindingLimits,
+        cancel: &CancellationToken,
+    ) -> LimitedFindings {
+        let mut findings = LimitedFindings::default();
+
+        let mut sources: Vec<_> = self.link_sources().collect();
+        sources.sort_by_key(|(file_path, _)| *file_path);
+        for (file_path, links) in sources {
+            if cancel.is_cancelled() {
+                findings.cancelled = true;
+                return findings;
+            }
+            let mut in_file = 0;
+            for link in links {
+                if limits.per_file.is_some_and(|per_file| in_file > per_file) {
+                    break;
+                }
+                let Some(broken_link) =
+                    self.broken_link(link, self.resolve(file_path, &link.target))
+                else {
+                    continue;
+                };
+                let reported = limits.per_file.is_none_or(|per_file| in_file < per_file)
+                    && limits
+                        .total
+                        .is_none_or(|total| findings.broken_links.len() < total);
+                if reported {
+                    for observer in &self.observers {
+                        observer.on_broken_link(&broken_link);
+                    }
+                }
+                if !findings.push(broken_link, &mut in_file, limits) {
+                    return findings;
+                }
+            }
+        }
+
+        findings
+    }
+
+    /// Each analyzed document with its links, then each `.order` file of an
+    /// Azure DevOps wiki with the pages it lists.
+    fn link_sources(&self) -> impl Iterator<Item = (&PathBuf, &Vec<MarkdownLink>)> {
+        self.documents.iter().chain(&self.order_files)
+    }
+
+    /// Every `http(s)://` link outside the published site, ordered by
+    /// document and line.
+    pub fn external_links(&self) -> Vec<MarkdownLink> {
+        let mut external_links: Vec<MarkdownLink> = self
+            .documents
+            .values()
+            .flatten()
+            .filter(|link| LinkKind::classify(&link.target) == LinkKind::External)
+            .filter(|link| self.published_path(&link.target).is_none())
+            .cloned()
+            .collect();
+        external_links
+            .sort_by(|a, b| (&a.file_path, a.line_number).cmp(&(&b.file_path, b.line_number)));
+        external_links
+    }
+
+    pub fn find_assumed_links(&self) -> Vec<MarkdownLink> {
+        let mut assumed_links = Vec::new();
+
+        for (file_path, links) in &self.documents {
+            for link in links {
+                if self.resolve(file_path, &link.target).assumed_present {
+                    assumed_links.push(link.clone());
+                }
+            }
+        }
+
+        assumed_links
+    }
+
+    #[cfg(feature = "git")]
+    pub fn find_links_to_removed(&self, changes: &[PathChange]) -> Vec<BrokenLink> {
+        let removed: HashMap<PathBuf, &PathChange> = changes
+            .iter()
+            .map(|change| {
+                (
+                    crate::normalize_path(&self.base_path.join(change.old_path())),
+                    change,
+                )
+            })
+            .collect();
+
+        let mut broken_links = Vec::new();
+
+        for (file_path, links) in &self.documents {
+            for link in links {
+                let Some(resolved_path) = self.resolve_target(file_path, &link.target) else {
+                    continue;
+                };
+
+                if self.fs.exists(&resolved_path) {
+                    continue;
+                }
+
+                if let Some(change) = removed.get(&crate::normalize_path(&resolved_path)) {
+                    let reason = match change {
+                        PathChange::Deleted { path } => {
+                            format!("File was deleted: {}", path.display())
+                        }
+                        PathChange::Renamed { from, to } => {
+                            format!("File was renamed: {} -> {}", from.display(), to.display())
+                        }
+                    };
+                    broken_links.push(BrokenLink {
+                        link: link.clone(),
+                        reason,
+                        suggestion: None,
+                    });
+                }
+            }
+        }
+
+        broken_links
+    }
+
+    /// Compare-view links (`.../compare/v1.2.2...v1.2.3`) into `repository`
+    /// naming a ref other than `HEAD` that is not in `refs`, e.g. a release
+    /// that was never tagged. With no `repository`, every compare link is
+    /// checked. Reference definitions are checked where they are defined,
+    /// since changelogs mostly use them from `## [1.2.3]` headings.
+    #[cfg(feature = "git")]
+    pub fn find_unknown_compare_refs(
+        &self,
+        repository: Option<&str>,
+        refs: &BTreeSet<String>,
+    ) -> Vec<BrokenLink> {
+        let mut links = Vec::new();
+        let mut defined = HashSet::new();
+        for file_path in self.document_paths() {
+            let Ok(content) = self.read_document(&file_path) else {
+                continue;
+            };
+            for (index, line) in content.lines().enumerate() {
+                let Some(caps) = crate::extract::REFERENCE_DEFINITION.captures(line) else {
+                    continue;
+                };
+                let (target, _) = crate::extract::split_destination(&caps[2]);
+                if self.is_ignored_target(&target) {
+                    continue;
+                }
+                defined.insert((file_path.clone(), target.clone()));
+                links.push(MarkdownLink {
+                    text: caps[1].to_string(),
+                    target,
+                    line_number: index + 1,
+                    file_path: file_path.clone(),
+                });
+            }
+        }
+        links.extend(
+            self.external_links()
+                .into_iter()
+                .filter(|link| !defined.contains(&(link.file_path.clone(), link.target.clone()))),
+        );
+
+        let mut broken_links = Vec::new();
+        for link in links {
+            let Some((linked, base, head)) = crate::git::compare_refs(&link.target) else {
+                continue;
+            };
+            if repository.is_some_and(|repository| repository != linked) {
+                continue;
+            }
+            let unknown: Vec<&str> = [base.as_str(), head.as_str()]
+                .into_iter()
+                .filter(|name| *name != "HEAD" && !refs.contains(*name))
+                .collect();
+            if !unknown.is_empty() {
+                broken_links.push(BrokenLink {
+                    link,
+                    reason: format!("Compare URL names unknown git refs: {}", unknown.join(", ")),
+                    suggestion: None,
+                });
+            }
+        }
+        broken_links.sort_by(|a, b| {
+            (&a.link.file_path, a.link.line_number).cmp(&(&b.link.file_path, b.link.line_number))
+        });
+        broken_links
+    }
+
+    /// Entry documents every other page should be reachable from: those
+    /// named in `root_names` at the top of the analyzed directory, such as
+    /// its README, then the configured `entry_points`, then a GitHub wiki's
+    /// sidebars and footers or the pages an Azure DevOps wiki's navigation
+    /// lists in `.order` files, then the documents matching `root_globs`.
+    pub fn root_documents(&self) -> Vec<PathBuf> {
+        let mut roots = Vec::new();
+        let mut add = |mut matches: Vec<&PathBuf>| {
+            matches.sort();
+            for doc in matches {
+                if !roots.contains(doc) {
+                    roots.push(doc.clone());
+                }
+            }
+        };
+        for root in self
+            .root_names
+            .iter()
+            .map(Path::new)
+            .chain(self.entry_points.iter().map(PathBuf::as_path))
+        {
+            let key = self.document_key(&self.base_path.join(root));
+            add(self
+                .documents
+                .keys()
+                .filter(|doc| self.document_key(doc) == key)
+                .collect());
+        }
+        if self.flavor == Flavor::GithubWiki {
+            add(self
+                .documents
+                .keys()
+                .filter(|doc| {
+                    doc.file_name()
+                        .is_some_and(|name| name == "_Sidebar.md" || name == "_Footer.md")
+                })
+                .collect());
+        }
+        if self.flavor == Flavor::AzureDevopsWiki {
+            let listed: HashSet<PathBuf> = self
+                .order_files
+                .iter()
+                .flat_map(|(path, entries)| {
+                    entries
+                        .iter()
+                        .filter_map(|entry| self.resolve(path, &entry.target).resolved)
+                })
+                .collect();
+            add(self
+                .documents
+                .keys()
+                .filter(|doc| listed.contains(&self.document_key(doc)))
+                .collect());
+        }
+        if !self.root_globs.is_empty() {
+            let base = normalize_path(&self.base_path);
+            add(self
+                .documents
+                .keys()
+                .filter(|doc| {
+                    normalize_path(doc)
+                        .strip_prefix(&base)
+                        .is_ok_and(|relative| self.root_globs.is_match(relative))
+                })
+                .collect());
+        }
+        roots
+    }
+
+    /// Graph of internal links between analyzed documents. Links to files
+    /// outside the analyzed set (images, missing pages) are left out.
+    pub fn link_graph(&self) -> LinkGraph {
+        let documents: HashMap<PathBuf, &PathBuf> = self
+            .documents
+            .keys()
+            .map(|doc| (self.document_key(doc), doc))
+            .collect();
+
+        let mut graph = LinkGraph::default();
+        for file_path in self.documents.keys() {
+            graph.add_node(file_path.clone());
+        }
+        for (key, sources) in &self.inbound {
+            let Some(target) = documents.get(key) else {
+                continue;
+            };
+            for inbound in sources {
+                graph.add_edge(inbound.source.clone(), (*target).clone());
+            }
+        }
+
+        graph
+    }
+
+    /// The analyzed document at `path`, however it is spelled, e.g. `./a.md`
+    /// or an absolute path for `docs/a.md`.
+    pub fn find_document(&self, path: &Path) -> Option<PathBuf> {
+        let mut key = self.document_key(path);
+        if path.is_absolute() != self.base_path.is_absolute() {
+            // Spell it the way the scan does.
+            let working_dir = normalize_path(&std::env::current_dir().ok()?);
+            key = if path.is_absolute() {
+                self.document_key(key.strip_prefix(&working_dir).ok()?)
+            } else {
+                self.document_key(&working_dir.join(key))
+            };
+        }
+        self.document_paths()
+            .into_iter()
+            .find(|doc| self.document_key(doc) == key)
+    }
+
+    /// The fewest links to follow from document `from` to document `to`, in
+    /// order: empty if they are the same document, `None` if `to` cannot be
+    /// reached.
+    pub fn shortest_path(&self, from: &Path, to: &Path) -> Option<Vec<MarkdownLink>> {
+        let graph = self.link_graph();
+        let documents = graph.shortest_path(from, to)?;
+        documents
+            .windows(2)
+            .map(|hop| {
+                let target = Some(self.document_key(&hop[1]));
+                self.documents.get(&hop[0])?.iter().find(|link| {
+                    LinkKind::classify(&link.target) != LinkKind::SameDocument
+                        && self.resolve(&hop[0], &link.target).resolved == target
+                })
+            })
+            .map(|link| link.cloned())
+            .collect()
+    }
+
+    /// Links inside HTML comments whose target no live link points to, e.g.
+    /// an image that is only referenced from commented-out Markdown.
+    pub fn find_comment_only_links(&self) -> Vec<MarkdownLink> {
+        let live: HashSet<PathBuf> = self
+            .documents
+            .iter()
+            .flat_map(|(file_path, links)| {
+                links
+                    .iter()
+                    .filter_map(|link| self.resolve(file_path, &link.target).resolved)
+            })
+            .collect();
+
+        let mut comment_only: Vec<MarkdownLink> = self
+            .commented
+            .iter()
+            .flat_map(|(file_path, links)| {
+                links.iter().filter(|link| {
+                    self.resolve(file_path, &link.target)
+                        .resolved
+                        .is_some_and(|resolved| !live.contains(&resolved))
+                })
+            })
+            .cloned()
+            .collect();
+        comment_only
+            .sort_by(|a, b| (&a.file_path, a.line_number).cmp(&(&b.file_path, b.line_number)));
+        comment_only
+    }
+
+    pub fn find_orphaned_documents(&self) -> Vec<PathBuf> {
+        let mut referenced_docs = HashSet::new();
+        for root in self.root_documents() {
+            referenced_docs.insert(self.document_key(&root));
+        }
+
+        referenced_docs.extend(
+            self.inbound
+                .iter()
+                .filter(|(_, sources)| !sources.is_empty())
+                .map(|(key, _)| key.clone()),
+        );
+
+        let mut orphaned = Vec::new();
+        for doc_path in self.documents.keys() {
+            if !referenced_docs.contains(&self.document_key(doc_path)) {
+                orphaned.push(doc_path.clone());
+            }
+        }
+
+        orphaned
+    }
+
+    /// Groups `broken_links` by what they are missing, the largest group
+    /// first, so one removed directory shows up as one cause rather than as
+    /// hundreds of findings.
+    pub fn group_by_cause(&self, broken_links: &[BrokenLink]) -> Vec<CauseGroup> {
+        let root = crate::normalize_path(&self.base_path);
+        let mut groups: HashMap<RootCause, Vec<BrokenLink>> = HashMap::new();
+
+        for broken_link in broken_links {
+            let link = &broken_link.link;
+            let resolution = self.resolve(&link.file_path, &link.target);
+            let cause = match (&resolution.resolved, &resolution.fragment) {
+                (Some(_), Some(anchor)) if resolution.anchor_found == Some(false) => {
+                    let document = resolution
+                        .candidates
+                        .iter()
+                        .find(|candidate| self.fs.exists(candidate))
+                        .map(|candidate| crate::normalize_path(candidate))
+                        .unwrap_or_default();
+                    RootCause::MissingAnchor {
+                        document,
+                        anchor: anchor.clone(),
+                    }
+                }
+                (None, _) if resolution.error.is_some() && !resolution.published => {
+                    match resolution.candidates.last() {
+                        Some(candidate) => self.missing_path_cause(&root, candidate),
+                        None => RootCause::Other(broken_link.reason.clone()),
+                    }
+                }
+                _ => RootCause::Other(broken_link.reason.clone()),
+            };
+            groups.entry(cause).or_default().push(broken_link.clone());
+        }
+
+        let mut groups: Vec<CauseGroup> = groups
+            .into_iter()
+            .map(|(cause, broken_links)| CauseGroup {
+                cause,
+                broken_links,
+            })
+            .collect();
+        groups.sort_by(|a, b| {
+            b.broken_links
+                .len()
+                .cmp(&a.broken_links.len())
+                .then_with(|| a.cause.cmp(&b.cause))
+        });
+        groups
+    }
+
+    /// Other paths the last scan reached the document at `path` under,
+    /// through symlinks, with `follow_symlinks`.
+    pub fn aliases(&self, path: &Path) -> &[PathBuf] {
+        self.aliases.get(path).map_or(&[], Vec::as_slice)
+    }
+
+    /// A front matter field of a scanned document, such as `owner` or `team`.
+    pub fn front_matter_field(&self, path: &Path, field: &str) -> Option<&str> {
+        self.front_matter.get(path)?.get(field).map(String::as_str)
+    }
+
+    /// Groups `broken_links` by a front matter field of the document they
+    /// are in, e.g. `owner`, the largest group first. Documents without the
+    /// field come last, in one group.
+    pub fn group_by_front_matter(
+        &self,
+        broken_links: &[BrokenLink],
+        field: &str,
+    ) -> Vec<FieldGroup> {
+        let mut groups: BTreeMap<Option<String>, Vec<BrokenLink>> = BTreeMap::new();
+        for broken_link in broken_links {
+            let value = self.front_matter_field(&broken_link.link.file_path, field);
+            groups
+                .entry(value.map(str::to_string))
+                .or_default()
+                .push(broken_link.clone());
+        }
+
+        let mut groups: Vec<FieldGroup> = groups
+            .into_iter()
+            .map(|(value, broken_links)| FieldGroup {
+                value,
+                broken_links,
+            })
+            .collect();
+        // Stable, so equal-sized groups stay in order of their value.
+        groups.sort_by_key(|group| {
+            (
+                group.value.is_none(),
+                std::cmp::Reverse(group.broken_links.len()),
+            )
+        });
+        groups
+    }
+
+    fn missing_path_cause(&self, root: &Path, candidate: &Path) -> RootCause {
+        let missing = crate::normalize_path(candidate);
+        let directory = missing
+            .ancestors()
+            .skip(1)
+            .take_while(|dir| {
+                *dir != root
+                    && dir.starts_with(root)
+                    && !dir.as_os_str().is_empty()
+                    && !self.fs.exists(dir)
+            })
+            .last();
+        match directory {
+            Some(directory) => RootCause::MissingDirectory(directory.to_path_buf()),
+            None => RootCause::MissingFile(missing),
+        }
+    }
+
+    /// Number of links found by the last `analyze_directory`.
+    pub fn link_count(&self) -> usize {
+        self.documents.values().map(Vec::len).sum()
+    }
+
+    /// Number of links in the analyzed documents left out because their
+    /// target matched `ignore_targets`.
+    pub fn ignored_target_count(&self) -> usize {
+        self.ignored_targets.values().sum()
+    }
+
+    pub(crate) fn is_ignored_target(&self, target: &str) -> bool {
+        self.ignore_targets
+            .iter()
+            .any(|pattern| pattern.is_match(target))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vfs;
+    use std::fs;
+    use std::io::Write;
+    use std::path::Path;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_find_broken_links() {
+        let temp_dir = TempDir::new().unwrap();
+        let base_path = temp_dir.path();
+
+        let doc1_path = base_path.join("doc1.md");
+        let mut doc1 = fs::File::create(&doc1_path).unwrap();
+        writeln!(doc1, "# Document 1").unwrap();
+        writeln!(doc1, "[Valid link](./doc2.md)").unwrap();
+        writeln!(doc1, "[Broken link](./nonexistent.md)").unwrap();
+
+        let doc2_path = base_path.join("doc2.md");
+        let mut doc2 = fs::File::create(&doc2_path).unwrap();
+        writeln!(doc2, "# Document 2").unwrap();
+
+        let mut analyzer = LinkAnalyzer::new(base_path.to_path_buf());
+        analyzer.analyze_directory().unwrap();
+
+        let broken_links = analyzer.find_broken_links();
+        assert_eq!(broken_links.len(), 1);
+        assert_eq!(broken_links[0].link.text, "Broken link");
+        assert_eq!(broken_links[0].link.target, "./nonexistent.md");
+    }
+
+    #[test]
+    fn test_broken_links_in_subdirectories() {
+        let temp_dir = TempDir::new().unwrap();
+        let base_path = temp_dir.path();
+
+        let docs_dir = base_path.join("docs");
+        fs::create_dir(&docs_dir).unwrap();
+
+        let doc1_path = base_path.join("README.md");
+        let mut doc1 = fs::File::create(&doc1_path).unwrap();
+        writeln!(doc1, "[Link to docs](./docs/guide.md)").unwrap();
+        writeln!(doc1, "[Broken link](./docs/missing.md)").unwrap();
+
+        let guide_path = docs_dir.join("guide.md");
+        let mut guide = fs::File::create(&guide_path).unwrap();
+        writeln!(guide, "# Guide").unwrap();
+
+        let mut analyzer = LinkAnalyzer::new(base_path.to_path_buf());
+        analyzer.analyze_directory().unwrap();
+
+        let broken_links = analyzer.find_broken_links();
+        assert_eq!(broken_links.len(), 1);
+        assert_eq!(broken_links[0].link.target, "./docs/missing.md");
+    }
+
+    #[test]
+    fn test_assumed_present_targets() {
+        let temp_dir = TempDir::new().unwrap();
+        let base_path = temp_dir.path();
+
+        let docs_dir = base_path.join("docs");
+        fs::create_dir(&docs_dir).unwrap();
+
+        let guide_path = docs_dir.join("guide.md");
+        let mut guide = fs::File::create(&guide_path).unwrap();
+        writeln!(guide, "[API](../api/index.html)").unwrap();
+        writeln!(guide, "[Changelog](/CHANGELOG.md)").unwrap();
+        writeln!(guide, "[Broken link](./missing.md)").unwrap();
+
+        let config = Config {
+            assume_present: vec!["api/**".to_string(), "CHANGELOG.md".to_string()],
+            ..Default::default()
+        };
+        let mut analyzer = LinkAnalyzer::with_config(base_path.to_path_buf(), &config).unwrap();
+        analyzer.analyze_directory().unwrap();
+
+        let broken_links = analyzer.find_broken_links();
+        assert_eq!(broken_links.len(), 1);
+        assert_eq!(broken_links[0].link.target, "./missing.md");
+
+        let assumed_links = analyzer.find_assumed_links();
+        assert_eq!(assumed_links.len(), 2);
+    }
+
+    #[test]
+    #[cfg(feature = "git")]
+    fn test_find_links_to_removed() {
+        let temp_dir = TempDir::new().unwrap();
+        let base_path = temp_dir.path();
+
+        let docs_dir = base_path.join("docs");
+        fs::create_dir(&docs_dir).unwrap();
+
+        let readme_path = base_path.join("README.md");
+        let mut readme = fs::File::create(&readme_path).unwrap();
+        writeln!(readme, "[Old guide](./docs/guide.md)").unwrap();
+        writeln!(readme, "[Gone](./docs/gone.md)").unwrap();
+        writeln!(readme, "[Unrelated](./missing.md)").unwrap();
+
+        let intro_path = docs_dir.join("intro.md");
+        let mut intro = fs::File::create(&intro_path).unwrap();
+        writeln!(intro, "[Guide](../docs/./guide.md)").unwrap();
+
+        let changes = vec![
+            PathChange::Renamed {
+                from: PathBuf::from("docs/guide.md"),
+                to: PathBuf::from("docs/user-guide.md"),
+            },
+            PathChange::Deleted {
+                path: PathBuf::from("docs/gone.md"),
+            },
+        ];
+
+        let mut analyzer = LinkAnalyzer::new(base_path.to_path_buf());
+        analyzer.analyze_directory().unwrap();
+
+        let mut broken_links = analyzer.find_links_to_removed(&changes);
+        broken_links.sort_by(|a, b| a.link.target.cmp(&b.link.target));
+
+        assert_eq!(broken_links.len(), 3);
+        assert_eq!(broken_links[0].link.target, "../docs/./guide.md");
+        assert!(broken_links[0].reason.contains("docs/user-guide.md"));
+        assert_eq!(broken_links[1].link.target, "./docs/gone.md");
+        assert!(broken_links[1].reason.starts_with("File was deleted"));
+        assert_eq!(broken_links[2].link.target, "./docs/guide.md");
+    }
+
+    #[test]
+    fn test_analyze_memory_file_system() {
+        let mut vfs = vfs::MemoryFileSystem::new();
+        vfs.insert(
+            "docs/README.md",
+            "[Guide](./guide.md)\n[Missing](./missing.md)",
+        );
+        vfs.insert("docs/guide.md", "[Home](/README.md)");
+        vfs.insert("docs/orphaned.md", "# Orphaned");
+
+        let mut analyzer = LinkAnalyzer::new(PathBuf::from("docs")).with_filesystem(vfs);
+        analyzer.analyze_directory().unwrap();
+
+        let broken_links = analyzer.find_broken_links();
+        assert_eq!(broken_links.len(), 1);
+        assert_eq!(broken_links[0].link.target, "./missing.md");
+
+        let orphaned_docs = analyzer.find_orphaned_documents();
+        assert_eq!(orphaned_docs, vec![PathBuf::from("docs/orphaned.md")]);
+    }
+
+    #[test]
+    fn test_snapshot_is_isolated_from_rescans() {
+        let temp_dir = TempDir::new().unwrap();
+        let base_path = temp_dir.path();
+
+        let mut readme = fs::File::create(base_path.join("README.md")).unwrap();
+        writeln!(readme, "[Guide](./guide.md)\n[Missing](./missing.md)").unwrap();
+        let mut guide = fs::File::create(base_path.join("guide.md")).unwrap();
+        writeln!(guide, "# Guide").unwrap();
+
+        let mut analyzer = LinkAnalyzer::new(base_path.to_path_buf());
+        analyzer.analyze_directory().unwrap();
+        let snapshot = analyzer.snapshot();
+
+        let mut missing = fs::File::create(base_path.join("missing.md")).unwrap();
+        writeln!(missing, "[Home](./README.md)").unwrap();
+        analyzer.analyze_directory().unwrap();
+
+        let copy = snapshot.clone();
+        assert!(copy.ptr_eq(&snapshot));
+        assert_eq!(snapshot.documents().count(), 2);
+        assert_eq!(snapshot.broken_links().len(), 1);
+        let backlinks: Vec<&PathBuf> = snapshot.backlinks(&base_path.join("guide.md")).collect();
+        assert_eq!(backlinks, vec![&base_path.join("README.md")]);
+        assert_eq!(snapshot.links(&base_path.join("README.md")).len(), 2);
+
+        let updated = analyzer.snapshot();
+        assert_eq!(updated.documents().count(), 3);
+        assert!(updated.broken_links().is_empty());
+    }
+
+    #[test]
+    fn test_observer_receives_findings() {
+        #[derive(Debug, Default)]
+        struct Recorder(std::sync::Mutex<Vec<String>>);
+
+        impl AnalysisObserver for Recorder {
+            fn on_file_scanned(&self, path: &Path, links: &[MarkdownLink]) {
+                let event = format!("scanned {} ({} links)", path.display(), links.len());
+                self.0.lock().unwrap().push(event);
+            }
+
+            fn on_link_found(&self, link: &MarkdownLink) {
+                self.0.lock().unwrap().push(format!("link {}", link.target));
+            }
+
+            fn on_broken_link(&self, broken_link: &BrokenLink) {
+                let event = format!("broken {}", broken_link.link.target);
+                self.0.lock().unwrap().push(event);
+            }
+        }
+
+        let mut vfs = vfs::MemoryFileSystem::new();
+        vfs.insert(
+            "docs/README.md",
+            "[Guide](./guide.md)\n[Missing](./missing.md)",
+        );
+        vfs.insert("docs/guide.md", "# Guide");
+
+        let recorder = std::sync::Arc::new(Recorder::default());
+        let mut analyzer = LinkAnalyzer::new(PathBuf::from("docs"))
+            .with_filesystem(vfs)
+            .with_observer(recorder.clone());
+        analyzer.analyze_directory().unwrap();
+        analyzer.find_broken_links();
+
+        assert_eq!(
+            *recorder.0.lock().unwrap(),
+            vec![
+                "link ./guide.md",
+                "link ./missing.md",
+                "scanned docs/README.md (2 links)",
+                "scanned docs/guide.md (0 links)",
+                "broken ./missing.md",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_group_by_cause() {
+        let mut vfs = vfs::MemoryFileSystem::new();
+        vfs.insert(
+            "docs/README.md",
+            "[1](v1/setup.md) [2](./v1/api/auth.md)\n[3](guide.md#nope) [4](gone.md)",
+        );
+        vfs.insert(
+            "docs/guide.md",
+            "# Guide\n\n[5](v1/setup.md#install) [6](#nope) [7](../outside/x.md)",
+        );
+        vfs.insert("docs/api/index.md", "[8](old/v2/auth.md)");
+
+        let mut analyzer = LinkAnalyzer::new(PathBuf::from("docs")).with_filesystem(vfs);
+        analyzer.analyze_directory().unwrap();
+        let groups = analyzer.group_by_cause(&analyzer.find_broken_links());
+
+        let summary: Vec<(RootCause, usize)> = groups
+            .iter()
+            .map(|group| (group.cause.clone(), group.broken_links.len()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (RootCause::MissingDirectory(PathBuf::from("docs/v1")), 3),
+                (
+                    RootCause::MissingAnchor {
+                        document: PathBuf::from("docs/guide.md"),
+                        anchor: "nope".to_string(),
+                    },
+                    2
+                ),
+                (
+                    RootCause::MissingDirectory(PathBuf::from("docs/api/old")),
+                    1
+                ),
+                (RootCause::MissingFile(PathBuf::from("docs/gone.md")), 1),
+                (RootCause::MissingFile(PathBuf::from("outside/x.md")), 1),
+            ]
+        );
+    }
+
+    #[cfg(feature = "git")]
+    #[test]
+    fn test_find_unknown_compare_refs() {
+        let mut vfs = vfs::MemoryFileSystem::new();
+        vfs.insert(
+            "docs/CHANGELOG.md",
+            "[1.2.0]: https://github.com/org/repo/compare/v1.1.0...v1.2.0\n\
+             [1.3.0]: https://github.com/org/repo/compare/v1.2.0...HEAD\n\
+             [2.0.0]: https://github.com/org/repo/compare/v1.2.0...v2.0.0\n\
+             [dep]: https://github.com/other/dep/compare/v0.1...v0.2\n\
+             [3.0.0]: https://github.com/org/repo/compare/v2.0.0...v3.0.0\n\
+             \n## [3.0.0]\n\n[a][1.2.0] [b][1.3.0] [c][2.0.0] [d][dep]\n\
+             [e](https://github.com/org/repo/compare/main...v4.0.0)",
+        );
+
+        let mut analyzer = LinkAnalyzer::new(PathBuf::from("docs")).with_filesystem(vfs);
+        analyzer.analyze_directory().unwrap();
+        let refs: BTreeSet<String> = ["v1.1.0", "v1.2.0", "main"]
+            .into_iter()
+            .map(str::to_string)
+            .collect();
+
+        let reasons = |repository| -> Vec<String> {
+            analyzer
+                .find_unknown_compare_refs(repository, &refs)
+                .into_iter()
+                .map(|broken| broken.reason)
+                .collect()
+        };
+        assert_eq!(
+            reasons(Some("github.com/org/repo")),
+            vec![
+                "Compare URL names unknown git refs: v2.0.0",
+                "Compare URL names unknown git refs: v2.0.0, v3.0.0",
+                "Compare URL names unknown git refs: v4.0.0",
+            ]
+        );
+        assert_eq!(reasons(None).len(), 4);
+    }
+
+    #[test]
+    fn test_finding_limits() {
+        let mut vfs = vfs::MemoryFileSystem::new();
+        vfs.insert("docs/a.md", "[1](x1.md) [2](x2.md) [3](x3.md)\n[4](x4.md)");
+        vfs.insert("docs/b.md", "[1](y1.md)");
+        vfs.insert("docs/c.md", "[1](z1.md) [2](z2.md)");
+        let mut analyzer = LinkAnalyzer::new(PathBuf::from("docs")).with_filesystem(vfs);
+        analyzer.analyze_directory().unwrap();
+
+        let targets = |findings: &LimitedFindings| -> Vec<String> {
+            findings
+                .broken_links
+                .iter()
+                .map(|broken| broken.link.target.clone())
+                .collect()
+        };
+
+        let per_file = FindingLimits {
+            per_file: Some(2),
+            total: None,
+        };
+        let findings = analyzer.find_broken_links_limited(&per_file, &CancellationToken::new());
+        assert_eq!(
+            targets(&findings),
+            vec!["x1.md", "x2.md", "y1.md", "z1.md", "z2.md"]
+        );
+        assert_eq!(findings.truncated_files, vec![PathBuf::from("docs/a.md")]);
+        assert!(!findings.aborted);
+        assert_eq!(per_file.apply(analyzer.find_broken_links()), findings);
+
+        let total = FindingLimits {
+            per_file: Some(1),
+            total: Some(2),
+        };
+        let findings = analyzer.find_broken_links_limited(&total, &CancellationToken::new());
+        assert_eq!(targets(&findings), vec!["x1.md", "y1.md"]);
+        assert!(findings.aborted);
+        assert_eq!(total.apply(analyzer.find_broken_links()), findings);
+
+        let exact = FindingLimits {
+            per_file: None,
+            total: Some(7),
+        };
+        assert!(
+            !analyzer
+                .find_broken_links_limited(&exact, &CancellationToken::new())
+                .aborted
+        );
+    }
+
+    #[test]
+    fn test_cancellation() {
+        let mut vfs = vfs::MemoryFileSystem::new();
+        vfs.insert("docs/a.md", "[x](missing.md)");
+        let mut analyzer = LinkAnalyzer::new(PathBuf::from("docs")).with_filesystem(vfs);
+
+        let cancel = CancellationToken::new();
+        cancel.cancel();
+        let error = analyzer.analyze_directory_with(&cancel).unwrap_err();
+        assert!(error.downcast_ref::<Cancelled>().is_some());
+        assert!(analyzer.document_paths().is_empty());
+
+        analyzer.analyze_directory().unwrap();
+        assert_eq!(analyzer.find_broken_links_with(&cancel), Err(Cancelled));
+        let findings = analyzer.find_broken_links_limited(&FindingLimits::default(), &cancel);
+        assert!(findings.cancelled);
+        assert!(findings.broken_links.is_empty());
+        assert_eq!(
+            analyzer
+                .find_broken_links_with(&CancellationToken::new())
+                .unwrap()
+                .len(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_ignore_targets() {
+        let mut vfs = vfs::MemoryFileSystem::new();
+        vfs.insert(
+            "docs/README.md",
+            "[a](http://localhost:8080/) [b](./generated/api.md) [c](./manual.pdf) [d](./missing.md)\n<!-- [e](./old.pdf) -->",
+        );
+        let config = Config {
+            ignore_targets: vec![
+                "^https?://localhost".to_string(),
+                r"^\./generated/".to_string(),
+                r"\.pdf$".to_string(),
+            ],
+            ..Default::default()
+        };
+        let mut analyzer = LinkAnalyzer::with_config(PathBuf::from("docs"), &config)
+            .unwrap()
+            .with_filesystem(vfs);
+        analyzer.analyze_directory().unwrap();
+
+        let broken = analyzer.find_broken_links();
+        assert_eq!(broken.len(), 1);
+        assert_eq!(broken[0].link.target, "./missing.md");
+        assert_eq!(analyzer.ignored_target_count(), 3);
+        assert!(analyzer.external_links().is_empty());
+        assert!(analyzer.find_comment_only_links().is_empty());
+
+        let invalid = Config {
+            ignore_targets: vec!["(".to_string()],
+            ..Default::default()
+        };
+        assert!(LinkAnalyzer::with_config(PathBuf::from("docs"), &invalid).is_err());
+    }
+
+    #[test]
+    fn test_ignored_targets_after_reanalyze() {
+        let temp_dir = TempDir::new().unwrap();
+        let docs = temp_dir.path().to_path_buf();
+        let readme = docs.join("README.md");
+        let guide = docs.join("guide.md");
+        fs::write(&readme, "[a](./manual.pdf) [b](./slides.pdf)\n").unwrap();
+        fs::write(&guide, "[c](./manual.pdf)\n").unwrap();
+        let config = Config {
+            ignore_targets: vec![r"\.pdf$".to_string()],
+            ..Default::default()
+        };
+        let mut analyzer = LinkAnalyzer::with_config(docs.clone(), &config).unwrap();
+        analyzer.analyze_directory().unwrap();
+        assert_eq!(analyzer.ignored_target_count(), 3);
+
+        analyzer.reanalyze(std::slice::from_ref(&readme)).unwrap();
+        analyzer.reanalyze(std::slice::from_ref(&readme)).unwrap();
+        assert_eq!(analyzer.ignored_target_count(), 3);
+
+        fs::write(&readme, "[a](./manual.pdf)\n").unwrap();
+        analyzer.reanalyze(std::slice::from_ref(&readme)).unwrap();
+        assert_eq!(analyzer.ignored_target_count(), 2);
+
+        fs::remove_file(&guide).unwrap();
+        analyzer.reanalyze(std::slice::from_ref(&guide)).unwrap();
+        assert_eq!(analyzer.ignored_target_count(), 1);
+    }
+
+    /// Counts how often each file is read, and how often paths are
+    /// canonicalized.
+    #[derive(Debug)]
+    struct CountingFileSystem {
+        inner: vfs::MemoryFileSystem,
+        reads: std::sync::Arc<std::sync::Mutex<HashMap<PathBuf, usize>>>,
+        canonicalized: std::sync::Arc<std::sync::atomic::AtomicUsize>,
+    }
+
+    impl FileSystem for CountingFileSystem {
+        fn files(&self, root: &Path) -> Result<Vec<PathBuf>, Box<dyn std::error::Error>> {
+            self.inner.files(root)
+        }
+
+        fn read_to_string(&self, path: &Path) -> io::Result<String> {
+            *self
+                .reads
+                .lock()
+                .unwrap()
+                .entry(path.to_path_buf())
+                .or_default() += 1;
+            self.inner.read_to_string(path)
+        }
+
+        fn exists(&self, path: &Path) -> bool {
+            self.inner.exists(path)
+        }
+
+        fn canonicalize(&self, path: &Path) -> io::Result<PathBuf> {
+            self.canonicalized
+                .fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+            self.inner.canonicalize(path)
+        }
+
+        fn file_size(&self, path: &Path) -> io::Result<u64> {
+            self.inner.file_size(path)
+        }
+    }
+
+    #[test]
+    fn test_anchor_targets_are_read_once() {
+        let mut inner = vfs::MemoryFileSystem::new();
+        inner.insert(
+            "docs/README.md",
+            "[a](./guide.md#usage) [b](./guide.md#setup) [c](../notes.md#todo) [d](../notes.md#gone)",
+        );
+        inner.insert("docs/guide.md", "# Usage\n\n[e](../notes.md#todo)");
+        inner.insert("notes.md", "# TODO\n");
+        let reads = std::sync::Arc::default();
+        let fs = CountingFileSystem {
+            inner,
+            reads: std::sync::Arc::clone(&reads),
+            canonicalized: std::sync::Arc::default(),
+        };
+
+        let mut analyzer = LinkAnalyzer::new(PathBuf::from("docs")).with_filesystem(fs);
+        analyzer.analyze_directory().unwrap();
+        let broken: Vec<String> = analyzer
+            .find_broken_links()
+            .into_iter()
+            .map(|broken| broken.link.target)
+            .collect();
+        assert_eq!(broken.len(), 2);
+        assert!(broken.contains(&"./guide.md#setup".to_string()));
+        assert!(broken.contains(&"../notes.md#gone".to_string()));
+
+        // Scanned documents are indexed as they are read; other targets on
+        // their first anchor check.
+        let reads = reads.lock().unwrap();
+        assert_eq!(reads.get(Path::new("docs/README.md")), Some(&1));
+        assert_eq!(reads.get(Path::new("docs/guide.md")), Some(&1));
+        assert_eq!(reads.get(Path::new("notes.md")), Some(&1));
+    }
+
+    #[test]
+    fn test_lexical_path_matching() {
+        let mut inner = vfs::MemoryFileSystem::new();
+        inner.insert(
+            "docs/README.md",
+            "[a](./missing/../guide.md#usage) [b](/guide.md)",
+        );
+        inner.insert("docs/guide.md", "# Usage\n\n[c](../docs/./README.md)");
+        inner.insert("docs/old.md", "# Old");
+        let canonicalized = std::sync::Arc::default();
+        let fs = CountingFileSystem {
+            inner,
+            reads: std::sync::Arc::default(),
+            canonicalized: std::sync::Arc::clone(&canonicalized),
+        };
+
+        let mut analyzer = LinkAnalyzer::new(PathBuf::from("./docs")).with_filesystem(fs);
+        analyzer.analyze_directory().unwrap();
+        assert!(analyzer.find_broken_links().is_empty());
+        assert_eq!(
+            analyzer.find_orphaned_documents(),
+            vec![PathBuf::from("docs/old.md")]
+        );
+        let graph = analyzer.link_graph();
+        assert_eq!(
+            graph
+                .successors(Path::new("docs/guide.md"))
+                .collect::<Vec<_>>(),
+            vec![&PathBuf::from("docs/README.md")]
+        );
+        let absolute = std::env::current_dir().unwrap().join("docs/guide.md");
+        assert_eq!(
+            analyzer.find_document(&absolute),
+            Some(PathBuf::from("docs/guide.md"))
+        );
+        assert_eq!(canonicalized.load(std::sync::atomic::Ordering::Relaxed), 0);
+
+        // `..` after a directory that doesn't exist still resolves, as it
+        // does in a browser.
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(
+            temp_dir.path().join("README.md"),
+            "[a](./missing/../guide.md)",
+        )
+        .unwrap();
+        fs::write(temp_dir.path().join("guide.md"), "# Guide").unwrap();
+        let mut analyzer = LinkAnalyzer::new(temp_dir.path().to_path_buf());
+        analyzer.analyze_directory().unwrap();
+        assert!(analyzer.find_broken_links().is_empty());
+        assert!(analyzer.find_orphaned_documents().is_empty());
+    }
+
+    #[test]
+    fn test_unicode_normalization() {
+        // Files named as macOS stores them (NFD), linked as typed (NFC), and
+        // the other way around.
+        let vfs = || {
+            let mut vfs = vfs::MemoryFileSystem::new();
+            vfs.insert(
+                "README.md",
+                "[a](./caf\u{e9}.md#menu) [b](./r\u{e9}sum\u{e9}/cv.md) [[na\u{ef}ve]]",
+            );
+            vfs.insert("cafe\u{301}.md", "# Menu");
+            vfs.insert("re\u{301}sume\u{301}/cv.md", "[back](../README.md)");
+            vfs.insert("nai\u{308}ve.md", "");
+            vfs.insert(
+                "notes.md",
+                "[c](./nai\u{308}ve.md) [d](./cafe\u{301}.md#menu)",
+            );
+            vfs
+        };
+        let analyze = |normalization: &str| {
+            let config = Config {
+                unicode_normalization: normalization.to_string(),
+                wiki_links: true,
+                entry_points: vec!["notes.md".to_string()],
+                ..Default::default()
+            };
+            let mut analyzer = LinkAnalyzer::with_config(PathBuf::new(), &config)
+                .unwrap()
+                .with_filesystem(vfs());
+            analyzer.analyze_directory().unwrap();
+            analyzer
+        };
+
+        let analyzer = analyze("");
+        let broken: Vec<String> = analyzer
+            .find_broken_links()
+            .into_iter()
+            .map(|broken| broken.link.target)
+            .collect();
+        assert_eq!(
+            broken,
+            vec![
+                "./caf\u{e9}.md#menu",
+                "./r\u{e9}sum\u{e9}/cv.md",
+                "[[na\u{ef}ve]]"
+            ]
+        );
+        assert_eq!(
+            analyzer.find_orphaned_documents(),
+            vec![PathBuf::from("re\u{301}sume\u{301}/cv.md")]
+        );
+
+        let analyzer = analyze("nfc");
+        assert!(analyzer.find_broken_links().is_empty());
+        assert!(analyzer.find_orphaned_documents().is_empty());
+        assert_eq!(analyzer.inbound_links(Path::new("caf\u{e9}.md")).len(), 2);
+        let resolution = analyzer.resolve(Path::new("README.md"), "./caf\u{e9}.md");
+        assert_eq!(resolution.resolved, Some(PathBuf::from("cafe\u{301}.md")));
+
+        assert!(analyze("nfkc").find_broken_links().is_empty());
+        assert!(LinkAnalyzer::with_config(
+            PathBuf::new(),
+            &Config {
+                unicode_normalization: "nfd".to_string(),
+                ..Default::default()
+            }
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_reanalyze() {
+        let temp_dir = TempDir::new().unwrap();
+        let docs = temp_dir.path().to_path_buf();
+        let readme = docs.join("README.md");
+        let guide = docs.join("guide.md");
+        let new = docs.join("new.md");
+        fs::write(&readme, "[Guide](guide.md#install)\n[New](new.md)\n").unwrap();
+        fs::write(&guide, "# Install\n").unwrap();
+        fs::write(docs.join("other.md"), "[Guide](guide.md)\n").unwrap();
+
+        let mut analyzer = LinkAnalyzer::new(docs.clone());
+        analyzer.analyze_directory().unwrap();
+        assert_eq!(analyzer.find_broken_links().len(), 1);
+
+        fs::write(&new, "# New\n").unwrap();
+        let affected = analyzer.reanalyze(std::slice::from_ref(&new)).unwrap();
+        assert_eq!(affected, vec![readme.clone(), new.clone()]);
+        assert!(analyzer.find_broken_links().is_empty());
+
+        // Documents linking to a changed one are re-checked for its anchors.
+        fs::write(&guide, "# Setup\n").unwrap();
+        let affected = analyzer.reanalyze(std::slice::from_ref(&guide)).unwrap();
+        assert_eq!(
+            affected,
+            vec![readme.clone(), guide.clone(), docs.join("other.md")]
+        );
+        let broken = analyzer.find_broken_links_in(&readme);
+        assert_eq!(broken.len(), 1);
+        assert_eq!(broken[0].link.target, "guide.md#install");
+
+        fs::remove_file(&new).unwrap();
+        let affected = analyzer.reanalyze(&[new, docs.join("notes.txt")]).unwrap();
+        assert_eq!(affected, vec![readme.clone()]);
+        assert_eq!(analyzer.document_paths().len(), 3);
+        assert_eq!(analyzer.find_broken_links_in(&readme).len(), 2);
+
+        fs::create_dir(docs.join("more")).unwrap();
+        fs::write(docs.join("more/a.md"), "[Gone](gone.md)\n").unwrap();
+        let affected = analyzer.reanalyze(&[docs.join("more")]).unwrap();
+        assert_eq!(affected, vec![docs.join("more/a.md")]);
+        fs::remove_dir_all(docs.join("more")).unwrap();
+        analyzer.reanalyze(&[docs.join("more")]).unwrap();
+        assert_eq!(analyzer.document_paths().len(), 3);
+    }
+
+    #[test]
+    fn test_rescan_forgets_deleted_documents() {
+        let temp_dir = TempDir::new().unwrap();
+        let docs = temp_dir.path().to_path_buf();
+        fs::write(docs.join("README.md"), "[Guide](guide.md)\n").unwrap();
+        fs::write(
+            docs.join("old.md"),
+            "[Gone](gone.md)\n<!-- [Img](a.png) -->\n",
+        )
+        .unwrap();
+        fs::write(docs.join("guide.md"), "# Guide\n").unwrap();
+
+        let mut analyzer = LinkAnalyzer::new(docs.clone());
+        analyzer.analyze_directory().unwrap();
+        assert_eq!(analyzer.document_paths().len(), 3);
+        assert_eq!(analyzer.link_count(), 2);
+        assert_eq!(analyzer.find_broken_links().len(), 1);
+
+        fs::remove_file(docs.join("old.md")).unwrap();
+        analyzer.analyze_directory().unwrap();
+        assert_eq!(
+            analyzer.document_paths(),
+            vec![docs.join("README.md"), docs.join("guide.md")]
+        );
+        assert_eq!(analyzer.link_count(), 1);
+        assert!(analyzer.find_broken_links().is_empty());
+        assert!(!analyzer.commented.contains_key(&docs.join("old.md")));
+    }
+
+    #[test]
+    fn test_heading_index_persistence() {
+        let temp_dir = TempDir::new().unwrap();
+        let docs = temp_dir.path().join("docs");
+        fs::create_dir(&docs).unwrap();
+        let readme = docs.join("README.md");
+        let guide = docs.join("guide.md");
+        fs::write(&readme, "[Install](./guide.md#install)\n").unwrap();
+        fs::write(&guide, "# Install\n").unwrap();
+        let index = temp_dir.path().join("cache").join("headings.json");
+
+        let mut analyzer = LinkAnalyzer::new(docs.clone());
+        analyzer.analyze_directory().unwrap();
+        analyzer.save_heading_index(&index).unwrap();
+
+        // With the same size and modification time the saved headings are
+        // trusted, so guide.md is not read again and this edit goes unseen.
+        let modified = fs::metadata(&guide).unwrap().modified().unwrap();
+        fs::write(&guide, "# Inst4ll\n").unwrap();
+        fs::File::options()
+            .write(true)
+            .open(&guide)
+            .unwrap()
+            .set_modified(modified)
+            .unwrap();
+        let mut editor = LinkAnalyzer::new(docs.clone());
+        assert_eq!(editor.load_heading_index(&index), 2);
+        editor.analyze_document(&readme).unwrap();
+        assert!(editor.find_broken_links_in(&readme).is_empty());
+
+        fs::write(&guide, "# Setup\n").unwrap();
+        let mut editor = LinkAnalyzer::new(docs.clone());
+        editor.load_heading_index(&index);
+        editor.analyze_document(&readme).unwrap();
+        let broken = editor.find_broken_links_in(&readme);
+        assert_eq!(broken.len(), 1);
+        assert_eq!(broken[0].link.target, "./guide.md#install");
+
+        let config = Config {
+            heading_styles: vec!["atx".to_string()],
+            ..Default::default()
+        };
+        let mut atx_only = LinkAnalyzer::with_config(docs.clone(), &config).unwrap();
+        assert_eq!(atx_only.load_heading_index(&index), 0);
+
+        let config = Config {
+            slugger: "pandoc".to_string(),
+            ..Default::default()
+        };
+        let mut pandoc = LinkAnalyzer::with_config(docs, &config).unwrap();
+        assert_eq!(pandoc.load_heading_index(&index), 0);
+    }
+
+    #[test]
+    fn test_pandoc_slugger() {
+        let vfs = || {
+            let mut vfs = vfs::MemoryFileSystem::new();
+            vfs.insert(
+                "README.md",
+                "# 1. Introduction\n## Setup {#install .unnumbered}\n## Maître d'hôtel\n\n\
+                 [a](#introduction) [b](#install) [c](#maître-dhôtel) [d](#1-introduction)",
+            );
+            vfs
+        };
+        let broken_targets = |slugger: &str| -> Vec<String> {
+            let config = Config {
+                slugger: slugger.to_string(),
+                ..Default::default()
+            };
+            let mut analyzer = LinkAnalyzer::with_config(PathBuf::new(), &config)
+                .unwrap()
+                .with_filesystem(vfs());
+            analyzer.analyze_directory().unwrap();
+            analyzer
+                .find_broken_links()
+                .into_iter()
+                .map(|broken| broken.link.target)
+                .collect()
+        };
+
+        assert_eq!(broken_targets("pandoc"), vec!["#1-introduction"]);
+        assert_eq!(broken_targets("github"), vec!["#introduction", "#install"]);
+        assert!(LinkAnalyzer::with_config(
+            PathBuf::new(),
+            &Config {
+                slugger: "kramdown".to_string(),
+                ..Default::default()
+            }
+        )
+        .is_err());
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_follow_symlinks() {
+        use std::os::unix::fs::symlink;
+
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        fs::create_dir_all(root.join("docs/real")).unwrap();
+        fs::write(root.join("README.md"), "[Guide](docs/real/guide.md)").unwrap();
+        fs::write(root.join("docs/real/guide.md"), "[Missing](missing.md)").unwrap();
+        symlink(root.join("docs/real"), root.join("docs/alias")).unwrap();
+        symlink(root, root.join("docs/real/loop")).unwrap();
+
+        let mut analyzer = LinkAnalyzer::new(root.to_path_buf());
+        analyzer.analyze_directory().unwrap();
+        assert_eq!(analyzer.documents.len(), 2);
+
+        let config = Config {
+            follow_symlinks: true,
+            ..Default::default()
+        };
+        let mut analyzer = LinkAnalyzer::with_config(root.to_path_buf(), &config).unwrap();
+        analyzer.analyze_directory().unwrap();
+        assert_eq!(analyzer.documents.len(), 2);
+        assert_eq!(analyzer.find_broken_links().len(), 1);
+        let guide = root.join("docs/real/guide.md");
+        assert_eq!(
+            analyzer.aliases(&guide),
+            &[root.join("docs/alias/guide.md")]
+        );
+        assert_eq!(
+            analyzer.get_statistics().document_stats[&guide].aliases,
+            vec![root.join("docs/alias/guide.md")]
+        );
+    }
+
+    #[test]
+    fn test_exclude_and_entry_points() {
+        let mut vfs = vfs::MemoryFileSystem::new();
+        vfs.insert("docs/README.md", "[Draft](drafts/idea.md)");
+        vfs.insert("docs/drafts/idea.md", "[Broken](./missing.md)");
+        vfs.insert("docs/api/index.md", "[Client](client.md)");
+        vfs.insert("docs/api/client.md", "# Client");
+
+        let config = Config {
+            exclude: vec!["drafts/**".to_string()],
+            entry_points: vec!["api/index.md".to_string()],
+            ..Default::default()
+        };
+        let mut analyzer = LinkAnalyzer::with_config(PathBuf::from("docs"), &config)
+            .unwrap()
+            .with_filesystem(vfs);
+        analyzer.analyze_directory().unwrap();
+
+        assert!(analyzer.find_broken_links().is_empty());
+        assert!(analyzer.find_orphaned_documents().is_empty());
+        assert_eq!(
+            analyzer.root_documents(),
+            vec![
+                PathBuf::from("docs/README.md"),
+                PathBuf::from("docs/api/index.md")
+            ]
+        );
+        assert!(analyzer.excluded_files().is_empty());
+    }
+
+    #[test]
+    fn test_root_names_and_globs() {
+        let vfs = || {
+            let mut vfs = vfs::MemoryFileSystem::new();
+            vfs.insert("wiki/Home.md", "[Setup](Setup.md)");
+            vfs.insert("wiki/Setup.md", "# Setup");
+            vfs.insert("wiki/Start.md", "[Setup](Setup.md)");
+            vfs.insert("wiki/teams/README.md", "# Teams");
+            vfs.insert("wiki/teams/sub/README.md", "# Sub");
+            vfs
+        };
+        let analyze = |config: &Config| {
+            let mut analyzer = LinkAnalyzer::with_config(PathBuf::from("wiki"), config)
+                .unwrap()
+                .with_filesystem(vfs());
+            analyzer.analyze_directory().unwrap();
+            analyzer
+        };
+
+        // A wiki's home page is a root by default.
+        let analyzer = analyze(&Config::default());
+        assert_eq!(
+            analyzer.root_documents(),
+            vec![PathBuf::from("wiki/Home.md")]
+        );
+        let mut orphans = analyzer.find_orphaned_documents();
+        orphans.sort();
+        assert_eq!(
+            orphans,
+            vec![
+                PathBuf::from("wiki/Start.md"),
+                PathBuf::from("wiki/teams/README.md"),
+                PathBuf::from("wiki/teams/sub/README.md"),
+            ]
+        );
+
+        let analyzer = analyze(&Config {
+            root_names: vec!["Start.md".to_string()],
+            root_globs: vec!["**/README.md".to_string()],
+            ..Default::default()
+        });
+        assert_eq!(
+            analyzer.root_documents(),
+            vec![
+                PathBuf::from("wiki/Start.md"),
+                PathBuf::from("wiki/teams/README.md"),
+                PathBuf::from("wiki/teams/sub/README.md"),
+            ]
+        );
+        assert_eq!(
+            analyzer.find_orphaned_documents(),
+            vec![PathBuf::from("wiki/Home.md")]
+        );
+    }
+
+    #[test]
+    fn test_gitignored_files() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        fs::create_dir_all(root.join(".git")).unwrap();
+        fs::create_dir_all(root.join("docs/build")).unwrap();
+        fs::write(root.join(".gitignore"), "build/\n").unwrap();
+        fs::write(root.join("docs/README.md"), "# Docs\n").unwrap();
+        fs::write(root.join("docs/build/api.md"), "[Gone](./gone.md)").unwrap();
+
+        let mut analyzer = LinkAnalyzer::new(root.join("docs"));
+        analyzer.analyze_directory().unwrap();
+        assert_eq!(analyzer.documents.len(), 1);
+        assert!(analyzer.find_orphaned_documents().is_empty());
+
+        let config = Config {
+            no_gitignore: true,
+            ..Default::default()
+        };
+        let mut analyzer = LinkAnalyzer::with_config(root.join("docs"), &config).unwrap();
+        analyzer.analyze_directory().unwrap();
+        assert_eq!(analyzer.documents.len(), 2);
+        assert_eq!(analyzer.find_broken_links().len(), 1);
+    }
+
+    #[test]
+    fn test_excluded_files() {
+        let mut vfs = vfs::MemoryFileSystem::new();
+        vfs.insert("docs/README.md", "# Docs\n");
+        vfs.insert("docs/guide.MD", "# Guide\n");
+        vfs.insert("docs/logo.png", "");
+        vfs.insert("docs/data.md", "\u{0}");
+
+        let mut analyzer = LinkAnalyzer::new(PathBuf::from("docs")).with_filesystem(vfs);
+        analyzer.analyze_directory().unwrap();
+
+        assert_eq!(
+            analyzer.document_paths(),
+            vec![PathBuf::from("docs/README.md")]
+        );
+        assert_eq!(
+            analyzer.excluded_files(),
+            vec![
+                (PathBuf::from("docs/data.md"), "Binary file".to_string()),
+                (
+                    PathBuf::from("docs/guide.MD"),
+                    "Not a Markdown (.md) file".to_string()
+                ),
+                (
+                    PathBuf::from("docs/logo.png"),
+                    "Not a Markdown (.md) file".to_string()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_group_by_front_matter() {
+        let mut vfs = vfs::MemoryFileSystem::new();
+        vfs.insert("docs/a.md", "---\nowner: alice\n---\n[x](x.md) [y](y.md)\n");
+        vfs.insert("docs/b.md", "---\nowner: bob\n---\n[z](z.md)\n");
+        vfs.insert("docs/c.md", "---\nteam: docs\n---\n[w](w.md)\n");
+        vfs.insert("docs/d.md", "[v](v.md)\n");
+
+        let mut analyzer = LinkAnalyzer::new(PathBuf::from("docs")).with_filesystem(vfs);
+        analyzer.analyze_directory().unwrap();
+        assert_eq!(
+            analyzer.front_matter_field(Path::new("docs/c.md"), "team"),
+            Some("docs")
+        );
+
+        let groups = analyzer.group_by_front_matter(&analyzer.find_broken_links(), "owner");
+        let groups: Vec<(Option<&str>, usize)> = groups
+            .iter()
+            .map(|group| (group.value.as_deref(), group.broken_links.len()))
+            .collect();
+        assert_eq!(
+            groups,
+            vec![(Some("alice"), 2), (Some("bob"), 1), (None, 2)]
+        );
+    }
+
+    #[test]
+    fn test_document_titles() {
+        let mut vfs = vfs::MemoryFileSystem::new();
+        vfs.insert(
+            "docs/start.md",
+            "---\ntitle: \"Getting Started\"\n---\n\n# Install\n",
+        );
+        vfs.insert("docs/guide.md", "Intro\n\n## Setup\n\n# Guide\n");
+        vfs.insert("docs/notes.md", "---\nauthor: me\n---\nNo heading.\n");
+
+        let mut analyzer = LinkAnalyzer::new(PathBuf::from("docs")).with_filesystem(vfs);
+        analyzer.analyze_directory().unwrap();
+
+        let names: Vec<String> = analyzer
+            .document_paths()
+            .iter()
+            .map(|path| analyzer.display_name(path))
+            .collect();
+        assert_eq!(
+            names,
+            vec!["Guide (guide.md)", "notes.md", "Getting Started (start.md)"]
+        );
+        assert_eq!(
+            analyzer.document_title(Path::new("docs/start.md")),
+            Some("Getting Started")
+        );
+    }
+
+    #[test]
+    fn test_inbound_links() {
+        let mut vfs = vfs::MemoryFileSystem::new();
+        vfs.insert(
+            "docs/README.md",
+            "[Guide](guide.md)\n[Setup](./guide.md#setup)",
+        );
+        vfs.insert("docs/api/index.md", "[Guide](../guide)\n[Gone](../gone.md)");
+        vfs.insert("docs/guide.md", "# Setup\n[Top](#setup)");
+        let config = Config {
+            extensionless_candidates: vec![".md".to_string()],
+            ..Default::default()
+        };
+        let mut analyzer = LinkAnalyzer::with_config(PathBuf::from("docs"), &config)
+            .unwrap()
+            .with_filesystem(vfs);
+        analyzer.analyze_directory().unwrap();
+
+        let inbound = |analyzer: &LinkAnalyzer, target: &str| -> Vec<(String, usize)> {
+            let mut inbound: Vec<(String, usize)> = analyzer
+                .inbound_links(Path::new(target))
+                .iter()
+                .map(|inbound| (inbound.source.display().to_string(), inbound.line_number))
+                .collect();
+            inbound.sort();
+            inbound
+        };
+        assert_eq!(
+            inbound(&analyzer, "./docs/api/../guide.md"),
+            vec![
+                ("docs/README.md".to_string(), 1),
+                ("docs/README.md".to_string(), 2),
+                ("docs/api/index.md".to_string(), 1),
+            ]
+        );
+        assert_eq!(
+            inbound(&analyzer, "docs/gone.md"),
+            vec![("docs/api/index.md".to_string(), 2)]
+        );
+        assert!(analyzer
+            .inbound_links(Path::new("docs/README.md"))
+            .is_empty());
+
+        // Re-reading a document replaces its entries.
+        let mut vfs = vfs::MemoryFileSystem::new();
+        vfs.insert("docs/README.md", "# Docs");
+        vfs.insert("docs/api/index.md", "[Guide](../guide)\n[Gone](../gone.md)");
+        vfs.insert("docs/guide.md", "# Setup");
+        analyzer = analyzer.with_filesystem(vfs);
+        analyzer
+            .analyze_document(Path::new("docs/README.md"))
+            .unwrap();
+        assert_eq!(
+            inbound(&analyzer, "docs/guide.md"),
+            vec![("docs/api/index.md".to_string(), 1)]
+        );
+    }
+
+    #[test]
+    fn test_link_graph() {
+        let mut vfs = vfs::MemoryFileSystem::new();
+        vfs.insert(
+            "docs/README.md",
+            "[Guide](./guide.md#usage)\n[Top](#top)\n[Image](./logo.png)",
+        );
+        vfs.insert("docs/guide.md", "# Usage\n[Home](/README.md)");
+        vfs.insert("docs/orphaned.md", "[Guide](guide.md)");
+        vfs.insert("docs/logo.png", "");
+
+        let mut analyzer = LinkAnalyzer::new(PathBuf::from("docs")).with_filesystem(vfs);
+        analyzer.analyze_directory().unwrap();
+
+        let roots = analyzer.root_documents();
+        assert_eq!(roots, vec![PathBuf::from("docs/README.md")]);
+
+        let graph = analyzer.link_graph();
+        assert_eq!(graph.nodes.len(), 3);
+        let successors: Vec<&PathBuf> = graph.successors(Path::new("docs/README.md")).collect();
+        assert_eq!(successors, vec![&PathBuf::from("docs/guide.md")]);
+        assert!(!graph
+            .reachable_from(&roots)
+            .contains(Path::new("docs/orphaned.md")));
+    }
+
+    #[test]
+    fn test_shortest_path() {
+        let mut vfs = vfs::MemoryFileSystem::new();
+        vfs.insert("docs/README.md", "[Guides](guides/index.md)\n[FAQ](faq.md)");
+        vfs.insert(
+            "docs/guides/index.md",
+            "[Top](#top)\n[Setup](./setup.md#install)",
+        );
+        vfs.insert("docs/guides/setup.md", "# Install\n[Home](../README.md)");
+        vfs.insert("docs/faq.md", "# FAQ");
+        vfs.insert("docs/orphaned.md", "[FAQ](faq.md)");
+
+        let mut analyzer = LinkAnalyzer::new(PathBuf::from("docs")).with_filesystem(vfs);
+        analyzer.analyze_directory().unwrap();
+
+        let readme = analyzer
+            .find_document(Path::new("docs/./README.md"))
+            .unwrap();
+        let setup = analyzer
+            .find_document(Path::new("docs/guides/setup.md"))
+            .unwrap();
+        let hops: Vec<(usize, String)> = analyzer
+            .shortest_path(&readme, &setup)
+            .unwrap()
+            .into_iter()
+            .map(|link| (link.line_number, link.target))
+            .collect();
+        assert_eq!(
+            hops,
+            vec![
+                (1, "guides/index.md".to_string()),
+                (2, "./setup.md#install".to_string())
+            ]
+        );
+        assert_eq!(analyzer.shortest_path(&readme, &readme), Some(Vec::new()));
+        assert_eq!(
+            analyzer.shortest_path(&readme, Path::new("docs/orphaned.md")),
+            None
+        );
+        assert_eq!(analyzer.find_document(Path::new("docs/missing.md")), None);
+    }
+
+    #[test]
+    fn test_find_orphaned_documents() {
+        let temp_dir = TempDir::new().unwrap();
+        let base_path = temp_dir.path();
+
+        let readme_path = base_path.join("README.md");
+        let mut readme = fs::File::create(&readme_path).unwrap();
+        writeln!(readme, "[Link to doc1](./doc1.md)").unwrap();
+
+        let doc1_path = base_path.join("doc1.md");
+        let mut doc1 = fs::File::create(&doc1_path).unwrap();
+        writeln!(doc1, "[Link to doc2](./doc2.md)").unwrap();
+
+        let doc2_path = base_path.join("doc2.md");
+        let mut doc2 = fs::File::create(&doc2_path).unwrap();
+        writeln!(doc2, "# Doc 2").unwrap();
+
+        let orphaned_path = base_path.join("orphaned.md");
+        let mut orphaned = fs::File::create(&orphaned_path).unwrap();
+        writeln!(orphaned, "# Orphaned Document").unwrap();
+
+        let mut analyzer = LinkAnalyzer::new(base_path.to_path_buf());
+        analyzer.analyze_directory().unwrap();
+
+        let orphaned_docs = analyzer.find_orphaned_documents();
+        assert_eq!(orphaned_docs.len(), 1);
+        assert!(orphaned_docs[0].ends_with("orphaned.md"));
+    }
+
+    #[test]
+    fn test_readme_not_orphaned() {
+        let temp_dir = TempDir::new().unwrap();
+        let base_path = temp_dir.path();
+
+        let readme_path = base_path.join("README.md");
+        let mut readme = fs::File::create(&readme_path).unwrap();
+        writeln!(readme, "# Main README").unwrap();
+
+        let mut analyzer = LinkAnalyzer::new(base_path.to_path_buf());
+        analyzer.analyze_directory().unwrap();
+
+        let orphaned_docs = analyzer.find_orphaned_documents();
+        assert_eq!(orphaned_docs.len(), 0);
+    }
+}
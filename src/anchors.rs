@@ -0,0 +1,749 @@
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, LazyLock, Mutex};
+use std::time::SystemTime;
+
+static ATX_HEADING: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^ {0,3}(#{1,6})(?:[ \t]+(.*?))?(?:[ \t]+#+)?[ \t]*$").unwrap());
+static BLOCK_ID: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?:^|\s)\^([A-Za-z0-9-]+)\s*$").unwrap());
+static INLINE_LINK: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"!?\[([^\]]*)\]\([^)]*\)").unwrap());
+static SETEXT_UNDERLINE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^ {0,3}(=+|-+)[ \t]*$").unwrap());
+static HTML_HEADING: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?i)<h([1-6])(\s[^>]*)?>(.*?)</h[1-6]\s*>").unwrap());
+static HTML_ID: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"(?i)\bid\s*=\s*(?:"([^"]*)"|'([^']*)')"#).unwrap());
+static HEADING_ATTRIBUTES: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\s*\{([^{}]*)\}\s*$").unwrap());
+static EMPHASIS_UNDERSCORES: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(^|\s)_+|_+(\s|$)").unwrap());
+static HTML_TAG: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"<[^>]*>").unwrap());
+static VERSION_FRAGMENT: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)^v?(\d+(?:[-._]\d+){1,3})(?:[-._]?((?:alpha|beta|rc|pre)[-._]?\d*))?$")
+        .unwrap()
+});
+static HEADING_VERSION: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)(?:^|[^\w.])v?(\d+(?:\.\d+){1,3})(?:-((?:alpha|beta|rc|pre)\.?\d*))?\b")
+        .unwrap()
+});
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Heading {
+    pub level: usize,
+    pub text: String,
+    pub line_number: usize,
+    /// Explicit `id` of an HTML heading, used as its anchor verbatim.
+    pub id: Option<String>,
+}
+
+/// Which heading syntaxes are indexed for anchor checks.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct HeadingStyles {
+    pub atx: bool,
+    pub setext: bool,
+    pub html: bool,
+}
+
+impl Default for HeadingStyles {
+    fn default() -> Self {
+        Self {
+            atx: true,
+            setext: true,
+            html: true,
+        }
+    }
+}
+
+impl HeadingStyles {
+    /// Parses the `heading_styles` setting; an empty list enables every style.
+    pub fn parse(names: &[String]) -> Result<Self, String> {
+        if names.is_empty() {
+            return Ok(Self::default());
+        }
+
+        let mut styles = Self {
+            atx: false,
+            setext: false,
+            html: false,
+        };
+        for name in names {
+            match name.as_str() {
+                "atx" => styles.atx = true,
+                "setext" => styles.setext = true,
+                "html" => styles.html = true,
+                _ => {
+                    return Err(format!(
+                        "Unknown heading style `{}` (expected atx, setext or html)",
+                        name
+                    ))
+                }
+            }
+        }
+        Ok(styles)
+    }
+}
+
+pub fn extract_headings(content: &str) -> Vec<Heading> {
+    extract_headings_with(content, HeadingStyles::default())
+}
+
+pub fn extract_headings_with(content: &str, styles: HeadingStyles) -> Vec<Heading> {
+    let content = content.strip_prefix('\u{feff}').unwrap_or(content);
+    let mut headings = Vec::new();
+    let mut fence: Option<&str> = None;
+    let mut front_matter = content.starts_with("---\n") || content.starts_with("---\r\n");
+    // Lines of the paragraph a setext underline would turn into a heading.
+    let mut paragraph: Vec<(usize, &str)> = Vec::new();
+
+    for (line_num, line) in content.lines().enumerate() {
+        let trimmed = line.trim_start();
+        if front_matter {
+            if line_num > 0 && (line.trim_end() == "---" || line.trim_end() == "...") {
+                front_matter = false;
+            }
+            continue;
+        }
+        if let Some(marker) = fence {
+            if trimmed.starts_with(marker) {
+                fence = None;
+            }
+            continue;
+        }
+        if trimmed.starts_with("```") {
+            fence = Some("```");
+            paragraph.clear();
+            continue;
+        }
+        if trimmed.starts_with("~~~") {
+            fence = Some("~~~");
+            paragraph.clear();
+            continue;
+        }
+
+        if let Some(caps) = SETEXT_UNDERLINE.captures(line) {
+            if let Some(&(first_line, _)) = paragraph.first() {
+                if styles.setext {
+                    let text: Vec<&str> = paragraph.iter().map(|(_, text)| text.trim()).collect();
+                    headings.push(Heading {
+                        level: if caps[1].starts_with('=') { 1 } else { 2 },
+                        text: text.join(" "),
+                        line_number: first_line + 1,
+                        id: None,
+                    });
+                }
+            }
+            // Without a paragraph above, `---` is a thematic break.
+            paragraph.clear();
+            continue;
+        }
+
+        if let Some(caps) = ATX_HEADING.captures(line) {
+            paragraph.clear();
+            if styles.atx {
+                headings.push(Heading {
+                    level: caps[1].len(),
+                    text: caps.get(2).map_or("", |m| m.as_str()).to_string(),
+                    line_number: line_num + 1,
+                    id: None,
+                });
+            }
+            continue;
+        }
+
+        if styles.html {
+            for caps in HTML_HEADING.captures_iter(line) {
+                let id = caps.get(2).and_then(|attrs| {
+                    let id = HTML_ID.captures(attrs.as_str())?;
+                    id.get(1)
+                        .or_else(|| id.get(2))
+                        .map(|m| m.as_str().to_string())
+                });
+                headings.push(Heading {
+                    level: caps[1].parse().unwrap_or(1),
+                    text: HTML_TAG.replace_all(&caps[3], "").trim().to_string(),
+                    line_number: line_num + 1,
+                    id,
+                });
+            }
+        }
+
+        if trimmed.is_empty() || (paragraph.is_empty() && !starts_paragraph(trimmed)) {
+            paragraph.clear();
+        } else {
+            paragraph.push((line_num, line));
+        }
+    }
+
+    headings
+}
+
+/// Whether a line can start a paragraph, i.e. is not a list item, quote,
+/// table row or HTML block, which a `---` underline would not turn into a heading.
+fn starts_paragraph(line: &str) -> bool {
+    let list_item = line
+        .trim_start_matches(|c: char| c.is_ascii_digit())
+        .strip_prefix(['.', ')'])
+        .filter(|_| line.starts_with(|c: char| c.is_ascii_digit()))
+        .or_else(|| line.strip_prefix(['-', '*', '+']))
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with([' ', '\t']));
+    !list_item && !line.starts_with(['>', '|', '<'])
+}
+
+/// GitHub-style anchor for a heading: lowercased, punctuation dropped,
+/// spaces turned into hyphens.
+pub fn github_slug(text: &str) -> String {
+    let text = INLINE_LINK.replace_all(text.trim(), "$1");
+    text.to_lowercase()
+        .chars()
+        .filter_map(|c| match c {
+            ' ' => Some('-'),
+            '-' | '_' => Some(c),
+            c if c.is_alphanumeric() => Some(c),
+            _ => None,
+        })
+        .collect()
+}
+
+/// How headings are turned into anchors, from the `slugger` setting.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+#[non_exhaustive]
+pub enum Slugger {
+    /// GitHub's rules, see `github_slug`.
+    #[default]
+    Github,
+    /// Pandoc's `auto_identifiers`, see `pandoc_slug`. `{#id}` attributes
+    /// on headings set their anchor.
+    Pandoc,
+}
+
+impl Slugger {
+    /// Parses the `slugger` setting; empty means GitHub.
+    pub fn parse(name: &str) -> Result<Self, String> {
+        match name {
+            "" | "github" => Ok(Slugger::Github),
+            "pandoc" => Ok(Slugger::Pandoc),
+            _ => Err(format!(
+                "Unknown slugger `{}` (expected github or pandoc)",
+                name
+            )),
+        }
+    }
+
+    pub fn slug(self, text: &str) -> String {
+        match self {
+            Slugger::Github => github_slug(text),
+            Slugger::Pandoc => pandoc_slug(text),
+        }
+    }
+
+    /// Anchors of `headings`, with `-1`, `-2`... suffixes for repeated ones.
+    pub fn anchors(self, headings: &[Heading]) -> Vec<String> {
+        let mut seen: HashMap<String, usize> = HashMap::new();
+        let mut anchors = Vec::new();
+
+        for heading in headings {
+            if let Some(id) = &heading.id {
+                anchors.push(id.clone());
+                continue;
+            }
+            let mut text = heading.text.as_str();
+            if self == Slugger::Pandoc {
+                if let Some(caps) = HEADING_ATTRIBUTES.captures(text) {
+                    let id = caps[1]
+                        .split_whitespace()
+                        .find_map(|attribute| attribute.strip_prefix('#'));
+                    if let Some(id) = id {
+                        anchors.push(id.to_string());
+                        continue;
+                    }
+                    text = &text[..caps.get(0).unwrap().start()];
+                }
+            }
+            let slug = self.slug(text);
+            let count = seen.entry(slug.clone()).or_insert(0);
+            if *count == 0 {
+                anchors.push(slug);
+            } else {
+                anchors.push(format!("{}-{}", slug, count));
+            }
+            *count += 1;
+        }
+
+        anchors
+    }
+}
+
+/// Pandoc-style anchor for a heading: formatting and punctuation other than
+/// `_`, `-` and `.` dropped, runs of spaces turned into a hyphen, lowercased,
+/// and everything before the first letter removed. `section` if nothing is left.
+pub fn pandoc_slug(text: &str) -> String {
+    let text = INLINE_LINK.replace_all(text.trim(), "$1");
+    let text = EMPHASIS_UNDERSCORES.replace_all(&text, "$1$2");
+    let words: Vec<String> = text
+        .split_whitespace()
+        .map(|word| {
+            word.chars()
+                .filter(|&c| c.is_alphanumeric() || matches!(c, '_' | '-' | '.'))
+                .flat_map(char::to_lowercase)
+                .collect::<String>()
+        })
+        .filter(|word| !word.is_empty())
+        .collect();
+    let slug = words.join("-");
+    match slug.find(char::is_alphabetic) {
+        Some(start) => slug[start..].to_string(),
+        None => "section".to_string(),
+    }
+}
+
+/// All anchors a document exposes, with GitHub's `-1`, `-2`... suffixes for
+/// repeated headings.
+pub fn heading_anchors(content: &str) -> Vec<String> {
+    heading_anchors_with(content, HeadingStyles::default())
+}
+
+pub fn heading_anchors_with(content: &str, styles: HeadingStyles) -> Vec<String> {
+    Slugger::Github.anchors(&extract_headings_with(content, styles))
+}
+
+/// A document's headings, each with the anchor it gets.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DocumentHeadings {
+    pub headings: Vec<Heading>,
+    /// `anchors[i]` is the anchor of `headings[i]`.
+    pub anchors: Vec<String>,
+}
+
+impl DocumentHeadings {
+    pub fn parse(content: &str, styles: HeadingStyles) -> Self {
+        Self::parse_with(content, styles, Slugger::Github)
+    }
+
+    pub fn parse_with(content: &str, styles: HeadingStyles, slugger: Slugger) -> Self {
+        let headings = extract_headings_with(content, styles);
+        let anchors = slugger.anchors(&headings);
+        Self { headings, anchors }
+    }
+}
+
+/// A document's title: the `title` in its front matter `fields` (see
+/// `front_matter::fields`), or else the text of its first level-1 heading.
+pub fn document_title(fields: &BTreeMap<String, String>, headings: &[Heading]) -> Option<String> {
+    fields.get("title").cloned().or_else(|| {
+        headings
+            .iter()
+            .find(|heading| heading.level == 1)
+            .map(|heading| heading.text.clone())
+    })
+}
+
+/// What a file looked like when it was read, to tell whether headings
+/// saved from it are still current.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct FileStamp {
+    pub size: u64,
+    pub modified: SystemTime,
+}
+
+/// Headings by `document_key` of the document. A scan fills it with every document
+/// it reads, and other link targets are added the first time an anchor is
+/// checked in them, so each file is read and parsed once rather than once
+/// per link. Files that can't be read are remembered as `None`.
+#[derive(Debug, Default)]
+pub(crate) struct HeadingIndex {
+    documents: Mutex<HashMap<PathBuf, IndexedHeadings>>,
+}
+
+#[derive(Debug, Clone)]
+struct IndexedHeadings {
+    headings: Option<Arc<DocumentHeadings>>,
+    stamp: Option<FileStamp>,
+    /// Read in this process, rather than loaded from a saved index and not
+    /// yet compared with the file.
+    current: bool,
+}
+
+/// A heading index as saved to disk. Only files with a stamp are saved.
+#[derive(Debug, Serialize, Deserialize)]
+struct SavedIndex {
+    version: u32,
+    styles: HeadingStyles,
+    slugger: Slugger,
+    documents: BTreeMap<PathBuf, SavedHeadings>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct SavedHeadings {
+    stamp: FileStamp,
+    headings: Option<DocumentHeadings>,
+}
+
+impl HeadingIndex {
+    /// Bumped whenever what is saved changes meaning, e.g. how headings or
+    /// anchors are extracted.
+    const VERSION: u32 = 1;
+
+    pub(crate) fn insert(
+        &self,
+        path: PathBuf,
+        headings: DocumentHeadings,
+        stamp: Option<FileStamp>,
+    ) {
+        let mut documents = self.documents.lock().unwrap();
+        documents.insert(
+            path,
+            IndexedHeadings {
+                headings: Some(Arc::new(headings)),
+                stamp,
+                current: true,
+            },
+        );
+    }
+
+    /// The headings indexed for `path`, or else the ones `parse` returns,
+    /// which are indexed. Loaded headings are used if `stamp` still matches
+    /// the file. The index isn't locked while parsing.
+    pub(crate) fn get_or_parse(
+        &self,
+        path: &Path,
+        stamp: impl FnOnce() -> Option<FileStamp>,
+        parse: impl FnOnce() -> Option<DocumentHeadings>,
+    ) -> Option<Arc<DocumentHeadings>> {
+        let saved = match self.documents.lock().unwrap().get(path) {
+            Some(indexed) if indexed.current => return indexed.headings.clone(),
+            Some(indexed) => indexed.stamp,
+            None => None,
+        };
+        let stamp = stamp();
+        let mut documents = self.documents.lock().unwrap();
+        if let Some(indexed) = documents
+            .get_mut(path)
+            .filter(|_| saved.is_some() && saved == stamp)
+        {
+            indexed.current = true;
+            return indexed.headings.clone();
+        }
+        drop(documents);
+
+        let headings = parse().map(Arc::new);
+        let mut documents = self.documents.lock().unwrap();
+        documents.insert(
+            path.to_path_buf(),
+            IndexedHeadings {
+                headings: headings.clone(),
+                stamp,
+                current: true,
+            },
+        );
+        headings
+    }
+
+    pub(crate) fn remove(&self, path: &Path) {
+        self.documents.lock().unwrap().remove(path);
+    }
+
+    pub(crate) fn clear(&self) {
+        self.documents.lock().unwrap().clear();
+    }
+
+    pub(crate) fn save(
+        &self,
+        path: &Path,
+        styles: HeadingStyles,
+        slugger: Slugger,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let documents = self.documents.lock().unwrap();
+        let saved = SavedIndex {
+            version: Self::VERSION,
+            styles,
+            slugger,
+            documents: documents
+                .iter()
+                .filter_map(|(path, indexed)| {
+                    let saved = SavedHeadings {
+                        stamp: indexed.stamp?,
+                        headings: indexed.headings.as_deref().cloned(),
+                    };
+                    Some((path.clone(), saved))
+                })
+                .collect(),
+        };
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, serde_json::to_string(&saved)?)?;
+        Ok(())
+    }
+
+    /// Adds the documents saved at `path` that aren't indexed yet, unless it
+    /// is missing, unreadable or was saved for other heading styles or
+    /// another slugger. Returns how many were added.
+    pub(crate) fn load(&self, path: &Path, styles: HeadingStyles, slugger: Slugger) -> usize {
+        let Some(saved) = fs::read_to_string(path)
+            .ok()
+            .and_then(|content| serde_json::from_str::<SavedIndex>(&content).ok())
+            .filter(|saved| {
+                saved.version == Self::VERSION && saved.styles == styles && saved.slugger == slugger
+            })
+        else {
+            return 0;
+        };
+        let mut documents = self.documents.lock().unwrap();
+        let mut loaded = 0;
+        for (path, saved) in saved.documents {
+            documents.entry(path).or_insert_with(|| {
+                loaded += 1;
+                IndexedHeadings {
+                    headings: saved.headings.map(Arc::new),
+                    stamp: Some(saved.stamp),
+                    current: false,
+                }
+            });
+        }
+        loaded
+    }
+}
+
+/// The release a changelog-style fragment such as `v1-2-3` or `1.2.0-rc.1`
+/// points at, normalized to `1.2.3` or `1.2.0-rc1`.
+pub fn version_anchor(fragment: &str) -> Option<String> {
+    let caps = VERSION_FRAGMENT.captures(fragment)?;
+    Some(normalize_version(&caps[1], caps.get(2).map(|m| m.as_str())))
+}
+
+/// The release a changelog heading is for, normalized like
+/// `version_anchor`: `[1.2.3] - 2024-05-01` and `Version 1.2.3` are both
+/// `1.2.3`.
+pub fn heading_version(text: &str) -> Option<String> {
+    let caps = HEADING_VERSION.captures(text)?;
+    Some(normalize_version(&caps[1], caps.get(2).map(|m| m.as_str())))
+}
+
+fn normalize_version(numbers: &str, pre_release: Option<&str>) -> String {
+    let numbers: Vec<String> = numbers
+        .split(['-', '.', '_'])
+        .map(|part| part.trim_start_matches('0'))
+        .map(|part| if part.is_empty() { "0" } else { part }.to_string())
+        .collect();
+    let mut version = numbers.join(".");
+    if let Some(pre_release) = pre_release {
+        version.push('-');
+        version.extend(
+            pre_release
+                .chars()
+                .filter(|c| c.is_alphanumeric())
+                .flat_map(char::to_lowercase),
+        );
+    }
+    version
+}
+
+/// Anchors of `old` that were renamed in `new`, mapped to their new name.
+/// Anchors present in both versions line the two up; between them, a run of
+/// removed anchors is paired in order with an equally long run of added ones.
+pub fn renamed_anchors(old: &[String], new: &[String]) -> HashMap<String, String> {
+    // Longest common subsequence table of the suffixes.
+    let mut lengths = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lengths[i][j] = if old[i] == new[j] {
+                lengths[i + 1][j + 1] + 1
+            } else {
+                lengths[i + 1][j].max(lengths[i][j + 1])
+            };
+        }
+    }
+
+    let mut renamed = HashMap::new();
+    let (mut removed, mut added) = (Vec::new(), Vec::new());
+    let mut pair_up = |removed: &mut Vec<&String>, added: &mut Vec<&String>| {
+        if removed.len() == added.len() {
+            for (from, to) in removed.iter().zip(added.iter()) {
+                renamed.insert((*from).clone(), (*to).clone());
+            }
+        }
+        removed.clear();
+        added.clear();
+    };
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            pair_up(&mut removed, &mut added);
+            i += 1;
+            j += 1;
+        } else if j == new.len() || (i < old.len() && lengths[i + 1][j] >= lengths[i][j + 1]) {
+            removed.push(&old[i]);
+            i += 1;
+        } else {
+            added.push(&new[j]);
+            j += 1;
+        }
+    }
+    pair_up(&mut removed, &mut added);
+
+    // An anchor that still exists was moved, not renamed.
+    renamed.retain(|from, _| !new.contains(from));
+    renamed
+}
+
+/// Obsidian block IDs: `^id` markers at the end of a line.
+pub fn block_ids(content: &str) -> Vec<String> {
+    content
+        .lines()
+        .filter_map(|line| BLOCK_ID.captures(line))
+        .map(|caps| caps[1].to_string())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_github_slug() {
+        assert_eq!(github_slug("Getting Started"), "getting-started");
+        assert_eq!(github_slug("What's new in v1.2?"), "whats-new-in-v12");
+        assert_eq!(
+            github_slug("The [API](./api.md) `Client`"),
+            "the-api-client"
+        );
+        assert_eq!(github_slug("snake_case & more"), "snake_case--more");
+    }
+
+    #[test]
+    fn test_pandoc_slug() {
+        // The examples from Pandoc's manual, "Extension: auto_identifiers".
+        assert_eq!(
+            pandoc_slug("Heading identifiers in HTML"),
+            "heading-identifiers-in-html"
+        );
+        assert_eq!(pandoc_slug("Maître d'hôtel"), "maître-dhôtel");
+        assert_eq!(pandoc_slug("*Dogs*?--in *my* house?"), "dogs--in-my-house");
+        assert_eq!(pandoc_slug("[HTML], [S5], or [RTF]?"), "html-s5-or-rtf");
+        assert_eq!(pandoc_slug("3. Applications"), "applications");
+        assert_eq!(pandoc_slug("33"), "section");
+
+        assert_eq!(
+            pandoc_slug("Version 1.2  _release_ notes"),
+            "version-1.2-release-notes"
+        );
+        assert_eq!(pandoc_slug("snake_case & more"), "snake_case-more");
+    }
+
+    #[test]
+    fn test_slugger_anchors() {
+        let headings = extract_headings("# Intro\n## Setup {#install}\n## Notes {-}\n# Intro");
+        assert_eq!(
+            Slugger::Pandoc.anchors(&headings),
+            vec!["intro", "install", "notes", "intro-1"]
+        );
+        assert_eq!(
+            Slugger::Github.anchors(&headings),
+            vec!["intro", "setup-install", "notes--", "intro-1"]
+        );
+        assert_eq!(Slugger::parse(""), Ok(Slugger::Github));
+        assert!(Slugger::parse("kramdown").is_err());
+    }
+
+    #[test]
+    fn test_block_ids() {
+        let content = "A paragraph. ^intro\n\n- item ^list-1\n^standalone\nNot a^block";
+        assert_eq!(block_ids(content), vec!["intro", "list-1", "standalone"]);
+    }
+
+    #[test]
+    fn test_renamed_anchors() {
+        let anchors =
+            |names: &[&str]| -> Vec<String> { names.iter().map(|name| name.to_string()).collect() };
+        let old = anchors(&["intro", "install", "usage", "faq", "license"]);
+        let new = anchors(&[
+            "intro",
+            "installation",
+            "usage",
+            "new-section",
+            "extra",
+            "license",
+        ]);
+        let renamed = renamed_anchors(&old, &new);
+        assert_eq!(renamed.len(), 1);
+        assert_eq!(renamed["install"], "installation");
+
+        let reordered = renamed_anchors(&anchors(&["a", "b"]), &anchors(&["b", "a"]));
+        assert!(reordered.is_empty());
+    }
+
+    #[test]
+    fn test_heading_anchors() {
+        let content = "# Intro\n\n```\n# not a heading\n```\n\n## Usage\n### Usage ##\n#NoSpace";
+        assert_eq!(heading_anchors(content), vec!["intro", "usage", "usage-1"]);
+    }
+
+    #[test]
+    fn test_setext_headings() {
+        let content = "---\ntitle: Doc\n---\nGetting\nStarted\n=======\n\nUsage\n-----\n\n- item\n---\n\n---\n| a |\n|---|";
+        let headings = extract_headings(content);
+        let texts: Vec<(&str, usize, usize)> = headings
+            .iter()
+            .map(|h| (h.text.as_str(), h.level, h.line_number))
+            .collect();
+        assert_eq!(texts, vec![("Getting Started", 1, 4), ("Usage", 2, 8)]);
+    }
+
+    #[test]
+    fn test_crlf_headings() {
+        let content =
+            "\u{feff}# Intro\r\n---\r\ntitle\r\n---\r\nUsage\r\n=====\r\n## Install ##\r\n";
+        assert_eq!(
+            heading_anchors(content),
+            vec!["intro", "title", "usage", "install"]
+        );
+    }
+
+    #[test]
+    fn test_html_headings() {
+        let content = "<h2 id=\"custom-id\">Custom</h2>\n<H3 class='x'>Plain <code>Text</code></H3>\n# Custom";
+        assert_eq!(
+            heading_anchors(content),
+            vec!["custom-id", "plain-text", "custom"]
+        );
+    }
+
+    #[test]
+    fn test_heading_styles() {
+        let content = "# Atx\nSetext\n===\n<h2 id=\"html\">Html</h2>";
+        let styles = HeadingStyles::parse(&["atx".to_string()]).unwrap();
+        assert_eq!(heading_anchors_with(content, styles), vec!["atx"]);
+        assert_eq!(heading_anchors(content), vec!["atx", "setext", "html"]);
+        assert!(HeadingStyles::parse(&["rst".to_string()]).is_err());
+    }
+
+    #[test]
+    fn test_versions() {
+        assert_eq!(version_anchor("v1-2-3").as_deref(), Some("1.2.3"));
+        assert_eq!(version_anchor("1.2.0-rc.1").as_deref(), Some("1.2.0-rc1"));
+        assert_eq!(version_anchor("V2_0").as_deref(), Some("2.0"));
+        assert_eq!(version_anchor("installation"), None);
+        assert_eq!(version_anchor("v1"), None);
+
+        assert_eq!(
+            heading_version("[1.2.3] - 2024-05-01").as_deref(),
+            Some("1.2.3")
+        );
+        assert_eq!(
+            heading_version("Version v1.2.0-RC.1").as_deref(),
+            Some("1.2.0-rc1")
+        );
+        assert_eq!(
+            heading_version("Release 2.0 (2023-01-02)").as_deref(),
+            Some("2.0")
+        );
+        assert_eq!(heading_version("Unreleased"), None);
+        assert_eq!(heading_version("Released 2024-05-01"), None);
+    }
+}
@@ -0,0 +1,199 @@
+use crate::{BrokenLink, LinkAnalyzer};
+use regex::Regex;
+use std::sync::LazyLock;
+
+/// File name of the badge `CheckCache` in the cache directory.
+pub const CACHE_FILE_NAME: &str = "badges.json";
+
+static BADGE_LABEL: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"<title>([^<]*)</title>|aria-label="([^"]*)""#).unwrap());
+
+/// Hosts that only serve badges.
+const BADGE_HOSTS: &[&str] = &[
+    "img.shields.io",
+    "badgen.net",
+    "flat.badgen.net",
+    "badge.fury.io",
+    "badges.gitter.im",
+];
+
+/// Services that no longer run, as a host or `host/path` prefix and why
+/// badges and links pointing at them are dead.
+pub const ARCHIVED_SERVICES: &[(&str, &str)] = &[
+    (
+        "travis-ci.org",
+        "travis-ci.org was shut down; open source builds moved to travis-ci.com",
+    ),
+    (
+        "david-dm.org",
+        "David dependency badges are no longer served",
+    ),
+    (
+        "img.shields.io/david/",
+        "David dependency badges are no longer served",
+    ),
+    ("gemnasium.com", "Gemnasium was shut down"),
+    ("lgtm.com", "LGTM.com was shut down"),
+    ("img.shields.io/lgtm/", "LGTM.com was shut down"),
+];
+
+/// Words a badge service draws into the badge instead of failing the request.
+const BADGE_ERRORS: &[&str] = &[
+    "invalid",
+    "not found",
+    "no longer available",
+    "inaccessible",
+    "deprecated",
+];
+
+/// `(host, path)` of an http(s) URL, with the host lowercased and the path
+/// without query or fragment.
+pub(crate) fn host_and_path(url: &str) -> Option<(String, &str)> {
+    let rest = url
+        .strip_prefix("https://")
+        .or_else(|| url.strip_prefix("http://"))?;
+    let rest = rest.split(['?', '#']).next().unwrap_or(rest);
+    let (host, path) = rest.split_at(rest.find('/').unwrap_or(rest.len()));
+    Some((host.to_lowercase(), path))
+}
+
+/// Whether `url` is a status badge image, e.g. from shields.io, a CI service
+/// or a coverage service.
+pub fn is_badge(url: &str) -> bool {
+    let Some((host, path)) = host_and_path(url) else {
+        return false;
+    };
+    BADGE_HOSTS.contains(&host.as_str())
+        || path.ends_with("badge.svg")
+        || path.ends_with("/badge")
+        || path.ends_with("status.svg")
+        || path.contains("/badges/")
+        || path.contains("/project_badges/")
+        || (host.starts_with("travis-ci.") && path.ends_with(".svg"))
+        || (host == "ci.appveyor.com" && path.starts_with("/api/projects/status"))
+}
+
+/// Why `url` points at a service that no longer runs, if it does.
+pub fn archived_service(url: &str) -> Option<&'static str> {
+    let (host, path) = host_and_path(url)?;
+    let address = format!("{}{}", host, path);
+    ARCHIVED_SERVICES
+        .iter()
+        .find(|(prefix, _)| {
+            if prefix.contains('/') {
+                address.starts_with(prefix)
+            } else {
+                host == *prefix || host.ends_with(&format!(".{}", prefix))
+            }
+        })
+        .map(|(_, reason)| *reason)
+}
+
+/// The error a badge image shows, such as `build: repo not found`. Badge
+/// services answer `200 OK` for badges they cannot render, so the status
+/// alone does not tell.
+pub fn badge_error(svg: &str) -> Option<String> {
+    let caps = BADGE_LABEL.captures(svg)?;
+    let label = caps.get(1).or_else(|| caps.get(2))?.as_str().trim();
+    let value = label.rsplit(':').next().unwrap_or(label).to_lowercase();
+    BADGE_ERRORS
+        .iter()
+        .any(|error| value.contains(error))
+        .then(|| format!("Badge shows \"{}\"", label))
+}
+
+impl LinkAnalyzer {
+    /// External links and badges pointing at archived services; see
+    /// `ARCHIVED_SERVICES`. Needs no network access.
+    pub fn find_archived_badges(&self) -> Vec<BrokenLink> {
+        self.external_links()
+            .into_iter()
+            .filter_map(|link| {
+                let reason = archived_service(&link.target)?;
+                Some(BrokenLink {
+                    link,
+                    reason: reason.to_string(),
+                    suggestion: None,
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vfs::MemoryFileSystem;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_is_badge() {
+        assert!(is_badge("https://img.shields.io/crates/v/serde.svg"));
+        assert!(is_badge(
+            "https://github.com/org/repo/actions/workflows/ci.yml/badge.svg?branch=main"
+        ));
+        assert!(is_badge("https://travis-ci.org/org/repo.svg?branch=master"));
+        assert!(is_badge(
+            "https://codecov.io/gh/org/repo/branch/main/graph/badge.svg"
+        ));
+        assert!(is_badge(
+            "https://ci.appveyor.com/api/projects/status/abc123"
+        ));
+        assert!(!is_badge("https://github.com/org/repo/actions"));
+        assert!(!is_badge("https://travis-ci.org/org/repo"));
+        assert!(!is_badge("./badge.svg"));
+    }
+
+    #[test]
+    fn test_archived_service() {
+        assert!(archived_service("https://travis-ci.org/org/repo.svg").is_some());
+        assert!(archived_service("https://api.travis-ci.org/org/repo.svg").is_some());
+        assert!(archived_service("https://img.shields.io/david/org/repo").is_some());
+        assert_eq!(archived_service("https://travis-ci.com/org/repo.svg"), None);
+        assert_eq!(
+            archived_service("https://img.shields.io/crates/v/serde"),
+            None
+        );
+        assert_eq!(archived_service("https://nottravis-ci.org/"), None);
+    }
+
+    #[test]
+    fn test_badge_error() {
+        let badge = |label: &str| {
+            format!(
+                r#"<svg xmlns="http://www.w3.org/2000/svg" aria-label="{}"><title>{}</title></svg>"#,
+                label, label
+            )
+        };
+        assert_eq!(
+            badge_error(&badge("build: repo not found")).as_deref(),
+            Some("Badge shows \"build: repo not found\"")
+        );
+        assert!(badge_error(&badge("dependencies: no longer available")).is_some());
+        assert_eq!(badge_error(&badge("build: failing")), None);
+        assert_eq!(badge_error(&badge("crates.io: v1.0.219")), None);
+        assert_eq!(badge_error("<svg></svg>"), None);
+    }
+
+    #[test]
+    fn test_find_archived_badges() {
+        let mut fs = MemoryFileSystem::new();
+        fs.insert(
+            "docs/README.md",
+            "[![Build](https://travis-ci.org/org/repo.svg)](https://travis-ci.org/org/repo)\n\
+             [![Crate](https://img.shields.io/crates/v/repo.svg)](https://crates.io/crates/repo)",
+        );
+        let mut analyzer = LinkAnalyzer::new(PathBuf::from("docs")).with_filesystem(fs);
+        analyzer.analyze_directory().unwrap();
+
+        let targets: Vec<String> = analyzer
+            .find_archived_badges()
+            .into_iter()
+            .map(|broken| broken.link.target)
+            .collect();
+        assert_eq!(targets.len(), 2);
+        assert!(targets
+            .iter()
+            .all(|target| target.starts_with("https://travis-ci.org/")));
+    }
+}
@@ -0,0 +1,420 @@
+use serde::de::Error as _;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// Globs (relative to the analyzed directory) for link targets that are
+    /// generated at build time and should be treated as present.
+    pub assume_present: Vec<String>,
+    /// Globs (relative to the analyzed directory) for files and directories
+    /// not to walk, e.g. `node_modules` or `drafts/**`. Links to them still resolve.
+    pub exclude: Vec<String>,
+    /// Documents (relative to the analyzed directory) readers start from
+    /// besides the top-level README. They are never orphans, and
+    /// reachability and sitemaps start from them too.
+    pub entry_points: Vec<String>,
+    /// File names that make a document at the top of the analyzed directory
+    /// a root like `entry_points`, e.g. `["Home.md"]` for a GitHub wiki.
+    /// Empty means `README.md`, `readme.md`, `index.md`, `Home.md` and
+    /// `SUMMARY.md`.
+    pub root_names: Vec<String>,
+    /// Globs (relative to the analyzed directory) for more root documents,
+    /// e.g. `**/README.md` to make every directory's README one.
+    pub root_globs: Vec<String>,
+    /// Walk into symlinked files and directories. A document reached under
+    /// several paths is analyzed once, and the other paths are its aliases.
+    pub follow_symlinks: bool,
+    /// Walk files and directories listed in `.gitignore` too, which are
+    /// skipped by default, e.g. generated Markdown in build output.
+    pub no_gitignore: bool,
+    /// Unicode normalization file names and link targets are compared under:
+    /// `none` (the default), `nfc`, so names macOS stores decomposed match
+    /// links written composed, or `nfkc`, which also folds compatibility
+    /// characters.
+    pub unicode_normalization: String,
+    /// URL the documentation is published at, e.g. `https://docs.example.com/`.
+    /// Absolute links under it are validated against the local files.
+    pub base_url: String,
+    /// Suffixes tried, in order, for link targets without an extension, e.g.
+    /// `[".md", "/index.md", "/README.md"]` resolves `./guide` to `guide.md`.
+    pub extensionless_candidates: Vec<String>,
+    /// Where the documents are published: `markdown` (the default),
+    /// `github-wiki`, which checks wiki links the way GitHub wikis resolve
+    /// them and never reports `_Sidebar.md` or `_Footer.md` as orphans, or
+    /// `azure-devops-wiki`, which resolves page links the way Azure DevOps
+    /// does and checks the pages listed in `.order` files.
+    pub flavor: String,
+    /// Check Obsidian-style `[[note]]` links and `![[file]]` embeds, resolved
+    /// by file name anywhere under the analyzed directory.
+    pub wiki_links: bool,
+    /// Also check links written inside inline code and code blocks, which
+    /// are skipped by default as examples.
+    pub code_links: bool,
+    /// Regexes for link targets to leave out of every check, e.g. `\.pdf$`.
+    pub ignore_targets: Vec<String>,
+    /// Regexes matched against HTML comment bodies whose first capture group
+    /// is a link target, e.g. `embed:\s*(\S+)` for `<!-- embed: ./x.md -->`.
+    pub comment_directives: Vec<String>,
+    /// Heading syntaxes anchors are checked against: any of `atx`, `setext`
+    /// and `html`. Empty means all of them.
+    pub heading_styles: Vec<String>,
+    /// How headings become anchors: `github` (the default) or `pandoc`, for
+    /// documents rendered by Pandoc, e.g. to LaTeX or PDF.
+    pub slugger: String,
+    /// Check changelog-style fragments such as `#v1-2-3` against the versions
+    /// headings name, e.g. `## [1.2.3] - 2024-05-01`, whatever their slug.
+    pub version_anchors: bool,
+    /// Judge external links by HTTP status details: permanent redirects warn,
+    /// 410 Gone is an error and 503 is ignored as transient.
+    pub external_status_rules: bool,
+    /// HTTP status codes external links may answer with and still pass,
+    /// e.g. `[403, 429]` for sites that turn away automated requests.
+    #[serde(deserialize_with = "status_codes")]
+    pub accepted_status: Vec<u16>,
+    /// Seconds to wait for each external request before giving up, unless
+    /// `--request-timeout` is given, also as a duration such as `"30s"` or
+    /// `"1m"`. `0` means 10 seconds.
+    #[serde(deserialize_with = "seconds")]
+    pub external_timeout: u64,
+    /// Headers sent with external requests to URLs starting with a prefix,
+    /// e.g. `[external_headers."https://api.github.com/"]` with
+    /// `Authorization = "Bearer …"`. The prefix `""` matches every URL.
+    pub external_headers: BTreeMap<String, BTreeMap<String, String>>,
+    /// Failing external URLs that passed in recent runs are reported as flaky
+    /// until they fail this many runs in a row. `0` disables flaky tracking.
+    pub flaky_threshold: usize,
+    /// Refuse to connect to loopback, private, link-local (cloud metadata)
+    /// and other internal addresses when checking external links.
+    pub external_block_internal: bool,
+    /// CIDR ranges external link checks may always connect to, even when
+    /// internal or denied, e.g. `["10.1.2.0/24"]`.
+    pub external_allow_cidrs: Vec<String>,
+    /// CIDR ranges external link checks must not connect to.
+    pub external_deny_cidrs: Vec<String>,
+    /// Skip documents larger than this many bytes, and don't read larger
+    /// link targets for their headings. `0` is unlimited.
+    pub max_file_bytes: u64,
+    /// Fail the scan once the documents read add up to more than this many
+    /// bytes. `0` is unlimited.
+    pub max_total_bytes: u64,
+    /// Tutorials whose pages must chain together in this order through
+    /// "Next" and "Previous" links, by name, e.g.
+    /// `[sequences] tutorial = ["tutorial/intro.md", "tutorial/setup.md"]`.
+    pub sequences: BTreeMap<String, Vec<String>>,
+    /// Output format of `check`, `text` (the default), `csv`, `sarif` or
+    /// `junit`, unless `--format` is given.
+    pub format: String,
+    /// Named sets of overrides, e.g. `[profile.ci]`, selected with `--profile`.
+    pub profile: BTreeMap<String, toml::Table>,
+}
+
+impl Config {
+    pub const FILE_NAME: &'static str = ".doclink.toml";
+    pub const ENV_PREFIX: &'static str = "DOCLINK_";
+    /// Variables read by the CLI itself rather than mapped onto config keys:
+    /// the config file and profile, and switches that only exist as flags.
+    pub const RESERVED_ENV: &'static [&'static str] = &[
+        "DOCLINK_CONFIG",
+        "DOCLINK_PROFILE",
+        "DOCLINK_EXTERNAL",
+        "DOCLINK_PACKAGES",
+        "DOCLINK_OFFLINE",
+        "DOCLINK_TIMEOUT",
+        "DOCLINK_REQUEST_TIMEOUT",
+        "DOCLINK_JOBS",
+    ];
+    /// Keys holding whole seconds, which also accept durations such as `5s`.
+    const SECONDS_KEYS: &'static [&'static str] = &["external_timeout"];
+
+    pub fn load(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        let content = fs::read_to_string(path)?;
+        Self::parse(&content).map_err(|e| format!("{}: {}", path.display(), e).into())
+    }
+
+    /// Parses a configuration file, rejecting unknown keys both at the top
+    /// level and inside every profile.
+    pub fn parse(content: &str) -> Result<Self, toml::de::Error> {
+        let config: Config = toml::from_str(content)?;
+        for (name, overrides) in &config.profile {
+            config
+                .apply(overrides)
+                .map_err(|e| toml::de::Error::custom(format!("profile.{}: {}", name, e)))?;
+        }
+        Ok(config)
+    }
+
+    /// Loads `.doclink.toml` from `base_path` if present, otherwise returns the defaults.
+    pub fn discover(base_path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        let path = base_path.join(Self::FILE_NAME);
+        if path.is_file() {
+            Self::load(&path)
+        } else {
+            Ok(Self::default())
+        }
+    }
+
+    /// Returns this configuration with the keys of profile `name` replacing
+    /// the top-level values.
+    pub fn with_profile(&self, name: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        let overrides = self.profile.get(name).ok_or_else(|| {
+            let available: Vec<&str> = self.profile.keys().map(String::as_str).collect();
+            if available.is_empty() {
+                format!("Unknown profile `{}` (no profiles are configured)", name)
+            } else {
+                format!(
+                    "Unknown profile `{}` (available: {})",
+                    name,
+                    available.join(", ")
+                )
+            }
+        })?;
+        Ok(self.apply(overrides)?)
+    }
+
+    /// Overrides config keys from `DOCLINK_<KEY>` variables, e.g.
+    /// `DOCLINK_ASSUME_PRESENT=api/**,CHANGELOG.md`. Lists are comma-separated.
+    pub fn apply_env(
+        &self,
+        vars: impl IntoIterator<Item = (String, String)>,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        let defaults = toml::Table::try_from(Config::default())?;
+        let mut overrides = toml::Table::new();
+
+        for (name, value) in vars {
+            let Some(key) = name.strip_prefix(Self::ENV_PREFIX) else {
+                continue;
+            };
+            if Self::RESERVED_ENV.contains(&name.as_str()) {
+                continue;
+            }
+
+            let key = key.to_lowercase();
+            let value = match defaults.get(&key) {
+                Some(_) if Self::SECONDS_KEYS.contains(&key.as_str()) => toml::Value::Integer(
+                    whole_seconds(&value).map_err(|e| format!("{}: {}", name, e))? as i64,
+                ),
+                Some(toml::Value::Array(_)) => toml::Value::Array(
+                    value
+                        .split(',')
+                        .map(str::trim)
+                        .filter(|item| !item.is_empty())
+                        .map(|item| toml::Value::String(item.to_string()))
+                        .collect(),
+                ),
+                Some(toml::Value::Boolean(_)) => toml::Value::Boolean(
+                    parse_bool(&value).map_err(|e| format!("{}: {}", name, e))?,
+                ),
+                Some(toml::Value::Integer(_)) => toml::Value::Integer(
+                    value
+                        .parse()
+                        .map_err(|_| format!("{}: expected an integer, got `{}`", name, value))?,
+                ),
+                Some(toml::Value::Table(_)) | None => {
+                    return Err(format!("Unknown environment variable {}", name).into())
+                }
+                Some(_) => toml::Value::String(value),
+            };
+            overrides.insert(key, value);
+        }
+
+        if overrides.is_empty() {
+            return Ok(self.clone());
+        }
+        Ok(self.apply(&overrides)?)
+    }
+
+    fn apply(&self, overrides: &toml::Table) -> Result<Self, toml::de::Error> {
+        if overrides.contains_key("profile") {
+            return Err(toml::de::Error::custom("profiles cannot be nested"));
+        }
+
+        let mut table = toml::Table::try_from(self).map_err(toml::de::Error::custom)?;
+        table.remove("profile");
+        for (key, value) in overrides {
+            table.insert(key.clone(), value.clone());
+        }
+
+        let mut merged: Config = toml::Value::Table(table).try_into()?;
+        merged.profile = self.profile.clone();
+        Ok(merged)
+    }
+}
+
+/// Parses `1`, `true`, `yes` and `on`, or `0`, `false`, `no`, `off` and the empty string.
+pub fn parse_bool(text: &str) -> Result<bool, String> {
+    match text.to_lowercase().as_str() {
+        "1" | "true" | "yes" | "on" => Ok(true),
+        "0" | "false" | "no" | "off" | "" => Ok(false),
+        _ => Err(format!("expected a boolean, got `{}`", text)),
+    }
+}
+
+/// Parses durations such as `120s`, `2m`, `1h` or `500ms`; a bare number is seconds.
+pub fn parse_duration(text: &str) -> Result<Duration, String> {
+    let text = text.trim();
+    let split = text
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(text.len());
+    let (number, unit) = text.split_at(split);
+    let number: f64 = number
+        .parse()
+        .map_err(|_| format!("invalid duration `{}`", text))?;
+    let seconds = match unit.trim() {
+        "" | "s" => number,
+        "ms" => number / 1000.0,
+        "m" => number * 60.0,
+        "h" => number * 3600.0,
+        unit => return Err(format!("unknown unit `{}` (use ms, s, m or h)", unit)),
+    };
+    Duration::try_from_secs_f64(seconds).map_err(|e| e.to_string())
+}
+
+fn whole_seconds(text: &str) -> Result<u64, String> {
+    let duration = parse_duration(text)?;
+    if duration.subsec_nanos() != 0 {
+        return Err(format!(
+            "`{}` is not a whole number of seconds",
+            text.trim()
+        ));
+    }
+    Ok(duration.as_secs())
+}
+
+/// Seconds as an integer, or as a duration string such as `"30s"`.
+fn seconds<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<u64, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Seconds {
+        Number(u64),
+        Text(String),
+    }
+
+    match Seconds::deserialize(deserializer)? {
+        Seconds::Number(seconds) => Ok(seconds),
+        Seconds::Text(text) => whole_seconds(&text).map_err(D::Error::custom),
+    }
+}
+
+/// Status codes as integers, or as the strings `DOCLINK_ACCEPTED_STATUS`
+/// splits into.
+fn status_codes<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<Vec<u16>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Code {
+        Number(u16),
+        Text(String),
+    }
+
+    Vec::<Code>::deserialize(deserializer)?
+        .into_iter()
+        .map(|code| match code {
+            Code::Number(code) => Ok(code),
+            Code::Text(text) => text
+                .parse()
+                .map_err(|_| D::Error::custom(format!("invalid status code `{}`", text))),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_config() {
+        let config = Config::parse("assume_present = [\"api/**\", \"CHANGELOG.md\"]").unwrap();
+        assert_eq!(config.assume_present, vec!["api/**", "CHANGELOG.md"]);
+
+        let config = Config::parse("accepted_status = [403, 429]").unwrap();
+        assert_eq!(config.accepted_status, vec![403, 429]);
+
+        let config = Config::parse("external_timeout = \"1m\"").unwrap();
+        assert_eq!(config.external_timeout, 60);
+
+        let config = Config::parse(
+            "[external_headers.\"https://api.github.com/\"]\nAuthorization = \"Bearer x\"",
+        )
+        .unwrap();
+        assert_eq!(
+            config.external_headers["https://api.github.com/"]["Authorization"],
+            "Bearer x"
+        );
+
+        let config = Config::parse("").unwrap();
+        assert_eq!(config, Config::default());
+    }
+
+    #[test]
+    fn test_unknown_fields_are_rejected() {
+        assert!(Config::parse("assume_presnt = []").is_err());
+        assert!(Config::parse("[profile.ci]\nassume_presnt = []").is_err());
+    }
+
+    #[test]
+    fn test_apply_env() {
+        let config = Config::parse("assume_present = [\"api/**\"]").unwrap();
+        let vars = vec![
+            ("HOME".to_string(), "/root".to_string()),
+            ("DOCLINK_PROFILE".to_string(), "ci".to_string()),
+            (
+                "DOCLINK_ASSUME_PRESENT".to_string(),
+                "CHANGELOG.md, dist/**".to_string(),
+            ),
+        ];
+
+        let config = config.apply_env(vars).unwrap();
+        assert_eq!(config.assume_present, vec!["CHANGELOG.md", "dist/**"]);
+
+        let status = vec![(
+            "DOCLINK_ACCEPTED_STATUS".to_string(),
+            "403, 429".to_string(),
+        )];
+        assert_eq!(
+            config.apply_env(status).unwrap().accepted_status,
+            vec![403, 429]
+        );
+        let status = vec![(
+            "DOCLINK_ACCEPTED_STATUS".to_string(),
+            "forbidden".to_string(),
+        )];
+        assert!(config.apply_env(status).is_err());
+
+        let timeout = vec![("DOCLINK_EXTERNAL_TIMEOUT".to_string(), "2m".to_string())];
+        assert_eq!(config.apply_env(timeout).unwrap().external_timeout, 120);
+        let timeout = vec![("DOCLINK_EXTERNAL_TIMEOUT".to_string(), "500ms".to_string())];
+        assert!(config.apply_env(timeout).is_err());
+        let cli_only = vec![("DOCLINK_OFFLINE".to_string(), "1".to_string())];
+        assert_eq!(config.apply_env(cli_only).unwrap(), config);
+
+        let err = config
+            .apply_env(vec![("DOCLINK_ASUME".to_string(), "x".to_string())])
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Unknown environment variable DOCLINK_ASUME"
+        );
+    }
+
+    #[test]
+    fn test_profiles() {
+        let content = "assume_present = [\"api/**\"]\n\n[profile.ci]\nassume_present = []\n\n[profile.local]\n";
+        let config = Config::parse(content).unwrap();
+
+        let ci = config.with_profile("ci").unwrap();
+        assert!(ci.assume_present.is_empty());
+
+        let local = config.with_profile("local").unwrap();
+        assert_eq!(local.assume_present, vec!["api/**"]);
+
+        let err = config.with_profile("nightly").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Unknown profile `nightly` (available: ci, local)"
+        );
+    }
+}
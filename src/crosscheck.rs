@@ -0,0 +1,232 @@
+use regex::Regex;
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+
+static SITEMAP_LOC: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"<loc>\s*([^<\s]+)\s*</loc>").unwrap());
+static LLMS_LINK: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\[[^\]]*\]\((https?://[^)\s]+)\)|(https?://[^\s)<>]+)").unwrap());
+
+/// Differences between a published site and the local documents.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[non_exhaustive]
+pub struct CrossCheck {
+    /// Published URLs with no local document behind them.
+    pub missing_locally: Vec<String>,
+    /// Local documents (relative paths) that are not published.
+    pub unpublished: Vec<PathBuf>,
+    /// Published URLs that only loosely map to a local document.
+    pub mismatched: Vec<(String, String)>,
+}
+
+impl CrossCheck {
+    pub fn is_clean(&self) -> bool {
+        self.missing_locally.is_empty() && self.unpublished.is_empty() && self.mismatched.is_empty()
+    }
+}
+
+/// URLs listed in a `sitemap.xml` (`<loc>` entries) or an `llms.txt` (links
+/// and bare URLs).
+pub fn published_urls(content: &str) -> Vec<String> {
+    let urls: Vec<String> = if content.contains("<urlset") || content.contains("<loc>") {
+        SITEMAP_LOC
+            .captures_iter(content)
+            .map(|caps| xml_unescape(&caps[1]))
+            .collect()
+    } else {
+        LLMS_LINK
+            .captures_iter(content)
+            .filter_map(|caps| caps.get(1).or_else(|| caps.get(2)))
+            .map(|m| m.as_str().to_string())
+            .collect()
+    };
+
+    let mut seen = BTreeSet::new();
+    urls.into_iter()
+        .filter(|url| seen.insert(url.clone()))
+        .collect()
+}
+
+/// Local paths a published URL path may have been generated from, most
+/// likely first: `guide/` maps to `guide/index.md`, `guide/README.md` or
+/// `guide.md`, and `guide.html` to `guide.md`.
+pub fn candidate_paths(url_path: &str) -> Vec<String> {
+    let path = url_path.trim_start_matches('/');
+    if path.is_empty() {
+        return vec!["README.md".to_string(), "index.md".to_string()];
+    }
+    if path.ends_with(".md") {
+        return vec![path.to_string()];
+    }
+    if let Some(dir) = path.strip_suffix('/') {
+        return vec![
+            format!("{}/index.md", dir),
+            format!("{}/README.md", dir),
+            format!("{}.md", dir),
+        ];
+    }
+    if let Some(stem) = path
+        .strip_suffix(".html")
+        .or_else(|| path.strip_suffix(".htm"))
+    {
+        return match stem.strip_suffix("index") {
+            Some(dir) => vec![format!("{}index.md", dir), format!("{}README.md", dir)],
+            None => vec![format!("{}.md", stem)],
+        };
+    }
+
+    vec![
+        format!("{}.md", path),
+        format!("{}/index.md", path),
+        format!("{}/README.md", path),
+    ]
+}
+
+/// Compares published URLs under `base_url` with the local documents, given
+/// as paths relative to the analyzed directory.
+pub fn cross_check(urls: &[String], base_url: &str, documents: &[PathBuf]) -> CrossCheck {
+    let base_url = base_url.trim_end_matches('/');
+    let local: BTreeMap<String, &PathBuf> = documents
+        .iter()
+        .map(|doc| (doc.to_string_lossy().replace('\\', "/"), doc))
+        .collect();
+    let mut published: BTreeSet<&PathBuf> = BTreeSet::new();
+    let mut result = CrossCheck::default();
+
+    for url in urls {
+        let Some(url_path) = url
+            .strip_prefix(base_url)
+            .filter(|rest| rest.is_empty() || rest.starts_with('/'))
+        else {
+            result
+                .mismatched
+                .push((url.clone(), format!("not under base URL {}", base_url)));
+            continue;
+        };
+        let url_path = url_path.split(['?', '#']).next().unwrap_or_default();
+        let candidates = candidate_paths(url_path);
+
+        if let Some(doc) = candidates.iter().find_map(|c| local.get(c)) {
+            published.insert(doc);
+            continue;
+        }
+
+        let loose = candidates.iter().find_map(|candidate| {
+            local
+                .iter()
+                .find(|(path, _)| path.eq_ignore_ascii_case(candidate))
+        });
+        match loose {
+            Some((path, doc)) => {
+                published.insert(doc);
+                result.mismatched.push((
+                    url.clone(),
+                    format!("matches {} only when ignoring case", path),
+                ));
+            }
+            None => result.missing_locally.push(url.clone()),
+        }
+    }
+
+    result.unpublished = documents
+        .iter()
+        .filter(|doc| !published.contains(doc))
+        .cloned()
+        .collect();
+    result.unpublished.sort();
+    result
+}
+
+/// Reads a sitemap or llms.txt from a local file or an http(s) URL.
+pub fn read_source(source: &str) -> Result<String, Box<dyn std::error::Error>> {
+    if source.starts_with("http://") || source.starts_with("https://") {
+        #[cfg(feature = "external-http")]
+        return Ok(reqwest::blocking::get(source)?.error_for_status()?.text()?);
+        #[cfg(not(feature = "external-http"))]
+        return Err("Fetching URLs requires the `external-http` feature".into());
+    } else {
+        Ok(std::fs::read_to_string(Path::new(source))?)
+    }
+}
+
+fn xml_unescape(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_published_urls() {
+        let sitemap = "<?xml version=\"1.0\"?>\n<urlset>\n  <url><loc>https://docs.example.com/guide/</loc></url>\n  <url><loc> https://docs.example.com/a?x=1&amp;y=2 </loc></url>\n</urlset>";
+        assert_eq!(
+            published_urls(sitemap),
+            vec![
+                "https://docs.example.com/guide/",
+                "https://docs.example.com/a?x=1&y=2"
+            ]
+        );
+
+        let llms = "# Example\n\n- [Guide](https://docs.example.com/guide.md): how to\n- https://docs.example.com/api/\n- [Guide again](https://docs.example.com/guide.md)";
+        assert_eq!(
+            published_urls(llms),
+            vec![
+                "https://docs.example.com/guide.md",
+                "https://docs.example.com/api/"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_candidate_paths() {
+        assert_eq!(candidate_paths("/"), vec!["README.md", "index.md"]);
+        assert_eq!(
+            candidate_paths("/guide/"),
+            vec!["guide/index.md", "guide/README.md", "guide.md"]
+        );
+        assert_eq!(candidate_paths("/guide/setup.html"), vec!["guide/setup.md"]);
+        assert_eq!(
+            candidate_paths("/guide/index.html"),
+            vec!["guide/index.md", "guide/README.md"]
+        );
+        assert_eq!(candidate_paths("/api.md"), vec!["api.md"]);
+    }
+
+    #[test]
+    fn test_cross_check() {
+        let urls: Vec<String> = [
+            "https://docs.example.com/",
+            "https://docs.example.com/guide/",
+            "https://docs.example.com/Install",
+            "https://docs.example.com/removed/",
+            "https://other.example.com/guide/",
+        ]
+        .iter()
+        .map(|url| url.to_string())
+        .collect();
+        let documents: Vec<PathBuf> = ["README.md", "guide/README.md", "install.md", "draft.md"]
+            .iter()
+            .map(PathBuf::from)
+            .collect();
+
+        let result = cross_check(&urls, "https://docs.example.com/", &documents);
+        assert_eq!(
+            result.missing_locally,
+            vec!["https://docs.example.com/removed/"]
+        );
+        assert_eq!(result.unpublished, vec![PathBuf::from("draft.md")]);
+        assert_eq!(result.mismatched.len(), 2);
+        assert_eq!(result.mismatched[0].0, "https://docs.example.com/Install");
+        assert_eq!(
+            result.mismatched[0].1,
+            "matches install.md only when ignoring case"
+        );
+        assert!(result.mismatched[1].1.starts_with("not under base URL"));
+    }
+}
@@ -0,0 +1,276 @@
+use crate::{BrokenLink, LinkAnalyzer, LinkKind};
+use rusqlite::{params, Connection};
+use std::collections::HashSet;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Bumped whenever a table or column changes, and stored as the database's
+/// `user_version`.
+pub const SCHEMA_VERSION: i32 = 2;
+
+/// Paths are relative to the run's `root`. Every row carries the `run_id` it
+/// was recorded in, so one database can hold the history of many runs.
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS runs (
+    id INTEGER PRIMARY KEY,
+    root TEXT NOT NULL,
+    checked_at INTEGER NOT NULL,
+    tool_version TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS documents (
+    run_id INTEGER NOT NULL REFERENCES runs(id),
+    path TEXT NOT NULL,
+    orphan INTEGER NOT NULL,
+    modified_at INTEGER,
+    PRIMARY KEY (run_id, path)
+);
+CREATE TABLE IF NOT EXISTS links (
+    run_id INTEGER NOT NULL REFERENCES runs(id),
+    source TEXT NOT NULL,
+    line INTEGER NOT NULL,
+    text TEXT NOT NULL,
+    target TEXT NOT NULL,
+    kind TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS edges (
+    run_id INTEGER NOT NULL REFERENCES runs(id),
+    source TEXT NOT NULL,
+    target TEXT NOT NULL,
+    PRIMARY KEY (run_id, source, target)
+);
+CREATE TABLE IF NOT EXISTS findings (
+    run_id INTEGER NOT NULL REFERENCES runs(id),
+    kind TEXT NOT NULL,
+    source TEXT NOT NULL,
+    line INTEGER NOT NULL,
+    text TEXT NOT NULL,
+    target TEXT NOT NULL,
+    reason TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS links_target ON links(run_id, target);
+CREATE INDEX IF NOT EXISTS findings_kind ON findings(run_id, kind);
+";
+
+/// What a check found, by how it was reported.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Findings<'a> {
+    pub broken: &'a [BrokenLink],
+    pub warnings: &'a [BrokenLink],
+    pub flaky: &'a [BrokenLink],
+    pub ignored: &'a [BrokenLink],
+}
+
+/// A SQLite database that analyses are appended to, for ad-hoc SQL over
+/// documentation health.
+pub struct Database {
+    pub(crate) conn: Connection,
+}
+
+impl Database {
+    /// Opens `path`, creating it and its tables if needed.
+    pub fn open(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        let conn = Connection::open(path)?;
+        let version: i32 = conn.query_row("PRAGMA user_version", [], |row| row.get(0))?;
+        if version > SCHEMA_VERSION {
+            return Err(format!(
+                "{} has schema version {}, newer than this doclink-checker understands ({})",
+                path.display(),
+                version,
+                SCHEMA_VERSION
+            )
+            .into());
+        }
+        if version == 1 {
+            conn.execute_batch("ALTER TABLE documents ADD COLUMN modified_at INTEGER")?;
+        }
+        conn.execute_batch(SCHEMA)?;
+        conn.pragma_update(None, "user_version", SCHEMA_VERSION)?;
+        Ok(Self { conn })
+    }
+
+    /// Records the documents, links and link graph of an analyzed directory
+    /// along with `findings`, as one new run. Returns the run's id.
+    pub fn record_run(
+        &mut self,
+        analyzer: &LinkAnalyzer,
+        findings: Findings,
+    ) -> Result<i64, Box<dyn std::error::Error>> {
+        let root = &analyzer.base_path;
+        let relative = |path: &Path| {
+            path.strip_prefix(root)
+                .unwrap_or(path)
+                .to_string_lossy()
+                .into_owned()
+        };
+        let checked_at = unix_seconds(SystemTime::now());
+
+        let tx = self.conn.transaction()?;
+        tx.execute(
+            "INSERT INTO runs (root, checked_at, tool_version) VALUES (?1, ?2, ?3)",
+            params![
+                root.to_string_lossy(),
+                checked_at,
+                env!("CARGO_PKG_VERSION")
+            ],
+        )?;
+        let run_id = tx.last_insert_rowid();
+
+        let orphans: HashSet<_> = analyzer.find_orphaned_documents().into_iter().collect();
+        {
+            let mut insert = tx.prepare(
+                "INSERT INTO documents (run_id, path, orphan, modified_at) VALUES (?1, ?2, ?3, ?4)",
+            )?;
+            for path in analyzer.document_paths() {
+                let modified_at = analyzer.fs.modified(&path).ok().map(unix_seconds);
+                insert.execute(params![
+                    run_id,
+                    relative(&path),
+                    orphans.contains(&path),
+                    modified_at
+                ])?;
+            }
+
+            let mut insert = tx.prepare(
+                "INSERT INTO links (run_id, source, line, text, target, kind)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            )?;
+            for link in analyzer.documents.values().flatten() {
+                let kind = serde_json::to_value(LinkKind::classify(&link.target))?;
+                insert.execute(params![
+                    run_id,
+                    relative(&link.file_path),
+                    link.line_number as i64,
+                    link.text,
+                    link.target,
+                    kind.as_str().unwrap_or_default(),
+                ])?;
+            }
+
+            let mut insert =
+                tx.prepare("INSERT INTO edges (run_id, source, target) VALUES (?1, ?2, ?3)")?;
+            for (source, targets) in &analyzer.link_graph().edges {
+                for target in targets {
+                    insert.execute(params![run_id, relative(source), relative(target)])?;
+                }
+            }
+
+            let mut insert = tx.prepare(
+                "INSERT INTO findings (run_id, kind, source, line, text, target, reason)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            )?;
+            for (kind, list) in [
+                ("broken", findings.broken),
+                ("warning", findings.warnings),
+                ("flaky", findings.flaky),
+                ("ignored", findings.ignored),
+            ] {
+                for finding in list {
+                    let link = &finding.link;
+                    insert.execute(params![
+                        run_id,
+                        kind,
+                        relative(&link.file_path),
+                        link.line_number as i64,
+                        link.text,
+                        link.target,
+                        finding.reason,
+                    ])?;
+                }
+            }
+        }
+        tx.commit()?;
+        Ok(run_id)
+    }
+}
+
+pub(crate) fn unix_seconds(time: SystemTime) -> i64 {
+    time.duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs() as i64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_record_run() {
+        let temp_dir = TempDir::new().unwrap();
+        let docs = temp_dir.path().join("docs");
+        fs::create_dir(&docs).unwrap();
+        fs::write(
+            docs.join("README.md"),
+            "# Docs\n\n[Guide](./guide.md)\n\n[Gone](./gone.md)\n\n[Site](https://example.com)\n",
+        )
+        .unwrap();
+        fs::write(docs.join("guide.md"), "# Guide\n").unwrap();
+        fs::write(docs.join("old.md"), "# Old\n").unwrap();
+
+        let mut analyzer = LinkAnalyzer::new(docs.clone());
+        analyzer.analyze_directory().unwrap();
+        let broken = analyzer.find_broken_links();
+
+        let path = temp_dir.path().join("findings.sqlite");
+        let mut database = Database::open(&path).unwrap();
+        let findings = Findings {
+            broken: &broken,
+            ..Findings::default()
+        };
+        let first = database.record_run(&analyzer, findings).unwrap();
+        let second = database.record_run(&analyzer, findings).unwrap();
+        assert_eq!(second, first + 1);
+        drop(database);
+
+        let database = Database::open(&path).unwrap();
+        let query = |sql: &str| -> Vec<String> {
+            let mut statement = database.conn.prepare(sql).unwrap();
+            statement
+                .query_map([second], |row| row.get(0))
+                .unwrap()
+                .map(Result::unwrap)
+                .collect()
+        };
+        assert_eq!(
+            query("SELECT path FROM documents WHERE run_id = ?1 AND orphan ORDER BY path"),
+            vec!["old.md"]
+        );
+        assert_eq!(
+            query("SELECT source || ' -> ' || target FROM edges WHERE run_id = ?1"),
+            vec!["README.md -> guide.md"]
+        );
+        assert_eq!(
+            query("SELECT kind FROM links WHERE run_id = ?1 ORDER BY line"),
+            vec!["internal", "internal", "external"]
+        );
+        assert_eq!(
+            query("SELECT target || ': ' || reason FROM findings WHERE run_id = ?1 AND kind = 'broken'"),
+            vec![format!("./gone.md: {}", broken[0].reason)]
+        );
+    }
+
+    #[test]
+    fn test_migrate_version_1() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("findings.sqlite");
+        let conn = Connection::open(&path).unwrap();
+        conn.execute_batch(
+            "CREATE TABLE documents (run_id INTEGER NOT NULL, path TEXT NOT NULL,
+                 orphan INTEGER NOT NULL, PRIMARY KEY (run_id, path));
+             PRAGMA user_version = 1;",
+        )
+        .unwrap();
+        drop(conn);
+
+        let database = Database::open(&path).unwrap();
+        let version: i32 = database
+            .conn
+            .query_row("PRAGMA user_version", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(version, SCHEMA_VERSION);
+        database
+            .conn
+            .execute("INSERT INTO documents VALUES (1, 'a.md', 0, 0)", [])
+            .unwrap();
+    }
+}
@@ -0,0 +1,331 @@
+use crate::config::Config;
+use globset::Glob;
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[non_exhaustive]
+pub enum Severity {
+    Ok,
+    Warning,
+    Error,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub check: &'static str,
+    pub message: String,
+}
+
+impl Diagnostic {
+    fn new(severity: Severity, check: &'static str, message: impl Into<String>) -> Self {
+        Self {
+            severity,
+            check,
+            message: message.into(),
+        }
+    }
+}
+
+/// Runs every self-test: the configuration file, the selected profile, the
+/// cache directory and the external tools doclink-checker shells out to.
+pub fn run(config_path: &Path, profile: Option<&str>, cache_dir: &Path) -> Vec<Diagnostic> {
+    let mut diagnostics = check_config_file(config_path);
+    if let Some(profile) = profile {
+        let config = if config_path.is_file() {
+            Config::load(config_path).ok()
+        } else {
+            Some(Config::default())
+        };
+        // An unloadable file has already been reported above.
+        diagnostics.extend(config.map(|config| check_profile(&config, profile)));
+    }
+    diagnostics.extend(check_env(std::env::vars()));
+    diagnostics.extend(check_cache(cache_dir));
+    diagnostics.push(check_git());
+    diagnostics
+}
+
+pub fn check_config_file(path: &Path) -> Vec<Diagnostic> {
+    if !path.is_file() {
+        return vec![Diagnostic::new(
+            Severity::Ok,
+            "config",
+            format!("No {} found, using defaults", path.display()),
+        )];
+    }
+
+    match fs::read_to_string(path) {
+        Ok(content) => check_config(&content),
+        Err(e) => vec![Diagnostic::new(
+            Severity::Error,
+            "config",
+            format!("Cannot read {}: {}", path.display(), e),
+        )],
+    }
+}
+
+pub fn check_config(content: &str) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+
+    let table: toml::Table = match toml::from_str(content) {
+        Ok(table) => table,
+        Err(e) => {
+            return vec![Diagnostic::new(
+                Severity::Error,
+                "config",
+                format!("Invalid TOML: {}", e.message()),
+            )]
+        }
+    };
+
+    let known_keys = toml::Table::try_from(Config::default()).unwrap_or_default();
+    check_keys(&table, &known_keys, "", &mut diagnostics);
+    if let Some(toml::Value::Table(profiles)) = table.get("profile") {
+        for (name, profile) in profiles {
+            if let toml::Value::Table(profile) = profile {
+                check_keys(
+                    profile,
+                    &known_keys,
+                    &format!("profile.{}.", name),
+                    &mut diagnostics,
+                );
+            }
+        }
+    }
+    if !diagnostics.is_empty() {
+        return diagnostics;
+    }
+
+    let config = match Config::parse(content) {
+        Ok(config) => config,
+        Err(e) => {
+            diagnostics.push(Diagnostic::new(
+                Severity::Error,
+                "config",
+                e.message().to_string(),
+            ));
+            return diagnostics;
+        }
+    };
+
+    check_patterns(&config, "", &mut diagnostics);
+    for name in config.profile.keys() {
+        if let Ok(profile) = config.with_profile(name) {
+            check_patterns(&profile, &format!("profile.{}.", name), &mut diagnostics);
+        }
+    }
+
+    if diagnostics.is_empty() {
+        diagnostics.push(Diagnostic::new(
+            Severity::Ok,
+            "config",
+            "Configuration is valid",
+        ));
+    }
+
+    diagnostics
+}
+
+fn check_keys(
+    table: &toml::Table,
+    known_keys: &toml::Table,
+    prefix: &str,
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    for key in table.keys() {
+        let nested_profile = key == "profile" && !prefix.is_empty();
+        if known_keys.contains_key(key) && !nested_profile {
+            continue;
+        }
+        let suggestion = known_keys
+            .keys()
+            .map(|known| (strsim::levenshtein(key, known), known))
+            .min()
+            .filter(|(distance, _)| *distance <= 3)
+            .map(|(_, known)| format!(" (did you mean `{}{}`?)", prefix, known))
+            .unwrap_or_default();
+        diagnostics.push(Diagnostic::new(
+            Severity::Error,
+            "config",
+            format!("Unknown key `{}{}`{}", prefix, key, suggestion),
+        ));
+    }
+}
+
+fn check_patterns(config: &Config, prefix: &str, diagnostics: &mut Vec<Diagnostic>) {
+    for pattern in &config.ignore_targets {
+        if let Err(e) = regex::Regex::new(pattern) {
+            diagnostics.push(Diagnostic::new(
+                Severity::Error,
+                "config",
+                format!("Invalid regex in `{}ignore_targets`: {}", prefix, e),
+            ));
+        }
+    }
+    for pattern in &config.comment_directives {
+        if let Err(e) = crate::compile_directive(pattern) {
+            diagnostics.push(Diagnostic::new(
+                Severity::Error,
+                "config",
+                format!("{} (in `{}comment_directives`)", e, prefix),
+            ));
+        }
+    }
+
+    for (index, pattern) in config.assume_present.iter().enumerate() {
+        if let Err(e) = Glob::new(pattern) {
+            diagnostics.push(Diagnostic::new(
+                Severity::Error,
+                "config",
+                format!("Invalid glob in `{}assume_present`: {}", prefix, e),
+            ));
+        }
+        if config.assume_present[..index].contains(pattern) {
+            diagnostics.push(Diagnostic::new(
+                Severity::Warning,
+                "config",
+                format!("Duplicate `{}assume_present` pattern `{}`", prefix, pattern),
+            ));
+        }
+    }
+}
+
+pub fn check_profile(config: &Config, name: &str) -> Diagnostic {
+    match config.with_profile(name) {
+        Ok(_) => Diagnostic::new(Severity::Ok, "config", format!("Using profile `{}`", name)),
+        Err(e) => Diagnostic::new(Severity::Error, "config", e.to_string()),
+    }
+}
+
+pub fn check_env(vars: impl IntoIterator<Item = (String, String)>) -> Option<Diagnostic> {
+    let vars: Vec<(String, String)> = vars
+        .into_iter()
+        .filter(|(name, _)| name.starts_with(Config::ENV_PREFIX))
+        .collect();
+    if vars.is_empty() {
+        return None;
+    }
+
+    let names: Vec<&str> = vars.iter().map(|(name, _)| name.as_str()).collect();
+    let message = format!("Environment overrides in effect: {}", names.join(", "));
+    Some(match Config::default().apply_env(vars.clone()) {
+        Ok(_) => Diagnostic::new(Severity::Ok, "env", message),
+        Err(e) => Diagnostic::new(Severity::Error, "env", e.to_string()),
+    })
+}
+
+pub fn check_cache(cache_dir: &Path) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+
+    let probe = cache_dir.join(".doctor-probe");
+    let writable = fs::create_dir_all(cache_dir)
+        .and_then(|_| fs::write(&probe, b"ok"))
+        .and_then(|_| fs::remove_file(&probe));
+    match writable {
+        Ok(()) => diagnostics.push(Diagnostic::new(
+            Severity::Ok,
+            "cache",
+            format!("Cache directory {} is writable", cache_dir.display()),
+        )),
+        Err(e) => {
+            diagnostics.push(Diagnostic::new(
+                Severity::Error,
+                "cache",
+                format!(
+                    "Cache directory {} is not writable: {} (set XDG_CACHE_HOME to a writable location)",
+                    cache_dir.display(),
+                    e
+                ),
+            ));
+            return diagnostics;
+        }
+    }
+
+    if let Ok(entries) = fs::read_dir(cache_dir.join("repos")) {
+        for entry in entries.flatten() {
+            if entry.path().is_dir() && !entry.path().join(".git").is_dir() {
+                diagnostics.push(Diagnostic::new(
+                    Severity::Warning,
+                    "cache",
+                    format!(
+                        "Cached checkout {} is not a git repository; delete it to re-clone",
+                        entry.path().display()
+                    ),
+                ));
+            }
+        }
+    }
+
+    diagnostics
+}
+
+pub fn check_git() -> Diagnostic {
+    match Command::new("git").arg("--version").output() {
+        Ok(output) if output.status.success() => Diagnostic::new(
+            Severity::Ok,
+            "git",
+            String::from_utf8_lossy(&output.stdout).trim().to_string(),
+        ),
+        _ => Diagnostic::new(
+            Severity::Warning,
+            "git",
+            "git not found on PATH; `check --against` and `check --git` are unavailable",
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_config_reports_unknown_keys() {
+        let diagnostics = check_config("assume_presnt = []\n\n[profile.ci]\nasume_present = []");
+        let messages: Vec<&str> = diagnostics.iter().map(|d| d.message.as_str()).collect();
+
+        assert_eq!(
+            messages,
+            vec![
+                "Unknown key `assume_presnt` (did you mean `assume_present`?)",
+                "Unknown key `profile.ci.asume_present` (did you mean `profile.ci.assume_present`?)",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_check_config_reports_bad_globs() {
+        let diagnostics = check_config(
+            "assume_present = [\"a/[\", \"x\", \"x\"]\n\n[profile.ci]\nassume_present = [\"{\"]",
+        );
+
+        assert_eq!(diagnostics.len(), 3);
+        assert!(diagnostics[0]
+            .message
+            .starts_with("Invalid glob in `assume_present`"));
+        assert_eq!(diagnostics[1].severity, Severity::Warning);
+        assert!(diagnostics[2]
+            .message
+            .starts_with("Invalid glob in `profile.ci.assume_present`"));
+    }
+
+    #[test]
+    fn test_check_profile() {
+        let config = Config::parse("[profile.ci]\nassume_present = [\"api/**\"]").unwrap();
+
+        assert_eq!(check_profile(&config, "ci").severity, Severity::Ok);
+        let unknown = check_profile(&config, "release");
+        assert_eq!(unknown.severity, Severity::Error);
+        assert_eq!(unknown.message, "Unknown profile `release` (available: ci)");
+    }
+
+    #[test]
+    fn test_check_config_valid() {
+        let diagnostics = check_config("assume_present = [\"api/**\"]");
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].severity, Severity::Ok);
+    }
+}
@@ -0,0 +1,204 @@
+use crate::LinkAnalyzer;
+use serde::Serialize;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::hash::{Hash, Hasher};
+use std::path::PathBuf;
+
+/// Words per shingle; five keeps boilerplate phrases from matching on their own.
+const SHINGLE_WORDS: usize = 5;
+/// Documents shorter than this are stubs, which would all match each other.
+pub const MIN_WORDS: usize = 20;
+/// MinHash signature length, split into `BANDS` bands for candidate lookup.
+/// 32 bands of 4 rows find nearly every pair above 60% similarity.
+const SIGNATURE_LEN: usize = 128;
+const BANDS: usize = 32;
+
+/// Two documents whose content is mostly the same.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[non_exhaustive]
+pub struct DuplicatePair {
+    pub first: PathBuf,
+    pub second: PathBuf,
+    /// Jaccard similarity of their word shingles, from 0.0 to 1.0.
+    pub similarity: f64,
+}
+
+/// Hashes of every run of `SHINGLE_WORDS` consecutive words, ignoring case
+/// and punctuation. Empty for documents under `MIN_WORDS` words.
+pub fn shingles(content: &str) -> HashSet<u64> {
+    let words: Vec<String> = content
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect();
+    if words.len() < MIN_WORDS {
+        return HashSet::new();
+    }
+    words
+        .windows(SHINGLE_WORDS)
+        .map(|window| {
+            let mut hasher = DefaultHasher::new();
+            window.hash(&mut hasher);
+            hasher.finish()
+        })
+        .collect()
+}
+
+/// Pairs of documents at least `threshold` similar, most similar first.
+///
+/// Candidates come from MinHash signatures bucketed by band, so the cost
+/// grows with the number of documents rather than the number of pairs; each
+/// candidate's similarity is then computed exactly.
+pub fn find_duplicates(documents: &[(PathBuf, String)], threshold: f64) -> Vec<DuplicatePair> {
+    let shingles: Vec<HashSet<u64>> = documents
+        .iter()
+        .map(|(_, content)| shingles(content))
+        .collect();
+
+    let mut buckets: HashMap<(usize, Vec<u64>), Vec<usize>> = HashMap::new();
+    for (index, set) in shingles.iter().enumerate() {
+        if set.is_empty() {
+            continue;
+        }
+        let signature = min_hash(set);
+        for (band, rows) in signature.chunks(SIGNATURE_LEN / BANDS).enumerate() {
+            buckets
+                .entry((band, rows.to_vec()))
+                .or_default()
+                .push(index);
+        }
+    }
+
+    let mut candidates = BTreeSet::new();
+    for members in buckets.values() {
+        for (i, &a) in members.iter().enumerate() {
+            for &b in &members[i + 1..] {
+                candidates.insert((a, b));
+            }
+        }
+    }
+
+    let mut pairs: Vec<DuplicatePair> = candidates
+        .into_iter()
+        .filter_map(|(a, b)| {
+            let similarity = jaccard(&shingles[a], &shingles[b]);
+            (similarity >= threshold).then(|| DuplicatePair {
+                first: documents[a].0.clone(),
+                second: documents[b].0.clone(),
+                similarity,
+            })
+        })
+        .collect();
+    pairs.sort_by(|a, b| {
+        b.similarity
+            .total_cmp(&a.similarity)
+            .then_with(|| a.first.cmp(&b.first))
+            .then_with(|| a.second.cmp(&b.second))
+    });
+    pairs
+}
+
+fn min_hash(shingles: &HashSet<u64>) -> Vec<u64> {
+    (0..SIGNATURE_LEN as u64)
+        .map(|seed| {
+            shingles
+                .iter()
+                .map(|&shingle| mix(shingle ^ seed.wrapping_mul(0x9e37_79b9_7f4a_7c15)))
+                .min()
+                .unwrap_or(u64::MAX)
+        })
+        .collect()
+}
+
+/// The SplitMix64 finalizer, turning one hash into an independent-looking one.
+fn mix(mut z: u64) -> u64 {
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+fn jaccard(a: &HashSet<u64>, b: &HashSet<u64>) -> f64 {
+    let shared = a.intersection(b).count();
+    let total = a.len() + b.len() - shared;
+    if total == 0 {
+        0.0
+    } else {
+        shared as f64 / total as f64
+    }
+}
+
+impl LinkAnalyzer {
+    /// Near-duplicate pairs among the analyzed documents; see
+    /// `find_duplicates`. Unreadable documents are skipped.
+    pub fn find_duplicate_documents(&self, threshold: f64) -> Vec<DuplicatePair> {
+        let documents: Vec<(PathBuf, String)> = self
+            .document_paths()
+            .into_iter()
+            .filter_map(|path| {
+                let content = self.read_document(&path).ok()?;
+                Some((path, content))
+            })
+            .collect();
+        find_duplicates(&documents, threshold)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vfs::MemoryFileSystem;
+
+    const GUIDE: &str =
+        "# Installing\n\nDownload the latest release archive for your platform from the \
+        releases page, unpack it somewhere on your PATH and run the binary once to create the \
+        default configuration file in your home directory. Afterwards edit the file to point at \
+        your documentation root and run a first check to make sure everything is wired up.";
+
+    #[test]
+    fn test_find_duplicate_documents() {
+        let mut fs = MemoryFileSystem::new();
+        fs.insert("docs/install.md", GUIDE);
+        fs.insert(
+            "docs/legacy/install.md",
+            GUIDE.replace("latest release", "newest release").as_str(),
+        );
+        fs.insert("docs/copy.md", format!("{}\n", GUIDE).as_str());
+        fs.insert(
+            "docs/other.md",
+            "# Contributing\n\nOpen an issue before starting on a large change so we can agree \
+             on the approach, then send a pull request with tests and a short description of \
+             what changed and why it matters for users of the tool.",
+        );
+        fs.insert("docs/stub-a.md", "# TODO\n\nWrite this page.");
+        fs.insert("docs/stub-b.md", "# TODO\n\nWrite this page.");
+
+        let mut analyzer = LinkAnalyzer::new(PathBuf::from("docs")).with_filesystem(fs);
+        analyzer.analyze_directory().unwrap();
+        let pairs = analyzer.find_duplicate_documents(0.8);
+
+        let summary: Vec<(&str, &str)> = pairs
+            .iter()
+            .map(|pair| (pair.first.to_str().unwrap(), pair.second.to_str().unwrap()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("docs/copy.md", "docs/install.md"),
+                ("docs/copy.md", "docs/legacy/install.md"),
+                ("docs/install.md", "docs/legacy/install.md"),
+            ]
+        );
+        assert_eq!(pairs[0].similarity, 1.0);
+        assert!(pairs[1].similarity > 0.8 && pairs[1].similarity < 1.0);
+    }
+
+    #[test]
+    fn test_shingles_ignore_case_and_punctuation() {
+        assert_eq!(
+            shingles(GUIDE),
+            shingles(&GUIDE.to_uppercase().replace(',', ";"))
+        );
+        assert!(shingles("# TODO\n\nWrite this page.").is_empty());
+    }
+}
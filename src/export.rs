@@ -0,0 +1,540 @@
+use crate::sitemap::xml_escape;
+use crate::{anchors, split_fragment, LinkAnalyzer, LinkKind, MarkdownLink};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+/// A document in an exported link graph.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[non_exhaustive]
+pub struct GraphNode {
+    /// Path relative to the analyzed directory, with `/` separators.
+    pub id: String,
+    /// Text of the first top-level heading, if any.
+    pub title: Option<String>,
+    pub words: usize,
+    pub orphan: bool,
+    /// Broken links in the document.
+    pub broken: usize,
+    /// Other paths the document was found under, through symlinks.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub aliases: Vec<String>,
+    /// In a section graph, how many documents readers could no longer reach
+    /// from the README if this section and its links were removed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub strands: Option<usize>,
+}
+
+/// One internal link between two documents. Documents linking to each other
+/// several times have one edge per link.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[non_exhaustive]
+pub struct GraphEdge {
+    pub source: String,
+    pub target: String,
+    pub line: usize,
+    pub text: String,
+}
+
+/// The whole link graph with document metadata, in D3's node-link layout:
+/// edges name their nodes by `id`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[non_exhaustive]
+pub struct GraphExport {
+    pub directed: bool,
+    pub nodes: Vec<GraphNode>,
+    pub links: Vec<GraphEdge>,
+}
+
+impl GraphExport {
+    /// The graph as GraphML, e.g. for Gephi or yEd.
+    pub fn to_graphml(&self) -> String {
+        let mut output = String::from(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">\n",
+        );
+        for (id, owner, name, kind) in [
+            ("title", "node", "title", "string"),
+            ("words", "node", "words", "int"),
+            ("orphan", "node", "orphan", "boolean"),
+            ("broken", "node", "broken", "int"),
+            ("strands", "node", "strands", "int"),
+            ("line", "edge", "line", "int"),
+            ("text", "edge", "text", "string"),
+        ] {
+            output.push_str(&format!(
+                "  <key id=\"{}\" for=\"{}\" attr.name=\"{}\" attr.type=\"{}\"/>\n",
+                id, owner, name, kind
+            ));
+        }
+        output.push_str("  <graph edgedefault=\"directed\">\n");
+        for node in &self.nodes {
+            output.push_str(&format!("    <node id=\"{}\">\n", xml_escape(&node.id)));
+            let mut data = Vec::new();
+            if let Some(title) = &node.title {
+                data.push(("title", xml_escape(title)));
+            }
+            data.push(("words", node.words.to_string()));
+            data.push(("orphan", node.orphan.to_string()));
+            data.push(("broken", node.broken.to_string()));
+            if let Some(strands) = node.strands {
+                data.push(("strands", strands.to_string()));
+            }
+            for (key, value) in data {
+                output.push_str(&format!("      <data key=\"{}\">{}</data>\n", key, value));
+            }
+            output.push_str("    </node>\n");
+        }
+        for edge in &self.links {
+            output.push_str(&format!(
+                "    <edge source=\"{}\" target=\"{}\">\n      <data key=\"line\">{}</data>\n      <data key=\"text\">{}</data>\n    </edge>\n",
+                xml_escape(&edge.source),
+                xml_escape(&edge.target),
+                edge.line,
+                xml_escape(&edge.text)
+            ));
+        }
+        output.push_str("  </graph>\n</graphml>\n");
+        output
+    }
+
+    /// A standalone HTML page drawing the graph with a force layout, nodes
+    /// colored by broken links and orphan status, and a search box.
+    #[cfg(feature = "html-report")]
+    pub fn to_html(&self) -> String {
+        // `</script>` inside a string would end the script early.
+        let data = serde_json::to_string(self)
+            .unwrap_or_default()
+            .replace("</", "<\\/");
+        HTML_TEMPLATE.replace("/*GRAPH*/null", &data)
+    }
+}
+
+#[cfg(feature = "html-report")]
+const HTML_TEMPLATE: &str = include_str!("export/graph.html");
+
+impl LinkAnalyzer {
+    /// The link graph of the last scan, with each document's title, word
+    /// count and orphan flag, and each link's line.
+    pub fn export_graph(&self) -> GraphExport {
+        let id = |path: &Path| self.graph_id(path);
+        let orphans: HashSet<PathBuf> = self.find_orphaned_documents().into_iter().collect();
+        let mut broken: HashMap<PathBuf, usize> = HashMap::new();
+        for broken_link in self.find_broken_links() {
+            *broken.entry(broken_link.link.file_path).or_default() += 1;
+        }
+        let documents: HashMap<PathBuf, PathBuf> = self
+            .documents
+            .keys()
+            .map(|doc| (self.document_key(doc), doc.clone()))
+            .collect();
+
+        let mut export = GraphExport {
+            directed: true,
+            nodes: Vec::new(),
+            links: Vec::new(),
+        };
+        for path in self.document_paths() {
+            let content = self.read_document(&path).unwrap_or_default();
+            export.nodes.push(GraphNode {
+                id: id(&path),
+                title: self.document_title(&path).map(str::to_string),
+                words: word_count(&content),
+                orphan: orphans.contains(&path),
+                broken: broken.get(&path).copied().unwrap_or(0),
+                aliases: self.aliases(&path).iter().map(|alias| id(alias)).collect(),
+                strands: None,
+            });
+
+            let mut links: Vec<_> = self.documents[&path].iter().collect();
+            links.sort_by_key(|link| link.line_number);
+            for link in links {
+                if LinkKind::classify(&link.target) == LinkKind::SameDocument {
+                    continue;
+                }
+                let Some(target) = self
+                    .resolve(&path, &link.target)
+                    .resolved
+                    .and_then(|resolved| documents.get(&resolved))
+                else {
+                    continue;
+                };
+                export.links.push(GraphEdge {
+                    source: id(&path),
+                    target: id(target),
+                    line: link.line_number,
+                    text: link.text.clone(),
+                });
+            }
+        }
+        export
+    }
+
+    /// Like `export_graph`, but with a node per section: `guide.md#usage`
+    /// for each heading, and `guide.md` for the text above the first one.
+    /// Edges run from the section a link is in to the section it points at,
+    /// or to the top of the document for links without a `#fragment`.
+    pub fn export_section_graph(&self) -> GraphExport {
+        let orphans: HashSet<PathBuf> = self.find_orphaned_documents().into_iter().collect();
+        let broken: Vec<MarkdownLink> = self
+            .find_broken_links()
+            .into_iter()
+            .map(|broken_link| broken_link.link)
+            .collect();
+        let paths = self.document_paths();
+        let index: HashMap<PathBuf, usize> = paths
+            .iter()
+            .enumerate()
+            .map(|(i, doc)| (self.document_key(doc), i))
+            .collect();
+        let sections: Vec<Vec<Section>> = paths
+            .iter()
+            .map(|path| self.sections(&self.read_document(path).unwrap_or_default()))
+            .collect();
+        // Node index of each document's first section.
+        let first: Vec<usize> = sections
+            .iter()
+            .scan(0, |next, sections| {
+                let first = *next;
+                *next += sections.len();
+                Some(first)
+            })
+            .collect();
+
+        let mut export = GraphExport {
+            directed: true,
+            nodes: Vec::new(),
+            links: Vec::new(),
+        };
+        // (source node, target document) of every edge, for `strands`.
+        let mut hops: Vec<(usize, usize)> = Vec::new();
+        for (doc, path) in paths.iter().enumerate() {
+            let section_id = |section: &Section| match &section.anchor {
+                Some(anchor) => format!("{}#{}", self.graph_id(path), anchor),
+                None => self.graph_id(path),
+            };
+            for section in &sections[doc] {
+                export.nodes.push(GraphNode {
+                    id: section_id(section),
+                    title: section.title.clone(),
+                    words: section.words,
+                    orphan: orphans.contains(path),
+                    broken: broken
+                        .iter()
+                        .filter(|link| {
+                            link.file_path == *path
+                                && section_at(&sections[doc], link.line_number).anchor
+                                    == section.anchor
+                        })
+                        .count(),
+                    aliases: Vec::new(),
+                    strands: None,
+                });
+            }
+
+            let mut links: Vec<_> = self.documents[path].iter().collect();
+            links.sort_by_key(|link| link.line_number);
+            for link in links {
+                let (target_doc, fragment) =
+                    if LinkKind::classify(&link.target) == LinkKind::SameDocument {
+                        (doc, split_fragment(&link.target).1.map(str::to_string))
+                    } else {
+                        let resolution = self.resolve(path, &link.target);
+                        let Some(&target_doc) = resolution
+                            .resolved
+                            .and_then(|resolved| index.get(&resolved))
+                        else {
+                            continue;
+                        };
+                        (target_doc, resolution.fragment)
+                    };
+                let target = match fragment.filter(|fragment| !fragment.is_empty()) {
+                    None => Some(&sections[target_doc][0]),
+                    Some(fragment) => [
+                        fragment.clone(),
+                        fragment.to_lowercase(),
+                        anchors::github_slug(&fragment),
+                    ]
+                    .into_iter()
+                    .find_map(|anchor| {
+                        sections[target_doc]
+                            .iter()
+                            .find(|section| section.anchor.as_ref() == Some(&anchor))
+                    }),
+                };
+                // Links to missing headings are broken links, not edges.
+                let Some(target) = target else {
+                    continue;
+                };
+                let source = section_at(&sections[doc], link.line_number);
+                let source_node = first[doc]
+                    + sections[doc]
+                        .iter()
+                        .position(|section| section.anchor == source.anchor)
+                        .unwrap_or(0);
+                hops.push((source_node, target_doc));
+                export.links.push(GraphEdge {
+                    source: section_id(source),
+                    target: match &target.anchor {
+                        Some(anchor) => format!("{}#{}", self.graph_id(&paths[target_doc]), anchor),
+                        None => self.graph_id(&paths[target_doc]),
+                    },
+                    line: link.line_number,
+                    text: link.text.clone(),
+                });
+            }
+        }
+
+        let roots: Vec<usize> = self
+            .root_documents()
+            .iter()
+            .filter_map(|root| index.get(&self.document_key(root)).copied())
+            .collect();
+        if !roots.is_empty() {
+            let node_doc: Vec<usize> = (0..paths.len())
+                .flat_map(|doc| std::iter::repeat_n(doc, sections[doc].len()))
+                .collect();
+            let mut outbound: Vec<Vec<(usize, usize)>> = vec![Vec::new(); paths.len()];
+            for &(node, target) in &hops {
+                outbound[node_doc[node]].push((node, target));
+            }
+            let reachable = |removed: Option<usize>| {
+                let mut seen = vec![false; paths.len()];
+                let mut stack = roots.clone();
+                for &root in &roots {
+                    seen[root] = true;
+                }
+                let mut count = 0;
+                while let Some(doc) = stack.pop() {
+                    count += 1;
+                    for &(node, target) in &outbound[doc] {
+                        if Some(node) != removed && !seen[target] {
+                            seen[target] = true;
+                            stack.push(target);
+                        }
+                    }
+                }
+                count
+            };
+            let all = reachable(None);
+            let linking: HashSet<usize> = hops.iter().map(|&(node, _)| node).collect();
+            for (node, graph_node) in export.nodes.iter_mut().enumerate() {
+                graph_node.strands = Some(if linking.contains(&node) {
+                    all - reachable(Some(node))
+                } else {
+                    0
+                });
+            }
+        }
+        export
+    }
+
+    /// Path relative to the analyzed directory, with `/` separators.
+    fn graph_id(&self, path: &Path) -> String {
+        path.strip_prefix(&self.base_path)
+            .unwrap_or(path)
+            .to_string_lossy()
+            .replace('\\', "/")
+    }
+
+    /// The text above the first heading, then one section per heading.
+    fn sections(&self, content: &str) -> Vec<Section> {
+        let anchors::DocumentHeadings { headings, anchors } =
+            anchors::DocumentHeadings::parse_with(content, self.heading_styles, self.slugger);
+        let mut sections = vec![Section {
+            anchor: None,
+            title: None,
+            line_number: 1,
+            words: 0,
+        }];
+        sections.extend(
+            headings
+                .into_iter()
+                .zip(anchors)
+                .map(|(heading, anchor)| Section {
+                    anchor: Some(anchor),
+                    title: Some(heading.text),
+                    line_number: heading.line_number,
+                    words: 0,
+                }),
+        );
+        for (i, line) in content.lines().enumerate() {
+            let section = sections
+                .iter_mut()
+                .rev()
+                .find(|section| section.line_number <= i + 1)
+                .unwrap();
+            section.words += word_count(line);
+        }
+        sections
+    }
+}
+
+struct Section {
+    /// `None` for the text above the first heading.
+    anchor: Option<String>,
+    title: Option<String>,
+    line_number: usize,
+    words: usize,
+}
+
+/// The section that `line_number` falls in.
+fn section_at(sections: &[Section], line_number: usize) -> &Section {
+    sections
+        .iter()
+        .rev()
+        .find(|section| section.line_number <= line_number)
+        .unwrap_or(&sections[0])
+}
+
+/// Words with at least one letter or digit, so `#` and `-` don't count.
+fn word_count(text: &str) -> usize {
+    text.split_whitespace()
+        .filter(|word| word.chars().any(char::is_alphanumeric))
+        .count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vfs::MemoryFileSystem;
+
+    #[test]
+    fn test_export_graph() {
+        let mut fs = MemoryFileSystem::new();
+        fs.insert(
+            "docs/README.md",
+            "# Home & About\n\n[Guide](guide.md)\n[Top](#home--about)\n\nSee the [guide](./guide.md#usage).\n",
+        );
+        fs.insert("docs/guide.md", "Intro text.\n\n# Usage\n\nRun it.\n");
+        fs.insert("docs/old.md", "No heading [here](gone.md).\n");
+        let mut analyzer = LinkAnalyzer::new(PathBuf::from("docs")).with_filesystem(fs);
+        analyzer.analyze_directory().unwrap();
+
+        let export = analyzer.export_graph();
+        assert_eq!(
+            export.nodes,
+            vec![
+                GraphNode {
+                    id: "README.md".to_string(),
+                    title: Some("Home & About".to_string()),
+                    words: 7,
+                    orphan: false,
+                    broken: 0,
+                    strands: None,
+                    aliases: Vec::new(),
+                },
+                GraphNode {
+                    id: "guide.md".to_string(),
+                    title: Some("Usage".to_string()),
+                    words: 5,
+                    orphan: false,
+                    broken: 0,
+                    strands: None,
+                    aliases: Vec::new(),
+                },
+                GraphNode {
+                    id: "old.md".to_string(),
+                    title: None,
+                    words: 3,
+                    orphan: true,
+                    broken: 1,
+                    strands: None,
+                    aliases: Vec::new(),
+                },
+            ]
+        );
+        let lines: Vec<(&str, &str, usize)> = export
+            .links
+            .iter()
+            .map(|edge| (edge.source.as_str(), edge.target.as_str(), edge.line))
+            .collect();
+        assert_eq!(
+            lines,
+            vec![("README.md", "guide.md", 3), ("README.md", "guide.md", 6)]
+        );
+
+        let json = serde_json::to_value(&export).unwrap();
+        assert_eq!(json["links"][0]["source"], "README.md");
+        assert_eq!(json["nodes"][2]["title"], serde_json::Value::Null);
+
+        let graphml = export.to_graphml();
+        assert!(graphml.contains("<data key=\"title\">Home &amp; About</data>"));
+        assert!(graphml.contains(
+            "<edge source=\"README.md\" target=\"guide.md\">\n      <data key=\"line\">6</data>"
+        ));
+        assert_eq!(graphml.matches("<node ").count(), 3);
+
+        #[cfg(feature = "html-report")]
+        {
+            let html = export.to_html();
+            assert!(html.starts_with("<!DOCTYPE html>"));
+            assert!(html.contains("\"title\":\"Home & About\""));
+            assert!(!html.contains("/*GRAPH*/"));
+        }
+    }
+
+    #[cfg(feature = "html-report")]
+    #[test]
+    fn test_html_escapes_script_end() {
+        let export = GraphExport {
+            directed: true,
+            nodes: vec![GraphNode {
+                id: "a.md".to_string(),
+                title: Some("</script><script>alert(1)</script>".to_string()),
+                words: 1,
+                orphan: false,
+                broken: 0,
+                strands: None,
+                aliases: Vec::new(),
+            }],
+            links: Vec::new(),
+        };
+        let html = export.to_html();
+        assert_eq!(html.matches("</script>").count(), 1);
+    }
+
+    #[test]
+    fn test_export_section_graph() {
+        let mut fs = MemoryFileSystem::new();
+        fs.insert(
+            "docs/README.md",
+            "Welcome.\n\n# Start\n\n[Install](guide.md#install)\n\n# Reference\n\n[API](api.md)\n[Start](#start)\n",
+        );
+        fs.insert(
+            "docs/guide.md",
+            "# Guide\n\n## Install\n\nRun the [installer](setup.md).\n[Missing](#nowhere)\n",
+        );
+        fs.insert("docs/setup.md", "# Setup\n");
+        fs.insert("docs/api.md", "# API\n\n[Setup](setup.md)\n");
+        let mut analyzer = LinkAnalyzer::new(PathBuf::from("docs")).with_filesystem(fs);
+        analyzer.analyze_directory().unwrap();
+
+        let export = analyzer.export_section_graph();
+        let edges: Vec<(&str, &str)> = export
+            .links
+            .iter()
+            .map(|edge| (edge.source.as_str(), edge.target.as_str()))
+            .collect();
+        assert_eq!(
+            edges,
+            vec![
+                ("README.md#start", "guide.md#install"),
+                ("README.md#reference", "api.md"),
+                ("README.md#reference", "README.md#start"),
+                ("api.md#api", "setup.md"),
+                ("guide.md#install", "setup.md"),
+            ]
+        );
+
+        let node = |id: &str| export.nodes.iter().find(|node| node.id == id).unwrap();
+        assert_eq!(node("README.md").words, 1);
+        assert_eq!(node("guide.md#install").title.as_deref(), Some("Install"));
+        assert_eq!(node("guide.md#install").broken, 1);
+        // Only README.md#start leads to the guide; setup.md is also reached
+        // through the API reference.
+        assert_eq!(node("README.md#start").strands, Some(1));
+        assert_eq!(node("README.md#reference").strands, Some(1));
+        assert_eq!(node("guide.md#install").strands, Some(0));
+        assert_eq!(node("setup.md#setup").strands, Some(0));
+    }
+}
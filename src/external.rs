@@ -0,0 +1,431 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+mod address;
+#[cfg(feature = "external-http")]
+mod http;
+
+pub use address::{AddressPolicy, Cidr};
+#[cfg(feature = "external-http")]
+pub use http::ExternalChecker;
+
+/// What an external URL answered with. Redirects are not followed so that
+/// permanent moves can be reported.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExternalResponse {
+    pub url: String,
+    pub status: Option<u16>,
+    pub location: Option<String>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum Verdict {
+    Ok,
+    Warning(String),
+    Error(String),
+    /// A failure that is not worth reporting, e.g. a transient outage.
+    Ignored(String),
+}
+
+impl ExternalResponse {
+    /// Judges the response. Without `status_rules` every 4xx/5xx status and
+    /// network failure is an error; with them, permanent redirects become
+    /// warnings, 410 stays an error and 503 is ignored as transient.
+    pub fn verdict(&self, status_rules: bool) -> Verdict {
+        self.verdict_accepting(status_rules, &[])
+    }
+
+    /// Like `verdict`, but a status in `accepted` always passes, e.g. a 403
+    /// from a site that turns away automated requests.
+    pub fn verdict_accepting(&self, status_rules: bool, accepted: &[u16]) -> Verdict {
+        let Some(code) = self.status else {
+            let error = self.error.as_deref().unwrap_or("no response");
+            return Verdict::Error(format!("Request failed: {}", error));
+        };
+        if accepted.contains(&code) {
+            return Verdict::Ok;
+        }
+        let description = describe_status(code);
+
+        if status_rules {
+            match code {
+                301 | 308 => {
+                    let location = self.location.as_deref().unwrap_or("unknown location");
+                    return Verdict::Warning(format!(
+                        "{}; update to new URL: {}",
+                        description, location
+                    ));
+                }
+                410 => return Verdict::Error(format!("{}; the page was removed", description)),
+                503 => return Verdict::Ignored(format!("{}; treated as transient", description)),
+                _ => {}
+            }
+        }
+
+        if code >= 400 {
+            Verdict::Error(description)
+        } else {
+            Verdict::Ok
+        }
+    }
+}
+
+fn describe_status(code: u16) -> String {
+    match reason_phrase(code) {
+        Some(reason) => format!("HTTP {} {}", code, reason),
+        None => format!("HTTP {}", code),
+    }
+}
+
+/// Standard reason phrase of an HTTP status code.
+fn reason_phrase(code: u16) -> Option<&'static str> {
+    Some(match code {
+        100 => "Continue",
+        101 => "Switching Protocols",
+        200 => "OK",
+        201 => "Created",
+        202 => "Accepted",
+        203 => "Non Authoritative Information",
+        204 => "No Content",
+        205 => "Reset Content",
+        206 => "Partial Content",
+        300 => "Multiple Choices",
+        301 => "Moved Permanently",
+        302 => "Found",
+        303 => "See Other",
+        304 => "Not Modified",
+        307 => "Temporary Redirect",
+        308 => "Permanent Redirect",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        402 => "Payment Required",
+        403 => "Forbidden",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        406 => "Not Acceptable",
+        407 => "Proxy Authentication Required",
+        408 => "Request Timeout",
+        409 => "Conflict",
+        410 => "Gone",
+        411 => "Length Required",
+        412 => "Precondition Failed",
+        413 => "Payload Too Large",
+        414 => "URI Too Long",
+        415 => "Unsupported Media Type",
+        416 => "Range Not Satisfiable",
+        417 => "Expectation Failed",
+        418 => "I'm a teapot",
+        421 => "Misdirected Request",
+        422 => "Unprocessable Entity",
+        423 => "Locked",
+        424 => "Failed Dependency",
+        426 => "Upgrade Required",
+        428 => "Precondition Required",
+        429 => "Too Many Requests",
+        431 => "Request Header Fields Too Large",
+        451 => "Unavailable For Legal Reasons",
+        500 => "Internal Server Error",
+        501 => "Not Implemented",
+        502 => "Bad Gateway",
+        503 => "Service Unavailable",
+        504 => "Gateway Timeout",
+        505 => "HTTP Version Not Supported",
+        _ => return None,
+    })
+}
+
+/// A cap on outbound requests shared by every check in a run, counting one
+/// per URL fetched; answers from a cache are free.
+#[derive(Debug)]
+pub struct RequestBudget {
+    remaining: AtomicUsize,
+    exceeded: AtomicBool,
+}
+
+impl RequestBudget {
+    /// A budget of `limit` requests, or an unlimited one.
+    pub fn new(limit: Option<usize>) -> Self {
+        Self {
+            remaining: AtomicUsize::new(limit.unwrap_or(usize::MAX)),
+            exceeded: AtomicBool::new(false),
+        }
+    }
+
+    /// Takes one request from the budget, or returns `false` once it is
+    /// spent and the URL must be left unchecked.
+    pub fn try_take(&self) -> bool {
+        let taken = self
+            .remaining
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| n.checked_sub(1))
+            .is_ok();
+        if !taken {
+            self.exceeded.store(true, Ordering::SeqCst);
+        }
+        taken
+    }
+
+    /// Whether a request was refused for lack of budget.
+    pub fn is_exceeded(&self) -> bool {
+        self.exceeded.load(Ordering::SeqCst)
+    }
+}
+
+/// Pass/fail outcomes of each external URL over recent runs, persisted in the
+/// cache directory so intermittent failures can be told apart from dead links.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct UrlHistory {
+    urls: BTreeMap<String, Vec<bool>>,
+}
+
+impl UrlHistory {
+    pub const FILE_NAME: &'static str = "external-history.json";
+    const MAX_RUNS: usize = 10;
+
+    /// Loads the history, starting afresh if it is missing or unreadable.
+    pub fn load(path: &Path) -> Self {
+        fs::read_to_string(path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, serde_json::to_string(self)?)?;
+        Ok(())
+    }
+
+    pub fn record(&mut self, url: &str, passed: bool) {
+        let runs = self.urls.entry(url.to_string()).or_default();
+        runs.push(passed);
+        if runs.len() > Self::MAX_RUNS {
+            runs.remove(0);
+        }
+    }
+
+    /// A failing URL is flaky while it has passed within the recorded runs
+    /// and has failed fewer than `threshold` runs in a row.
+    pub fn is_flaky(&self, url: &str, threshold: usize) -> bool {
+        let Some(runs) = self.urls.get(url) else {
+            return false;
+        };
+        let consecutive_failures = runs.iter().rev().take_while(|passed| !**passed).count();
+        consecutive_failures > 0 && consecutive_failures < threshold && runs.contains(&true)
+    }
+
+    /// `(failed, total)` runs recorded for `url`.
+    pub fn failures(&self, url: &str) -> (usize, usize) {
+        self.urls.get(url).map_or((0, 0), |runs| {
+            (runs.iter().filter(|passed| !**passed).count(), runs.len())
+        })
+    }
+}
+
+/// Results of slow or rate-limited checks, such as badges and package
+/// registry lookups, persisted in the cache directory so each URL is fetched
+/// at most once per `TTL`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CheckCache {
+    entries: BTreeMap<String, CachedCheck>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CachedCheck {
+    /// Seconds since the Unix epoch.
+    pub checked_at: u64,
+    /// Why the URL is broken, or `None` if it was fine.
+    pub error: Option<String>,
+}
+
+impl CheckCache {
+    pub const TTL: Duration = Duration::from_secs(24 * 60 * 60);
+
+    /// Loads the cache, starting afresh if it is missing or unreadable.
+    pub fn load(path: &Path) -> Self {
+        fs::read_to_string(path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    /// Saves the cache, leaving out entries that have expired.
+    pub fn save(&self, path: &Path, now: SystemTime) -> Result<(), Box<dyn std::error::Error>> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let fresh = CheckCache {
+            entries: self
+                .entries
+                .iter()
+                .filter(|(_, entry)| is_fresh(entry, now))
+                .map(|(url, entry)| (url.clone(), entry.clone()))
+                .collect(),
+        };
+        fs::write(path, serde_json::to_string(&fresh)?)?;
+        Ok(())
+    }
+
+    /// The result for `url` if it was checked within `TTL` of `now`.
+    pub fn get(&self, url: &str, now: SystemTime) -> Option<&CachedCheck> {
+        self.entries.get(url).filter(|entry| is_fresh(entry, now))
+    }
+
+    pub fn record(&mut self, url: &str, error: Option<String>, now: SystemTime) {
+        self.entries.insert(
+            url.to_string(),
+            CachedCheck {
+                checked_at: unix_seconds(now),
+                error,
+            },
+        );
+    }
+}
+
+fn unix_seconds(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+fn is_fresh(entry: &CachedCheck, now: SystemTime) -> bool {
+    unix_seconds(now).saturating_sub(entry.checked_at) < CheckCache::TTL.as_secs()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn response(status: Option<u16>, location: Option<&str>) -> ExternalResponse {
+        ExternalResponse {
+            url: "https://example.com/".to_string(),
+            status,
+            location: location.map(str::to_string),
+            error: status.is_none().then(|| "connection refused".to_string()),
+        }
+    }
+
+    #[test]
+    fn test_verdict_without_status_rules() {
+        assert_eq!(response(Some(200), None).verdict(false), Verdict::Ok);
+        assert_eq!(
+            response(Some(301), Some("https://new.example.com/")).verdict(false),
+            Verdict::Ok
+        );
+        assert_eq!(
+            response(Some(503), None).verdict(false),
+            Verdict::Error("HTTP 503 Service Unavailable".to_string())
+        );
+        assert_eq!(
+            response(None, None).verdict(false),
+            Verdict::Error("Request failed: connection refused".to_string())
+        );
+    }
+
+    #[test]
+    fn test_verdict_with_status_rules() {
+        assert_eq!(
+            response(Some(301), Some("https://new.example.com/")).verdict(true),
+            Verdict::Warning(
+                "HTTP 301 Moved Permanently; update to new URL: https://new.example.com/"
+                    .to_string()
+            )
+        );
+        assert_eq!(response(Some(302), None).verdict(true), Verdict::Ok);
+        assert_eq!(
+            response(Some(410), None).verdict(true),
+            Verdict::Error("HTTP 410 Gone; the page was removed".to_string())
+        );
+        assert_eq!(
+            response(Some(503), None).verdict(true),
+            Verdict::Ignored("HTTP 503 Service Unavailable; treated as transient".to_string())
+        );
+        assert_eq!(
+            response(Some(404), None).verdict(true),
+            Verdict::Error("HTTP 404 Not Found".to_string())
+        );
+
+        let accepted = [403, 429];
+        assert_eq!(
+            response(Some(403), None).verdict_accepting(false, &accepted),
+            Verdict::Ok
+        );
+        assert_eq!(
+            response(Some(404), None).verdict_accepting(false, &accepted),
+            Verdict::Error("HTTP 404 Not Found".to_string())
+        );
+    }
+
+    #[test]
+    fn test_url_history_flakiness() {
+        let url = "https://example.com/";
+        let mut history = UrlHistory::default();
+
+        history.record(url, false);
+        assert!(!history.is_flaky(url, 3), "never passed, so it is broken");
+
+        let mut history = UrlHistory::default();
+        history.record(url, true);
+        history.record(url, false);
+        assert!(history.is_flaky(url, 3));
+        history.record(url, false);
+        assert!(history.is_flaky(url, 3));
+        history.record(url, false);
+        assert!(
+            !history.is_flaky(url, 3),
+            "third failure in a row graduates"
+        );
+        assert_eq!(history.failures(url), (3, 4));
+
+        for _ in 0..20 {
+            history.record(url, true);
+        }
+        assert_eq!(history.failures(url), (0, 10));
+        assert!(!history.is_flaky(url, 3));
+    }
+
+    #[test]
+    fn test_url_history_round_trip() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("cache").join(UrlHistory::FILE_NAME);
+        assert_eq!(UrlHistory::load(&path), UrlHistory::default());
+
+        let mut history = UrlHistory::default();
+        history.record("https://example.com/", true);
+        history.save(&path).unwrap();
+        assert_eq!(UrlHistory::load(&path), history);
+    }
+
+    #[test]
+    fn test_request_budget() {
+        let budget = RequestBudget::new(Some(2));
+        assert!(budget.try_take());
+        assert!(budget.try_take());
+        assert!(!budget.is_exceeded());
+        assert!(!budget.try_take());
+        assert!(budget.is_exceeded());
+
+        let unlimited = RequestBudget::new(None);
+        assert!((0..1000).all(|_| unlimited.try_take()));
+        assert!(!unlimited.is_exceeded());
+    }
+
+    #[test]
+    fn test_check_cache_expiry() {
+        let url = "https://img.shields.io/crates/v/serde.svg";
+        let checked = UNIX_EPOCH + Duration::from_secs(1_000_000);
+        let mut cache = CheckCache::default();
+        cache.record(url, Some("Badge shows \"invalid\"".to_string()), checked);
+
+        assert!(cache.get(url, checked + Duration::from_secs(60)).is_some());
+        assert!(cache.get(url, checked + CheckCache::TTL).is_none());
+        assert!(cache.get("https://badgen.net/other", checked).is_none());
+    }
+}
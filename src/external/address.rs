@@ -0,0 +1,215 @@
+use crate::Config;
+use std::fmt;
+use std::net::IpAddr;
+use std::str::FromStr;
+use std::sync::LazyLock;
+
+/// Loopback, private, link-local (including the `169.254.169.254` cloud
+/// metadata endpoint), shared, multicast and reserved ranges.
+const INTERNAL_RANGES: &[&str] = &[
+    "0.0.0.0/8",
+    "10.0.0.0/8",
+    "100.64.0.0/10",
+    "127.0.0.0/8",
+    "169.254.0.0/16",
+    "172.16.0.0/12",
+    "192.0.0.0/24",
+    "192.168.0.0/16",
+    "198.18.0.0/15",
+    "224.0.0.0/4",
+    "240.0.0.0/4",
+    "::/128",
+    "::1/128",
+    "fc00::/7",
+    "fe80::/10",
+    "ff00::/8",
+];
+
+static INTERNAL: LazyLock<Vec<Cidr>> = LazyLock::new(|| {
+    INTERNAL_RANGES
+        .iter()
+        .map(|range| range.parse().unwrap())
+        .collect()
+});
+
+/// An IP network such as `10.0.0.0/8` or `fd00::/8`. A bare address is a
+/// network of one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cidr {
+    network: IpAddr,
+    prefix: u8,
+}
+
+impl Cidr {
+    pub fn contains(&self, ip: IpAddr) -> bool {
+        match (self.network, canonical(ip)) {
+            (IpAddr::V4(network), IpAddr::V4(ip)) => {
+                let mask = u32::MAX
+                    .checked_shl(32 - u32::from(self.prefix))
+                    .unwrap_or(0);
+                u32::from(network) & mask == u32::from(ip) & mask
+            }
+            (IpAddr::V6(network), IpAddr::V6(ip)) => {
+                let mask = u128::MAX
+                    .checked_shl(128 - u32::from(self.prefix))
+                    .unwrap_or(0);
+                u128::from(network) & mask == u128::from(ip) & mask
+            }
+            _ => false,
+        }
+    }
+}
+
+impl FromStr for Cidr {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("Invalid CIDR range `{}`", text);
+        let (address, prefix) = match text.trim().split_once('/') {
+            Some((address, prefix)) => (address, Some(prefix)),
+            None => (text.trim(), None),
+        };
+        let network = canonical(address.parse().map_err(|_| invalid())?);
+        let max = if network.is_ipv4() { 32 } else { 128 };
+        let prefix = match prefix {
+            Some(prefix) => prefix
+                .parse()
+                .ok()
+                .filter(|&p| p <= max)
+                .ok_or_else(invalid)?,
+            None => max,
+        };
+        Ok(Self { network, prefix })
+    }
+}
+
+impl fmt::Display for Cidr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.network, self.prefix)
+    }
+}
+
+/// IPv4 addresses written as IPv4-mapped IPv6 (`::ffff:10.0.0.1`) are
+/// judged as the IPv4 address they reach.
+fn canonical(ip: IpAddr) -> IpAddr {
+    match ip {
+        IpAddr::V6(v6) => v6.to_ipv4_mapped().map_or(ip, IpAddr::V4),
+        IpAddr::V4(_) => ip,
+    }
+}
+
+/// Which addresses external link checks may connect to, so documents cannot
+/// make a shared checking service probe its internal network.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AddressPolicy {
+    /// Refuse loopback, private, link-local and other internal addresses.
+    pub block_internal: bool,
+    /// Ranges that are always allowed, even if internal or denied.
+    pub allow: Vec<Cidr>,
+    /// Ranges that are refused.
+    pub deny: Vec<Cidr>,
+}
+
+impl AddressPolicy {
+    /// The policy set by `external_block_internal`, `external_allow_cidrs`
+    /// and `external_deny_cidrs`.
+    pub fn from_config(config: &Config) -> Result<Self, Box<dyn std::error::Error>> {
+        let parse = |ranges: &[String]| -> Result<Vec<Cidr>, String> {
+            ranges.iter().map(|range| range.parse()).collect()
+        };
+        Ok(Self {
+            block_internal: config.external_block_internal,
+            allow: parse(&config.external_allow_cidrs)?,
+            deny: parse(&config.external_deny_cidrs)?,
+        })
+    }
+
+    /// Whether every address is allowed.
+    pub fn is_open(&self) -> bool {
+        !self.block_internal && self.deny.is_empty()
+    }
+
+    /// Why connecting to `ip` is refused, if it is.
+    pub fn check(&self, ip: IpAddr) -> Result<(), String> {
+        if self.allow.iter().any(|range| range.contains(ip)) {
+            return Ok(());
+        }
+        if let Some(range) = self.deny.iter().find(|range| range.contains(ip)) {
+            return Err(format!("{} is in denied range {}", ip, range));
+        }
+        if self.block_internal && INTERNAL.iter().any(|range| range.contains(ip)) {
+            return Err(format!("{} is an internal address", ip));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ip(text: &str) -> IpAddr {
+        text.parse().unwrap()
+    }
+
+    #[test]
+    fn test_cidr() {
+        let range: Cidr = "10.0.0.0/8".parse().unwrap();
+        assert!(range.contains(ip("10.20.30.40")));
+        assert!(range.contains(ip("::ffff:10.0.0.1")));
+        assert!(!range.contains(ip("11.0.0.1")));
+        assert!(!range.contains(ip("::1")));
+
+        let host: Cidr = "192.168.1.5".parse().unwrap();
+        assert_eq!(host.to_string(), "192.168.1.5/32");
+        assert!(!host.contains(ip("192.168.1.6")));
+
+        let v6: Cidr = "fd00::/8".parse().unwrap();
+        assert!(v6.contains(ip("fd12:3456::1")));
+        assert!("0.0.0.0/0".parse::<Cidr>().unwrap().contains(ip("8.8.8.8")));
+
+        assert!("10.0.0.0/33".parse::<Cidr>().is_err());
+        assert!("example.com/8".parse::<Cidr>().is_err());
+    }
+
+    #[test]
+    fn test_address_policy() {
+        assert!(AddressPolicy::default().check(ip("127.0.0.1")).is_ok());
+        assert!(AddressPolicy::default().is_open());
+
+        let policy = AddressPolicy {
+            block_internal: true,
+            allow: vec!["10.1.2.0/24".parse().unwrap()],
+            deny: vec!["203.0.113.0/24".parse().unwrap()],
+        };
+        assert_eq!(
+            policy.check(ip("169.254.169.254")),
+            Err("169.254.169.254 is an internal address".to_string())
+        );
+        assert!(policy.check(ip("::ffff:127.0.0.1")).is_err());
+        assert!(policy.check(ip("fe80::1")).is_err());
+        assert!(policy.check(ip("10.1.2.3")).is_ok());
+        assert_eq!(
+            policy.check(ip("203.0.113.9")),
+            Err("203.0.113.9 is in denied range 203.0.113.0/24".to_string())
+        );
+        assert!(policy.check(ip("93.184.215.14")).is_ok());
+    }
+
+    #[test]
+    fn test_from_config() {
+        let config = Config::parse(
+            "external_block_internal = true\nexternal_deny_cidrs = [\"203.0.113.0/24\"]",
+        )
+        .unwrap();
+        let policy = AddressPolicy::from_config(&config).unwrap();
+        assert!(policy.block_internal);
+        assert_eq!(policy.deny.len(), 1);
+
+        let config = Config::parse("external_allow_cidrs = [\"10.0.0.0/40\"]").unwrap();
+        assert_eq!(
+            AddressPolicy::from_config(&config).unwrap_err().to_string(),
+            "Invalid CIDR range `10.0.0.0/40`"
+        );
+    }
+}
@@ -0,0 +1,514 @@
+use super::{AddressPolicy, ExternalResponse};
+use reqwest::blocking::{Client, RequestBuilder, Response};
+use reqwest::dns::{Addrs, Name, Resolve, Resolving};
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use reqwest::redirect::Policy;
+use reqwest::{StatusCode, Url};
+use std::borrow::Borrow;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::future::Future;
+use std::net::{IpAddr, SocketAddr, ToSocketAddrs};
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Waker};
+use std::time::{Duration, Instant};
+
+/// How long resolved addresses are reused.
+const DNS_TTL: Duration = Duration::from_secs(300);
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+/// How long idle connections are kept for the next request to their host.
+const POOL_IDLE_TIMEOUT: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Clone)]
+pub struct ExternalChecker {
+    client: Client,
+    timeout: Duration,
+    policy: Arc<AddressPolicy>,
+    /// Headers sent to URLs starting with each prefix.
+    headers: Arc<Vec<(String, HeaderMap)>>,
+}
+
+impl ExternalChecker {
+    pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(10);
+
+    pub fn new(timeout: Duration) -> Result<Self, Box<dyn std::error::Error>> {
+        Self::with_address_policy(timeout, AddressPolicy::default())
+    }
+
+    /// Like `new`, but only connecting to addresses `policy` allows. Host
+    /// names are checked as they are resolved for the connection, so they
+    /// cannot be re-pointed in between; proxies are bypassed because they
+    /// would resolve names themselves.
+    ///
+    /// Clones share one connection pool and DNS cache, so a run should
+    /// create one checker and clone it rather than create one per target.
+    /// Connections are kept alive between requests to the same host, and
+    /// HTTP/2 is negotiated where servers offer it, multiplexing requests
+    /// over one connection.
+    pub fn with_address_policy(
+        timeout: Duration,
+        policy: AddressPolicy,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        let policy = Arc::new(policy);
+        let mut builder = Client::builder()
+            .redirect(Policy::none())
+            .timeout(timeout)
+            .connect_timeout(timeout.min(CONNECT_TIMEOUT))
+            .pool_idle_timeout(POOL_IDLE_TIMEOUT)
+            .tcp_keepalive(POOL_IDLE_TIMEOUT)
+            .user_agent(concat!("doclink-checker/", env!("CARGO_PKG_VERSION")))
+            .dns_resolver(Arc::new(Resolver {
+                policy: Arc::clone(&policy),
+                cache: Arc::default(),
+            }));
+        if !policy.is_open() {
+            builder = builder.no_proxy();
+        }
+        Ok(Self {
+            client: builder.build()?,
+            timeout,
+            policy,
+            headers: Arc::default(),
+        })
+    }
+
+    /// Sends the headers under each URL prefix, as in the `external_headers`
+    /// setting, with requests to URLs starting with it.
+    pub fn with_headers(
+        mut self,
+        headers: &BTreeMap<String, BTreeMap<String, String>>,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        let mut prefixed = Vec::new();
+        for (prefix, fields) in headers {
+            let mut map = HeaderMap::new();
+            for (name, value) in fields {
+                let name = HeaderName::from_bytes(name.as_bytes())
+                    .map_err(|_| format!("Invalid header name `{}`", name))?;
+                let value = HeaderValue::from_str(value)
+                    .map_err(|_| format!("Invalid value for header `{}`", name))?;
+                map.insert(name, value);
+            }
+            prefixed.push((prefix.clone(), map));
+        }
+        self.headers = Arc::new(prefixed);
+        Ok(self)
+    }
+
+    /// Sends a HEAD request, falling back to GET for servers that reject HEAD.
+    pub fn check(&self, url: &str) -> ExternalResponse {
+        self.check_before(url, None)
+    }
+
+    /// Like `check`, but gives up at `deadline` even if the client timeout
+    /// has not elapsed yet.
+    pub fn check_before(&self, url: &str, deadline: Option<Instant>) -> ExternalResponse {
+        if let Some(refused) = self.refuse_address(url) {
+            return refused;
+        }
+        let response = self
+            .send(url, self.client.head(url), deadline)
+            .and_then(|response| match response.status() {
+                StatusCode::METHOD_NOT_ALLOWED | StatusCode::NOT_IMPLEMENTED => {
+                    self.send(url, self.client.get(url), deadline)
+                }
+                _ => Ok(response),
+            });
+        to_external_response(url, response)
+    }
+
+    /// GETs a badge image, returning the response and the error drawn into
+    /// the badge, if any; see `badges::badge_error`.
+    pub fn check_badge_before(
+        &self,
+        url: &str,
+        deadline: Option<Instant>,
+    ) -> (ExternalResponse, Option<String>) {
+        if let Some(refused) = self.refuse_address(url) {
+            return (refused, None);
+        }
+        match self.send(url, self.client.get(url), deadline) {
+            Ok(response) if response.status().is_success() => {
+                let external = to_external_response(url, Ok(&response));
+                let error = response
+                    .text()
+                    .ok()
+                    .and_then(|svg| crate::badges::badge_error(&svg));
+                (external, error)
+            }
+            response => (to_external_response(url, response), None),
+        }
+    }
+
+    /// A failed response for URLs whose host is an IP address the policy
+    /// refuses. Such hosts are never resolved, so `Resolver` does not
+    /// see them.
+    fn refuse_address(&self, url: &str) -> Option<ExternalResponse> {
+        let parsed = Url::parse(url).ok()?;
+        let host = parsed.host_str()?;
+        let ip: IpAddr = host
+            .trim_start_matches('[')
+            .trim_end_matches(']')
+            .parse()
+            .ok()?;
+        let reason = self.policy.check(ip).err()?;
+        Some(ExternalResponse {
+            url: url.to_string(),
+            status: None,
+            location: None,
+            error: Some(format!("Blocked: {}", reason)),
+        })
+    }
+
+    fn send(
+        &self,
+        url: &str,
+        mut request: RequestBuilder,
+        deadline: Option<Instant>,
+    ) -> reqwest::Result<Response> {
+        for (prefix, headers) in self.headers.iter() {
+            if url.starts_with(prefix.as_str()) {
+                request = request.headers(headers.clone());
+            }
+        }
+        match deadline {
+            Some(deadline) => request
+                .timeout(
+                    deadline
+                        .saturating_duration_since(Instant::now())
+                        .min(self.timeout),
+                )
+                .send(),
+            None => request.send(),
+        }
+    }
+}
+
+fn to_external_response(
+    url: &str,
+    response: Result<impl Borrow<Response>, reqwest::Error>,
+) -> ExternalResponse {
+    match response {
+        Ok(response) => {
+            let response = response.borrow();
+            ExternalResponse {
+                url: url.to_string(),
+                status: Some(response.status().as_u16()),
+                location: response
+                    .headers()
+                    .get(reqwest::header::LOCATION)
+                    .and_then(|location| location.to_str().ok())
+                    .map(str::to_string),
+                error: None,
+            }
+        }
+        Err(e) => ExternalResponse {
+            url: url.to_string(),
+            status: None,
+            location: None,
+            error: Some(if e.is_timeout() {
+                "timed out".to_string()
+            } else if let Some(blocked) = blocked_by_policy(&e) {
+                format!("Blocked: {}", blocked)
+            } else {
+                e.to_string()
+            }),
+        },
+    }
+}
+
+/// Why `Resolver` refused a host name.
+#[derive(Debug)]
+struct Blocked(String);
+
+impl fmt::Display for Blocked {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for Blocked {}
+
+fn blocked_by_policy(error: &reqwest::Error) -> Option<&Blocked> {
+    let mut source = std::error::Error::source(error);
+    while let Some(error) = source {
+        if let Some(blocked) = error.downcast_ref::<Blocked>() {
+            return Some(blocked);
+        }
+        source = error.source();
+    }
+    None
+}
+
+/// Resolves host names like the default resolver, but answers each name
+/// from memory for `DNS_TTL` and fails for names with any address the policy
+/// refuses.
+struct Resolver {
+    policy: Arc<AddressPolicy>,
+    cache: Arc<Mutex<DnsCache>>,
+}
+
+/// Host name to when it was resolved and its addresses.
+type DnsCache = HashMap<String, (Instant, Vec<SocketAddr>)>;
+
+impl Resolver {
+    fn cached(&self, host: &str) -> Option<Vec<SocketAddr>> {
+        let cache = self.cache.lock().unwrap();
+        let (resolved_at, addresses) = cache.get(host)?;
+        (resolved_at.elapsed() < DNS_TTL).then(|| addresses.clone())
+    }
+}
+
+impl Resolve for Resolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        let host = name.as_str().to_string();
+        if let Some(addresses) = self.cached(&host) {
+            return Box::pin(std::future::ready(Ok(
+                Box::new(addresses.into_iter()) as Addrs
+            )));
+        }
+
+        let policy = Arc::clone(&self.policy);
+        let cache = Arc::clone(&self.cache);
+        let lookup = Lookup::default();
+        let shared = lookup.clone();
+        // Lookups block, so they get their own thread rather than stalling
+        // every other request on the client's runtime.
+        std::thread::spawn(move || {
+            let result = resolve_allowed(&host, &policy);
+            if let Ok(addresses) = &result {
+                cache
+                    .lock()
+                    .unwrap()
+                    .insert(host, (Instant::now(), addresses.clone()));
+            }
+            let mut state = shared.0.lock().unwrap();
+            state.0 = Some(result);
+            if let Some(waker) = state.1.take() {
+                waker.wake();
+            }
+        });
+        Box::pin(lookup)
+    }
+}
+
+type LookupResult = Result<Vec<SocketAddr>, Box<dyn std::error::Error + Send + Sync>>;
+
+fn resolve_allowed(host: &str, policy: &AddressPolicy) -> LookupResult {
+    let addresses: Vec<SocketAddr> = (host, 0).to_socket_addrs()?.collect();
+    for address in &addresses {
+        policy
+            .check(address.ip())
+            .map_err(|reason| Blocked(format!("{}: {}", host, reason)))?;
+    }
+    Ok(addresses)
+}
+
+/// A lookup running on another thread: its result once finished, and the
+/// task to wake when it does.
+#[derive(Clone, Default)]
+struct Lookup(Arc<Mutex<(Option<LookupResult>, Option<Waker>)>>);
+
+impl Future for Lookup {
+    type Output = Result<Addrs, Box<dyn std::error::Error + Send + Sync>>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut state = self.0.lock().unwrap();
+        match state.0.take() {
+            Some(result) => {
+                Poll::Ready(result.map(|addresses| Box::new(addresses.into_iter()) as Addrs))
+            }
+            None => {
+                state.1 = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
+
+    #[test]
+    fn test_check_local_server() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        std::thread::spawn(move || {
+            for stream in listener.incoming().take(3) {
+                let mut stream = stream.unwrap();
+                let mut request_line = String::new();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                reader.read_line(&mut request_line).unwrap();
+                let mut header = String::new();
+                while reader.read_line(&mut header).unwrap() > 2 {
+                    header.clear();
+                }
+
+                let reply = if request_line.starts_with("HEAD /moved") {
+                    "HTTP/1.1 301 Moved Permanently\r\nLocation: /new\r\n"
+                } else if request_line.starts_with("HEAD /no-head") {
+                    "HTTP/1.1 405 Method Not Allowed\r\n"
+                } else {
+                    "HTTP/1.1 200 OK\r\n"
+                };
+                write!(
+                    stream,
+                    "{}Content-Length: 0\r\nConnection: close\r\n\r\n",
+                    reply
+                )
+                .unwrap();
+            }
+        });
+
+        let checker = ExternalChecker::new(Duration::from_secs(5)).unwrap();
+        let moved = checker.check(&format!("http://{}/moved", address));
+        assert_eq!(moved.status, Some(301));
+        assert_eq!(moved.location.as_deref(), Some("/new"));
+
+        let no_head = checker.check(&format!("http://{}/no-head", address));
+        assert_eq!(no_head.status, Some(200));
+    }
+
+    #[test]
+    fn test_check_badge() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        std::thread::spawn(move || {
+            for stream in listener.incoming().take(2) {
+                let mut stream = stream.unwrap();
+                let mut request_line = String::new();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                reader.read_line(&mut request_line).unwrap();
+                let mut header = String::new();
+                while reader.read_line(&mut header).unwrap() > 2 {
+                    header.clear();
+                }
+
+                let label = if request_line.starts_with("GET /missing") {
+                    "build: repo not found"
+                } else {
+                    "build: passing"
+                };
+                let body = format!("<svg><title>{}</title></svg>", label);
+                write!(
+                    stream,
+                    "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    body.len(),
+                    body
+                )
+                .unwrap();
+            }
+        });
+
+        let checker = ExternalChecker::new(Duration::from_secs(5)).unwrap();
+        let (response, error) =
+            checker.check_badge_before(&format!("http://{}/missing.svg", address), None);
+        assert_eq!(response.status, Some(200));
+        assert_eq!(
+            error.as_deref(),
+            Some("Badge shows \"build: repo not found\"")
+        );
+        let (_, error) = checker.check_badge_before(&format!("http://{}/ok.svg", address), None);
+        assert_eq!(error, None);
+    }
+
+    #[test]
+    fn test_address_policy() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut line = String::new();
+                while reader.read_line(&mut line).unwrap() > 2 {
+                    line.clear();
+                }
+                stream
+                    .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 0\r\n\r\n")
+                    .unwrap();
+            }
+        });
+
+        let policy = AddressPolicy {
+            block_internal: true,
+            ..AddressPolicy::default()
+        };
+        let checker = ExternalChecker::with_address_policy(Duration::from_secs(5), policy).unwrap();
+        let response = checker.check(&format!("http://{}/", address));
+        assert_eq!(
+            response.error.as_deref(),
+            Some("Blocked: 127.0.0.1 is an internal address")
+        );
+        let response = checker.check(&format!("http://localhost:{}/", address.port()));
+        assert_eq!(response.status, None);
+        assert!(response.error.unwrap().starts_with("Blocked: localhost: "));
+
+        let policy = AddressPolicy {
+            block_internal: true,
+            allow: vec!["127.0.0.0/8".parse().unwrap(), "::1".parse().unwrap()],
+            ..AddressPolicy::default()
+        };
+        let checker = ExternalChecker::with_address_policy(Duration::from_secs(5), policy).unwrap();
+        assert_eq!(
+            checker
+                .check(&format!("http://localhost:{}/", address.port()))
+                .status,
+            Some(200)
+        );
+    }
+
+    #[test]
+    fn test_resolver_cache() {
+        let resolver = Resolver {
+            policy: Arc::default(),
+            cache: Arc::default(),
+        };
+        let resolve = |resolver: &Resolver| {
+            let mut lookup = resolver.resolve("localhost".parse().unwrap());
+            let mut cx = Context::from_waker(Waker::noop());
+            loop {
+                if let Poll::Ready(result) = lookup.as_mut().poll(&mut cx) {
+                    return result.unwrap().collect::<Vec<_>>();
+                }
+                std::thread::sleep(Duration::from_millis(1));
+            }
+        };
+
+        let addresses = resolve(&resolver);
+        assert!(!addresses.is_empty());
+        assert_eq!(resolver.cached("localhost"), Some(addresses.clone()));
+        assert_eq!(resolve(&resolver), addresses);
+
+        let stale = Instant::now().checked_sub(DNS_TTL).unwrap();
+        resolver
+            .cache
+            .lock()
+            .unwrap()
+            .get_mut("localhost")
+            .unwrap()
+            .0 = stale;
+        assert_eq!(resolver.cached("localhost"), None);
+    }
+
+    #[test]
+    fn test_check_before_deadline() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        std::thread::spawn(move || {
+            // Accept the connection but never answer.
+            let _stream = listener.accept().unwrap();
+            std::thread::sleep(Duration::from_secs(10));
+        });
+
+        let checker = ExternalChecker::new(Duration::from_secs(30)).unwrap();
+        let started = Instant::now();
+        let deadline = started + Duration::from_millis(200);
+        let response = checker.check_before(&format!("http://{}/slow", address), Some(deadline));
+        assert_eq!(response.error.as_deref(), Some("timed out"));
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+}
@@ -0,0 +1,756 @@
+use crate::{LinkAnalyzer, LinkKind};
+use pulldown_cmark::{CowStr, Event, LinkType, Options, Parser, Tag, TagEnd};
+use regex::Regex;
+use serde::Serialize;
+use std::ops::Range;
+use std::path::PathBuf;
+use std::sync::LazyLock;
+
+pub(crate) static WIKI_LINK: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"!?\[\[([^\[\]\n]+)\]\]").unwrap());
+
+pub(crate) static REFERENCE_DEFINITION: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^ {0,3}\[([^\]]+)\]:\s*(.+)$").unwrap());
+
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct MarkdownLink {
+    pub text: String,
+    pub target: String,
+    pub line_number: usize,
+    pub file_path: PathBuf,
+}
+
+impl MarkdownLink {
+    pub fn new(
+        text: impl Into<String>,
+        target: impl Into<String>,
+        line_number: usize,
+        file_path: impl Into<PathBuf>,
+    ) -> Self {
+        Self {
+            text: text.into(),
+            target: target.into(),
+            line_number,
+            file_path: file_path.into(),
+        }
+    }
+}
+
+/// How a link is written in the source.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[non_exhaustive]
+#[serde(rename_all = "kebab-case")]
+pub enum LinkSyntax {
+    /// `[text](target "title")`
+    Inline,
+    /// `![alt](target "title")` or `![alt][label]`
+    Image,
+    /// `[text][label]`, `[text][]` or `[text]`, resolved through a
+    /// `[label]: target` definition.
+    Reference,
+    /// `<https://example.com>`
+    Autolink,
+}
+
+/// A link as written in a document, from `LinkAnalyzer::extract_links_detailed`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[non_exhaustive]
+pub struct ExtractedLink {
+    pub syntax: LinkSyntax,
+    pub kind: LinkKind,
+    pub text: String,
+    /// Destination without its title or surrounding `<>`.
+    pub target: String,
+    pub title: Option<String>,
+    /// Label of a reference link or image, as written (or the text for
+    /// `[text][]` and `[text]`).
+    pub reference_label: Option<String>,
+    /// Line and 1-based column, in characters, of the link's opening `[`
+    /// (or `!` for an image), even when the link wraps onto later lines.
+    pub line_number: usize,
+    pub column: usize,
+    /// Byte range of the whole link in the document, e.g. `[text](target)`.
+    pub span: Range<usize>,
+}
+
+impl LinkAnalyzer {
+    /// Links in `content` as `(text, target, line_number)`, ignoring anything
+    /// inside `<!-- -->` comments. See `extract_links_detailed` for more.
+    pub fn extract_links(content: &str) -> Vec<(String, String, usize)> {
+        Self::extract_links_detailed(content)
+            .into_iter()
+            .map(|link| (link.text, link.target, link.line_number))
+            .collect()
+    }
+
+    /// Links that only appear inside `<!-- -->` comments.
+    pub fn extract_commented_links(content: &str) -> Vec<(String, String, usize)> {
+        let positions = Positions::new(content);
+        html_comment_bodies(content)
+            .into_iter()
+            .flat_map(|body| parse_links(content, body, &positions))
+            .map(|link| (link.text, link.target, link.line_number))
+            .collect()
+    }
+
+    /// Links written inside inline code spans and fenced or indented code
+    /// blocks, such as examples in a style guide. Only checked with `code_links`.
+    pub fn extract_code_links(content: &str) -> Vec<(String, String, usize)> {
+        let positions = Positions::new(content);
+        code_ranges(content)
+            .into_iter()
+            .flat_map(|code| parse_links(content, code, &positions))
+            .map(|link| (link.text, link.target, link.line_number))
+            .collect()
+    }
+
+    /// Targets named by tooling directives inside `<!-- -->` comments, such
+    /// as `<!-- embed: ./snippets/x.md -->`: each pattern's first capture
+    /// group is the target and the whole match is the link text.
+    pub fn extract_directive_links(
+        content: &str,
+        directives: &[Regex],
+    ) -> Vec<(String, String, usize)> {
+        let commented = split_html_comments(content).1;
+        let mut links = Vec::new();
+
+        for directive in directives {
+            for caps in directive.captures_iter(&commented) {
+                let (Some(whole), Some(target)) = (caps.get(0), caps.get(1)) else {
+                    continue;
+                };
+                let line_number = commented[..whole.start()].matches('\n').count() + 1;
+                links.push((
+                    whole.as_str().trim().to_string(),
+                    target.as_str().trim_end().to_string(),
+                    line_number,
+                ));
+            }
+        }
+
+        links.sort_by_key(|(_, _, line_number)| *line_number);
+        links
+    }
+
+    /// Obsidian `[[note]]`, `[[note|alias]]`, `[[note#Heading]]`,
+    /// `[[note#^block]]` links and `![[file]]` embeds, outside HTML comments
+    /// and code. The target is the whole `[[...]]`; the text is the alias or
+    /// note name.
+    pub fn extract_wiki_links(content: &str) -> Vec<(String, String, usize)> {
+        let mut live = split_html_comments(content).0;
+        for code in code_ranges(content) {
+            let blanked = blank(&live[code.clone()]);
+            live.replace_range(code, &blanked);
+        }
+        wiki_links(&live)
+    }
+
+    /// Wiki links written inside inline code spans and code blocks. Only
+    /// checked with `code_links`.
+    pub fn extract_code_wiki_links(content: &str) -> Vec<(String, String, usize)> {
+        let mut code_only = blank(content);
+        for code in code_ranges(content) {
+            code_only.replace_range(code.clone(), &content[code]);
+        }
+        wiki_links(&code_only)
+    }
+
+    /// Markdown links outside `<!-- -->` comments and code, as CommonMark
+    /// finds them, with everything known about them: syntax, title,
+    /// reference label and byte span. Links come in document order, except
+    /// that an image inside a link comes before the link.
+    pub fn extract_links_detailed(content: &str) -> Vec<ExtractedLink> {
+        // A byte order mark, as Windows editors write, would hide a first-line link.
+        let start = if content.starts_with('\u{feff}') {
+            3
+        } else {
+            0
+        };
+        parse_links(content, start..content.len(), &Positions::new(content))
+    }
+}
+
+/// A link or image whose end `parse_links` hasn't reached yet.
+struct OpenLink<'a> {
+    link_type: LinkType,
+    image: bool,
+    target: CowStr<'a>,
+    title: CowStr<'a>,
+    label: CowStr<'a>,
+    span: Range<usize>,
+    /// Where the link text is in the document, once any of it was seen.
+    text: Option<Range<usize>>,
+}
+
+/// Links in the part of `content` within `range`, parsed as a document of
+/// its own.
+fn parse_links(content: &str, range: Range<usize>, positions: &Positions) -> Vec<ExtractedLink> {
+    let offset = range.start;
+    let mut links = Vec::new();
+    let mut open: Vec<OpenLink> = Vec::new();
+
+    for (event, span) in parser(&content[range]).into_offset_iter() {
+        let span = offset + span.start..offset + span.end;
+        if let Event::End(TagEnd::Link | TagEnd::Image) = event {
+            let Some(link) = open.pop() else {
+                continue;
+            };
+            let syntax = match link.link_type {
+                // `<me@example.com>` has no target to check.
+                LinkType::Email => continue,
+                _ if link.image => LinkSyntax::Image,
+                LinkType::Inline => LinkSyntax::Inline,
+                LinkType::Autolink => LinkSyntax::Autolink,
+                _ => LinkSyntax::Reference,
+            };
+            // Link text is kept as written, so it can be written back.
+            let text = unwrap_lines(link.text.map_or("", |text| &content[text]));
+            let reference_label =
+                (!matches!(link.link_type, LinkType::Inline | LinkType::Autolink)).then(|| {
+                    match link.label.as_ref() {
+                        "" => text.clone(),
+                        label => unwrap_lines(label),
+                    }
+                });
+            let mut span = link.span;
+            // CommonMark's range for `[text][]` ends before the `[]`.
+            if link.link_type == LinkType::Collapsed && content[span.end..].starts_with("[]") {
+                span.end += 2;
+            }
+            let (line_number, column) = positions.of(span.start);
+            links.push(ExtractedLink {
+                syntax,
+                kind: LinkKind::classify(&link.target),
+                text,
+                target: link.target.into_string(),
+                title: (!link.title.is_empty()).then(|| link.title.into_string()),
+                reference_label,
+                line_number,
+                column,
+                span,
+            });
+            continue;
+        }
+
+        if let Some(link) = open.last_mut() {
+            let text = link.text.get_or_insert(span.clone());
+            *text = text.start.min(span.start)..text.end.max(span.end);
+        }
+        if let Event::Start(
+            Tag::Link {
+                link_type,
+                dest_url,
+                title,
+                id,
+            }
+            | Tag::Image {
+                link_type,
+                dest_url,
+                title,
+                id,
+            },
+        ) = event
+        {
+            open.push(OpenLink {
+                link_type,
+                image: content[span.clone()].starts_with('!'),
+                target: dest_url,
+                title,
+                label: id,
+                span,
+                text: None,
+            });
+        }
+    }
+
+    links
+}
+
+/// The CommonMark parser, with the GitHub extensions that change what is a link.
+fn parser(text: &str) -> Parser<'_> {
+    let options = Options::ENABLE_TABLES
+        | Options::ENABLE_FOOTNOTES
+        | Options::ENABLE_STRIKETHROUGH
+        | Options::ENABLE_TASKLISTS
+        | Options::ENABLE_YAML_STYLE_METADATA_BLOCKS;
+    Parser::new_ext(text, options)
+}
+
+/// Byte ranges of what is written inside inline code spans and code blocks
+/// in `content`, outside `<!-- -->` comments.
+fn code_ranges(content: &str) -> Vec<Range<usize>> {
+    let start = if content.starts_with('\u{feff}') {
+        3
+    } else {
+        0
+    };
+    let mut ranges = Vec::new();
+    let mut in_block = false;
+    for (event, span) in parser(&content[start..]).into_offset_iter() {
+        let span = start + span.start..start + span.end;
+        match event {
+            Event::Start(Tag::CodeBlock(_)) => in_block = true,
+            Event::End(TagEnd::CodeBlock) => in_block = false,
+            Event::Text(_) if in_block => ranges.push(span),
+            Event::Code(_) => {
+                // The span includes its backticks.
+                let ticks = content[span.clone()].len()
+                    - content[span.clone()].trim_start_matches('`').len();
+                ranges.push(span.start + ticks..span.end - ticks);
+            }
+            _ => {}
+        }
+    }
+    ranges
+}
+
+/// `[[...]]` links in `text`, with the line each is on.
+fn wiki_links(text: &str) -> Vec<(String, String, usize)> {
+    let mut links = Vec::new();
+
+    for (line_num, line) in text.lines().enumerate() {
+        for caps in WIKI_LINK.captures_iter(line) {
+            let inner = &caps[1];
+            let text = match inner.split_once('|') {
+                Some((_, alias)) => alias,
+                None => inner,
+            };
+            links.push((text.to_string(), format!("[[{}]]", inner), line_num + 1));
+        }
+    }
+
+    links
+}
+
+/// `text` with everything but line breaks replaced by spaces. Blanked text
+/// keeps the byte length of the original, so spans still match.
+fn blank(text: &str) -> String {
+    text.chars()
+        .map(|c| match c {
+            '\n' | '\r' => c.to_string(),
+            c => " ".repeat(c.len_utf8()),
+        })
+        .collect()
+}
+
+/// Byte ranges of the bodies of the `<!-- -->` comments in `content`.
+fn html_comment_bodies(content: &str) -> Vec<Range<usize>> {
+    let mut bodies = Vec::new();
+    let mut position = 0;
+    while let Some(start) = content[position..].find("<!--") {
+        let start = position + start + 4;
+        let end = content[start..]
+            .find("-->")
+            .map_or(content.len(), |end| start + end);
+        bodies.push(start..end);
+        position = (end + 3).min(content.len());
+    }
+    bodies
+}
+
+/// Link text or a label that wraps onto following lines, joined back into
+/// one line the way it renders.
+fn unwrap_lines(text: &str) -> String {
+    if text.contains('\n') {
+        text.split('\n')
+            .map(str::trim)
+            .collect::<Vec<_>>()
+            .join(" ")
+    } else {
+        text.to_string()
+    }
+}
+
+/// Line and column lookup for byte offsets into a document.
+struct Positions<'a> {
+    content: &'a str,
+    line_starts: Vec<usize>,
+}
+
+impl<'a> Positions<'a> {
+    fn new(content: &'a str) -> Self {
+        let line_starts = std::iter::once(0)
+            .chain(content.match_indices('\n').map(|(index, _)| index + 1))
+            .collect();
+        Self {
+            content,
+            line_starts,
+        }
+    }
+
+    /// 1-based line and column, in characters, of the byte at `offset`. A
+    /// byte order mark isn't a column, as editors don't show it.
+    fn of(&self, offset: usize) -> (usize, usize) {
+        let line = self.line_starts.partition_point(|&start| start <= offset);
+        let start = self.line_starts[line - 1];
+        let before = self.content[start..offset].trim_start_matches('\u{feff}');
+        (line, before.chars().count() + 1)
+    }
+}
+
+/// Splits a link destination such as `./a.md "Title"` or `<a b.md>` into the
+/// target and the optional title.
+pub(crate) fn split_destination(destination: &str) -> (String, Option<String>) {
+    let destination = destination.trim();
+    let (target, rest) = match destination.strip_prefix('<') {
+        Some(rest) => rest.split_once('>').unwrap_or((rest, "")),
+        None => destination
+            .split_once(char::is_whitespace)
+            .unwrap_or((destination, "")),
+    };
+
+    let rest = rest.trim();
+    let title = [('"', '"'), ('\'', '\''), ('(', ')')]
+        .iter()
+        .find_map(|(open, close)| rest.strip_prefix(*open)?.strip_suffix(*close))
+        .map(str::to_string);
+
+    (target.to_string(), title)
+}
+
+/// Compiles a `comment_directives` pattern, which must capture the target.
+pub fn compile_directive(pattern: &str) -> Result<Regex, String> {
+    let regex = Regex::new(pattern)
+        .map_err(|e| format!("Invalid comment directive `{}`: {}", pattern, e))?;
+    if regex.captures_len() < 2 {
+        return Err(format!(
+            "Comment directive `{}` has no capture group for the link target",
+            pattern
+        ));
+    }
+    Ok(regex)
+}
+
+/// Splits `content` into `(live, commented)`: the first with every HTML
+/// comment blanked out, the second with everything but comment bodies blanked.
+/// Newlines are kept in both so line numbers still match the original.
+pub(crate) fn split_html_comments(content: &str) -> (String, String) {
+    let mut live = String::with_capacity(content.len());
+    let mut commented = String::with_capacity(content.len());
+    let mut rest = content;
+    // A byte order mark, as Windows editors write, would hide a first-line link.
+    if let Some(after_bom) = content.strip_prefix('\u{feff}') {
+        live.push_str("   ");
+        commented.push_str("   ");
+        rest = after_bom;
+    }
+    while let Some(start) = rest.find("<!--") {
+        let (before, comment) = rest.split_at(start);
+        live.push_str(before);
+        commented.push_str(&blank(before));
+
+        let end = comment[4..]
+            .find("-->")
+            .map_or(comment.len(), |end| end + 7);
+        let (comment, after) = comment.split_at(end);
+        live.push_str(&blank(comment));
+        commented.push_str("    ");
+        let body = comment[4..].strip_suffix("-->").unwrap_or(&comment[4..]);
+        commented.push_str(body);
+        if comment.ends_with("-->") {
+            commented.push_str("   ");
+        }
+        rest = after;
+    }
+    live.push_str(rest);
+    commented.push_str(&blank(rest));
+
+    (live, commented)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{vfs, Config};
+
+    #[test]
+    fn test_extract_inline_links() {
+        let content = "Check out [Rust](https://www.rust-lang.org) for more info.";
+        let links = LinkAnalyzer::extract_links(content);
+
+        assert_eq!(links.len(), 1);
+        assert_eq!(links[0].0, "Rust");
+        assert_eq!(links[0].1, "https://www.rust-lang.org");
+        assert_eq!(links[0].2, 1);
+    }
+
+    #[test]
+    fn test_extract_reference_links() {
+        let content =
+            "Check out [Rust][rust-lang] for more info.\n\n[rust-lang]: https://www.rust-lang.org";
+        let links = LinkAnalyzer::extract_links(content);
+
+        assert_eq!(links.len(), 1);
+        assert_eq!(links[0].0, "Rust");
+        assert_eq!(links[0].1, "https://www.rust-lang.org");
+        assert_eq!(links[0].2, 1);
+    }
+
+    #[test]
+    fn test_extract_relative_links() {
+        let content = "See [documentation](./docs/README.md) for details.";
+        let links = LinkAnalyzer::extract_links(content);
+
+        assert_eq!(links.len(), 1);
+        assert_eq!(links[0].0, "documentation");
+        assert_eq!(links[0].1, "./docs/README.md");
+        assert_eq!(links[0].2, 1);
+    }
+
+    #[test]
+    fn test_extract_links_detailed() {
+        let content = "See <!-- é --> [a](<./a b.md> \"A\") and ![logo](img/logo.png 'Logo').\n[Ref][r] [r][]\n\n[r]: ./r.md#top (R)";
+        let links = LinkAnalyzer::extract_links_detailed(content);
+
+        assert_eq!(links.len(), 4);
+        assert_eq!(links[0].syntax, LinkSyntax::Inline);
+        assert_eq!(links[0].target, "./a b.md");
+        assert_eq!(links[0].title.as_deref(), Some("A"));
+        assert_eq!(&content[links[0].span.clone()], "[a](<./a b.md> \"A\")");
+
+        assert_eq!(links[1].syntax, LinkSyntax::Image);
+        assert_eq!(links[1].target, "img/logo.png");
+        assert_eq!(links[1].title.as_deref(), Some("Logo"));
+        assert_eq!(
+            &content[links[1].span.clone()],
+            "![logo](img/logo.png 'Logo')"
+        );
+
+        assert_eq!(links[2].syntax, LinkSyntax::Reference);
+        assert_eq!(links[2].kind, LinkKind::Internal);
+        assert_eq!(links[2].target, "./r.md#top");
+        assert_eq!(links[2].title.as_deref(), Some("R"));
+        assert_eq!(links[2].reference_label.as_deref(), Some("r"));
+        assert_eq!(links[2].line_number, 2);
+        assert_eq!(&content[links[2].span.clone()], "[Ref][r]");
+        assert_eq!(links[3].reference_label.as_deref(), Some("r"));
+    }
+
+    #[test]
+    fn test_extract_links_crlf() {
+        let content = "\u{feff}[top]: ./top.md \"Top\"\r\n\r\nSee [a](./a.md) and [Top][top].\r\n<!--\r\nembed: ./b.md\r\n-->\r\n   [b]: ./b.md\r\n[b][]";
+        let links = LinkAnalyzer::extract_links_detailed(content);
+        let summary: Vec<(&str, usize, &str)> = links
+            .iter()
+            .map(|link| {
+                (
+                    link.target.as_str(),
+                    link.line_number,
+                    &content[link.span.clone()],
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("./a.md", 3, "[a](./a.md)"),
+                ("./top.md", 3, "[Top][top]"),
+                ("./b.md", 8, "[b][]"),
+            ]
+        );
+        assert_eq!(links[1].title.as_deref(), Some("Top"));
+
+        let directives = vec![compile_directive(r"embed: (.+)").unwrap()];
+        let links = LinkAnalyzer::extract_directive_links(content, &directives);
+        assert_eq!(links[0].1, "./b.md");
+        assert_eq!(links[0].2, 5);
+    }
+
+    #[test]
+    fn test_extract_wrapped_links() {
+        let content = "\u{feff}A <!-- é --> `[code](c.md)` [a](a.md)\nSee the [setup\n  guide](\n./setup.md \"Setup\") and [b][B\nref].\n\n[not\n\n](x.md)\n\n[b ref]: ./b.md";
+        let links = LinkAnalyzer::extract_links_detailed(content);
+        let summary: Vec<(&str, &str, usize, usize)> = links
+            .iter()
+            .map(|link| {
+                (
+                    link.text.as_str(),
+                    link.target.as_str(),
+                    link.line_number,
+                    link.column,
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("a", "a.md", 1, 29),
+                ("setup guide", "./setup.md", 2, 9),
+                ("b", "./b.md", 4, 25),
+            ]
+        );
+        assert_eq!(links[1].title.as_deref(), Some("Setup"));
+        assert_eq!(links[2].reference_label.as_deref(), Some("B ref"));
+        assert_eq!(&content[links[2].span.clone()], "[b][B\nref]");
+    }
+
+    #[test]
+    fn test_code_links() {
+        let content = "Use `[x](./inline.md)` or ``[y](./double.md)``.\n\n```md\n[fenced](./fenced.md)\n```\n\n    [indented](./indented.md)\n\n[live](./live.md) <!-- `[c](./commented.md)` -->";
+        let live = LinkAnalyzer::extract_links(content);
+        let targets: Vec<&str> = live.iter().map(|l| l.1.as_str()).collect();
+        assert_eq!(targets, vec!["./live.md"]);
+
+        let code = LinkAnalyzer::extract_code_links(content);
+        let summary: Vec<(&str, usize)> = code.iter().map(|l| (l.1.as_str(), l.2)).collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("./inline.md", 1),
+                ("./double.md", 1),
+                ("./fenced.md", 4),
+                ("./indented.md", 7),
+            ]
+        );
+
+        let docs = || {
+            let mut vfs = vfs::MemoryFileSystem::new();
+            vfs.insert("docs/README.md", content);
+            vfs.insert("docs/live.md", "");
+            vfs
+        };
+        let mut analyzer = LinkAnalyzer::new(PathBuf::from("docs")).with_filesystem(docs());
+        analyzer.analyze_directory().unwrap();
+        assert!(analyzer.find_broken_links().is_empty());
+
+        let config = Config {
+            code_links: true,
+            ..Default::default()
+        };
+        let mut analyzer = LinkAnalyzer::with_config(PathBuf::from("docs"), &config)
+            .unwrap()
+            .with_filesystem(docs());
+        analyzer.analyze_directory().unwrap();
+        assert_eq!(analyzer.find_broken_links().len(), 4);
+    }
+
+    #[test]
+    fn test_wiki_links_in_code() {
+        let content = "[[Live]] `[[inline]]`\n\n```md\n[[fenced]]\n```\n\n    [[indented]]\n";
+        let targets = |links: Vec<(String, String, usize)>| -> Vec<(String, usize)> {
+            links
+                .into_iter()
+                .map(|(_, target, line)| (target, line))
+                .collect()
+        };
+        assert_eq!(
+            targets(LinkAnalyzer::extract_wiki_links(content)),
+            vec![("[[Live]]".to_string(), 1)]
+        );
+        assert_eq!(
+            targets(LinkAnalyzer::extract_code_wiki_links(content)),
+            vec![
+                ("[[inline]]".to_string(), 1),
+                ("[[fenced]]".to_string(), 4),
+                ("[[indented]]".to_string(), 7),
+            ]
+        );
+
+        let docs = || {
+            let mut vfs = vfs::MemoryFileSystem::new();
+            vfs.insert("vault/README.md", content);
+            vfs.insert("vault/Live.md", "");
+            vfs
+        };
+        let config = Config {
+            wiki_links: true,
+            ..Default::default()
+        };
+        let mut analyzer = LinkAnalyzer::with_config(PathBuf::from("vault"), &config)
+            .unwrap()
+            .with_filesystem(docs());
+        analyzer.analyze_directory().unwrap();
+        assert!(analyzer.find_broken_links().is_empty());
+
+        let config = Config {
+            wiki_links: true,
+            code_links: true,
+            ..Default::default()
+        };
+        let mut analyzer = LinkAnalyzer::with_config(PathBuf::from("vault"), &config)
+            .unwrap()
+            .with_filesystem(docs());
+        analyzer.analyze_directory().unwrap();
+        assert_eq!(analyzer.find_broken_links().len(), 3);
+    }
+
+    #[test]
+    fn test_extract_multiple_links_with_line_numbers() {
+        let content = "First [link1](url1)\n\nSecond [link2](url2)\nThird [link3](url3)";
+        let links = LinkAnalyzer::extract_links(content);
+
+        assert_eq!(links.len(), 3);
+        assert_eq!(links[0].2, 1);
+        assert_eq!(links[1].2, 3);
+        assert_eq!(links[2].2, 4);
+    }
+
+    #[test]
+    fn test_html_comments() {
+        let content = "[Live](a.md) <!-- [Old](old.md) -->\n<!--\n![Diagram](img/diagram.png)\n-->\n[After](b.md)";
+        let live = LinkAnalyzer::extract_links(content);
+        let targets: Vec<&str> = live.iter().map(|l| l.1.as_str()).collect();
+        assert_eq!(targets, vec!["a.md", "b.md"]);
+        assert_eq!(live[1].2, 5);
+
+        let commented = LinkAnalyzer::extract_commented_links(content);
+        assert_eq!(commented.len(), 2);
+        assert_eq!(commented[1].1, "img/diagram.png");
+        assert_eq!(commented[1].2, 3);
+
+        let mut vfs = vfs::MemoryFileSystem::new();
+        vfs.insert("docs/README.md", content);
+        vfs.insert("docs/a.md", "<!-- [Back](README.md) -->");
+        vfs.insert("docs/b.md", "");
+        vfs.insert("docs/img/diagram.png", "");
+
+        let mut analyzer = LinkAnalyzer::new(PathBuf::from("docs")).with_filesystem(vfs);
+        analyzer.analyze_directory().unwrap();
+
+        assert_eq!(analyzer.find_broken_links().len(), 0);
+        let comment_only = analyzer.find_comment_only_links();
+        let targets: Vec<&str> = comment_only.iter().map(|l| l.target.as_str()).collect();
+        assert_eq!(targets, vec!["img/diagram.png", "README.md"]);
+    }
+
+    #[test]
+    fn test_comment_directives() {
+        let content = "# Doc\n<!-- embed: ./snippets/a.md -->\n<!--\n  include::./snippets/missing.adoc[]\n-->\nembed: ./live.md";
+        let directives = vec![
+            compile_directive(r"embed:\s*(\S+)").unwrap(),
+            compile_directive(r"include::([^\[\s]+)\[").unwrap(),
+        ];
+        let links = LinkAnalyzer::extract_directive_links(content, &directives);
+        assert_eq!(
+            links,
+            vec![
+                (
+                    "embed: ./snippets/a.md".to_string(),
+                    "./snippets/a.md".to_string(),
+                    2
+                ),
+                (
+                    "include::./snippets/missing.adoc[".to_string(),
+                    "./snippets/missing.adoc".to_string(),
+                    4
+                ),
+            ]
+        );
+        assert!(compile_directive("embed: \\S+").is_err());
+
+        let mut vfs = vfs::MemoryFileSystem::new();
+        vfs.insert("docs/README.md", content);
+        vfs.insert("docs/snippets/a.md", "Snippet");
+        let config = Config {
+            comment_directives: vec![r"embed:\s*(\S+)".to_string()],
+            ..Default::default()
+        };
+        let mut analyzer = LinkAnalyzer::with_config(PathBuf::from("docs"), &config)
+            .unwrap()
+            .with_filesystem(vfs);
+        analyzer.analyze_directory().unwrap();
+        assert!(analyzer.find_broken_links().is_empty());
+        assert!(analyzer.find_orphaned_documents().is_empty());
+    }
+}
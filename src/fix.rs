@@ -0,0 +1,1181 @@
+use crate::extract::{split_destination, REFERENCE_DEFINITION};
+use crate::{anchors, include, normalize_path, split_fragment, LinkAnalyzer, LinkKind, LinkSyntax};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+/// How sure a suggested fix is to be what the author meant.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[non_exhaustive]
+#[serde(rename_all = "kebab-case")]
+pub enum Confidence {
+    /// A single exact match, e.g. the only file with the link's file name.
+    Safe,
+    /// A guess: a similar file name, or one of several files with that name.
+    Risky,
+}
+
+/// A rewrite of one link target in one document.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[non_exhaustive]
+pub struct Fix {
+    pub file_path: PathBuf,
+    pub line_number: usize,
+    pub target: String,
+    pub replacement: String,
+    pub confidence: Confidence,
+}
+
+impl Fix {
+    pub fn new(
+        file_path: impl Into<PathBuf>,
+        line_number: usize,
+        target: impl Into<String>,
+        replacement: impl Into<String>,
+        confidence: Confidence,
+    ) -> Self {
+        Self {
+            file_path: file_path.into(),
+            line_number,
+            target: target.into(),
+            replacement: replacement.into(),
+            confidence,
+        }
+    }
+}
+
+/// One replacement made in a document: `span` is where `after` sits in the
+/// new content, in place of `before`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AppliedEdit {
+    pub span: Range<usize>,
+    pub before: String,
+    pub after: String,
+}
+
+/// Applies `fixes` to a document's content, returning the new content and
+/// how many fixes took effect. Inline links are found where the extractor
+/// puts them, starting on the fix's line even when they wrap onto later
+/// ones; for reference links the matching `[label]: target` definition is
+/// rewritten. Fixes only touch targets, so each line keeps its `\n` or `\r\n`.
+pub fn apply_to_content(content: &str, fixes: &[&Fix]) -> (String, usize) {
+    let (content, edits) = apply_with_edits(content, fixes);
+    (content, edits.len())
+}
+
+/// Like `apply_to_content`, but returns every edit that was made.
+pub fn apply_with_edits(content: &str, fixes: &[&Fix]) -> (String, Vec<AppliedEdit>) {
+    let links = LinkAnalyzer::extract_links_detailed(content);
+    // Byte range of the target in `content` and the fix for it, never overlapping.
+    let mut replacements: Vec<(Range<usize>, &Fix)> = Vec::new();
+    let is_free = |replacements: &[(Range<usize>, &Fix)], span: &Range<usize>| {
+        replacements
+            .iter()
+            .all(|(taken, _)| taken.end <= span.start || span.end <= taken.start)
+    };
+
+    for fix in fixes {
+        let inline = links
+            .iter()
+            .filter(|link| {
+                link.line_number == fix.line_number
+                    && link.reference_label.is_none()
+                    && link.target == fix.target
+            })
+            .filter_map(|link| {
+                let start =
+                    link.span.start + find_target(&content[link.span.clone()], &fix.target)?;
+                Some(start..start + fix.target.len())
+            })
+            .find(|span| is_free(&replacements, span));
+        if let Some(span) = inline {
+            replacements.push((span, fix));
+            continue;
+        }
+
+        let mut offset = 0;
+        let definition = content.split_inclusive('\n').find_map(|line| {
+            let line_start = offset;
+            offset += line.len();
+            let (_, rest) = line.split_once("]:")?;
+            let trimmed = rest.trim_start();
+            let start = line_start + line.len() - trimmed.len();
+            let rest = trimmed.strip_prefix(fix.target.as_str())?;
+            let span = start..start + fix.target.len();
+            (rest.chars().next().is_none_or(char::is_whitespace) && is_free(&replacements, &span))
+                .then_some(span)
+        });
+        if let Some(span) = definition {
+            replacements.push((span, fix));
+        }
+    }
+    replacements.sort_by_key(|(span, _)| span.start);
+
+    let mut after = String::with_capacity(content.len());
+    let mut edits = Vec::with_capacity(replacements.len());
+    let mut position = 0;
+    for (span, fix) in replacements {
+        after.push_str(&content[position..span.start]);
+        let start = after.len();
+        after.push_str(&fix.replacement);
+        edits.push(AppliedEdit {
+            span: start..after.len(),
+            before: fix.target.clone(),
+            after: fix.replacement.clone(),
+        });
+        position = span.end;
+    }
+    after.push_str(&content[position..]);
+    (after, edits)
+}
+
+/// Position of `target` after the last `](` in `link`, the source of one
+/// inline link, where it ends the destination, i.e. is followed by `)` or a
+/// title. The last match skips images inside the link text, as in
+/// `[![logo](a.png)](a.png)`.
+fn find_target(link: &str, target: &str) -> Option<usize> {
+    let inline = format!("]({}", target);
+    link.rmatch_indices(&inline)
+        .map(|(index, _)| index + 2)
+        .find(|index| {
+            matches!(
+                link[index + target.len()..].chars().next(),
+                Some(')') | Some(' ') | Some('\t') | Some('\n') | Some('\r')
+            )
+        })
+}
+
+/// How `apply_fixes` writes files.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FixOptions {
+    /// Keep the original of every rewritten file as `<file>.bak`.
+    pub backup: bool,
+}
+
+/// The new content of one file after its fixes.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FileChange {
+    pub path: PathBuf,
+    pub before: String,
+    pub after: String,
+    /// The replacements that took effect, by position.
+    pub edits: Vec<AppliedEdit>,
+}
+
+/// Reads the affected files and computes their new content without writing
+/// anything. Files where no fix applies are left out.
+pub fn plan_fixes(fixes: &[Fix]) -> Result<Vec<FileChange>, Box<dyn std::error::Error>> {
+    let mut by_file: BTreeMap<&PathBuf, Vec<&Fix>> = BTreeMap::new();
+    for fix in fixes {
+        by_file.entry(&fix.file_path).or_default().push(fix);
+    }
+
+    let mut changes = Vec::new();
+    for (file_path, fixes) in by_file {
+        let before = fs::read_to_string(file_path)?;
+        let (after, edits) = apply_with_edits(&before, &fixes);
+        if !edits.is_empty() && after != before {
+            changes.push(FileChange {
+                path: file_path.clone(),
+                before,
+                after,
+                edits,
+            });
+        }
+    }
+
+    Ok(changes)
+}
+
+/// How links are written in a document.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[non_exhaustive]
+#[serde(rename_all = "kebab-case")]
+pub enum LinkStyle {
+    /// `[text](target)`
+    Inline,
+    /// `[text][label]` with a `[label]: target` definition.
+    Reference,
+}
+
+/// How relative links to other documents are written.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[non_exhaustive]
+#[serde(rename_all = "kebab-case")]
+pub enum PathStyle {
+    /// `./guide/setup.md`, or `../guide/setup.md` from a sibling directory.
+    DotPrefixed,
+    /// `guide/setup.md`
+    Plain,
+    /// `/docs/guide/setup.md`, relative to the analyzed directory.
+    RootRelative,
+}
+
+/// Rewrites every link in `content` to `style`. Inline links become reference
+/// links whose definitions are appended to the document; links to the same
+/// target share one definition, reusing an existing one when there is one.
+/// Reference links become inline links and their definitions are removed.
+pub fn convert_style(content: &str, style: LinkStyle) -> (String, Vec<AppliedEdit>) {
+    // (span in `content`, replacement), in order and never overlapping.
+    let mut replacements: Vec<(Range<usize>, String)> = Vec::new();
+    let links = crate::LinkAnalyzer::extract_links_detailed(content);
+
+    match style {
+        LinkStyle::Reference => {
+            let mut labels: Vec<String> = Vec::new();
+            let mut by_destination: BTreeMap<(String, Option<String>), String> = BTreeMap::new();
+            for line in content.lines() {
+                if let Some(caps) = REFERENCE_DEFINITION.captures(line) {
+                    labels.push(caps[1].to_lowercase());
+                    by_destination
+                        .entry(split_destination(&caps[2]))
+                        .or_insert_with(|| caps[1].to_string());
+                }
+            }
+
+            let mut definitions = String::new();
+            for link in &links {
+                if link.reference_label.is_some() || link.syntax == LinkSyntax::Autolink {
+                    continue;
+                }
+                let destination = (link.target.clone(), link.title.clone());
+                let label = match by_destination.get(&destination) {
+                    Some(label) => label.clone(),
+                    None => {
+                        let label = unique_label(&link.text, &labels);
+                        labels.push(label.to_lowercase());
+                        definitions.push_str(&format!(
+                            "[{}]: {}\n",
+                            label,
+                            format_destination(&link.target, link.title.as_deref())
+                        ));
+                        by_destination.insert(destination, label.clone());
+                        label
+                    }
+                };
+                let bang = if link.syntax == LinkSyntax::Image {
+                    "!"
+                } else {
+                    ""
+                };
+                replacements.push((
+                    link.span.clone(),
+                    format!("{}[{}][{}]", bang, link.text, label),
+                ));
+            }
+
+            if !definitions.is_empty() {
+                let separator = match content {
+                    "" => "",
+                    _ if content.ends_with("\n\n") => "",
+                    _ if content.ends_with('\n') => "\n",
+                    _ => "\n\n",
+                };
+                replacements.push((
+                    content.len()..content.len(),
+                    format!("{}{}", separator, definitions),
+                ));
+            }
+        }
+        LinkStyle::Inline => {
+            let mut converted = HashSet::new();
+            for link in &links {
+                let Some(label) = &link.reference_label else {
+                    continue;
+                };
+                converted.insert(label.to_lowercase());
+                let bang = if link.syntax == LinkSyntax::Image {
+                    "!"
+                } else {
+                    ""
+                };
+                let destination = format_destination(&link.target, link.title.as_deref());
+                replacements.push((
+                    link.span.clone(),
+                    format!("{}[{}]({})", bang, link.text, destination),
+                ));
+            }
+
+            // (span, whether it is removed) of every line.
+            let mut lines: Vec<(Range<usize>, bool)> = Vec::new();
+            let mut offset = 0;
+            for line in content.split_inclusive('\n') {
+                let is_converted = REFERENCE_DEFINITION
+                    .captures(line.trim_end_matches(['\r', '\n']))
+                    .is_some_and(|caps| converted.contains(&caps[1].to_lowercase()));
+                lines.push((offset..offset + line.len(), is_converted));
+                offset += line.len();
+            }
+            // A definition block at the end goes with the blank lines before it.
+            let trailing = lines
+                .iter()
+                .rev()
+                .take_while(|(_, removed)| *removed)
+                .count();
+            if trailing > 0 {
+                let start = lines.len() - trailing;
+                for (span, removed) in lines[..start].iter_mut().rev() {
+                    if !content[span.clone()].trim().is_empty() {
+                        break;
+                    }
+                    *removed = true;
+                }
+            }
+            for (span, removed) in lines {
+                if removed {
+                    replacements.push((span, String::new()));
+                }
+            }
+        }
+    }
+    replacements.sort_by_key(|(span, _)| span.start);
+
+    let mut after = String::with_capacity(content.len());
+    let mut edits = Vec::new();
+    let mut position = 0;
+    for (span, replacement) in replacements {
+        // A removed definition line can contain a link of its own, as in
+        // `[a]: [b][a]`; the edit that starts first wins.
+        if span.start < position {
+            continue;
+        }
+        after.push_str(&content[position..span.start]);
+        let start = after.len();
+        after.push_str(&replacement);
+        edits.push(AppliedEdit {
+            span: start..after.len(),
+            before: content[span.clone()].to_string(),
+            after: replacement,
+        });
+        position = span.end;
+    }
+    after.push_str(&content[position..]);
+    (after, edits)
+}
+
+/// Plans converting every document in `paths` to `style`.
+pub fn plan_style(
+    paths: &[PathBuf],
+    style: LinkStyle,
+) -> Result<Vec<FileChange>, Box<dyn std::error::Error>> {
+    let mut changes = Vec::new();
+    for path in paths {
+        let before = fs::read_to_string(path)?;
+        let (after, edits) = convert_style(&before, style);
+        if after != before {
+            changes.push(FileChange {
+                path: path.clone(),
+                before,
+                after,
+                edits,
+            });
+        }
+    }
+    Ok(changes)
+}
+
+/// A reference label derived from the link text, e.g. `getting-started`,
+/// numbered if it is already taken.
+fn unique_label(text: &str, taken: &[String]) -> String {
+    let mut base = String::new();
+    for c in text.to_lowercase().chars() {
+        if c.is_alphanumeric() {
+            base.push(c);
+        } else if !base.is_empty() && !base.ends_with('-') {
+            base.push('-');
+        }
+    }
+    let base = match base.trim_end_matches('-') {
+        "" => "link".to_string(),
+        trimmed => trimmed.to_string(),
+    };
+
+    let mut label = base.clone();
+    let mut number = 2;
+    while taken.contains(&label) {
+        label = format!("{}-{}", base, number);
+        number += 1;
+    }
+    label
+}
+
+/// `target "title"`, with `<>` around targets that contain spaces.
+fn format_destination(target: &str, title: Option<&str>) -> String {
+    let target = if target.contains(char::is_whitespace) {
+        format!("<{}>", target)
+    } else {
+        target.to_string()
+    };
+    match title {
+        Some(title) => format!("{} \"{}\"", target, title),
+        None => target,
+    }
+}
+
+/// Writes every change through a temporary file that is renamed over the
+/// original. All temporary files (and backups) are written before the first
+/// rename, so a failure to write one changes no document. Should a rename
+/// fail, the documents already renamed over are written back with their
+/// original content; any that cannot be are named in the error.
+pub fn write_changes(
+    changes: &[FileChange],
+    options: &FixOptions,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut staged: Vec<(PathBuf, &Path)> = Vec::new();
+    let stage = |change: &FileChange| -> std::io::Result<PathBuf> {
+        let temp = sibling(&change.path, |name| format!(".{}.doclink-tmp", name));
+        fs::write(&temp, &change.after)?;
+        fs::set_permissions(&temp, fs::metadata(&change.path)?.permissions())?;
+        if options.backup {
+            fs::copy(
+                &change.path,
+                sibling(&change.path, |name| format!("{}.bak", name)),
+            )?;
+        }
+        Ok(temp)
+    };
+
+    for change in changes {
+        match stage(change) {
+            Ok(temp) => staged.push((temp, &change.path)),
+            Err(e) => {
+                let _ = fs::remove_file(sibling(&change.path, |name| {
+                    format!(".{}.doclink-tmp", name)
+                }));
+                for (temp, _) in &staged {
+                    let _ = fs::remove_file(temp);
+                }
+                return Err(format!("{}: {}", change.path.display(), e).into());
+            }
+        }
+    }
+
+    for (index, (temp, path)) in staged.iter().enumerate() {
+        if let Err(e) = fs::rename(temp, path) {
+            for (temp, _) in &staged[index..] {
+                let _ = fs::remove_file(temp);
+            }
+            let unrestored: Vec<String> = changes[..index]
+                .iter()
+                .filter(|change| fs::write(&change.path, &change.before).is_err())
+                .map(|change| change.path.display().to_string())
+                .collect();
+            return Err(if unrestored.is_empty() {
+                format!("{}: {}; no file was changed", path.display(), e)
+            } else {
+                format!(
+                    "{}: {}; left rewritten: {}",
+                    path.display(),
+                    e,
+                    unrestored.join(", ")
+                )
+            }
+            .into());
+        }
+    }
+    Ok(())
+}
+
+/// Rewrites the affected files on disk, returning how many fixes were applied.
+pub fn apply_fixes(
+    fixes: &[Fix],
+    options: &FixOptions,
+) -> Result<usize, Box<dyn std::error::Error>> {
+    let changes = plan_fixes(fixes)?;
+    write_changes(&changes, options)?;
+    Ok(changes.iter().map(|change| change.edits.len()).sum())
+}
+
+/// A `git apply`-compatible patch of all changes, with paths relative to `base`.
+pub fn patch(changes: &[FileChange], base: &Path) -> String {
+    changes
+        .iter()
+        .map(|change| {
+            let path = change.path.strip_prefix(base).unwrap_or(&change.path);
+            let label = path.to_string_lossy().replace('\\', "/");
+            unified_diff(&label, &change.before, &change.after)
+        })
+        .collect()
+}
+
+fn sibling(path: &Path, name: impl Fn(&str) -> String) -> PathBuf {
+    let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+    path.with_file_name(name(&file_name))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Edit {
+    Keep(usize, usize),
+    Remove(usize),
+    Add(usize),
+}
+
+/// Unified diff of two versions of `label`, with three lines of context.
+pub fn unified_diff(label: &str, before: &str, after: &str) -> String {
+    const CONTEXT: usize = 3;
+    let old: Vec<&str> = before.split_inclusive('\n').collect();
+    let new: Vec<&str> = after.split_inclusive('\n').collect();
+    let edits = diff_lines(&old, &new);
+
+    let changed: Vec<usize> = edits
+        .iter()
+        .enumerate()
+        .filter(|(_, edit)| !matches!(edit, Edit::Keep(..)))
+        .map(|(index, _)| index)
+        .collect();
+    if changed.is_empty() {
+        return String::new();
+    }
+
+    let mut out = format!("--- a/{}\n+++ b/{}\n", label, label);
+    let mut group_start = 0;
+    while group_start < changed.len() {
+        let mut group_end = group_start;
+        while group_end + 1 < changed.len()
+            && changed[group_end + 1] - changed[group_end] <= 2 * CONTEXT
+        {
+            group_end += 1;
+        }
+        let start = changed[group_start].saturating_sub(CONTEXT);
+        let end = (changed[group_end] + CONTEXT + 1).min(edits.len());
+        let hunk = &edits[start..end];
+
+        let old_before = edits[..start]
+            .iter()
+            .filter(|edit| !matches!(edit, Edit::Add(_)))
+            .count();
+        let new_before = edits[..start]
+            .iter()
+            .filter(|edit| !matches!(edit, Edit::Remove(_)))
+            .count();
+        let old_count = hunk.iter().filter(|e| !matches!(e, Edit::Add(_))).count();
+        let new_count = hunk
+            .iter()
+            .filter(|e| !matches!(e, Edit::Remove(_)))
+            .count();
+        let range = |before: usize, count: usize| {
+            let first = if count == 0 { before } else { before + 1 };
+            format!("{},{}", first, count)
+        };
+        out.push_str(&format!(
+            "@@ -{} +{} @@\n",
+            range(old_before, old_count),
+            range(new_before, new_count)
+        ));
+
+        for edit in hunk {
+            let (prefix, line) = match *edit {
+                Edit::Keep(i, _) => (' ', old[i]),
+                Edit::Remove(i) => ('-', old[i]),
+                Edit::Add(j) => ('+', new[j]),
+            };
+            out.push(prefix);
+            out.push_str(line);
+            if !line.ends_with('\n') {
+                out.push_str("\n\\ No newline at end of file\n");
+            }
+        }
+        group_start = group_end + 1;
+    }
+
+    out
+}
+
+/// Line edits turning `old` into `new`: common prefix and suffix are kept,
+/// and the middle is diffed by longest common subsequence when small enough.
+fn diff_lines(old: &[&str], new: &[&str]) -> Vec<Edit> {
+    let prefix = old.iter().zip(new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let (old_mid, new_mid) = (
+        &old[prefix..old.len() - suffix],
+        &new[prefix..new.len() - suffix],
+    );
+
+    let mut edits: Vec<Edit> = (0..prefix).map(|i| Edit::Keep(i, i)).collect();
+    let (n, m) = (old_mid.len(), new_mid.len());
+    if n * m <= 4_000_000 {
+        // lcs[i][j]: length of the LCS of old_mid[i..] and new_mid[j..].
+        let mut lcs = vec![vec![0u32; m + 1]; n + 1];
+        for i in (0..n).rev() {
+            for j in (0..m).rev() {
+                lcs[i][j] = if old_mid[i] == new_mid[j] {
+                    lcs[i + 1][j + 1] + 1
+                } else {
+                    lcs[i + 1][j].max(lcs[i][j + 1])
+                };
+            }
+        }
+        let (mut i, mut j) = (0, 0);
+        while i < n || j < m {
+            if i < n && j < m && old_mid[i] == new_mid[j] {
+                edits.push(Edit::Keep(prefix + i, prefix + j));
+                i += 1;
+                j += 1;
+            } else if i < n && (j == m || lcs[i + 1][j] >= lcs[i][j + 1]) {
+                edits.push(Edit::Remove(prefix + i));
+                i += 1;
+            } else {
+                edits.push(Edit::Add(prefix + j));
+                j += 1;
+            }
+        }
+    } else {
+        edits.extend((0..n).map(|i| Edit::Remove(prefix + i)));
+        edits.extend((0..m).map(|j| Edit::Add(prefix + j)));
+    }
+    edits.extend((0..suffix).map(|k| Edit::Keep(old.len() - suffix + k, new.len() - suffix + k)));
+
+    edits
+}
+
+impl LinkAnalyzer {
+    /// Rewrites for every link whose resolution suggests a portable target:
+    /// absolute links to the published site and absolute filesystem paths.
+    pub fn suggested_fixes(&self) -> Vec<Fix> {
+        let mut fixes = Vec::new();
+
+        for (file_path, links) in &self.documents {
+            for link in links {
+                let resolution = self.resolve(file_path, &link.target);
+                if let Some(replacement) = resolution.suggestion {
+                    fixes.push(Fix {
+                        file_path: file_path.clone(),
+                        line_number: link.line_number,
+                        target: link.target.clone(),
+                        replacement,
+                        confidence: resolution.confidence.unwrap_or(Confidence::Safe),
+                    });
+                }
+            }
+        }
+
+        fixes.sort_by(|a, b| (&a.file_path, a.line_number).cmp(&(&b.file_path, b.line_number)));
+        fixes
+    }
+
+    /// Rewrites that put every internal link in `style`, keeping what it
+    /// points to. Links that would leave the analyzed directory are not
+    /// made root-relative.
+    pub fn normalize_fixes(&self, style: PathStyle) -> Vec<Fix> {
+        let base = normalize_path(&self.base_path);
+        let mut fixes = Vec::new();
+
+        for (file_path, links) in &self.documents {
+            for link in links {
+                let target = &link.target;
+                let (path, fragment) = split_fragment(target);
+                if LinkKind::classify(target) != LinkKind::Internal || path.is_empty() {
+                    continue;
+                }
+                let Some(resolved) = self.resolve_target(file_path, target) else {
+                    continue;
+                };
+                let resolved = normalize_path(&resolved);
+
+                let mut normalized = match style {
+                    PathStyle::RootRelative => match resolved.strip_prefix(&base) {
+                        Ok(relative) if !relative.as_os_str().is_empty() => {
+                            format!("/{}", relative.to_string_lossy().replace('\\', "/"))
+                        }
+                        _ => continue,
+                    },
+                    _ => {
+                        let relative = self.relative_target(file_path, &resolved);
+                        if relative.is_empty() {
+                            continue;
+                        }
+                        if style == PathStyle::DotPrefixed && !relative.starts_with("../") {
+                            format!("./{}", relative)
+                        } else {
+                            relative
+                        }
+                    }
+                };
+                if path.ends_with('/') {
+                    normalized.push('/');
+                }
+                if let Some(fragment) = fragment {
+                    normalized = format!("{}#{}", normalized, fragment);
+                }
+
+                if normalized != *target {
+                    fixes.push(Fix {
+                        file_path: file_path.clone(),
+                        line_number: link.line_number,
+                        target: target.clone(),
+                        replacement: normalized,
+                        confidence: Confidence::Safe,
+                    });
+                }
+            }
+        }
+
+        fixes.sort_by(|a, b| (&a.file_path, a.line_number).cmp(&(&b.file_path, b.line_number)));
+        fixes
+    }
+
+    /// Rewrites for fragments that no longer match a heading. `previous`
+    /// returns a document's earlier content, e.g. from git: a heading renamed
+    /// since then gives a safe fix. Otherwise the closest existing anchor is
+    /// suggested as a risky one.
+    pub fn anchor_fixes(&self, previous: impl Fn(&Path) -> Option<String>) -> Vec<Fix> {
+        let mut renames: HashMap<PathBuf, HashMap<String, String>> = HashMap::new();
+        let mut fixes = Vec::new();
+
+        for (file_path, links) in &self.documents {
+            for link in links {
+                let resolution = self.resolve(file_path, &link.target);
+                if resolution.anchor_found != Some(false) {
+                    continue;
+                }
+                let (Some(resolved), Some(anchors)) = (&resolution.resolved, &resolution.anchors)
+                else {
+                    continue;
+                };
+                let (path, Some(fragment)) = split_fragment(&link.target) else {
+                    continue;
+                };
+                let fragment = fragment.to_lowercase();
+
+                let renamed = renames.entry(resolved.clone()).or_insert_with(|| {
+                    previous(resolved)
+                        .map(|old| {
+                            let old = anchors::DocumentHeadings::parse_with(
+                                &old,
+                                self.heading_styles,
+                                self.slugger,
+                            )
+                            .anchors;
+                            anchors::renamed_anchors(&old, anchors)
+                        })
+                        .unwrap_or_default()
+                });
+                let replacement = match renamed.get(&fragment) {
+                    Some(anchor) => Some((anchor.clone(), Confidence::Safe)),
+                    None => closest_anchor(&fragment, anchors)
+                        .map(|anchor| (anchor.clone(), Confidence::Risky)),
+                };
+
+                if let Some((anchor, confidence)) = replacement {
+                    fixes.push(Fix {
+                        file_path: file_path.clone(),
+                        line_number: link.line_number,
+                        target: link.target.clone(),
+                        replacement: format!("{}#{}", path, anchor),
+                        confidence,
+                    });
+                }
+            }
+        }
+
+        fixes.sort_by(|a, b| (&a.file_path, a.line_number).cmp(&(&b.file_path, b.line_number)));
+        fixes
+    }
+
+    /// Rewrites for every link whose whole target matches `from`, replaced by
+    /// `to` with `$1`-style references to its capture groups.
+    pub fn rewrite_fixes(&self, from: &Regex, to: &str) -> Result<Vec<Fix>, String> {
+        let anchored =
+            Regex::new(&format!("^(?:{})$", from.as_str())).map_err(|e| e.to_string())?;
+        let mut fixes = Vec::new();
+
+        for (file_path, links) in &self.documents {
+            for link in links {
+                if include::is_directive(&link.target) || !anchored.is_match(&link.target) {
+                    continue;
+                }
+                let replacement = anchored.replace(&link.target, to).into_owned();
+                if replacement != link.target {
+                    fixes.push(Fix {
+                        file_path: file_path.clone(),
+                        line_number: link.line_number,
+                        target: link.target.clone(),
+                        replacement,
+                        confidence: Confidence::Safe,
+                    });
+                }
+            }
+        }
+
+        fixes.sort_by(|a, b| (&a.file_path, a.line_number).cmp(&(&b.file_path, b.line_number)));
+        Ok(fixes)
+    }
+}
+
+/// The one anchor closest to `fragment` by edit distance, if it is close
+/// enough to be a typo or a small rename.
+pub(crate) fn closest_anchor<'a>(fragment: &str, anchors: &'a [String]) -> Option<&'a String> {
+    let mut closest: Vec<(usize, &String)> = anchors
+        .iter()
+        .map(|anchor| (strsim::levenshtein(fragment, anchor), anchor))
+        .filter(|(distance, _)| *distance <= 3 && distance * 3 <= fragment.len())
+        .collect();
+    closest.sort();
+    closest.dedup_by_key(|(_, anchor)| *anchor);
+    match closest.as_slice() {
+        [(distance, anchor), rest @ ..] if rest.first().is_none_or(|(d, _)| d > distance) => {
+            Some(anchor)
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vfs;
+
+    fn fix(line_number: usize, target: &str, replacement: &str) -> Fix {
+        Fix {
+            file_path: PathBuf::from("doc.md"),
+            line_number,
+            target: target.to_string(),
+            replacement: replacement.to_string(),
+            confidence: Confidence::Safe,
+        }
+    }
+
+    #[test]
+    fn test_apply_inline_fixes() {
+        let content = "# Doc\r\nSee [a](https://x.io/a) and [ab](https://x.io/ab \"Title\").\r\n";
+        let fixes = [
+            fix(2, "https://x.io/a", "a.md"),
+            fix(2, "https://x.io/ab", "ab.md"),
+        ];
+        let (updated, applied) = apply_to_content(content, &fixes.iter().collect::<Vec<_>>());
+
+        assert_eq!(applied, 2);
+        assert_eq!(
+            updated,
+            "# Doc\r\nSee [a](a.md) and [ab](ab.md \"Title\").\r\n"
+        );
+    }
+
+    #[test]
+    fn test_edit_spans() {
+        let content = "x\r\n[a](aaaa) [b](bb) [c](cccc)\n\n[d]: dd\n";
+        let fixes = [
+            fix(2, "bb", "bbbbbb"),
+            fix(2, "aaaa", "a"),
+            fix(4, "dd", "d"),
+            fix(2, "cccc", "cc"),
+        ];
+        let (updated, edits) = apply_with_edits(content, &fixes.iter().collect::<Vec<_>>());
+
+        assert_eq!(updated, "x\r\n[a](a) [b](bbbbbb) [c](cc)\n\n[d]: d\n");
+        let replaced: Vec<(&str, &str)> = edits
+            .iter()
+            .map(|edit| (&updated[edit.span.clone()], edit.before.as_str()))
+            .collect();
+        assert_eq!(
+            replaced,
+            vec![("a", "aaaa"), ("bbbbbb", "bb"), ("cc", "cccc"), ("d", "dd")]
+        );
+    }
+
+    #[test]
+    fn test_apply_fix_to_wrapped_link() {
+        let content =
+            "Read the [setup\r\nguide](stup.md \"Setup\") and [logo ![stup](stup.md)](stup.md).\n";
+        let fixes = [
+            fix(1, "stup.md", "setup.md"),
+            fix(2, "stup.md", "setup.md"),
+            fix(2, "stup.md", "setup.md"),
+        ];
+        let (updated, edits) = apply_with_edits(content, &fixes.iter().collect::<Vec<_>>());
+
+        assert_eq!(
+            updated,
+            "Read the [setup\r\nguide](setup.md \"Setup\") and [logo ![stup](setup.md)](setup.md).\n"
+        );
+        assert_eq!(edits.len(), 3);
+        assert!(edits
+            .iter()
+            .all(|edit| &updated[edit.span.clone()] == "setup.md"));
+    }
+
+    #[test]
+    fn test_apply_reference_fix() {
+        let content = "Read the [guide][g].\n\n[g]: https://x.io/guide/ \"Guide\"\n";
+        let (updated, applied) =
+            apply_to_content(content, &[&fix(1, "https://x.io/guide/", "guide/index.md")]);
+
+        assert_eq!(applied, 1);
+        assert_eq!(
+            updated,
+            "Read the [guide][g].\n\n[g]: guide/index.md \"Guide\"\n"
+        );
+    }
+
+    #[test]
+    fn test_crlf_line_endings_are_preserved() {
+        let content = "See [a][a] and [b](https://x.io/b).\r\nMore\n\r\n[a]: https://x.io/a\r\n";
+        let fixes = [
+            fix(1, "https://x.io/a", "a.md"),
+            fix(1, "https://x.io/b", "b.md"),
+        ];
+        let (updated, applied) = apply_to_content(content, &fixes.iter().collect::<Vec<_>>());
+
+        assert_eq!(applied, 2);
+        assert_eq!(
+            updated,
+            "See [a][a] and [b](b.md).\r\nMore\n\r\n[a]: a.md\r\n"
+        );
+    }
+
+    #[test]
+    fn test_unmatched_fix_is_skipped() {
+        let content = "[a](b.md)\n";
+        let (updated, applied) = apply_to_content(content, &[&fix(1, "c.md", "d.md")]);
+        assert_eq!(applied, 0);
+        assert_eq!(updated, content);
+    }
+
+    #[test]
+    fn test_unified_diff() {
+        let before = "1\n2\n3\n4\n5\n6\n7\n8\n9\n10\n11\n12";
+        let after = "1\nTWO\n3\n4\n5\n6\n7\n8\n9\n10\n11\n12\n13\n";
+        assert_eq!(
+            unified_diff("doc.md", before, after),
+            "--- a/doc.md\n+++ b/doc.md\n@@ -1,5 +1,5 @@\n 1\n-2\n+TWO\n 3\n 4\n 5\n@@ -9,4 +9,5 @@\n 9\n 10\n 11\n-12\n\\ No newline at end of file\n+12\n+13\n"
+        );
+        assert_eq!(unified_diff("doc.md", before, before), "");
+    }
+
+    #[test]
+    fn test_apply_fixes_atomically_with_backup() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let doc = temp_dir.path().join("doc.md");
+        fs::write(&doc, "[a](https://x.io/a)\n").unwrap();
+        let fixes = [Fix {
+            file_path: doc.clone(),
+            ..fix(1, "https://x.io/a", "a.md")
+        }];
+
+        let changes = plan_fixes(&fixes).unwrap();
+        assert_eq!(
+            patch(&changes, temp_dir.path()),
+            "--- a/doc.md\n+++ b/doc.md\n@@ -1,1 +1,1 @@\n-[a](https://x.io/a)\n+[a](a.md)\n"
+        );
+
+        let applied = apply_fixes(&fixes, &FixOptions { backup: true }).unwrap();
+        assert_eq!(applied, 1);
+        assert_eq!(fs::read_to_string(&doc).unwrap(), "[a](a.md)\n");
+        assert_eq!(
+            fs::read_to_string(temp_dir.path().join("doc.md.bak")).unwrap(),
+            "[a](https://x.io/a)\n"
+        );
+        let mut names: Vec<String> = fs::read_dir(temp_dir.path())
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+            .collect();
+        names.sort();
+        assert_eq!(names, vec!["doc.md", "doc.md.bak"]);
+    }
+
+    #[test]
+    fn test_failed_write_changes_nothing() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let doc = temp_dir.path().join("doc.md");
+        fs::write(&doc, "old\n").unwrap();
+        let changes = [
+            FileChange {
+                path: doc.clone(),
+                before: "old\n".to_string(),
+                after: "new\n".to_string(),
+                edits: Vec::new(),
+            },
+            FileChange {
+                path: temp_dir.path().join("missing/doc.md"),
+                before: String::new(),
+                after: "new\n".to_string(),
+                edits: Vec::new(),
+            },
+        ];
+
+        assert!(write_changes(&changes, &FixOptions::default()).is_err());
+        assert_eq!(fs::read_to_string(&doc).unwrap(), "old\n");
+        assert_eq!(fs::read_dir(temp_dir.path()).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn test_failed_rename_restores_documents() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let doc = temp_dir.path().join("doc.md");
+        fs::write(&doc, "old\n").unwrap();
+        // A file can't be renamed over a directory.
+        let dir = temp_dir.path().join("dir.md");
+        fs::create_dir(&dir).unwrap();
+        let changes = [
+            FileChange {
+                path: doc.clone(),
+                before: "old\n".to_string(),
+                after: "new\n".to_string(),
+                edits: Vec::new(),
+            },
+            FileChange {
+                path: dir,
+                before: String::new(),
+                after: "new\n".to_string(),
+                edits: Vec::new(),
+            },
+        ];
+
+        let err = write_changes(&changes, &FixOptions::default()).unwrap_err();
+        assert!(err.to_string().ends_with("no file was changed"), "{}", err);
+        assert_eq!(fs::read_to_string(&doc).unwrap(), "old\n");
+        assert_eq!(fs::read_dir(temp_dir.path()).unwrap().count(), 2);
+    }
+
+    #[test]
+    fn test_convert_to_reference_style() {
+        let content = "See [the guide](https://example.com/guide \"Guide\") and ![logo](img/logo.png).\nAgain: [Guide](https://example.com/guide \"Guide\"), [API](https://example.com/api).\n\n[api]: https://example.com/v1\n";
+        let (after, edits) = convert_style(content, LinkStyle::Reference);
+        assert_eq!(
+            after,
+            "See [the guide][the-guide] and ![logo][logo].\nAgain: [Guide][the-guide], [API][api-2].\n\n[api]: https://example.com/v1\n\n[the-guide]: https://example.com/guide \"Guide\"\n[logo]: img/logo.png\n[api-2]: https://example.com/api\n"
+        );
+        for edit in &edits {
+            assert_eq!(&after[edit.span.clone()], edit.after);
+        }
+
+        let (inline, _) = convert_style(&after, LinkStyle::Inline);
+        assert_eq!(
+            inline,
+            "See [the guide](https://example.com/guide \"Guide\") and ![logo](img/logo.png).\nAgain: [Guide](https://example.com/guide \"Guide\"), [API](https://example.com/api).\n\n[api]: https://example.com/v1\n"
+        );
+    }
+
+    #[test]
+    fn test_convert_to_inline_style() {
+        let content = "[A][a], [b][] and [C][a].\r\n\r\n[a]: <docs/a b.md>\r\n[b]: b.md 'B'\r\n[unused]: c.md\r\n";
+        let (after, edits) = convert_style(content, LinkStyle::Inline);
+        assert_eq!(
+            after,
+            "[A](<docs/a b.md>), [b](b.md \"B\") and [C](<docs/a b.md>).\r\n\r\n[unused]: c.md\r\n"
+        );
+        assert_eq!(edits.len(), 5);
+        assert_eq!(convert_style(&after, LinkStyle::Inline).0, after);
+
+        // The definition is removed along with the link written on its line.
+        assert_eq!(
+            convert_style("See [b][a].\n\n[a]: [b][a]\n", LinkStyle::Inline).0,
+            "See [b]([b][a]).\n"
+        );
+    }
+
+    #[test]
+    fn test_rewrite_fixes() {
+        let mut vfs = vfs::MemoryFileSystem::new();
+        vfs.insert(
+            "docs/README.md",
+            "[A](https://old.example.com/guide/)\n[B](https://old.example.com.evil/x)\n[C][c]\n\n[c]: https://old.example.com/api#client",
+        );
+
+        let mut analyzer = LinkAnalyzer::new(PathBuf::from("docs")).with_filesystem(vfs);
+        analyzer.analyze_directory().unwrap();
+
+        let from = Regex::new(r"https://old\.example\.com/(.*)").unwrap();
+        let fixes: Vec<_> = analyzer
+            .rewrite_fixes(&from, "https://docs.example.com/$1")
+            .unwrap()
+            .into_iter()
+            .map(|fix| (fix.line_number, fix.replacement))
+            .collect();
+        assert_eq!(
+            fixes,
+            vec![
+                (1, "https://docs.example.com/guide/".to_string()),
+                (3, "https://docs.example.com/api#client".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_normalize_fixes() {
+        let mut vfs = vfs::MemoryFileSystem::new();
+        vfs.insert(
+            "docs/guide/index.md",
+            "[A](./setup.md#install)\n[B](../api/)\n[C](/guide/setup.md)\n[D](#top)\n[E](../../outside.md)",
+        );
+        vfs.insert("docs/guide/setup.md", "");
+
+        let mut analyzer = LinkAnalyzer::new(PathBuf::from("docs")).with_filesystem(vfs);
+        analyzer.analyze_directory().unwrap();
+
+        let normalized = |style| -> Vec<(String, String)> {
+            analyzer
+                .normalize_fixes(style)
+                .into_iter()
+                .map(|fix| (fix.target, fix.replacement))
+                .collect()
+        };
+        let pair = |a: &str, b: &str| (a.to_string(), b.to_string());
+        assert_eq!(
+            normalized(PathStyle::Plain),
+            vec![
+                pair("./setup.md#install", "setup.md#install"),
+                pair("/guide/setup.md", "setup.md"),
+            ]
+        );
+        assert_eq!(
+            normalized(PathStyle::DotPrefixed),
+            vec![pair("/guide/setup.md", "./setup.md")]
+        );
+        assert_eq!(
+            normalized(PathStyle::RootRelative),
+            vec![
+                pair("./setup.md#install", "/guide/setup.md#install"),
+                pair("../api/", "/api/"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_anchor_fixes() {
+        let mut vfs = vfs::MemoryFileSystem::new();
+        vfs.insert(
+            "docs/README.md",
+            "[A](guide.md#install)\n[B](guide.md#usagee)\n[C](#intro)\n[D](guide.md#gone)",
+        );
+        vfs.insert(
+            "docs/guide.md",
+            "# Guide\n## Installation\n## Usage\n## Configuration",
+        );
+
+        let mut analyzer = LinkAnalyzer::new(PathBuf::from("docs")).with_filesystem(vfs);
+        analyzer.analyze_directory().unwrap();
+
+        let fixes = |previous: &dyn Fn(&Path) -> Option<String>| -> Vec<(String, Confidence)> {
+            analyzer
+                .anchor_fixes(previous)
+                .into_iter()
+                .map(|fix| (fix.replacement, fix.confidence))
+                .collect()
+        };
+        assert_eq!(
+            fixes(&|_| None),
+            vec![("guide.md#usage".to_string(), Confidence::Risky)]
+        );
+        let old_guide = |path: &Path| {
+            path.ends_with("guide.md")
+                .then(|| "# Guide\n## Install\n## Usage\n## Gone".to_string())
+        };
+        assert_eq!(
+            fixes(&old_guide),
+            vec![
+                ("guide.md#installation".to_string(), Confidence::Safe),
+                ("guide.md#usage".to_string(), Confidence::Risky),
+                ("guide.md#configuration".to_string(), Confidence::Safe),
+            ]
+        );
+    }
+}
@@ -0,0 +1,201 @@
+use crate::vfs::MemoryFileSystem;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Shape of a synthetic documentation tree, for stress tests and benchmarks.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FixtureSpec {
+    pub documents: usize,
+    /// Links in every document besides the one back to the README.
+    pub links_per_document: usize,
+    /// Fraction of links, from 0.0 to 1.0, that point at a missing file or
+    /// heading.
+    pub broken_ratio: f64,
+    /// Documents per directory before the next directory is started.
+    pub documents_per_directory: usize,
+    /// Sections per document, each an anchor other documents link to.
+    pub sections: usize,
+    /// The same seed always generates the same tree.
+    pub seed: u64,
+}
+
+impl Default for FixtureSpec {
+    fn default() -> Self {
+        Self {
+            documents: 1000,
+            links_per_document: 10,
+            broken_ratio: 0.05,
+            documents_per_directory: 50,
+            sections: 5,
+            seed: 0,
+        }
+    }
+}
+
+/// A generated tree: file contents by path relative to its root, plus what
+/// a check of it must find.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Fixture {
+    pub files: BTreeMap<PathBuf, String>,
+    /// Every Markdown link written, broken or not.
+    pub links: usize,
+    /// Links to missing files or headings.
+    pub broken: usize,
+}
+
+impl Fixture {
+    /// Generates a tree of `README.md` and `section-N/doc-M.md` documents.
+    /// The README links to the first document of every directory; the other
+    /// links go to random documents, half of them to one of their sections,
+    /// and one in ten is external.
+    pub fn generate(spec: &FixtureSpec) -> Self {
+        let mut rng = SplitMix64(spec.seed);
+        let per_directory = spec.documents_per_directory.max(1);
+        let paths: Vec<PathBuf> = (0..spec.documents)
+            .map(|index| {
+                PathBuf::from(format!("section-{}", index / per_directory))
+                    .join(format!("doc-{}.md", index))
+            })
+            .collect();
+        let mut fixture = Fixture::default();
+
+        let mut readme = String::from("# Synthetic documentation\n\n");
+        for path in paths.iter().step_by(per_directory) {
+            readme.push_str(&format!("- [{}](./{})\n", path.display(), path.display()));
+            fixture.links += 1;
+        }
+        fixture.files.insert(PathBuf::from("README.md"), readme);
+
+        for (index, path) in paths.iter().enumerate() {
+            let mut content = format!("# Document {}\n\n[Home](../README.md)\n", index);
+            fixture.links += 1;
+
+            for link in 0..spec.links_per_document {
+                let broken = rng.next_f64() < spec.broken_ratio;
+                let target_index = rng.below(paths.len());
+                let mut target = relative_link(path, &paths[target_index]);
+                if rng.below(10) == 0 {
+                    target = format!("https://example.com/docs/{}", target_index);
+                } else if broken && rng.below(2) == 0 {
+                    target = relative_link(path, &PathBuf::from(format!("missing-{}.md", link)));
+                    fixture.broken += 1;
+                } else if broken {
+                    target.push_str("#no-such-section");
+                    fixture.broken += 1;
+                } else if spec.sections > 0 && rng.below(2) == 0 {
+                    target.push_str(&format!("#section-{}", rng.below(spec.sections) + 1));
+                }
+                content.push_str(&format!("\nSee [link {}]({}) for details.\n", link, target));
+                fixture.links += 1;
+            }
+
+            for section in 1..=spec.sections {
+                content.push_str(&format!(
+                    "\n## Section {}\n\nSynthetic text for section {} of document {}.\n",
+                    section, section, index
+                ));
+            }
+            fixture.files.insert(path.clone(), content);
+        }
+
+        fixture
+    }
+
+    /// Writes every file under `root`, creating directories as needed.
+    pub fn write_to(&self, root: &Path) -> std::io::Result<()> {
+        for (path, content) in &self.files {
+            let path = root.join(path);
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::write(path, content)?;
+        }
+        Ok(())
+    }
+
+    /// The tree as an in-memory file system rooted at `root`.
+    pub fn to_memory_file_system(&self, root: &Path) -> MemoryFileSystem {
+        let mut fs = MemoryFileSystem::new();
+        for (path, content) in &self.files {
+            fs.insert(root.join(path), content.as_str());
+        }
+        fs
+    }
+}
+
+/// `to` as written in a link from document `from`, both relative to the root.
+fn relative_link(from: &Path, to: &Path) -> String {
+    let from_dir = from.parent().unwrap_or(Path::new(""));
+    if to.parent() == Some(from_dir) {
+        return format!("./{}", to.file_name().unwrap_or_default().to_string_lossy());
+    }
+    let ups = "../".repeat(from_dir.components().count());
+    format!("{}{}", ups, to.display()).replace('\\', "/")
+}
+
+/// A small, dependency-free PRNG; quality is plenty for picking link targets.
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    fn below(&mut self, bound: usize) -> usize {
+        (self.next_u64() % bound.max(1) as u64) as usize
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::LinkAnalyzer;
+
+    #[test]
+    fn test_generated_fixture_matches_analysis() {
+        let spec = FixtureSpec {
+            documents: 120,
+            documents_per_directory: 25,
+            broken_ratio: 0.2,
+            seed: 7,
+            ..FixtureSpec::default()
+        };
+        let fixture = Fixture::generate(&spec);
+        assert_eq!(fixture, Fixture::generate(&spec));
+        assert_eq!(fixture.files.len(), 121);
+        assert!(fixture.broken > 0);
+
+        let root = PathBuf::from("docs");
+        let mut analyzer =
+            LinkAnalyzer::new(root.clone()).with_filesystem(fixture.to_memory_file_system(&root));
+        analyzer.analyze_directory().unwrap();
+        assert_eq!(analyzer.link_count(), fixture.links);
+        assert_eq!(analyzer.find_broken_links().len(), fixture.broken);
+    }
+
+    #[test]
+    fn test_relative_link() {
+        let doc = Path::new("section-0/doc-1.md");
+        assert_eq!(
+            relative_link(doc, Path::new("section-0/doc-2.md")),
+            "./doc-2.md"
+        );
+        assert_eq!(
+            relative_link(doc, Path::new("section-3/doc-9.md")),
+            "../section-3/doc-9.md"
+        );
+        assert_eq!(
+            relative_link(doc, Path::new("missing-0.md")),
+            "../missing-0.md"
+        );
+    }
+}
@@ -0,0 +1,64 @@
+use std::collections::BTreeMap;
+
+/// Top-level scalar fields of a document's YAML front matter, such as
+/// `title: Setup` or `owner: "@docs-team"`, with quotes and trailing
+/// comments removed. Lists, maps and block scalars are left out.
+pub fn fields(content: &str) -> BTreeMap<String, String> {
+    let content = content.strip_prefix('\u{feff}').unwrap_or(content);
+    let mut fields = BTreeMap::new();
+    let mut lines = content.lines();
+    if lines.next().map(str::trim_end) != Some("---") {
+        return fields;
+    }
+
+    for line in lines {
+        let line = line.trim_end();
+        if line == "---" || line == "..." {
+            break;
+        }
+        if line.starts_with([' ', '\t', '#', '-']) {
+            continue;
+        }
+        let Some((key, value)) = line.split_once(':') else {
+            continue;
+        };
+        if let Some(value) = scalar(value.trim()) {
+            fields.insert(key.trim().to_string(), value);
+        }
+    }
+    fields
+}
+
+fn scalar(value: &str) -> Option<String> {
+    if let Some(quoted) = [('"', '"'), ('\'', '\'')]
+        .iter()
+        .find_map(|(open, close)| value.strip_prefix(*open)?.strip_suffix(*close))
+    {
+        return Some(quoted.to_string());
+    }
+    let value = value.split(" #").next().unwrap_or_default().trim_end();
+    if value.is_empty() || value.starts_with(['[', '{', '|', '>', '&', '*']) {
+        return None;
+    }
+    Some(value.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fields() {
+        let content = "---\ntitle: \"Getting Started\"\nowner: '@docs-team'\nteam: platform # since 2024\ntags: [a, b]\nsummary: |\n  Long text\nnested:\n  owner: nobody\n---\nowner: body text\n";
+        let fields = fields(content);
+        assert_eq!(
+            fields.into_iter().collect::<Vec<_>>(),
+            vec![
+                ("owner".to_string(), "@docs-team".to_string()),
+                ("team".to_string(), "platform".to_string()),
+                ("title".to_string(), "Getting Started".to_string()),
+            ]
+        );
+        assert!(super::fields("# No front matter\nowner: x\n").is_empty());
+    }
+}
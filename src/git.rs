@@ -0,0 +1,357 @@
+use regex::Regex;
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::LazyLock;
+
+static COMPARE_URL: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"^https?://([^/]+)/(.+?)(?:/-)?/compare/([^/?#]+?)\.{2,3}([^/?#]+?)(?:\.diff|\.patch)?(?:[?#].*)?$",
+    )
+    .unwrap()
+});
+
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum PathChange {
+    Deleted { path: PathBuf },
+    Renamed { from: PathBuf, to: PathBuf },
+}
+
+impl PathChange {
+    pub fn old_path(&self) -> &Path {
+        match self {
+            PathChange::Deleted { path } => path,
+            PathChange::Renamed { from, .. } => from,
+        }
+    }
+}
+
+/// Lists files under `base_path` that were deleted or renamed since `git_ref`,
+/// comparing against the working tree. Paths are relative to `base_path`.
+pub fn removed_paths(
+    base_path: &Path,
+    git_ref: &str,
+) -> Result<Vec<PathChange>, Box<dyn std::error::Error>> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(base_path)
+        .args([
+            "diff",
+            "--name-status",
+            "-z",
+            "-M",
+            "--relative",
+            git_ref,
+            "--",
+        ])
+        .output()?;
+
+    if !output.status.success() {
+        return Err(format!(
+            "git diff against '{}' failed: {}",
+            git_ref,
+            String::from_utf8_lossy(&output.stderr).trim()
+        )
+        .into());
+    }
+
+    Ok(parse_name_status(&String::from_utf8_lossy(&output.stdout)))
+}
+
+/// Content of `path` as of `git_ref`, or `None` if it did not exist then (or
+/// is not in a git repository).
+pub fn show_file(path: &Path, git_ref: &str) -> Option<String> {
+    let dir = path.parent().filter(|dir| !dir.as_os_str().is_empty());
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(dir.unwrap_or(Path::new(".")))
+        .arg("show")
+        .arg(format!(
+            "{}:./{}",
+            git_ref,
+            path.file_name()?.to_string_lossy()
+        ))
+        .output()
+        .ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Tag and branch names of the repository containing `dir`.
+pub fn refs(dir: &Path) -> Result<BTreeSet<String>, Box<dyn std::error::Error>> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args([
+            "for-each-ref",
+            "--format=%(refname:short)",
+            "refs/tags",
+            "refs/heads",
+        ])
+        .output()?;
+    if !output.status.success() {
+        return Err(format!(
+            "git for-each-ref failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        )
+        .into());
+    }
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(str::to_string)
+        .collect())
+}
+
+/// The `origin` remote of the repository containing `dir` in the form
+/// `compare_refs` returns, e.g. `github.com/org/repo`.
+pub fn origin_repository(dir: &Path) -> Option<String> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(["remote", "get-url", "origin"])
+        .output()
+        .ok()?;
+    output
+        .status
+        .success()
+        .then(|| repository_name(String::from_utf8_lossy(&output.stdout).trim()))
+}
+
+/// `https://github.com/org/repo.git`, `git@github.com:org/repo` and
+/// `ssh://git@github.com/org/repo` all become `github.com/org/repo`.
+fn repository_name(remote: &str) -> String {
+    let remote = remote
+        .split_once("://")
+        .map_or(remote, |(_, rest)| rest)
+        .trim_end_matches('/');
+    let remote = remote.strip_suffix(".git").unwrap_or(remote);
+    let remote = remote.split_once('@').map_or(remote, |(_, host)| host);
+    remote.replacen(':', "/", 1).to_lowercase()
+}
+
+/// Repository, base and head of a GitHub, GitLab or Gitea compare view such
+/// as `https://github.com/org/repo/compare/v1.2.2...v1.2.3`.
+pub fn compare_refs(url: &str) -> Option<(String, String, String)> {
+    let caps = COMPARE_URL.captures(url)?;
+    Some((
+        format!("{}/{}", &caps[1], &caps[2]).to_lowercase(),
+        caps[3].to_string(),
+        caps[4].to_string(),
+    ))
+}
+
+/// Shallow-fetches `url` at `git_ref` (a branch, tag or commit; the remote HEAD
+/// when `None`) into `cache_dir` and returns the checkout directory. An existing
+/// checkout is updated in place, and reused as-is if the remote can't be reached.
+pub fn checkout_remote(
+    url: &str,
+    git_ref: Option<&str>,
+    cache_dir: &Path,
+) -> Result<PathBuf, Box<dyn std::error::Error>> {
+    let checkout = cache_dir.join(checkout_name(url, git_ref));
+    let cached = checkout.join(".git").is_dir();
+
+    if !cached {
+        fs::create_dir_all(&checkout)?;
+        run_git(&checkout, &["init", "--quiet"])?;
+    }
+
+    let fetched = run_git(
+        &checkout,
+        &[
+            "fetch",
+            "--quiet",
+            "--depth",
+            "1",
+            url,
+            git_ref.unwrap_or("HEAD"),
+        ],
+    )
+    .and_then(|_| {
+        run_git(
+            &checkout,
+            &["checkout", "--quiet", "--detach", "FETCH_HEAD"],
+        )
+    });
+
+    match fetched {
+        Ok(()) => Ok(checkout),
+        Err(_) if cached => Ok(checkout),
+        Err(e) => {
+            let _ = fs::remove_dir_all(&checkout);
+            Err(e)
+        }
+    }
+}
+
+fn checkout_name(url: &str, git_ref: Option<&str>) -> String {
+    let url = url
+        .trim_end_matches('/')
+        .trim_end_matches(".git")
+        .split("://")
+        .last()
+        .unwrap_or(url);
+    let name = match git_ref {
+        Some(git_ref) => format!("{}@{}", url, git_ref),
+        None => url.to_string(),
+    };
+    let name: String = name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_' | '@') {
+                c
+            } else {
+                '-'
+            }
+        })
+        .collect();
+    name.trim_start_matches(['-', '.']).to_string()
+}
+
+fn run_git(dir: &Path, args: &[&str]) -> Result<(), Box<dyn std::error::Error>> {
+    let output = Command::new("git").arg("-C").arg(dir).args(args).output()?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(format!(
+            "git {} failed: {}",
+            args.first().unwrap_or(&""),
+            String::from_utf8_lossy(&output.stderr).trim()
+        )
+        .into())
+    }
+}
+
+/// Commit checked out in the repository containing `dir`, with the branch
+/// name unless the HEAD is detached. `None` outside a git repository.
+pub fn head(dir: &Path) -> Option<(String, Option<String>)> {
+    let rev_parse = |args: &[&str]| {
+        let output = Command::new("git")
+            .arg("-C")
+            .arg(dir)
+            .arg("rev-parse")
+            .args(args)
+            .output()
+            .ok()?;
+        output
+            .status
+            .success()
+            .then(|| String::from_utf8_lossy(&output.stdout).trim().to_string())
+    };
+    let sha = rev_parse(&["HEAD"])?;
+    let branch = rev_parse(&["--abbrev-ref", "HEAD"]).filter(|branch| branch != "HEAD");
+    Some((sha, branch))
+}
+
+pub fn parse_name_status(output: &str) -> Vec<PathChange> {
+    let mut changes = Vec::new();
+    let mut fields = output.split('\0').filter(|f| !f.is_empty());
+
+    while let Some(status) = fields.next() {
+        match status.chars().next() {
+            Some('D') => {
+                if let Some(path) = fields.next() {
+                    changes.push(PathChange::Deleted {
+                        path: PathBuf::from(path),
+                    });
+                }
+            }
+            Some('R') => {
+                if let (Some(from), Some(to)) = (fields.next(), fields.next()) {
+                    changes.push(PathChange::Renamed {
+                        from: PathBuf::from(from),
+                        to: PathBuf::from(to),
+                    });
+                }
+            }
+            Some('C') => {
+                fields.next();
+                fields.next();
+            }
+            _ => {
+                fields.next();
+            }
+        }
+    }
+
+    changes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_checkout_name() {
+        assert_eq!(
+            checkout_name("https://github.com/org/repo.git", Some("v1.2.0")),
+            "github.com-org-repo@v1.2.0"
+        );
+        assert_eq!(
+            checkout_name("git@host:org/repo", None),
+            "git@host-org-repo"
+        );
+    }
+
+    #[test]
+    fn test_compare_refs() {
+        assert_eq!(
+            compare_refs("https://github.com/Org/Repo/compare/v1.2.2...v1.2.3"),
+            Some((
+                "github.com/org/repo".to_string(),
+                "v1.2.2".to_string(),
+                "v1.2.3".to_string()
+            ))
+        );
+        assert_eq!(
+            compare_refs(
+                "https://gitlab.com/group/sub/repo/-/compare/v1.0..main?from_project_id=1"
+            ),
+            Some((
+                "gitlab.com/group/sub/repo".to_string(),
+                "v1.0".to_string(),
+                "main".to_string()
+            ))
+        );
+        assert_eq!(
+            compare_refs("https://github.com/org/repo/releases/tag/v1.0"),
+            None
+        );
+
+        assert_eq!(
+            repository_name("git@github.com:Org/repo.git"),
+            "github.com/org/repo"
+        );
+        assert_eq!(
+            repository_name("ssh://git@github.com/org/repo"),
+            "github.com/org/repo"
+        );
+        assert_eq!(
+            repository_name("https://github.com/org/repo.git"),
+            "github.com/org/repo"
+        );
+    }
+
+    #[test]
+    fn test_parse_name_status() {
+        let output = "M\0README.md\0D\0docs/old.md\0R087\0docs/a.md\0docs/b.md\0A\0new.md\0";
+        let changes = parse_name_status(output);
+
+        assert_eq!(
+            changes,
+            vec![
+                PathChange::Deleted {
+                    path: PathBuf::from("docs/old.md")
+                },
+                PathChange::Renamed {
+                    from: PathBuf::from("docs/a.md"),
+                    to: PathBuf::from("docs/b.md")
+                },
+            ]
+        );
+    }
+}
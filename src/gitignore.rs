@@ -0,0 +1,143 @@
+use crate::normalize_path;
+use crate::vfs::FileSystem;
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use ignore::Match;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// The `.gitignore` files that apply to a directory tree: those in the tree,
+/// read as the walk reaches each directory, and those above it up to the
+/// repository root.
+pub struct GitignoreRules<'a> {
+    fs: &'a dyn FileSystem,
+    root: PathBuf,
+    /// Directories above `root` with a `.gitignore`, outermost first.
+    outer: Vec<(PathBuf, Gitignore)>,
+    inner: RefCell<HashMap<PathBuf, Option<Gitignore>>>,
+}
+
+impl<'a> GitignoreRules<'a> {
+    pub fn new(fs: &'a dyn FileSystem, root: &Path) -> Self {
+        let root = normalize_path(root);
+        let mut outer = Vec::new();
+        if !fs.exists(&root.join(".git")) {
+            // Only a `.gitignore` inside the same repository applies, and
+            // only directories spelled out in `root` can be looked at.
+            let mut dir = root.clone();
+            let mut in_repository = false;
+            while dir.file_name().is_some() && dir.pop() {
+                if let Some(gitignore) = read(fs, &dir) {
+                    outer.push((dir.clone(), gitignore));
+                }
+                if fs.exists(&dir.join(".git")) {
+                    in_repository = true;
+                    break;
+                }
+            }
+            if !in_repository {
+                outer.clear();
+            }
+            outer.reverse();
+        }
+
+        Self {
+            fs,
+            root,
+            outer,
+            inner: RefCell::default(),
+        }
+    }
+
+    /// Whether `path`, a file or directory under the root, is ignored. Its
+    /// parent directories are taken not to be, as a walk never enters
+    /// ignored ones.
+    pub fn is_ignored(&self, path: &Path, is_dir: bool) -> bool {
+        let path = normalize_path(path);
+        let Ok(relative) = path.strip_prefix(&self.root) else {
+            return false;
+        };
+
+        // Deeper files win over shallower ones, as in git.
+        let mut matched = false;
+        for (dir, gitignore) in &self.outer {
+            matched = decide(gitignore, path.strip_prefix(dir), is_dir, matched);
+        }
+        let mut dirs = vec![self.root.clone()];
+        for component in relative.parent().into_iter().flat_map(Path::components) {
+            let dir = dirs[dirs.len() - 1].join(component);
+            dirs.push(dir);
+        }
+        let mut inner = self.inner.borrow_mut();
+        for dir in dirs {
+            let relative = path.strip_prefix(&dir);
+            let gitignore = inner
+                .entry(dir)
+                .or_insert_with_key(|dir| read(self.fs, dir));
+            if let Some(gitignore) = gitignore {
+                matched = decide(gitignore, relative, is_dir, matched);
+            }
+        }
+        matched
+    }
+}
+
+fn read(fs: &dyn FileSystem, dir: &Path) -> Option<Gitignore> {
+    let content = fs.read_to_string(&dir.join(".gitignore")).ok()?;
+    // Relative to `.`, so paths are matched as given.
+    let mut builder = GitignoreBuilder::new(".");
+    for line in content.lines() {
+        // Git skips patterns it can't parse, and so does the scan.
+        let _ = builder.add_line(None, line);
+    }
+    builder
+        .build()
+        .ok()
+        .filter(|gitignore| !gitignore.is_empty())
+}
+
+fn decide<E>(
+    gitignore: &Gitignore,
+    relative: Result<&Path, E>,
+    is_dir: bool,
+    matched: bool,
+) -> bool {
+    let Ok(relative) = relative else {
+        return matched;
+    };
+    match gitignore.matched(relative, is_dir) {
+        Match::None => matched,
+        Match::Ignore(_) => true,
+        Match::Whitelist(_) => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vfs::MemoryFileSystem;
+
+    #[test]
+    fn test_gitignore_rules() {
+        let mut vfs = MemoryFileSystem::new();
+        vfs.insert("repo/.git/HEAD", "ref: refs/heads/main");
+        vfs.insert("repo/.gitignore", "build/\n*.generated.md\n");
+        vfs.insert("repo/docs/.gitignore", "!keep.generated.md\ndrafts\n");
+        vfs.insert("repo/docs/guide.md", "");
+
+        let rules = GitignoreRules::new(&vfs, Path::new("./repo/docs"));
+        assert!(rules.is_ignored(Path::new("./repo/docs/build"), true));
+        assert!(!rules.is_ignored(Path::new("./repo/docs/build"), false));
+        assert!(rules.is_ignored(Path::new("repo/docs/api.generated.md"), false));
+        assert!(!rules.is_ignored(Path::new("repo/docs/keep.generated.md"), false));
+        assert!(rules.is_ignored(Path::new("repo/docs/guide/drafts"), true));
+        assert!(!rules.is_ignored(Path::new("repo/docs/guide.md"), false));
+
+        // Outside a repository, only the tree's own files count.
+        let mut vfs = MemoryFileSystem::new();
+        vfs.insert(".gitignore", "*.md\n");
+        vfs.insert("docs/guide.md", "");
+        let rules = GitignoreRules::new(&vfs, Path::new("docs"));
+        assert!(!rules.is_ignored(Path::new("docs/guide.md"), false));
+    }
+}
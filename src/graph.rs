@@ -0,0 +1,303 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+use std::path::{Path, PathBuf};
+
+/// Directed graph of documents, with an edge for every internal link from one
+/// analyzed document to another.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LinkGraph {
+    pub nodes: BTreeSet<PathBuf>,
+    pub edges: BTreeMap<PathBuf, BTreeSet<PathBuf>>,
+}
+
+impl LinkGraph {
+    pub fn add_node(&mut self, node: PathBuf) {
+        self.nodes.insert(node);
+    }
+
+    pub fn add_edge(&mut self, from: PathBuf, to: PathBuf) {
+        self.nodes.insert(from.clone());
+        self.nodes.insert(to.clone());
+        self.edges.entry(from).or_default().insert(to);
+    }
+
+    pub fn successors(&self, node: &Path) -> impl Iterator<Item = &PathBuf> {
+        self.edges.get(node).into_iter().flatten()
+    }
+
+    /// Breadth-first spanning tree from `roots`: maps every reachable node to
+    /// the node it was first reached from (`None` for the roots themselves).
+    pub fn spanning_tree(&self, roots: &[PathBuf]) -> BTreeMap<PathBuf, Option<PathBuf>> {
+        let mut parents = BTreeMap::new();
+        let mut queue = VecDeque::new();
+
+        for root in roots {
+            if self.nodes.contains(root) && !parents.contains_key(root) {
+                parents.insert(root.clone(), None);
+                queue.push_back(root.clone());
+            }
+        }
+
+        while let Some(node) = queue.pop_front() {
+            for next in self.successors(&node) {
+                if !parents.contains_key(next) {
+                    parents.insert(next.clone(), Some(node.clone()));
+                    queue.push_back(next.clone());
+                }
+            }
+        }
+
+        parents
+    }
+
+    /// Nodes on a shortest path from `from` to `to`, both included.
+    pub fn shortest_path(&self, from: &Path, to: &Path) -> Option<Vec<PathBuf>> {
+        let tree = self.spanning_tree(&[from.to_path_buf()]);
+        let mut path = vec![to.to_path_buf()];
+        let mut current = tree.get(to)?;
+        while let Some(parent) = current {
+            path.push(parent.clone());
+            current = &tree[parent];
+        }
+        path.reverse();
+        Some(path)
+    }
+
+    pub fn reachable_from(&self, roots: &[PathBuf]) -> BTreeSet<PathBuf> {
+        self.spanning_tree(roots).into_keys().collect()
+    }
+
+    /// Fewest links to follow from any of `roots` to every reachable node
+    /// (0 for the roots themselves).
+    pub fn depths(&self, roots: &[PathBuf]) -> BTreeMap<PathBuf, usize> {
+        let tree = self.spanning_tree(roots);
+        let mut depths = BTreeMap::new();
+        for node in tree.keys() {
+            let mut depth = 0;
+            let mut current = node;
+            while let Some(Some(parent)) = tree.get(current) {
+                depth += 1;
+                current = parent;
+            }
+            depths.insert(node.clone(), depth);
+        }
+        depths
+    }
+
+    /// Number of links into each node from other nodes.
+    pub fn in_degrees(&self) -> BTreeMap<&PathBuf, usize> {
+        let mut degrees: BTreeMap<&PathBuf, usize> =
+            self.nodes.iter().map(|node| (node, 0)).collect();
+        for target in self.edges.values().flatten() {
+            *degrees.entry(target).or_default() += 1;
+        }
+        degrees
+    }
+
+    pub fn metrics(&self) -> GraphMetrics {
+        let index: BTreeMap<&PathBuf, usize> = self
+            .nodes
+            .iter()
+            .enumerate()
+            .map(|(i, node)| (node, i))
+            .collect();
+        let mut outbound = vec![Vec::new(); self.nodes.len()];
+        let mut inbound = vec![Vec::new(); self.nodes.len()];
+        for (from, targets) in &self.edges {
+            for to in targets {
+                outbound[index[from]].push(index[to]);
+                inbound[index[to]].push(index[from]);
+            }
+        }
+
+        let mut metrics = GraphMetrics {
+            documents: self.nodes.len(),
+            edges: inbound.iter().map(Vec::len).sum(),
+            ..GraphMetrics::default()
+        };
+        for (outbound, inbound) in outbound.iter().zip(&inbound) {
+            *metrics
+                .out_degree_distribution
+                .entry(outbound.len())
+                .or_default() += 1;
+            *metrics
+                .in_degree_distribution
+                .entry(inbound.len())
+                .or_default() += 1;
+            metrics.max_out_degree = metrics.max_out_degree.max(outbound.len());
+            metrics.max_in_degree = metrics.max_in_degree.max(inbound.len());
+            if outbound.is_empty() && inbound.is_empty() {
+                metrics.isolated_documents += 1;
+            }
+        }
+        if metrics.documents > 0 {
+            metrics.average_degree = metrics.edges as f64 / metrics.documents as f64;
+        }
+
+        let undirected: Vec<Vec<usize>> = outbound
+            .iter()
+            .zip(&inbound)
+            .map(|(outbound, inbound)| outbound.iter().chain(inbound).copied().collect())
+            .collect();
+        let components = component_sizes(&undirected, 0..self.nodes.len());
+        metrics.components = components.len();
+        metrics.largest_component = components.into_iter().max().unwrap_or(0);
+
+        // Kosaraju: nodes by DFS finish time, then components of the reversed
+        // graph in reverse finish order are the strongly connected ones.
+        let mut visited = vec![false; self.nodes.len()];
+        let mut finished = Vec::with_capacity(self.nodes.len());
+        for start in 0..self.nodes.len() {
+            if visited[start] {
+                continue;
+            }
+            visited[start] = true;
+            let mut stack = vec![(start, 0)];
+            while let Some(top) = stack.last_mut() {
+                let node = top.0;
+                match outbound[node].get(top.1) {
+                    Some(&next) => {
+                        top.1 += 1;
+                        if !visited[next] {
+                            visited[next] = true;
+                            stack.push((next, 0));
+                        }
+                    }
+                    None => {
+                        finished.push(node);
+                        stack.pop();
+                    }
+                }
+            }
+        }
+        let strong = component_sizes(&inbound, finished.into_iter().rev());
+        metrics.strongly_connected_components = strong.len();
+        metrics.largest_strongly_connected_component = strong.into_iter().max().unwrap_or(0);
+
+        metrics
+    }
+}
+
+/// Sizes of the components found by flooding `adjacency` from each of
+/// `starts` not yet reached, in that order.
+fn component_sizes(adjacency: &[Vec<usize>], starts: impl Iterator<Item = usize>) -> Vec<usize> {
+    let mut seen = vec![false; adjacency.len()];
+    let mut sizes = Vec::new();
+    for start in starts {
+        if seen[start] {
+            continue;
+        }
+        seen[start] = true;
+        let mut size = 0;
+        let mut stack = vec![start];
+        while let Some(node) = stack.pop() {
+            size += 1;
+            for &next in &adjacency[node] {
+                if !seen[next] {
+                    seen[next] = true;
+                    stack.push(next);
+                }
+            }
+        }
+        sizes.push(size);
+    }
+    sizes
+}
+
+/// The shape of a link graph: how fragmented it is and how evenly links are
+/// spread, for `stats --detailed`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct GraphMetrics {
+    pub documents: usize,
+    /// Distinct document-to-document links.
+    pub edges: usize,
+    /// Groups of documents connected by links in either direction.
+    pub components: usize,
+    pub largest_component: usize,
+    /// Documents with no links to or from other documents.
+    pub isolated_documents: usize,
+    /// Links out of, and so also into, the average document.
+    pub average_degree: f64,
+    pub max_in_degree: usize,
+    pub max_out_degree: usize,
+    /// Number of documents by how many documents link to them.
+    pub in_degree_distribution: BTreeMap<usize, usize>,
+    /// Number of documents by how many documents they link to.
+    pub out_degree_distribution: BTreeMap<usize, usize>,
+    /// Groups of documents that can all reach each other by following links.
+    pub strongly_connected_components: usize,
+    pub largest_strongly_connected_component: usize,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_spanning_tree() {
+        let mut graph = LinkGraph::default();
+        graph.add_edge("a.md".into(), "b.md".into());
+        graph.add_edge("a.md".into(), "c.md".into());
+        graph.add_edge("b.md".into(), "c.md".into());
+        graph.add_edge("c.md".into(), "a.md".into());
+        graph.add_node("d.md".into());
+
+        let tree = graph.spanning_tree(&["a.md".into()]);
+        assert_eq!(tree[Path::new("a.md")], None);
+        assert_eq!(tree[Path::new("c.md")], Some(PathBuf::from("a.md")));
+        assert!(!tree.contains_key(Path::new("d.md")));
+
+        let reachable = graph.reachable_from(&["b.md".into()]);
+        assert_eq!(reachable.len(), 3);
+
+        let depths = graph.depths(&["b.md".into()]);
+        assert_eq!(depths[Path::new("b.md")], 0);
+        assert_eq!(depths[Path::new("c.md")], 1);
+        assert_eq!(depths[Path::new("a.md")], 2);
+
+        assert_eq!(
+            graph.shortest_path(Path::new("b.md"), Path::new("a.md")),
+            Some(vec!["b.md".into(), "c.md".into(), "a.md".into()])
+        );
+        assert_eq!(
+            graph.shortest_path(Path::new("a.md"), Path::new("a.md")),
+            Some(vec!["a.md".into()])
+        );
+        assert_eq!(
+            graph.shortest_path(Path::new("a.md"), Path::new("d.md")),
+            None
+        );
+    }
+
+    #[test]
+    fn test_metrics() {
+        let mut graph = LinkGraph::default();
+        // a <-> b -> c, with c -> d -> c, and e on its own.
+        graph.add_edge("a.md".into(), "b.md".into());
+        graph.add_edge("b.md".into(), "a.md".into());
+        graph.add_edge("b.md".into(), "c.md".into());
+        graph.add_edge("c.md".into(), "d.md".into());
+        graph.add_edge("d.md".into(), "c.md".into());
+        graph.add_node("e.md".into());
+
+        let metrics = graph.metrics();
+        assert_eq!(metrics.documents, 5);
+        assert_eq!(metrics.edges, 5);
+        assert_eq!(metrics.components, 2);
+        assert_eq!(metrics.largest_component, 4);
+        assert_eq!(metrics.isolated_documents, 1);
+        assert_eq!(metrics.average_degree, 1.0);
+        assert_eq!(metrics.max_in_degree, 2);
+        assert_eq!(metrics.max_out_degree, 2);
+        assert_eq!(
+            metrics.in_degree_distribution,
+            BTreeMap::from([(0, 1), (1, 3), (2, 1)])
+        );
+        assert_eq!(metrics.strongly_connected_components, 3);
+        assert_eq!(metrics.largest_strongly_connected_component, 2);
+
+        assert_eq!(graph.in_degrees()[&PathBuf::from("c.md")], 2);
+        assert_eq!(LinkGraph::default().metrics(), GraphMetrics::default());
+    }
+}
@@ -0,0 +1,135 @@
+use crate::external::{ExternalResponse, Verdict};
+use regex::Regex;
+use std::fmt;
+use std::sync::LazyLock;
+
+/// File name of the identifier `CheckCache` in the cache directory.
+pub const CACHE_FILE_NAME: &str = "identifiers.json";
+
+static DOI_LINK: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)^https?://(?:dx\.)?doi\.org/(10\.\d{4,9}/[^?#\s]+?)/?(?:[?#].*)?$").unwrap()
+});
+static RFC_LINK: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"(?i)^https?://(?:www\.)?(?:rfc-editor\.org/(?:rfc|info)/|datatracker\.ietf\.org/doc/(?:html/)?|tools\.ietf\.org/html/)rfc(\d+)(?:\.(?:html|txt|pdf|xml|json))?/?(?:[?#].*)?$",
+    )
+    .unwrap()
+});
+
+/// A document identifier with an authority that can say whether it exists,
+/// which is more reliable than requesting the landing page: publishers often
+/// block automated requests.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[non_exhaustive]
+pub enum Identifier {
+    Doi(String),
+    Rfc(u32),
+}
+
+impl Identifier {
+    /// The identifier a `doi.org`, `rfc-editor.org` or `datatracker.ietf.org`
+    /// link points at.
+    pub fn from_url(url: &str) -> Option<Self> {
+        if let Some(caps) = DOI_LINK.captures(url) {
+            return Some(Identifier::Doi(caps[1].to_string()));
+        }
+        let caps = RFC_LINK.captures(url)?;
+        caps[1].parse().ok().map(Identifier::Rfc)
+    }
+
+    /// Endpoint that answers 404 for an identifier that does not exist: the
+    /// DOI handle API, or the RFC Editor's metadata for published RFCs.
+    pub fn api_url(&self) -> String {
+        match self {
+            Identifier::Doi(doi) => format!("https://doi.org/api/handles/{}", doi),
+            Identifier::Rfc(number) => format!("https://www.rfc-editor.org/rfc/rfc{}.json", number),
+        }
+    }
+
+    pub fn verdict(&self, response: &ExternalResponse) -> Verdict {
+        match (self, response.status) {
+            (Identifier::Doi(_), Some(404)) => {
+                Verdict::Error(format!("{} is not registered", self))
+            }
+            (Identifier::Rfc(_), Some(404)) => {
+                Verdict::Error(format!("{} has not been published", self))
+            }
+            _ => response.verdict(false),
+        }
+    }
+}
+
+impl fmt::Display for Identifier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Identifier::Doi(doi) => write!(f, "DOI {}", doi),
+            Identifier::Rfc(number) => write!(f, "RFC {}", number),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_url() {
+        let doi = |doi: &str| Some(Identifier::Doi(doi.to_string()));
+        assert_eq!(
+            Identifier::from_url("https://doi.org/10.1145/3290368"),
+            doi("10.1145/3290368")
+        );
+        assert_eq!(
+            Identifier::from_url("http://dx.doi.org/10.1000/xyz(123)/abc/"),
+            doi("10.1000/xyz(123)/abc")
+        );
+        assert_eq!(Identifier::from_url("https://doi.org/"), None);
+        assert_eq!(Identifier::from_url("https://doi.org/api/handles"), None);
+
+        for url in [
+            "https://www.rfc-editor.org/rfc/rfc9110.html",
+            "https://rfc-editor.org/rfc/rfc9110#section-15",
+            "https://www.rfc-editor.org/info/rfc9110",
+            "https://datatracker.ietf.org/doc/html/rfc9110",
+            "https://datatracker.ietf.org/doc/rfc9110/",
+            "https://tools.ietf.org/html/rfc9110",
+        ] {
+            assert_eq!(
+                Identifier::from_url(url),
+                Some(Identifier::Rfc(9110)),
+                "{}",
+                url
+            );
+        }
+        assert_eq!(
+            Identifier::from_url("https://datatracker.ietf.org/doc/draft-ietf-httpbis-semantics/"),
+            None
+        );
+        assert_eq!(
+            Identifier::from_url("https://www.rfc-editor.org/rfc/"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_verdict() {
+        let response = |identifier: &Identifier, status| ExternalResponse {
+            url: identifier.api_url(),
+            status: Some(status),
+            location: None,
+            error: None,
+        };
+        let doi = Identifier::Doi("10.1000/missing".to_string());
+        assert_eq!(doi.api_url(), "https://doi.org/api/handles/10.1000/missing");
+        assert_eq!(
+            doi.verdict(&response(&doi, 404)),
+            Verdict::Error("DOI 10.1000/missing is not registered".to_string())
+        );
+        let rfc = Identifier::Rfc(99999);
+        assert_eq!(
+            rfc.verdict(&response(&rfc, 404)),
+            Verdict::Error("RFC 99999 has not been published".to_string())
+        );
+        assert_eq!(rfc.verdict(&response(&rfc, 200)), Verdict::Ok);
+    }
+}
@@ -0,0 +1,336 @@
+use crate::normalize_path;
+use crate::resolve::{percent_decode, split_fragment, LinkKind};
+use crate::vfs::MemoryFileSystem;
+use regex::{Captures, Regex};
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+use walkdir::WalkDir;
+
+static SKIPPED: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?is)<!--.*?-->|<script\b.*?</script\s*>|<style\b.*?</style\s*>").unwrap()
+});
+static ELEMENT: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"(?is)<a\s([^>]*)>(.*?)</a\s*>|<img\s([^>]*)>|<h([1-6])(\s[^>]*)?>(.*?)</h[1-6]\s*>",
+    )
+    .unwrap()
+});
+static ATTRIBUTE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"(?is)([a-z][a-z0-9-]*)\s*=\s*(?:"([^"]*)"|'([^']*)'|([^\s"'>]+))"#).unwrap()
+});
+static TAG: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"<[^>]*>").unwrap());
+static ENTITY: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"&(#[0-9]{1,7}|#[xX][0-9a-fA-F]{1,6}|[a-zA-Z]+);").unwrap());
+
+/// Loads an exported Confluence or Notion space, from a directory or a
+/// `.zip` archive, and converts it with [`convert`].
+pub fn load(path: &Path) -> Result<MemoryFileSystem, Box<dyn std::error::Error>> {
+    let export = if path.is_dir() {
+        let mut export = MemoryFileSystem::new();
+        for entry in WalkDir::new(path) {
+            let entry = entry?;
+            if entry.file_type().is_file() {
+                export.insert(entry.path().strip_prefix(path)?, fs::read(entry.path())?);
+            }
+        }
+        export
+    } else {
+        from_archive(path)?
+    };
+    Ok(convert(export))
+}
+
+#[cfg(feature = "archive")]
+fn from_archive(path: &Path) -> Result<MemoryFileSystem, Box<dyn std::error::Error>> {
+    MemoryFileSystem::from_archive(path)
+}
+
+#[cfg(not(feature = "archive"))]
+fn from_archive(path: &Path) -> Result<MemoryFileSystem, Box<dyn std::error::Error>> {
+    Err(format!(
+        "Reading {} needs the `archive` feature; extract it and pass the directory",
+        path.display()
+    )
+    .into())
+}
+
+/// Turns the HTML pages of an export (`Page_123.html`) into Markdown
+/// documents (`Page_123.md`) holding the page's links, images and headings
+/// on the lines they appear on, so findings point at the HTML source lines.
+/// Links between pages follow the conversion; everything else, including
+/// Markdown pages, is kept as it is. `.zip` parts at the top of the export,
+/// as Notion splits large spaces, are unpacked in place.
+pub fn convert(export: MemoryFileSystem) -> MemoryFileSystem {
+    let files = expand_parts(export);
+    let pages: BTreeSet<PathBuf> = files
+        .iter()
+        .map(|(path, _)| path)
+        .filter(|path| is_html(path))
+        .cloned()
+        .collect();
+
+    let mut converted = MemoryFileSystem::new();
+    for (path, contents) in files {
+        if pages.contains(&path) {
+            let dir = path.parent().unwrap_or(Path::new(""));
+            let markdown = page_to_markdown(&String::from_utf8_lossy(&contents), dir, &pages);
+            converted.insert(path.with_extension("md"), markdown);
+        } else {
+            converted.insert(path, contents);
+        }
+    }
+    converted
+}
+
+#[cfg(feature = "archive")]
+fn expand_parts(export: MemoryFileSystem) -> Vec<(PathBuf, Vec<u8>)> {
+    let mut files = Vec::new();
+    for (path, contents) in export {
+        let is_part = path.parent() == Some(Path::new(""))
+            && path
+                .extension()
+                .is_some_and(|ext| ext.eq_ignore_ascii_case("zip"));
+        match is_part.then(|| MemoryFileSystem::from_zip(std::io::Cursor::new(&contents))) {
+            Some(Ok(part)) => files.extend(part),
+            _ => files.push((path, contents)),
+        }
+    }
+    files
+}
+
+#[cfg(not(feature = "archive"))]
+fn expand_parts(export: MemoryFileSystem) -> Vec<(PathBuf, Vec<u8>)> {
+    export.into_iter().collect()
+}
+
+fn is_html(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("html") || ext.eq_ignore_ascii_case("htm"))
+}
+
+fn page_to_markdown(html: &str, dir: &Path, pages: &BTreeSet<PathBuf>) -> String {
+    // Blank out comments and scripts, keeping their line breaks.
+    let html = SKIPPED.replace_all(html, |caps: &Captures| {
+        "\n".repeat(caps[0].matches('\n').count())
+    });
+    let mut lines: Vec<Vec<String>> = vec![Vec::new(); html.split('\n').count()];
+
+    for caps in ELEMENT.captures_iter(&html) {
+        let start = caps.get(0).unwrap().start();
+        let item = if let Some(attrs) = caps.get(1) {
+            let Some(href) = attribute(attrs.as_str(), "href").filter(|href| !href.is_empty())
+            else {
+                continue;
+            };
+            format!(
+                "[{}](<{}>)",
+                link_text(&caps[2]),
+                destination(&page_target(dir, &href, pages))
+            )
+        } else if let Some(attrs) = caps.get(3) {
+            let Some(src) = attribute(attrs.as_str(), "src").filter(|src| !src.is_empty()) else {
+                continue;
+            };
+            let alt = attribute(attrs.as_str(), "alt").unwrap_or_default();
+            format!(
+                "![{}](<{}>)",
+                link_text(&alt),
+                destination(&page_target(dir, &src, pages))
+            )
+        } else {
+            let level = &caps[4];
+            let text = collapse_whitespace(&TAG.replace_all(&caps[6], ""));
+            match caps
+                .get(5)
+                .and_then(|attrs| attribute(attrs.as_str(), "id"))
+            {
+                Some(id) => format!(
+                    "<h{} id=\"{}\">{}</h{}>",
+                    level,
+                    id.replace('"', "&quot;"),
+                    text,
+                    level
+                ),
+                None => format!("<h{}>{}</h{}>", level, text, level),
+            }
+        };
+        lines[html[..start].matches('\n').count()].push(item);
+    }
+
+    lines
+        .into_iter()
+        .map(|items| {
+            let line = items.join(" ");
+            // A line opening with a heading tag would start an HTML block and
+            // hide the links on the lines after it.
+            if line.starts_with('<') {
+                format!("\\{}", line)
+            } else {
+                line
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Points links to exported HTML pages at their converted documents.
+fn page_target(dir: &Path, target: &str, pages: &BTreeSet<PathBuf>) -> String {
+    if LinkKind::classify(target) != LinkKind::Internal || target.starts_with('/') {
+        return target.to_string();
+    }
+    let (path, fragment) = split_fragment(target);
+    let decoded = percent_decode(path).unwrap_or_else(|| path.to_string());
+    let Some(stem) = path.rfind('.').map(|dot| &path[..dot]) else {
+        return target.to_string();
+    };
+    if !pages.contains(&normalize_path(&dir.join(decoded))) {
+        return target.to_string();
+    }
+    match fragment {
+        Some(fragment) => format!("{}.md#{}", stem, fragment),
+        None => format!("{}.md", stem),
+    }
+}
+
+fn attribute(attrs: &str, name: &str) -> Option<String> {
+    ATTRIBUTE
+        .captures_iter(attrs)
+        .find(|caps| caps[1].eq_ignore_ascii_case(name))
+        .map(|caps| {
+            let value = caps
+                .get(2)
+                .or_else(|| caps.get(3))
+                .or_else(|| caps.get(4))
+                .map_or("", |m| m.as_str());
+            decode_entities(value.trim())
+        })
+}
+
+fn link_text(html: &str) -> String {
+    let text = decode_entities(&collapse_whitespace(&TAG.replace_all(html, "")));
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(c, '[' | ']' | '\\' | '`' | '<') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// Keeps a destination inside `<…>`, which allows spaces but not brackets.
+fn destination(target: &str) -> String {
+    target
+        .replace('<', "%3C")
+        .replace('>', "%3E")
+        .replace(['\n', '\r'], "")
+}
+
+fn collapse_whitespace(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn decode_entities(text: &str) -> String {
+    ENTITY
+        .replace_all(text, |caps: &Captures| {
+            let entity = &caps[1];
+            let decoded = if let Some(hex) = entity
+                .strip_prefix("#x")
+                .or_else(|| entity.strip_prefix("#X"))
+            {
+                u32::from_str_radix(hex, 16).ok().and_then(char::from_u32)
+            } else if let Some(decimal) = entity.strip_prefix('#') {
+                decimal.parse().ok().and_then(char::from_u32)
+            } else {
+                match entity {
+                    "amp" => Some('&'),
+                    "lt" => Some('<'),
+                    "gt" => Some('>'),
+                    "quot" => Some('"'),
+                    "apos" => Some('\''),
+                    "nbsp" => Some(' '),
+                    _ => None,
+                }
+            };
+            decoded.map_or_else(|| caps[0].to_string(), String::from)
+        })
+        .into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::LinkAnalyzer;
+
+    #[test]
+    fn test_convert_confluence_export() {
+        let mut export = MemoryFileSystem::new();
+        export.insert(
+            "SPACE/index.html",
+            "<html>\n<head><title>Space</title>\n<!-- <a href=\"old.html\">x</a>\n-->\n</head>\n<body>\n\
+             <h1 id=\"title-heading\">Space : Home</h1>\n\
+             <p>See <a href=\"Setup_123.html#Setup-Install\">the\n setup &amp; install</a> guide.</p>\n\
+             <a href=\"Gone_456.html\">Gone</a> <img src=\"attachments/123/diagram.png\" alt=\"Diagram\">\n\
+             <img src=\"attachments/123/missing%20file.png\">\n\
+             <a href=\"/display/SPACE/Old+Page\">Old</a> <a href=\"https://example.com\">Site</a>\n\
+             </body>\n</html>",
+        );
+        export.insert(
+            "SPACE/Setup_123.html",
+            "<h2 id=\"Setup-Install\">Install</h2>\n<a href=\"index.html\">Home</a>",
+        );
+        export.insert(
+            "SPACE/attachments/123/diagram.png",
+            vec![0x89, b'P', b'N', b'G'],
+        );
+
+        let vfs = convert(export);
+        assert_eq!(vfs.len(), 3);
+        let mut analyzer = LinkAnalyzer::new(PathBuf::new()).with_filesystem(vfs);
+        analyzer.analyze_directory().unwrap();
+
+        assert_eq!(
+            analyzer.document_title(Path::new("SPACE/index.md")),
+            Some("Space : Home")
+        );
+        let links = &analyzer.documents[Path::new("SPACE/index.md")];
+        assert_eq!(links[0].text, "the setup & install");
+        assert_eq!(links[0].target, "Setup_123.md#Setup-Install");
+        assert_eq!(links[0].line_number, 8);
+
+        let broken: Vec<(usize, String)> = analyzer
+            .find_broken_links()
+            .into_iter()
+            .map(|broken| (broken.link.line_number, broken.link.target))
+            .collect();
+        assert_eq!(
+            broken,
+            vec![
+                (10, "Gone_456.html".to_string()),
+                (11, "attachments/123/missing%20file.png".to_string()),
+                (12, "/display/SPACE/Old+Page".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_convert_notion_export() {
+        let mut export = MemoryFileSystem::new();
+        export.insert(
+            "Home 0123.md",
+            "# Home\n\n[Roadmap](Home%200123/Roadmap%204567.md)\n[Tasks](Home%200123/Tasks%2089ab.csv)",
+        );
+        export.insert("Home 0123/Roadmap 4567.md", "# Roadmap");
+
+        let vfs = convert(export);
+        let mut analyzer = LinkAnalyzer::new(PathBuf::new()).with_filesystem(vfs);
+        analyzer.analyze_directory().unwrap();
+
+        let broken_links = analyzer.find_broken_links();
+        assert_eq!(broken_links.len(), 1);
+        assert_eq!(broken_links[0].link.target, "Home%200123/Tasks%2089ab.csv");
+    }
+}
@@ -0,0 +1,193 @@
+use regex::Regex;
+use std::sync::LazyLock;
+
+static MDBOOK_INCLUDE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"\{\{#(?:include|rustdoc_include|playground)\s+([^}\s]+)[^}]*\}\}").unwrap()
+});
+static SNIPPET: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"^\s*-{1,}8<-{1,}\s+["']([^"']+)["']"#).unwrap());
+static COMMENT_INCLUDE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"<!--\s*include:\s*(\S+?)\s*-->").unwrap());
+
+/// Part of the included file a directive selects.
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum Selector {
+    /// A named region: mdBook's `ANCHOR: name` / `ANCHOR_END: name` or
+    /// snippets' `--8<-- [start:name]` / `--8<-- [end:name]` markers.
+    Anchor(String),
+    /// An inclusive, 1-based line range; either end may be open.
+    Lines(Option<usize>, Option<usize>),
+}
+
+/// A file transcluded by an include directive.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Include {
+    pub path: String,
+    pub selector: Option<Selector>,
+}
+
+/// Include directives in `content` as `(directive, line_number)`. Supported
+/// forms are mdBook's `{{#include file.rs:anchor}}`, snippets'
+/// `--8<-- "file.md"` and `<!-- include: file.md -->`.
+pub fn extract_directives(content: &str) -> Vec<(String, usize)> {
+    let mut directives = Vec::new();
+
+    for (line_num, line) in content.lines().enumerate() {
+        for m in MDBOOK_INCLUDE.find_iter(line) {
+            if !line[..m.start()].ends_with('\\') {
+                directives.push((m.as_str().to_string(), line_num + 1));
+            }
+        }
+        if let Some(m) = SNIPPET.find(line) {
+            directives.push((m.as_str().trim().to_string(), line_num + 1));
+        }
+        for m in COMMENT_INCLUDE.find_iter(line) {
+            directives.push((m.as_str().to_string(), line_num + 1));
+        }
+    }
+
+    directives
+}
+
+/// Whether a link target is an include directive.
+pub fn is_directive(target: &str) -> bool {
+    parse(target).is_some()
+}
+
+/// Parses a directive returned by `extract_directives`.
+pub fn parse(directive: &str) -> Option<Include> {
+    if let Some(caps) = MDBOOK_INCLUDE.captures(directive) {
+        return Some(split_selector(&caps[1]));
+    }
+    if let Some(caps) = SNIPPET.captures(directive) {
+        return Some(split_selector(&caps[1]));
+    }
+    if let Some(caps) = COMMENT_INCLUDE.captures(directive) {
+        return Some(Include {
+            path: caps[1].to_string(),
+            selector: None,
+        });
+    }
+    None
+}
+
+/// Splits `file.rs:anchor`, `file.rs:10:20`, `file.rs::20` or `file.rs:10`
+/// into the path and what it selects.
+fn split_selector(spec: &str) -> Include {
+    let Some((path, rest)) = spec.split_once(':') else {
+        return Include {
+            path: spec.to_string(),
+            selector: None,
+        };
+    };
+
+    let line = |text: &str| text.parse::<usize>().ok();
+    let selector = match rest.split_once(':') {
+        Some((start, end)) => Selector::Lines(line(start), line(end)),
+        None => match line(rest) {
+            Some(number) => Selector::Lines(Some(number), Some(number)),
+            None => Selector::Anchor(rest.to_string()),
+        },
+    };
+
+    Include {
+        path: path.to_string(),
+        selector: Some(selector),
+    }
+}
+
+/// Checks that `selector` exists in the included file's `source`.
+pub fn check_selector(source: &str, selector: &Selector) -> Result<(), String> {
+    match selector {
+        Selector::Anchor(name) => {
+            let has = |marker: String| source.lines().any(|line| line.contains(&marker));
+            let mdbook = has(format!("ANCHOR: {}", name)) && has(format!("ANCHOR_END: {}", name));
+            let snippets = has(format!("[start:{}]", name)) && has(format!("[end:{}]", name));
+            if mdbook || snippets {
+                Ok(())
+            } else {
+                Err(format!("Anchor `{}` not found", name))
+            }
+        }
+        Selector::Lines(start, end) => {
+            let count = source.lines().count();
+            let beyond = [start, end]
+                .into_iter()
+                .flatten()
+                .any(|line| *line == 0 || *line > count);
+            if beyond || start.zip(*end).is_some_and(|(start, end)| start > end) {
+                let bound = |line: &Option<usize>| line.map(|l| l.to_string()).unwrap_or_default();
+                Err(format!(
+                    "Lines {}:{} are outside the file ({} lines)",
+                    bound(start),
+                    bound(end),
+                    count
+                ))
+            } else {
+                Ok(())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_directives() {
+        let content = "```rust\n{{#include ../src/lib.rs:setup}}\n```\n\\{{#include escaped.rs}}\n--8<-- \"snippets/note.md\"\n<!-- include: parts/intro.md -->";
+        assert_eq!(
+            extract_directives(content),
+            vec![
+                ("{{#include ../src/lib.rs:setup}}".to_string(), 2),
+                ("--8<-- \"snippets/note.md\"".to_string(), 5),
+                ("<!-- include: parts/intro.md -->".to_string(), 6),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse() {
+        let include = |path: &str, selector| {
+            Some(Include {
+                path: path.to_string(),
+                selector,
+            })
+        };
+        assert_eq!(
+            parse("{{#include file.rs:setup}}"),
+            include("file.rs", Some(Selector::Anchor("setup".to_string())))
+        );
+        assert_eq!(
+            parse("{{#rustdoc_include file.rs:2:5}}"),
+            include("file.rs", Some(Selector::Lines(Some(2), Some(5))))
+        );
+        assert_eq!(
+            parse("{{#include file.rs::5}}"),
+            include("file.rs", Some(Selector::Lines(None, Some(5))))
+        );
+        assert_eq!(
+            parse("{{#include file.rs:3}}"),
+            include("file.rs", Some(Selector::Lines(Some(3), Some(3))))
+        );
+        assert_eq!(parse("--8<-- \"a.md\""), include("a.md", None));
+        assert_eq!(parse("<!-- include: b.md -->"), include("b.md", None));
+        assert_eq!(parse("./b.md"), None);
+    }
+
+    #[test]
+    fn test_check_selector() {
+        let source = "// ANCHOR: setup\nlet x = 1;\n// ANCHOR_END: setup\n# --8<-- [start:usage]\n# --8<-- [end:usage]";
+        assert!(check_selector(source, &Selector::Anchor("setup".to_string())).is_ok());
+        assert!(check_selector(source, &Selector::Anchor("usage".to_string())).is_ok());
+        assert!(check_selector(source, &Selector::Anchor("missing".to_string())).is_err());
+        assert!(check_selector(source, &Selector::Lines(Some(2), Some(5))).is_ok());
+        assert_eq!(
+            check_selector(source, &Selector::Lines(Some(4), Some(9))),
+            Err("Lines 4:9 are outside the file (5 lines)".to_string())
+        );
+        assert!(check_selector(source, &Selector::Lines(Some(3), Some(2))).is_err());
+    }
+}
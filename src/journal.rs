@@ -0,0 +1,207 @@
+use crate::fix::{self, AppliedEdit, FileChange, FixOptions};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Fix sessions applied to disk, newest last, kept in the cache directory so
+/// `doclink-checker undo` can revert them without relying on git.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Journal {
+    sessions: Vec<FixSession>,
+}
+
+/// Everything one command rewrote.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FixSession {
+    /// Seconds since the Unix epoch.
+    pub timestamp: u64,
+    /// The command that made the changes, e.g. `fix`.
+    pub command: String,
+    pub files: Vec<JournalFile>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JournalFile {
+    /// Absolute path of the rewritten document.
+    pub path: PathBuf,
+    pub edits: Vec<AppliedEdit>,
+}
+
+impl FixSession {
+    pub fn new(command: &str, changes: &[FileChange]) -> Self {
+        Self {
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |elapsed| elapsed.as_secs()),
+            command: command.to_string(),
+            files: changes
+                .iter()
+                .map(|change| JournalFile {
+                    path: fs::canonicalize(&change.path).unwrap_or_else(|_| change.path.clone()),
+                    edits: change.edits.clone(),
+                })
+                .collect(),
+        }
+    }
+
+    pub fn edit_count(&self) -> usize {
+        self.files.iter().map(|file| file.edits.len()).sum()
+    }
+
+    /// The changes that put every file back as it was before the session.
+    /// Fails without planning anything if a file was modified where the
+    /// session edited it.
+    pub fn plan_undo(&self) -> Result<Vec<FileChange>, Box<dyn std::error::Error>> {
+        let mut changes = Vec::new();
+
+        for file in &self.files {
+            let current = fs::read_to_string(&file.path)
+                .map_err(|e| format!("{}: {}", file.path.display(), e))?;
+            let mut reverted = current.clone();
+            let mut edits = Vec::new();
+
+            for edit in file.edits.iter().rev() {
+                if reverted.get(edit.span.clone()) != Some(edit.after.as_str()) {
+                    return Err(format!(
+                        "{} has changed since the fix was applied (expected `{}` at byte {})",
+                        file.path.display(),
+                        edit.after,
+                        edit.span.start
+                    )
+                    .into());
+                }
+                reverted.replace_range(edit.span.clone(), &edit.before);
+                edits.push(AppliedEdit {
+                    span: edit.span.start..edit.span.start + edit.before.len(),
+                    before: edit.after.clone(),
+                    after: edit.before.clone(),
+                });
+            }
+
+            edits.reverse();
+            changes.push(FileChange {
+                path: file.path.clone(),
+                before: current,
+                after: reverted,
+                edits,
+            });
+        }
+
+        Ok(changes)
+    }
+}
+
+impl Journal {
+    pub const FILE_NAME: &'static str = "fix-journal.json";
+    const MAX_SESSIONS: usize = 20;
+
+    /// Loads the journal, starting afresh if it is missing or unreadable.
+    pub fn load(path: &Path) -> Self {
+        fs::read_to_string(path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    pub fn record(&mut self, session: FixSession) {
+        self.sessions.push(session);
+        let excess = self.sessions.len().saturating_sub(Self::MAX_SESSIONS);
+        self.sessions.drain(..excess);
+    }
+
+    pub fn last(&self) -> Option<&FixSession> {
+        self.sessions.last()
+    }
+
+    /// Reverts the most recent session on disk and drops it from the journal.
+    pub fn undo_last(&mut self) -> Result<Option<FixSession>, Box<dyn std::error::Error>> {
+        let Some(session) = self.sessions.last() else {
+            return Ok(None);
+        };
+        let changes = session.plan_undo()?;
+        fix::write_changes(&changes, &FixOptions::default())?;
+        Ok(self.sessions.pop())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fix::Confidence;
+    use crate::Fix;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_undo_last_session() {
+        let temp_dir = TempDir::new().unwrap();
+        let doc = temp_dir.path().join("doc.md");
+        let original = "[a](https://x.io/a) and [b](https://x.io/bb)\n";
+        fs::write(&doc, original).unwrap();
+
+        let fix = |target: &str, replacement: &str| Fix {
+            file_path: doc.clone(),
+            line_number: 1,
+            target: target.to_string(),
+            replacement: replacement.to_string(),
+            confidence: Confidence::Safe,
+        };
+        let changes = fix::plan_fixes(&[
+            fix("https://x.io/a", "a.md"),
+            fix("https://x.io/bb", "b.md"),
+        ])
+        .unwrap();
+        fix::write_changes(&changes, &FixOptions::default()).unwrap();
+        assert_eq!(
+            fs::read_to_string(&doc).unwrap(),
+            "[a](a.md) and [b](b.md)\n"
+        );
+
+        let journal_path = temp_dir.path().join("cache").join(Journal::FILE_NAME);
+        let mut journal = Journal::default();
+        journal.record(FixSession::new("fix", &changes));
+        journal.save(&journal_path).unwrap();
+
+        let mut journal = Journal::load(&journal_path);
+        assert_eq!(journal.last().unwrap().edit_count(), 2);
+        let undone = journal.undo_last().unwrap().unwrap();
+        assert_eq!(undone.command, "fix");
+        assert_eq!(fs::read_to_string(&doc).unwrap(), original);
+        assert!(journal.last().is_none());
+        assert!(journal.undo_last().unwrap().is_none());
+    }
+
+    #[test]
+    fn test_undo_refuses_modified_files() {
+        let temp_dir = TempDir::new().unwrap();
+        let doc = temp_dir.path().join("doc.md");
+        fs::write(&doc, "[a](a.md)\n").unwrap();
+
+        let mut journal = Journal::default();
+        journal.record(FixSession {
+            timestamp: 0,
+            command: "fix".to_string(),
+            files: vec![JournalFile {
+                path: doc.clone(),
+                edits: vec![AppliedEdit {
+                    span: 4..8,
+                    before: "https://x.io/a".to_string(),
+                    after: "a.md".to_string(),
+                }],
+            }],
+        });
+        fs::write(&doc, "[a](z.md)\n").unwrap();
+
+        assert!(journal.undo_last().is_err());
+        assert_eq!(fs::read_to_string(&doc).unwrap(), "[a](z.md)\n");
+        assert!(journal.last().is_some());
+    }
+}
@@ -0,0 +1,336 @@
+use crate::{normalize_path, BrokenLink};
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+
+/// A JUnit XML report of broken links, for CI systems such as Jenkins and
+/// GitLab: a test suite per checked directory and a test case per document,
+/// which fails with the document's broken links.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[non_exhaustive]
+pub struct JunitReport {
+    pub suites: Vec<JunitSuite>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct JunitSuite {
+    pub name: String,
+    pub cases: Vec<JunitCase>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct JunitCase {
+    /// The document's path relative to the checked directory.
+    pub name: String,
+    pub file: PathBuf,
+    pub failures: Vec<BrokenLink>,
+    /// Findings that don't fail the check, listed in the case's output.
+    pub warnings: Vec<BrokenLink>,
+    /// Why the case could not be checked at all.
+    pub error: Option<String>,
+}
+
+impl JunitReport {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a suite named `name` for the directory `root`, with a case for
+    /// each of `documents` and for any other file a finding is in.
+    pub fn push_suite(
+        &mut self,
+        name: &str,
+        root: &Path,
+        documents: &[PathBuf],
+        broken_links: &[BrokenLink],
+        warnings: &[BrokenLink],
+    ) {
+        let root = normalize_path(root);
+        let mut cases: BTreeMap<PathBuf, JunitCase> = BTreeMap::new();
+        for document in documents {
+            case(&mut cases, &root, document);
+        }
+        for broken_link in broken_links {
+            case(&mut cases, &root, &broken_link.link.file_path)
+                .failures
+                .push(broken_link.clone());
+        }
+        for warning in warnings {
+            case(&mut cases, &root, &warning.link.file_path)
+                .warnings
+                .push(warning.clone());
+        }
+
+        self.suites.push(JunitSuite {
+            name: name.to_string(),
+            cases: cases.into_values().collect(),
+        });
+    }
+
+    /// Adds a suite named `name` with a single case in error, for a target
+    /// that could not be checked.
+    pub fn push_error(&mut self, name: &str, message: &str) {
+        self.suites.push(JunitSuite {
+            name: name.to_string(),
+            cases: vec![JunitCase {
+                name: name.to_string(),
+                file: PathBuf::from(name),
+                failures: Vec::new(),
+                warnings: Vec::new(),
+                error: Some(message.to_string()),
+            }],
+        });
+    }
+
+    pub fn tests(&self) -> usize {
+        self.suites.iter().map(JunitSuite::tests).sum()
+    }
+
+    pub fn failures(&self) -> usize {
+        self.suites.iter().map(JunitSuite::failures).sum()
+    }
+
+    pub fn errors(&self) -> usize {
+        self.suites.iter().map(JunitSuite::errors).sum()
+    }
+
+    pub fn to_xml(&self) -> String {
+        let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        let _ = writeln!(
+            xml,
+            "<testsuites name=\"{}\" tests=\"{}\" failures=\"{}\"{}>",
+            env!("CARGO_PKG_NAME"),
+            self.tests(),
+            self.failures(),
+            errors_attribute(self.errors())
+        );
+        for suite in &self.suites {
+            let _ = writeln!(
+                xml,
+                "  <testsuite name=\"{}\" tests=\"{}\" failures=\"{}\"{}>",
+                escape(&suite.name),
+                suite.tests(),
+                suite.failures(),
+                errors_attribute(suite.errors())
+            );
+            for case in &suite.cases {
+                let _ = write!(
+                    xml,
+                    "    <testcase classname=\"{}\" name=\"{}\" file=\"{}\"",
+                    escape(&suite.name),
+                    escape(&case.name),
+                    escape(&case.file.to_string_lossy())
+                );
+                if case.failures.is_empty() && case.warnings.is_empty() && case.error.is_none() {
+                    xml.push_str("/>\n");
+                    continue;
+                }
+                xml.push_str(">\n");
+                if let Some(error) = &case.error {
+                    let _ = writeln!(xml, "      <error message=\"{}\"/>", escape(error));
+                }
+                if let [first, ..] = case.failures.as_slice() {
+                    let message = match case.failures.len() {
+                        1 => format!("{}: {}", first.link.target, first.reason),
+                        count => format!("{} broken links", count),
+                    };
+                    let _ = writeln!(
+                        xml,
+                        "      <failure message=\"{}\" type=\"{}\">{}</failure>",
+                        escape(&message),
+                        crate::sarif::rule_id(first),
+                        escape(&findings(&case.failures))
+                    );
+                }
+                if !case.warnings.is_empty() {
+                    let _ = writeln!(
+                        xml,
+                        "      <system-out>{}</system-out>",
+                        escape(&findings(&case.warnings))
+                    );
+                }
+                xml.push_str("    </testcase>\n");
+            }
+            xml.push_str("  </testsuite>\n");
+        }
+        xml.push_str("</testsuites>\n");
+        xml
+    }
+}
+
+impl JunitSuite {
+    pub fn tests(&self) -> usize {
+        self.cases.len()
+    }
+
+    /// Cases with at least one broken link.
+    pub fn failures(&self) -> usize {
+        self.cases
+            .iter()
+            .filter(|case| !case.failures.is_empty())
+            .count()
+    }
+
+    /// Cases that could not be checked.
+    pub fn errors(&self) -> usize {
+        self.cases
+            .iter()
+            .filter(|case| case.error.is_some())
+            .count()
+    }
+}
+
+/// ` errors="N"`, left out while there are none.
+fn errors_attribute(errors: usize) -> String {
+    if errors == 0 {
+        String::new()
+    } else {
+        format!(" errors=\"{}\"", errors)
+    }
+}
+
+/// The case for `file`, added if it is new.
+fn case<'a>(
+    cases: &'a mut BTreeMap<PathBuf, JunitCase>,
+    root: &Path,
+    file: &Path,
+) -> &'a mut JunitCase {
+    let file = normalize_path(file);
+    cases.entry(file.clone()).or_insert_with(|| JunitCase {
+        name: file
+            .strip_prefix(root)
+            .unwrap_or(&file)
+            .to_string_lossy()
+            .replace('\\', "/"),
+        file,
+        failures: Vec::new(),
+        warnings: Vec::new(),
+        error: None,
+    })
+}
+
+/// One `file:line: target: reason` line per finding.
+fn findings(broken_links: &[BrokenLink]) -> String {
+    broken_links
+        .iter()
+        .map(|broken| {
+            let link = &broken.link;
+            format!(
+                "{}:{}: {}: {}\n",
+                link.file_path.display(),
+                link.line_number,
+                link.target,
+                broken.reason
+            )
+        })
+        .collect()
+}
+
+/// `text` escaped for XML attributes and text, without the control
+/// characters XML 1.0 can't hold.
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            '\n' => escaped.push_str("&#10;"),
+            c if c.is_control() && c != '\t' && c != '\r' => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MarkdownLink;
+
+    fn broken(file_path: &str, line_number: usize, target: &str, reason: &str) -> BrokenLink {
+        BrokenLink {
+            link: MarkdownLink {
+                text: "Link".to_string(),
+                target: target.to_string(),
+                line_number,
+                file_path: PathBuf::from(file_path),
+            },
+            reason: reason.to_string(),
+            suggestion: None,
+        }
+    }
+
+    #[test]
+    fn test_junit_report() {
+        let mut report = JunitReport::new();
+        report.push_suite(
+            "./docs",
+            Path::new("./docs"),
+            &[
+                PathBuf::from("./docs/README.md"),
+                PathBuf::from("./docs/guide.md"),
+            ],
+            &[
+                broken(
+                    "./docs/guide.md",
+                    3,
+                    "./gone.md",
+                    "File not found: docs/gone.md",
+                ),
+                broken(
+                    "./docs/guide.md",
+                    7,
+                    "#a&b",
+                    "Anchor not found: #a&b in docs/guide.md",
+                ),
+            ],
+            &[broken(
+                "./docs/README.md",
+                1,
+                "https://travis-ci.org/x.svg",
+                "Badge from a shut-down service",
+            )],
+        );
+        assert_eq!(report.tests(), 2);
+        assert_eq!(report.failures(), 1);
+
+        assert_eq!(
+            report.to_xml(),
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+             <testsuites name=\"doclink-checker\" tests=\"2\" failures=\"1\">\n  \
+             <testsuite name=\"./docs\" tests=\"2\" failures=\"1\">\n    \
+             <testcase classname=\"./docs\" name=\"README.md\" file=\"docs/README.md\">\n      \
+             <system-out>./docs/README.md:1: https://travis-ci.org/x.svg: Badge from a shut-down service&#10;</system-out>\n    \
+             </testcase>\n    \
+             <testcase classname=\"./docs\" name=\"guide.md\" file=\"docs/guide.md\">\n      \
+             <failure message=\"2 broken links\" type=\"broken-link\">\
+             ./docs/guide.md:3: ./gone.md: File not found: docs/gone.md&#10;\
+             ./docs/guide.md:7: #a&amp;b: Anchor not found: #a&amp;b in docs/guide.md&#10;\
+             </failure>\n    \
+             </testcase>\n  \
+             </testsuite>\n\
+             </testsuites>\n"
+        );
+
+        // A document without findings passes.
+        let mut report = JunitReport::new();
+        report.push_suite("a", Path::new("a"), &[PathBuf::from("a/b.md")], &[], &[]);
+        assert!(report
+            .to_xml()
+            .contains("<testcase classname=\"a\" name=\"b.md\" file=\"a/b.md\"/>"));
+
+        // A target that could not be checked is a case in error.
+        report.push_error("https://example.com/repo.git", "could not clone <repo>");
+        assert_eq!(report.errors(), 1);
+        let xml = report.to_xml();
+        assert!(xml.contains(
+            "<testsuites name=\"doclink-checker\" tests=\"2\" failures=\"0\" errors=\"1\">"
+        ));
+        assert!(xml.contains("<error message=\"could not clone &lt;repo&gt;\"/>"));
+    }
+}
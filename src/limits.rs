@@ -0,0 +1,128 @@
+use crate::LinkAnalyzer;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Caps on how much is read, so a scan of untrusted contributions cannot be
+/// made to exhaust memory. `None` is unlimited.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ReadLimits {
+    /// Files larger than this are skipped, both as documents and when their
+    /// headings are looked up.
+    pub max_file_bytes: Option<u64>,
+    /// The scan fails once the documents read add up to more than this.
+    pub max_total_bytes: Option<u64>,
+}
+
+impl ReadLimits {
+    /// The limits set by `max_file_bytes` and `max_total_bytes`, where `0`
+    /// is unlimited.
+    pub fn from_config(config: &crate::Config) -> Self {
+        Self {
+            max_file_bytes: (config.max_file_bytes > 0).then_some(config.max_file_bytes),
+            max_total_bytes: (config.max_total_bytes > 0).then_some(config.max_total_bytes),
+        }
+    }
+}
+
+impl LinkAnalyzer {
+    pub fn with_read_limits(mut self, limits: ReadLimits) -> Self {
+        self.read_limits = limits;
+        self
+    }
+
+    /// Reads a document or link target as text. Files over `max_file_bytes`
+    /// and binary files, ones with NUL bytes or invalid UTF-8 even if named
+    /// `.md`, fail with `InvalidData`.
+    pub(crate) fn read_document(&self, path: &Path) -> io::Result<String> {
+        if let Some(max) = self.read_limits.max_file_bytes {
+            let size = self.fs.file_size(path)?;
+            if size > max {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("File is {} bytes, over max_file_bytes ({})", size, max),
+                ));
+            }
+        }
+        let binary = || io::Error::new(io::ErrorKind::InvalidData, "Binary file");
+        let content = self.fs.read_to_string(path).map_err(|e| match e.kind() {
+            io::ErrorKind::InvalidData => binary(),
+            _ => e,
+        })?;
+        if content.contains('\0') {
+            return Err(binary());
+        }
+        Ok(content)
+    }
+
+    /// Files the last scan left out, with why: too large or binary.
+    pub fn skipped_files(&self) -> &[(PathBuf, String)] {
+        &self.skipped_files
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vfs::MemoryFileSystem;
+
+    #[test]
+    fn test_read_limits() {
+        let mut fs = MemoryFileSystem::new();
+        fs.insert("docs/README.md", "# Docs\n\n[Big](./big.md#intro)\n");
+        fs.insert(
+            "docs/big.md",
+            format!("# Intro\n\n{}", "x".repeat(200)).as_str(),
+        );
+        fs.insert("docs/image.md", "\u{0}PNG\r\n");
+        fs.insert("docs/latin1.md", b"caf\xe9".to_vec());
+
+        let limits = ReadLimits {
+            max_file_bytes: Some(100),
+            ..ReadLimits::default()
+        };
+        let mut analyzer = LinkAnalyzer::new(PathBuf::from("docs"))
+            .with_filesystem(fs)
+            .with_read_limits(limits);
+        analyzer.analyze_directory().unwrap();
+
+        assert_eq!(
+            analyzer.document_paths(),
+            vec![PathBuf::from("docs/README.md")]
+        );
+        let skipped: Vec<(&str, &str)> = analyzer
+            .skipped_files()
+            .iter()
+            .map(|(path, reason)| (path.to_str().unwrap(), reason.as_str()))
+            .collect();
+        assert_eq!(
+            skipped,
+            vec![
+                (
+                    "docs/big.md",
+                    "File is 209 bytes, over max_file_bytes (100)"
+                ),
+                ("docs/image.md", "Binary file"),
+                ("docs/latin1.md", "Binary file"),
+            ]
+        );
+        // The anchor cannot be checked in a skipped file, so it is not
+        // reported either.
+        assert!(analyzer.find_broken_links().is_empty());
+
+        let mut fs = MemoryFileSystem::new();
+        fs.insert("docs/a.md", "# A\n\nSome text.\n");
+        fs.insert("docs/b.md", "# B\n\nSome text.\n");
+        let limits = ReadLimits {
+            max_total_bytes: Some(20),
+            ..ReadLimits::default()
+        };
+        let mut analyzer = LinkAnalyzer::new(PathBuf::from("docs"))
+            .with_filesystem(fs)
+            .with_read_limits(limits);
+        let err = analyzer.analyze_directory().unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Documents add up to more than max_total_bytes (20) at docs/b.md"
+        );
+    }
+}
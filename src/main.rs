@@ -1,37 +1,177 @@
-use clap::{Parser, Subcommand};
+use clap::{Args, Parser, Subcommand};
 use colored::*;
-use doclink_checker::{LinkAnalyzer, LinkStatistics};
-use std::path::PathBuf;
+use doclink_checker::badges;
+use doclink_checker::config::{parse_bool, parse_duration};
+use doclink_checker::database::{Database, Findings};
+use doclink_checker::doctor::{self, Severity};
+use doclink_checker::external::{
+    AddressPolicy, CheckCache, ExternalChecker, ExternalResponse, RequestBudget, UrlHistory,
+    Verdict,
+};
+use doclink_checker::fix::Confidence;
+use doclink_checker::fixture::{Fixture, FixtureSpec};
+use doclink_checker::graph::GraphMetrics;
+use doclink_checker::identifiers::{self, Identifier};
+use doclink_checker::import;
+use doclink_checker::journal::{FixSession, Journal};
+use doclink_checker::junit::JunitReport;
+use doclink_checker::migrate::{ImportedConfig, SourceTool};
+use doclink_checker::observer::AnalysisObserver;
+use doclink_checker::query::Query;
+use doclink_checker::registry::{self, Registry};
+use doclink_checker::report::{self, Report, ReportMetadata};
+use doclink_checker::sarif::{SarifLevel, SarifLog};
+use doclink_checker::scaffold;
+use doclink_checker::sitemap::{self, SitemapLayout};
+use doclink_checker::vfs::MemoryFileSystem;
+use doclink_checker::{
+    cache, git, AnchorUsage, BrokenLink, CancellationToken, Cancelled, CauseGroup, Config,
+    DirectoryStats, FieldGroup, FindingLimits, Fix, LinkAnalyzer, LinkKind, LinkStatistics,
+    MarkdownLink, RootCause,
+};
+use doclink_checker::{crosscheck, fix};
+use notify::{RecursiveMode, Watcher};
+use regex::Regex;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::env;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
 use std::process;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{mpsc, Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime};
 
 #[derive(Parser)]
 #[command(author, version, about, long_about = None)]
 #[command(name = "doclink-checker")]
 #[command(about = "A tool to analyze markdown documents for broken links and statistics")]
 struct Cli {
+    #[command(flatten)]
+    config: ConfigArgs,
+    /// Format of diagnostics on stderr (text, or json lines for log pipelines)
+    #[arg(long, global = true, default_value = "text", value_parser = ["text", "json"])]
+    log_format: String,
     #[command(subcommand)]
     command: Commands,
 }
 
+impl Cli {
+    /// Applies `DOCLINK_*` environment variables over the parsed flags, so a
+    /// variable wins over both the configuration file and the command line.
+    fn apply_env(
+        &mut self,
+        vars: impl IntoIterator<Item = (String, String)>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let vars: Vec<(String, String)> = vars
+            .into_iter()
+            .filter(|(name, _)| name.starts_with(Config::ENV_PREFIX))
+            .collect();
+        for (name, value) in &vars {
+            match name.as_str() {
+                "DOCLINK_CONFIG" => self.config.config = Some(PathBuf::from(value)),
+                "DOCLINK_PROFILE" => self.config.profile = Some(value.clone()),
+                _ => {}
+            }
+        }
+        if let Commands::Check(args) = &mut self.command {
+            args.apply_env(&vars)?;
+        }
+        self.config.env = vars;
+        Ok(())
+    }
+}
+
+#[derive(Args)]
+struct ConfigArgs {
+    /// Path to a configuration file (defaults to .doclink.toml in the analyzed directory)
+    #[arg(long, global = true, value_name = "FILE")]
+    config: Option<PathBuf>,
+    /// Configuration profile to apply, e.g. `ci` for [profile.ci]
+    #[arg(long, global = true, value_name = "NAME")]
+    profile: Option<String>,
+    /// Files or directories not to walk, as globs relative to the analyzed
+    /// directory, e.g. `node_modules` (repeatable; added to `exclude`)
+    #[arg(long, global = true, value_name = "GLOB")]
+    exclude: Vec<String>,
+    /// Documents to treat as roots, which are never orphans, as globs relative
+    /// to the analyzed directory, e.g. `**/README.md` (repeatable; added to `root_globs`)
+    #[arg(long, global = true, value_name = "GLOB")]
+    root_glob: Vec<String>,
+    /// Conventions of where the documents are published: markdown,
+    /// github-wiki or azure-devops-wiki (sets `flavor`)
+    #[arg(
+        long,
+        global = true,
+        value_name = "FLAVOR",
+        value_parser = ["markdown", "github-wiki", "azure-devops-wiki"]
+    )]
+    flavor: Option<String>,
+    /// Walk into symlinked files and directories (sets `follow_symlinks`)
+    #[arg(long, global = true)]
+    follow_symlinks: bool,
+    /// Walk files and directories listed in .gitignore too (sets `no_gitignore`)
+    #[arg(long, global = true)]
+    no_gitignore: bool,
+    /// `DOCLINK_*` environment variables, captured by `Cli::apply_env`
+    #[arg(skip)]
+    env: Vec<(String, String)>,
+}
+
+impl ConfigArgs {
+    /// Settings are layered as: config file, then profile, then CLI flags, then
+    /// `DOCLINK_*` environment variables, which win over everything else.
+    fn load(&self, path: &Path) -> Result<Config, Box<dyn std::error::Error>> {
+        let mut config = match &self.config {
+            Some(config_path) => Config::load(config_path)?,
+            None => Config::discover(path)?,
+        };
+        if let Some(profile) = &self.profile {
+            config = config.with_profile(profile)?;
+        }
+        config.exclude.extend(self.exclude.iter().cloned());
+        config.root_globs.extend(self.root_glob.iter().cloned());
+        if let Some(flavor) = &self.flavor {
+            config.flavor = flavor.clone();
+        }
+        config.follow_symlinks |= self.follow_symlinks;
+        config.no_gitignore |= self.no_gitignore;
+        config.apply_env(self.env.iter().cloned())
+    }
+
+    fn analyzer(&self, path: &Path) -> Result<LinkAnalyzer, Box<dyn std::error::Error>> {
+        let config = self.load(path)?;
+        LinkAnalyzer::with_config(path.to_path_buf(), &config)
+    }
+}
+
 #[derive(Subcommand)]
 enum Commands {
     /// Check for broken links in markdown documents
-    Check {
-        /// Directory to analyze
+    Check(Box<CheckArgs>),
+    /// Check once, then re-check the documents each change affects until interrupted
+    Watch {
+        /// Directory to watch
         #[arg(short, long, default_value = ".")]
         path: PathBuf,
-        /// Show detailed output
-        #[arg(short, long)]
-        verbose: bool,
     },
     /// Show statistics about links in markdown documents
-    Stats {
+    Stats(StatsArgs),
+    /// Show where links are breaking, e.g. as a heatmap of directories
+    Report(ReportArgs),
+    /// Query a database written by `check --output-db`, e.g. "orphans older:90d"
+    Query(QueryArgs),
+    /// Export the link graph with document metadata for Gephi, D3 and similar tools
+    Graph(GraphArgs),
+    /// List the files a check would scan, one per line, like `git ls-files`
+    LsFiles {
         /// Directory to analyze
         #[arg(short, long, default_value = ".")]
         path: PathBuf,
-        /// Output format (text or json)
-        #[arg(short, long, default_value = "text")]
-        format: String,
+        /// List the files left out instead, with why
+        #[arg(long)]
+        excluded: bool,
     },
     /// Find orphaned documents (not linked from anywhere)
     Orphans {
@@ -39,196 +179,3705 @@ enum Commands {
         #[arg(short, long, default_value = ".")]
         path: PathBuf,
     },
+    /// Show the shortest chain of links from one document to another
+    #[command(name = "path")]
+    ShortestPath {
+        /// Document to start from, e.g. the landing page
+        from: PathBuf,
+        /// Document to reach
+        to: PathBuf,
+        /// Directory to analyze
+        #[arg(short, long, default_value = ".")]
+        path: PathBuf,
+    },
+    /// Show which headings are linked to most and which are never linked
+    Anchors(AnchorsArgs),
+    /// Find near-duplicate documents, e.g. copy-pasted guides
+    Duplicates {
+        /// Directory to analyze
+        #[arg(short, long, default_value = ".")]
+        path: PathBuf,
+        /// Minimum similarity (0.0 to 1.0) for two documents to be reported
+        #[arg(long, default_value_t = 0.8, value_parser = parse_ratio)]
+        threshold: f64,
+        /// Output format: text or json
+        #[arg(short, long, default_value = "text", value_parser = ["text", "json"])]
+        format: String,
+    },
+    /// Generate a Markdown or XML sitemap of all documents
+    Sitemap(SitemapArgs),
+    /// Compare a published sitemap.xml or llms.txt with the local documents
+    Crosscheck {
+        /// Path or URL of the sitemap.xml or llms.txt
+        source: String,
+        /// Published URL of the analyzed directory (defaults to the source URL's directory)
+        #[arg(long, value_name = "URL")]
+        base_url: Option<String>,
+        /// Directory to analyze
+        #[arg(short, long, default_value = ".")]
+        path: PathBuf,
+    },
+    /// Create a document from a template and link to it from an index
+    New(NewArgs),
+    /// Apply automatic fixes to links
+    Fix(FixArgs),
+    /// Rewrite link targets matching a regular expression, e.g. for a domain migration
+    Rewrite(RewriteArgs),
+    /// Revert the most recent fix session
+    Undo {
+        /// Show what would be reverted without changing any file
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Translate a lychee or markdown-link-check configuration into .doclink.toml
+    ImportConfig(ImportConfigArgs),
+    /// Validate the configuration file, cache and external tools
+    Doctor {
+        /// Directory whose configuration should be checked
+        #[arg(short, long, default_value = ".")]
+        path: PathBuf,
+    },
+    /// Explain step by step how a link target is resolved from a document
+    Resolve {
+        /// Document containing the link
+        file: PathBuf,
+        /// Link target as written in the document
+        target: String,
+        /// Directory the analysis is rooted at (used for absolute targets)
+        #[arg(short, long, default_value = ".")]
+        path: PathBuf,
+    },
+    /// Tools for developing doclink-checker itself
+    #[command(hide = true, subcommand)]
+    Dev(DevCommands),
+}
+
+#[derive(Args)]
+struct QueryArgs {
+    /// What to list and how to filter it, e.g. "links from:docs/api/** kind:external"
+    query: Query,
+    /// Database written by `check --output-db`
+    #[arg(long, value_name = "PATH")]
+    db: PathBuf,
+    /// Run to query (defaults to the most recent)
+    #[arg(long, value_name = "ID")]
+    run: Option<i64>,
+    /// Output format (text, json or csv)
+    #[arg(short, long, default_value = "text", value_parser = ["text", "json", "csv"])]
+    format: String,
+}
+
+#[derive(Args)]
+struct ReportArgs {
+    /// Directory to analyze
+    #[arg(short, long, default_value = ".")]
+    path: PathBuf,
+    /// Output format: a colored text grid (heatmap) or a standalone HTML page (heatmap-html)
+    #[arg(short, long, default_value = "heatmap", value_parser = ["heatmap", "heatmap-html"])]
+    format: String,
+    /// Number of directory levels to group by
+    #[arg(long, default_value_t = 1)]
+    depth: usize,
+    /// Write the report to FILE instead of stdout
+    #[arg(short, long, value_name = "FILE")]
+    output: Option<PathBuf>,
+}
+
+#[derive(Args)]
+struct GraphArgs {
+    /// Directory to analyze
+    #[arg(short, long, default_value = ".")]
+    path: PathBuf,
+    /// Output format: JSON in D3's node-link layout, GraphML, or a standalone
+    /// interactive HTML page
+    #[arg(short, long, default_value = "json", value_parser = ["json", "graphml", "html"])]
+    format: String,
+    /// One node per section (document#anchor) instead of per document
+    #[arg(long)]
+    anchors: bool,
+    /// Write the graph to FILE instead of stdout
+    #[arg(short, long, value_name = "FILE")]
+    output: Option<PathBuf>,
+}
+
+#[derive(Args)]
+struct NewArgs {
+    /// Document to create, e.g. docs/guides/setup.md
+    path: PathBuf,
+    /// Template to copy; `{{title}}` in it is replaced by the title
+    #[arg(long, value_name = "TEMPLATE")]
+    from: Option<PathBuf>,
+    /// Index document to add a link to the new document to
+    #[arg(long, value_name = "INDEX")]
+    link_from: Option<PathBuf>,
+    /// Title of the document (defaults to one made from the file name)
+    #[arg(long)]
+    title: Option<String>,
+}
+
+#[derive(Args)]
+struct ImportConfigArgs {
+    /// The other tool's configuration, e.g. lychee.toml or .markdown-link-check.json
+    #[arg(long, value_name = "FILE")]
+    from: PathBuf,
+    /// Which tool `--from` configures (guessed from its extension by default)
+    #[arg(long, value_parser = ["lychee", "markdown-link-check"])]
+    tool: Option<String>,
+    /// Where to write the translated configuration
+    #[arg(short, long, default_value = Config::FILE_NAME)]
+    output: PathBuf,
+    /// Print the translated configuration instead of writing it
+    #[arg(long)]
+    dry_run: bool,
+}
+
+#[derive(Args)]
+struct AnchorsArgs {
+    /// Directory to analyze
+    #[arg(short, long, default_value = ".")]
+    path: PathBuf,
+    /// Number of most linked anchors to show
+    #[arg(long, default_value_t = 10)]
+    top: usize,
+    /// Only list anchors nothing links to
+    #[arg(long)]
+    unused: bool,
+    /// Output format: text or json
+    #[arg(short, long, default_value = "text", value_parser = ["text", "json"])]
+    format: String,
+}
+
+#[derive(Subcommand)]
+enum DevCommands {
+    /// Generate a synthetic documentation tree for stress tests and benchmarks
+    Fixture(FixtureArgs),
+}
+
+#[derive(Args)]
+struct FixtureArgs {
+    /// Directory to create the tree in (must not exist or be empty)
+    path: PathBuf,
+    /// Number of documents
+    #[arg(long, default_value_t = 1000)]
+    documents: usize,
+    /// Links per document
+    #[arg(long, default_value_t = 10)]
+    links_per_document: usize,
+    /// Fraction of links (0.0 to 1.0) that point at missing files or headings
+    #[arg(long, default_value_t = 0.05, value_parser = parse_ratio)]
+    broken_ratio: f64,
+    /// Documents per directory
+    #[arg(long, default_value_t = 50)]
+    documents_per_directory: usize,
+    /// Sections (linkable headings) per document
+    #[arg(long, default_value_t = 5)]
+    sections: usize,
+    /// Seed for the generator; the same seed always produces the same tree
+    #[arg(long, default_value_t = 0)]
+    seed: u64,
+}
+
+#[derive(Args)]
+struct CheckArgs {
+    /// Directory to analyze (repeatable; defaults to the current directory)
+    #[arg(short, long)]
+    path: Vec<PathBuf>,
+    /// Show detailed output
+    #[arg(short, long)]
+    verbose: bool,
+    /// Only report links broken by files deleted or renamed since this git ref
+    #[arg(long, value_name = "GIT_REF")]
+    against: Option<String>,
+    /// Analyze documents inside a .tar, .tar.gz/.tgz or .zip archive (repeatable)
+    #[arg(long, value_name = "FILE")]
+    archive: Vec<PathBuf>,
+    /// Analyze an exported Confluence or Notion space, a directory or .zip,
+    /// with its HTML pages converted to Markdown (repeatable)
+    #[arg(long, value_name = "EXPORT")]
+    import: Vec<PathBuf>,
+    /// Analyze a remote git repository, shallow-cloned into the cache directory (repeatable)
+    #[arg(long, value_name = "URL")]
+    git: Vec<String>,
+    /// Branch, tag or commit to check out for every --git repository
+    #[arg(long = "ref", value_name = "GIT_REF", requires = "git")]
+    git_ref: Option<String>,
+    /// Number of targets, and of URL and package requests, to check concurrently (default: one per CPU)
+    #[arg(short, long, value_name = "N")]
+    jobs: Option<usize>,
+    /// Output format (text, csv, sarif or junit; defaults to `format` in the configuration, else text)
+    #[arg(short, long, value_parser = ["text", "csv", "sarif", "junit"])]
+    format: Option<String>,
+    /// Also check external http(s) links over the network
+    #[arg(long)]
+    external: bool,
+    /// Skip the network checks of --external and --packages, even when given
+    #[arg(long)]
+    offline: bool,
+    /// Show a running count of scanned files and findings on stderr
+    #[arg(long)]
+    progress: bool,
+    /// Where to print the final `doclink: files=… broken=…` summary line (stdout, stderr or off)
+    #[arg(long, value_name = "STREAM", default_value = "stderr", value_parser = ["stdout", "stderr", "off"])]
+    summary_line: String,
+    /// Append a Markdown job summary to $GITHUB_STEP_SUMMARY (GitHub Actions)
+    #[arg(long)]
+    github_summary: bool,
+    /// Also list files that are only linked from inside HTML comments
+    #[arg(long)]
+    commented: bool,
+    /// Report changelog compare URLs (`.../compare/v1.2.2...v1.2.3`) naming
+    /// tags or branches missing from the local repository
+    #[arg(long)]
+    compare_tags: bool,
+    /// Look up crates.io, docs.rs, npm and PyPI links in their registries and
+    /// report packages that do not exist (results are cached for a day)
+    #[arg(long)]
+    packages: bool,
+    /// Report script and data URIs, credentials in URLs, punycode or
+    /// look-alike domains and link shorteners
+    #[arg(long)]
+    security: bool,
+    /// Group broken links by what they are missing (a directory, file or
+    /// heading) in text output
+    #[arg(long)]
+    group_by_cause: bool,
+    /// Group broken links by a front matter field of the document they are
+    /// in, e.g. `frontmatter:owner`, in text output
+    #[arg(long, value_name = "KEY", value_parser = parse_group_by, conflicts_with = "group_by_cause")]
+    group_by: Option<String>,
+    /// Report at most N broken links per file
+    #[arg(long, value_name = "N")]
+    max_findings_per_file: Option<usize>,
+    /// Stop after N broken links per target
+    #[arg(long, value_name = "N")]
+    max_findings: Option<usize>,
+    /// Give up on the whole run after this long, e.g. `120s` or `2m`, and
+    /// report partial results
+    #[arg(long, value_name = "DURATION", value_parser = parse_duration)]
+    timeout: Option<Duration>,
+    /// Give up on a single external request after this long (default 10s)
+    #[arg(long, value_name = "DURATION", value_parser = parse_duration)]
+    request_timeout: Option<Duration>,
+    /// Fetch at most N URLs over the network in the whole run (--external and
+    /// --packages); the rest are left unchecked and the run is incomplete
+    #[arg(long, value_name = "N")]
+    max_requests: Option<usize>,
+    /// Append documents, links, the link graph and findings to a SQLite
+    /// database, creating it if needed
+    #[arg(long, value_name = "PATH")]
+    output_db: Option<PathBuf>,
+    /// What a target with no Markdown documents does: pass (ok), pass with a
+    /// warning (warn) or fail (error)
+    #[arg(long, value_name = "POLICY", default_value = "warn", value_parser = ["ok", "warn", "error"])]
+    if_empty: String,
+}
+
+impl CheckArgs {
+    /// Sets the switches that have no config key from their `DOCLINK_*`
+    /// variables, e.g. `DOCLINK_OFFLINE=1` or `DOCLINK_TIMEOUT=5s`.
+    fn apply_env(&mut self, vars: &[(String, String)]) -> Result<(), Box<dyn std::error::Error>> {
+        for (name, value) in vars {
+            let invalid = |e: String| format!("{}: {}", name, e);
+            match name.as_str() {
+                "DOCLINK_JOBS" => {
+                    self.jobs =
+                        Some(value.parse().map_err(|_| {
+                            format!("{}: expected an integer, got `{}`", name, value)
+                        })?)
+                }
+                "DOCLINK_EXTERNAL" => self.external = parse_bool(value).map_err(invalid)?,
+                "DOCLINK_PACKAGES" => self.packages = parse_bool(value).map_err(invalid)?,
+                "DOCLINK_OFFLINE" => self.offline = parse_bool(value).map_err(invalid)?,
+                "DOCLINK_TIMEOUT" => self.timeout = Some(parse_duration(value).map_err(invalid)?),
+                "DOCLINK_REQUEST_TIMEOUT" => {
+                    self.request_timeout = Some(parse_duration(value).map_err(invalid)?)
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Args)]
+struct FixArgs {
+    /// Directory to analyze
+    #[arg(short, long, default_value = ".")]
+    path: PathBuf,
+    /// Print the fixes and a unified diff of them without changing any file
+    #[arg(long)]
+    dry_run: bool,
+    /// Keep the original of every rewritten file as <file>.bak
+    #[arg(long)]
+    backup: bool,
+    /// Also write all changes as a patch file (works with --dry-run)
+    #[arg(long, value_name = "FILE")]
+    patch: Option<PathBuf>,
+    /// Also apply risky fixes (a similar file name, or one of several matches)
+    #[arg(long)]
+    apply_risky: bool,
+    /// Ask before applying each risky fix
+    #[arg(short, long, conflicts_with = "apply_risky")]
+    interactive: bool,
+    /// Output format (text or json)
+    #[arg(short, long, default_value = "text", value_parser = ["text", "json"])]
+    format: String,
+    /// Instead of fixing targets, convert every link to inline or reference style
+    #[arg(long, value_parser = ["inline", "reference"], conflicts_with_all = ["apply_risky", "interactive", "format"])]
+    style: Option<String>,
+    /// Instead of fixing targets, rewrite internal links to one form: `./`-prefixed,
+    /// plain relative or root-relative
+    #[arg(long, value_parser = ["dot", "plain", "root"], conflicts_with = "style")]
+    relative_style: Option<String>,
+    /// Instead of fixing targets, point fragments at renamed or closely matching headings
+    #[arg(long, conflicts_with_all = ["style", "relative_style"])]
+    sync_anchors: bool,
+    /// With --sync-anchors, compare headings against this git ref to detect renames
+    #[arg(long, value_name = "GIT_REF", requires = "sync_anchors")]
+    since: Option<String>,
+}
+
+#[derive(Args)]
+struct RewriteArgs {
+    /// Directory to analyze
+    #[arg(short, long, default_value = ".")]
+    path: PathBuf,
+    /// Regular expression that must match the whole link target
+    #[arg(long, value_name = "REGEX")]
+    from: String,
+    /// Replacement target; `$1`, `${name}` refer to groups captured by --from
+    #[arg(long, value_name = "REPLACEMENT")]
+    to: String,
+    /// Print a diff of the changes without changing any file
+    #[arg(long)]
+    dry_run: bool,
+    /// Keep the original of every rewritten file as <file>.bak
+    #[arg(long)]
+    backup: bool,
+    /// Also write all changes as a patch file (works with --dry-run)
+    #[arg(long, value_name = "FILE")]
+    patch: Option<PathBuf>,
+}
+
+/// One suggested fix in `fix --format json` output.
+#[derive(Serialize)]
+struct FixReportEntry<'a> {
+    #[serde(flatten)]
+    fix: &'a Fix,
+    applied: bool,
+}
+
+#[derive(Args)]
+struct StatsArgs {
+    /// Directory to analyze
+    #[arg(short, long, default_value = ".")]
+    path: PathBuf,
+    /// Output format (text, json or csv)
+    #[arg(short, long, default_value = "text", value_parser = ["text", "json", "csv"])]
+    format: String,
+    /// Aggregate counts per directory instead of per document
+    #[arg(long)]
+    by_dir: bool,
+    /// Number of directory levels to group by with --by-dir
+    #[arg(long, default_value_t = 1, requires = "by_dir")]
+    depth: usize,
+    /// Fail (exit code 1) when the health score is below SCORE (0-100)
+    #[arg(long, value_name = "SCORE", conflicts_with = "by_dir")]
+    min_score: Option<f64>,
+    /// Add link graph metrics: components, degree distribution and the
+    /// largest strongly connected component
+    #[arg(long, conflicts_with = "by_dir")]
+    detailed: bool,
+}
+
+#[derive(Args)]
+struct SitemapArgs {
+    /// Directory to analyze
+    #[arg(short, long, default_value = ".")]
+    path: PathBuf,
+    /// Output format (markdown or xml)
+    #[arg(short, long, default_value = "markdown", value_parser = ["markdown", "xml"])]
+    format: String,
+    /// Organize pages by the link graph from README.md or by directory
+    #[arg(long, default_value = "graph", value_parser = ["graph", "directory"])]
+    layout: String,
+    /// URL prefix for <loc> entries in the XML sitemap
+    #[arg(long, value_name = "URL", default_value = "")]
+    base_url: String,
+}
+
+enum CheckTarget {
+    Path(PathBuf),
+    Archive(PathBuf),
+    Import(PathBuf),
+    Git(String),
+}
+
+impl CheckTarget {
+    fn label(&self) -> String {
+        match self {
+            CheckTarget::Path(path) | CheckTarget::Archive(path) | CheckTarget::Import(path) => {
+                path.display().to_string()
+            }
+            CheckTarget::Git(url) => url.clone(),
+        }
+    }
+}
+
+/// Live counters drawn on stderr while `check --progress` runs.
+#[derive(Debug, Default)]
+struct Progress {
+    files: AtomicUsize,
+    links: AtomicUsize,
+    broken: AtomicUsize,
+}
+
+impl Progress {
+    fn draw(&self) {
+        eprint!(
+            "\r{} Scanned {} files, {} links, {} broken",
+            "…".blue().bold(),
+            self.files.load(Ordering::Relaxed),
+            self.links.load(Ordering::Relaxed),
+            self.broken.load(Ordering::Relaxed)
+        );
+    }
+}
+
+impl AnalysisObserver for Progress {
+    fn on_file_scanned(&self, _path: &Path, links: &[MarkdownLink]) {
+        self.files.fetch_add(1, Ordering::Relaxed);
+        self.links.fetch_add(links.len(), Ordering::Relaxed);
+        self.draw();
+    }
+
+    fn on_broken_link(&self, _broken_link: &BrokenLink) {
+        self.broken.fetch_add(1, Ordering::Relaxed);
+        self.draw();
+    }
+}
+
+/// What an `ExternalChecker` is built from.
+type CheckerSettings = (
+    AddressPolicy,
+    Duration,
+    BTreeMap<String, BTreeMap<String, String>>,
+);
+
+/// State shared by every target of a `check` run. The caches are kept in the
+/// cache directory between runs, and each is only loaded and saved when the
+/// flag using it is set.
+struct CheckRun {
+    history: Mutex<UrlHistory>,
+    badges: Mutex<CheckCache>,
+    identifiers: Mutex<CheckCache>,
+    packages: Mutex<CheckCache>,
+    budget: RequestBudget,
+    /// `--request-timeout`, which overrides `external_timeout`.
+    request_timeout: Option<Duration>,
+    /// One checker per address policy, timeout and headers, so targets share
+    /// pooled connections and cached DNS answers.
+    checkers: Mutex<Vec<(CheckerSettings, ExternalChecker)>>,
+    /// Where each target is recorded as it finishes, with --output-db.
+    database: Option<Mutex<Database>>,
+}
+
+impl CheckRun {
+    fn load(args: &CheckArgs) -> Result<Self, Box<dyn std::error::Error>> {
+        let load = |enabled: bool, path: PathBuf| {
+            if enabled {
+                CheckCache::load(&path)
+            } else {
+                CheckCache::default()
+            }
+        };
+        let database = match &args.output_db {
+            Some(path) => Some(Mutex::new(Database::open(path)?)),
+            None => None,
+        };
+        Ok(Self {
+            history: Mutex::new(if args.external {
+                UrlHistory::load(&cache::cache_dir().join(UrlHistory::FILE_NAME))
+            } else {
+                UrlHistory::default()
+            }),
+            badges: Mutex::new(load(
+                args.external,
+                cache::cache_dir().join(badges::CACHE_FILE_NAME),
+            )),
+            identifiers: Mutex::new(load(
+                args.external,
+                cache::cache_dir().join(identifiers::CACHE_FILE_NAME),
+            )),
+            packages: Mutex::new(load(
+                args.packages,
+                cache::cache_dir().join(registry::CACHE_FILE_NAME),
+            )),
+            budget: RequestBudget::new(args.max_requests),
+            request_timeout: args.request_timeout,
+            checkers: Mutex::new(Vec::new()),
+            database,
+        })
+    }
+
+    fn checker(&self, settings: &Config) -> Result<ExternalChecker, Box<dyn std::error::Error>> {
+        let timeout = match (self.request_timeout, settings.external_timeout) {
+            (Some(timeout), _) => timeout,
+            (None, 0) => ExternalChecker::DEFAULT_TIMEOUT,
+            (None, seconds) => Duration::from_secs(seconds),
+        };
+        let key = (
+            AddressPolicy::from_config(settings)?,
+            timeout,
+            settings.external_headers.clone(),
+        );
+        let mut checkers = self.checkers.lock().unwrap();
+        if let Some((_, checker)) = checkers.iter().find(|(known, _)| *known == key) {
+            return Ok(checker.clone());
+        }
+        let checker =
+            ExternalChecker::with_address_policy(timeout, key.0.clone())?.with_headers(&key.2)?;
+        checkers.push((key, checker.clone()));
+        Ok(checker)
+    }
+
+    fn save(self, args: &CheckArgs) {
+        let now = SystemTime::now();
+        if args.external {
+            let path = cache::cache_dir().join(UrlHistory::FILE_NAME);
+            if let Err(e) = self.history.into_inner().unwrap().save(&path) {
+                log(
+                    Level::Warn,
+                    format!("Could not save external link history: {}", e),
+                );
+            }
+            let path = cache::cache_dir().join(badges::CACHE_FILE_NAME);
+            if let Err(e) = self.badges.into_inner().unwrap().save(&path, now) {
+                log(Level::Warn, format!("Could not save badge cache: {}", e));
+            }
+            let path = cache::cache_dir().join(identifiers::CACHE_FILE_NAME);
+            if let Err(e) = self.identifiers.into_inner().unwrap().save(&path, now) {
+                log(
+                    Level::Warn,
+                    format!("Could not save DOI and RFC cache: {}", e),
+                );
+            }
+        }
+        if args.packages {
+            let path = cache::cache_dir().join(registry::CACHE_FILE_NAME);
+            if let Err(e) = self.packages.into_inner().unwrap().save(&path, now) {
+                log(Level::Warn, format!("Could not save package cache: {}", e));
+            }
+        }
+    }
+}
+
+/// Exit code for a run cut short by `check --timeout` or `--max-requests`.
+const EXIT_INCOMPLETE: i32 = 2;
+
+#[derive(Default)]
+struct CheckOutcome {
+    root: PathBuf,
+    broken_links: Vec<BrokenLink>,
+    /// `broken_links` grouped by root cause, with --group-by-cause.
+    causes: Vec<CauseGroup>,
+    /// The --group-by field and `broken_links` grouped by it.
+    groups: Option<(String, Vec<FieldGroup>)>,
+    warnings: Vec<BrokenLink>,
+    ignored: Vec<BrokenLink>,
+    flaky: Vec<BrokenLink>,
+    assumed_links: Vec<MarkdownLink>,
+    comment_only: Vec<MarkdownLink>,
+    ignored_targets: usize,
+    /// Documents skipped as too large or binary, with why.
+    skipped_files: Vec<(PathBuf, String)>,
+    /// Files with more broken links than --max-findings-per-file.
+    truncated_files: usize,
+    /// The scan stopped at --max-findings.
+    aborted: bool,
+    /// What --timeout cut short, if anything.
+    incomplete: Option<String>,
+    /// Titles of the documents with broken links.
+    titles: HashMap<PathBuf, String>,
+    /// `owner` and `team` front matter fields of the documents with broken
+    /// links that have them.
+    owners: HashMap<PathBuf, Vec<(&'static str, String)>>,
+    /// The scanned documents, sorted.
+    documents: Vec<PathBuf>,
+    links: usize,
+    orphans: usize,
+}
+
+/// Set by `--log-format json`.
+static JSON_LOGS: AtomicBool = AtomicBool::new(false);
+
+/// Severity of a diagnostic about the run itself, as opposed to a finding
+/// in the documents.
+#[derive(Debug, Clone, Copy)]
+enum Level {
+    Warn,
+    Error,
+}
+
+/// Reports a diagnostic on stderr, as colored text or as one JSON object per
+/// line with a timestamp and level.
+fn log(level: Level, message: impl std::fmt::Display) {
+    if JSON_LOGS.load(Ordering::Relaxed) {
+        let line = serde_json::json!({
+            "timestamp": report::rfc3339(SystemTime::now()),
+            "level": match level {
+                Level::Warn => "warn",
+                Level::Error => "error",
+            },
+            "message": message.to_string(),
+        });
+        eprintln!("{}", line);
+        return;
+    }
+    match level {
+        Level::Warn => eprintln!("{} {}", "⚠".yellow().bold(), message),
+        Level::Error => eprintln!("{} {}", "Error:".red().bold(), message),
+    }
+}
+
+fn main() {
+    let mut cli = Cli::parse();
+    JSON_LOGS.store(cli.log_format == "json", Ordering::Relaxed);
+    if let Err(e) = cli.apply_env(env::vars()) {
+        log(Level::Error, e);
+        process::exit(1);
+    }
+
+    match cli.command {
+        Commands::Check(mut args) => {
+            if args.offline {
+                args.external = false;
+                args.packages = false;
+            }
+            if let Err(e) = check_links(&args, &cli.config) {
+                log(Level::Error, e);
+                process::exit(1);
+            }
+        }
+        Commands::Watch { path } => {
+            if let Err(e) = watch_links(&path, &cli.config) {
+                log(Level::Error, e);
+                process::exit(1);
+            }
+        }
+        Commands::Stats(args) => {
+            if let Err(e) = show_statistics(&args, &cli.config) {
+                log(Level::Error, e);
+                process::exit(1);
+            }
+        }
+        Commands::Report(args) => {
+            if let Err(e) = generate_report(&args, &cli.config) {
+                log(Level::Error, e);
+                process::exit(1);
+            }
+        }
+        Commands::Graph(args) => {
+            if let Err(e) = export_graph(&args, &cli.config) {
+                log(Level::Error, e);
+                process::exit(1);
+            }
+        }
+        Commands::Query(args) => {
+            if let Err(e) = run_query(&args) {
+                log(Level::Error, e);
+                process::exit(1);
+            }
+        }
+        Commands::LsFiles { path, excluded } => {
+            if let Err(e) = list_files(&path, excluded, &cli.config) {
+                log(Level::Error, e);
+                process::exit(1);
+            }
+        }
+        Commands::Orphans { path } => {
+            if let Err(e) = find_orphans(path, &cli.config) {
+                log(Level::Error, e);
+                process::exit(1);
+            }
+        }
+        Commands::ShortestPath { from, to, path } => {
+            match show_shortest_path(path, &cli.config, &from, &to) {
+                Ok(true) => {}
+                Ok(false) => process::exit(1),
+                Err(e) => {
+                    log(Level::Error, e);
+                    process::exit(1);
+                }
+            }
+        }
+        Commands::Anchors(args) => {
+            if let Err(e) = show_anchor_usage(&args, &cli.config) {
+                log(Level::Error, e);
+                process::exit(1);
+            }
+        }
+        Commands::Duplicates {
+            path,
+            threshold,
+            format,
+        } => {
+            if let Err(e) = find_duplicates(&path, threshold, &format, &cli.config) {
+                log(Level::Error, e);
+                process::exit(1);
+            }
+        }
+        Commands::Sitemap(args) => {
+            if let Err(e) = generate_sitemap(&args, &cli.config) {
+                log(Level::Error, e);
+                process::exit(1);
+            }
+        }
+        Commands::Crosscheck {
+            source,
+            base_url,
+            path,
+        } => {
+            if let Err(e) = cross_check_published(path, &cli.config, &source, base_url) {
+                log(Level::Error, e);
+                process::exit(1);
+            }
+        }
+        Commands::New(args) => {
+            if let Err(e) = create_document(&args) {
+                log(Level::Error, e);
+                process::exit(1);
+            }
+        }
+        Commands::Fix(args) => {
+            if let Err(e) = fix_links(&args, &cli.config) {
+                log(Level::Error, e);
+                process::exit(1);
+            }
+        }
+        Commands::Rewrite(args) => {
+            if let Err(e) = rewrite_links(&args, &cli.config) {
+                log(Level::Error, e);
+                process::exit(1);
+            }
+        }
+        Commands::Undo { dry_run } => {
+            if let Err(e) = undo_fixes(dry_run) {
+                log(Level::Error, e);
+                process::exit(1);
+            }
+        }
+        Commands::ImportConfig(args) => {
+            if let Err(e) = import_config(&args) {
+                log(Level::Error, e);
+                process::exit(1);
+            }
+        }
+        Commands::Doctor { path } => {
+            let config_path = cli
+                .config
+                .config
+                .clone()
+                .unwrap_or_else(|| path.join(Config::FILE_NAME));
+            run_doctor(&config_path, cli.config.profile.as_deref());
+        }
+        Commands::Resolve { file, target, path } => {
+            if let Err(e) = explain_resolution(path, &cli.config, &file, &target) {
+                log(Level::Error, e);
+                process::exit(1);
+            }
+        }
+        Commands::Dev(DevCommands::Fixture(args)) => {
+            if let Err(e) = generate_fixture(&args) {
+                log(Level::Error, e);
+                process::exit(1);
+            }
+        }
+    }
+}
+
+fn check_links(args: &CheckArgs, config: &ConfigArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let started = Instant::now();
+    let mut targets: Vec<CheckTarget> = args.path.iter().cloned().map(CheckTarget::Path).collect();
+    targets.extend(args.archive.iter().cloned().map(CheckTarget::Archive));
+    targets.extend(args.import.iter().cloned().map(CheckTarget::Import));
+    targets.extend(args.git.iter().cloned().map(CheckTarget::Git));
+    if targets.is_empty() {
+        targets.push(CheckTarget::Path(PathBuf::from(".")));
+    }
+
+    let format = match &args.format {
+        Some(format) => format.clone(),
+        None => {
+            let dir = args.path.first().map_or(Path::new("."), PathBuf::as_path);
+            match config.load(dir)?.format.as_str() {
+                "" | "text" => "text".to_string(),
+                format @ ("csv" | "sarif" | "junit") => format.to_string(),
+                other => {
+                    return Err(format!(
+                        "Unknown format `{}` (expected text, csv, sarif or junit)",
+                        other
+                    )
+                    .into())
+                }
+            }
+        }
+    };
+
+    let run = CheckRun::load(args)?;
+    let progress = args.progress.then(|| Arc::new(Progress::default()));
+    let cancel = match args.timeout {
+        Some(timeout) => CancellationToken::with_deadline(Instant::now() + timeout),
+        None => CancellationToken::new(),
+    };
+    let outcomes = run_concurrently(&targets, jobs(args), |target| {
+        if cancel.is_cancelled() {
+            return Ok(CheckOutcome {
+                root: PathBuf::from(target.label()),
+                incomplete: Some("timed out before this target was checked".to_string()),
+                ..Default::default()
+            });
+        }
+        run_check(target, args, config, &run, progress.as_ref(), &cancel).map_err(|e| e.to_string())
+    });
+    if progress.is_some() {
+        eprintln!();
+    }
+    run.save(args);
+
+    let code = report_outcomes(args, &format, &targets, &outcomes).unwrap_or_else(|e| {
+        log(Level::Error, e);
+        1
+    });
+    print_summary_line(args, &outcomes, started.elapsed());
+    if code != 0 {
+        process::exit(code);
+    }
+    Ok(())
+}
+
+/// One stable `doclink: files=… links=… broken=… orphans=… duration=…s`
+/// line totalling all targets, for scripts that only grep for it.
+fn print_summary_line(
+    args: &CheckArgs,
+    outcomes: &[Result<CheckOutcome, String>],
+    duration: Duration,
+) {
+    let total =
+        |count: fn(&CheckOutcome) -> usize| -> usize { outcomes.iter().flatten().map(count).sum() };
+    let line = format!(
+        "doclink: files={} links={} broken={} orphans={} duration={:.1}s",
+        total(|outcome| outcome.documents.len()),
+        total(|outcome| outcome.links),
+        total(|outcome| outcome.broken_links.len()),
+        total(|outcome| outcome.orphans),
+        duration.as_secs_f64()
+    );
+    match args.summary_line.as_str() {
+        "stdout" => println!("{}", line),
+        "stderr" => eprintln!("{}", line),
+        _ => {}
+    }
+}
+
+/// A target that was scanned in full and had no documents to check, which
+/// usually means a wrong path.
+fn is_empty(outcome: &CheckOutcome) -> bool {
+    outcome.documents.is_empty() && outcome.incomplete.is_none()
+}
+
+/// Reports an empty target as `--if-empty` says, and returns whether it
+/// fails the target.
+fn report_empty(outcome: &CheckOutcome, if_empty: &str) -> bool {
+    if !is_empty(outcome) {
+        return false;
+    }
+    let message = format!(
+        "No Markdown documents were analyzed in {}; is the path right?",
+        outcome.root.display()
+    );
+    match if_empty {
+        "error" => {
+            log(Level::Error, message);
+            true
+        }
+        "warn" => {
+            log(Level::Warn, message);
+            false
+        }
+        _ => false,
+    }
+}
+
+/// Prints the outcome of every target and returns the exit code.
+fn report_outcomes(
+    args: &CheckArgs,
+    format: &str,
+    targets: &[CheckTarget],
+    outcomes: &[Result<CheckOutcome, String>],
+) -> Result<i32, Box<dyn std::error::Error>> {
+    if args.github_summary {
+        write_github_summary(targets, outcomes, &args.if_empty);
+    }
+
+    if format != "text" {
+        if format == "csv" {
+            println!(
+                "{}",
+                csv_row(&["root", "file", "line", "text", "target", "reason"])
+            );
+        }
+        let mut sarif = SarifLog::new();
+        let mut junit = JunitReport::new();
+        let mut failed = false;
+        for (target, outcome) in targets.iter().zip(outcomes) {
+            // Report a target that could not be checked with the others
+            // rather than dropping the whole report.
+            let outcome = match outcome {
+                Ok(outcome) => outcome,
+                Err(e) => {
+                    failed = true;
+                    let label = target.label();
+                    match format {
+                        "sarif" => sarif.push_error(&format!("{}: {}", label, e)),
+                        "junit" => junit.push_error(&label, e),
+                        _ => println!("{}", csv_row(&[&label, "", "", "", "", e])),
+                    }
+                    log(Level::Error, format!("{}: {}", label, e));
+                    continue;
+                }
+            };
+            let mut broken_links: Vec<&BrokenLink> = outcome.broken_links.iter().collect();
+            broken_links.sort_by(|a, b| {
+                (&a.link.file_path, a.link.line_number)
+                    .cmp(&(&b.link.file_path, b.link.line_number))
+            });
+            for broken_link in broken_links {
+                failed = true;
+                if format == "sarif" {
+                    sarif.push(broken_link, SarifLevel::Error);
+                    continue;
+                }
+                if format == "junit" {
+                    continue;
+                }
+                let link = &broken_link.link;
+                let file_path = link
+                    .file_path
+                    .strip_prefix(&outcome.root)
+                    .unwrap_or(&link.file_path);
+                println!(
+                    "{}",
+                    csv_row(&[
+                        &outcome.root.display().to_string(),
+                        &file_path.display().to_string(),
+                        &link.line_number.to_string(),
+                        &link.text,
+                        &link.target,
+                        &broken_link.reason,
+                    ])
+                );
+            }
+            if format == "sarif" {
+                for warning in &outcome.warnings {
+                    sarif.push(warning, SarifLevel::Warning);
+                }
+            }
+            if format == "junit" {
+                junit.push_suite(
+                    &target.label(),
+                    &outcome.root,
+                    &outcome.documents,
+                    &outcome.broken_links,
+                    &outcome.warnings,
+                );
+            }
+            failed |= report_empty(outcome, &args.if_empty);
+        }
+        if format == "sarif" {
+            println!("{}", serde_json::to_string_pretty(&sarif)?);
+        }
+        if format == "junit" {
+            print!("{}", junit.to_xml());
+        }
+        let incomplete: Vec<&String> = outcomes
+            .iter()
+            .flatten()
+            .filter_map(|outcome| outcome.incomplete.as_ref())
+            .collect();
+        for reason in &incomplete {
+            log(Level::Warn, format!("Incomplete: {}", reason));
+        }
+        if !incomplete.is_empty() {
+            return Ok(EXIT_INCOMPLETE);
+        }
+        if failed {
+            return Ok(1);
+        }
+        return Ok(0);
+    }
+
+    if let [outcome] = outcomes {
+        let outcome = outcome.as_ref().map_err(|e| e.clone())?;
+        print_check_outcome(outcome, args.verbose);
+        let empty = report_empty(outcome, &args.if_empty);
+        if outcome.incomplete.is_some() {
+            return Ok(EXIT_INCOMPLETE);
+        }
+        if !outcome.broken_links.is_empty() || empty {
+            return Ok(1);
+        }
+        return Ok(0);
+    }
+
+    let mut failed_targets = 0;
+    for (target, outcome) in targets.iter().zip(outcomes) {
+        println!("{}", format!("== {} ==", target.label()).bold());
+        match outcome {
+            Ok(outcome) => {
+                print_check_outcome(outcome, args.verbose);
+                let empty = report_empty(outcome, &args.if_empty);
+                if !outcome.broken_links.is_empty() || empty {
+                    failed_targets += 1;
+                }
+            }
+            Err(e) => {
+                log(Level::Error, e);
+                failed_targets += 1;
+            }
+        }
+        println!();
+    }
+
+    let total_broken: usize = outcomes
+        .iter()
+        .flatten()
+        .map(|outcome| outcome.broken_links.len())
+        .sum();
+    let incomplete_targets = outcomes
+        .iter()
+        .flatten()
+        .filter(|outcome| outcome.incomplete.is_some())
+        .count();
+    if incomplete_targets > 0 {
+        println!(
+            "{} {} of {} targets are incomplete (--timeout or --max-requests)",
+            "⚠".yellow().bold(),
+            incomplete_targets,
+            targets.len()
+        );
+    }
+
+    if failed_targets == 0 && incomplete_targets == 0 {
+        println!(
+            "{} {} targets checked, no broken links found!",
+            "✓".green().bold(),
+            targets.len()
+        );
+        Ok(0)
+    } else if failed_targets == 0 {
+        Ok(EXIT_INCOMPLETE)
+    } else {
+        println!(
+            "{} {} of {} targets failed ({} broken links)",
+            "✗".red().bold(),
+            failed_targets,
+            targets.len(),
+            total_broken
+        );
+        Ok(if incomplete_targets > 0 {
+            EXIT_INCOMPLETE
+        } else {
+            1
+        })
+    }
 }
 
-fn main() {
-    let cli = Cli::parse();
+fn run_check(
+    target: &CheckTarget,
+    args: &CheckArgs,
+    config: &ConfigArgs,
+    run: &CheckRun,
+    progress: Option<&Arc<Progress>>,
+    cancel: &CancellationToken,
+) -> Result<CheckOutcome, Box<dyn std::error::Error>> {
+    let (root, settings) = match target {
+        CheckTarget::Path(path) => (path.clone(), config.load(path)?),
+        CheckTarget::Git(url) => {
+            let checkout = git::checkout_remote(
+                url,
+                args.git_ref.as_deref(),
+                &cache::cache_dir().join("repos"),
+            )?;
+            let settings = config.load(&checkout)?;
+            (checkout, settings)
+        }
+        CheckTarget::Archive(_) | CheckTarget::Import(_) => {
+            (PathBuf::new(), config.load(Path::new("."))?)
+        }
+    };
+    let mut analyzer = LinkAnalyzer::with_config(root.clone(), &settings)?;
+    match target {
+        CheckTarget::Archive(archive) => {
+            analyzer = analyzer.with_filesystem(MemoryFileSystem::from_archive(archive)?);
+        }
+        CheckTarget::Import(export) => {
+            analyzer = analyzer.with_filesystem(import::load(export)?);
+        }
+        _ => {}
+    }
+    if let Some(progress) = progress {
+        analyzer = analyzer.with_observer(Arc::clone(progress));
+    }
+    if let Err(e) = analyzer.analyze_directory_with(cancel) {
+        if e.is::<Cancelled>() {
+            return Ok(CheckOutcome {
+                root,
+                incomplete: Some("timed out while scanning documents".to_string()),
+                ..Default::default()
+            });
+        }
+        return Err(e);
+    }
+
+    let limits = FindingLimits {
+        per_file: args.max_findings_per_file,
+        total: args.max_findings,
+    };
+    let findings = match (&args.against, target) {
+        (Some(git_ref), CheckTarget::Path(path)) => {
+            limits.apply(analyzer.find_links_to_removed(&git::removed_paths(path, git_ref)?))
+        }
+        (Some(_), _) => return Err("--against can only be used with directory targets".into()),
+        (None, _) => analyzer.find_broken_links_limited(&limits, cancel),
+    };
+    let mut broken_links = findings.broken_links;
+    if args.compare_tags {
+        let CheckTarget::Path(path) = target else {
+            return Err("--compare-tags can only be used with directory targets".into());
+        };
+        broken_links.extend(
+            analyzer.find_unknown_compare_refs(
+                git::origin_repository(path).as_deref(),
+                &git::refs(path)?,
+            ),
+        );
+        sort_by_location(&mut broken_links);
+    }
+    if args.security {
+        let suspicious = analyzer.find_suspicious_links();
+        // A `javascript:` link is also a missing file; report it once.
+        broken_links.retain(|broken| !suspicious.iter().any(|s| s.link == broken.link));
+        broken_links.extend(suspicious);
+        sort_by_location(&mut broken_links);
+    }
+    if !settings.sequences.is_empty() {
+        broken_links.extend(analyzer.find_sequence_breaks());
+        sort_by_location(&mut broken_links);
+    }
+
+    let mut warnings = analyzer.find_archived_badges();
+    let mut ignored = Vec::new();
+    let mut flaky = Vec::new();
+    let mut incomplete = findings
+        .cancelled
+        .then(|| "timed out while resolving links".to_string());
+    if args.external && !findings.aborted && !findings.cancelled {
+        let links = analyzer.external_links();
+        let mut urls: Vec<&str> = links.iter().map(|link| link.target.as_str()).collect();
+        urls.sort();
+        urls.dedup();
+        // Already reported as archived, and usually slow to fail.
+        urls.retain(|url| badges::archived_service(url).is_none());
+
+        let checker = run.checker(&settings)?;
+        // URLs the deadline cut off are left out rather than reported as broken.
+        let verdicts: BTreeMap<&str, Verdict> = urls
+            .iter()
+            .copied()
+            .zip(run_concurrently(&urls, jobs(args), |url| {
+                if cancel.is_cancelled() {
+                    return None;
+                }
+                if badges::is_badge(url) {
+                    return check_badge(&checker, url, run, &settings, cancel);
+                }
+                if let Some(identifier) = Identifier::from_url(url) {
+                    return check_identifier(&checker, &identifier, run, cancel);
+                }
+                if !run.budget.try_take() {
+                    return None;
+                }
+                let response = checker.check_before(url, cancel.deadline());
+                (response.error.is_none() || !cancel.is_cancelled()).then(|| {
+                    response.verdict_accepting(
+                        settings.external_status_rules,
+                        &settings.accepted_status,
+                    )
+                })
+            }))
+            .filter_map(|(url, verdict)| Some((url, verdict?)))
+            .collect();
+        if verdicts.len() < urls.len() {
+            incomplete = Some(format!(
+                "{} with {} of {} external URLs unchecked",
+                cut_short_by(&run.budget, args),
+                urls.len() - verdicts.len(),
+                urls.len()
+            ));
+        }
+
+        let mut history = run.history.lock().unwrap();
+        for (url, verdict) in &verdicts {
+            history.record(url, !matches!(verdict, Verdict::Error(_)));
+        }
+
+        for link in &links {
+            let url = link.target.as_str();
+            let finding = |reason| BrokenLink::new(link.clone(), reason);
+            let Some(verdict) = verdicts.get(url) else {
+                continue;
+            };
+            match verdict.clone() {
+                Verdict::Ok => {}
+                Verdict::Warning(reason) => warnings.push(finding(reason)),
+                Verdict::Error(reason) if history.is_flaky(url, settings.flaky_threshold) => {
+                    let (failed, runs) = history.failures(url);
+                    flaky.push(finding(format!(
+                        "{}; failed {} of the last {} runs",
+                        reason, failed, runs
+                    )));
+                }
+                Verdict::Error(reason) => broken_links.push(finding(reason)),
+                Verdict::Ignored(reason) => ignored.push(finding(reason)),
+                _ => {}
+            }
+        }
+    }
+
+    if args.packages && !findings.aborted && !findings.cancelled {
+        let packages: Vec<((Registry, String), Vec<MarkdownLink>)> =
+            analyzer.package_links().into_iter().collect();
+        let checker = run.checker(&settings)?;
+        let verdicts = run_concurrently(&packages, jobs(args), |((registry, name), _)| {
+            if cancel.is_cancelled() {
+                return None;
+            }
+            check_package(&checker, *registry, name, run, cancel)
+        });
+        let unchecked = verdicts.iter().filter(|verdict| verdict.is_none()).count();
+        if unchecked > 0 {
+            incomplete = Some(format!(
+                "{} with {} of {} packages unchecked",
+                cut_short_by(&run.budget, args),
+                unchecked,
+                packages.len()
+            ));
+        }
+        for ((_, links), verdict) in packages.into_iter().zip(verdicts) {
+            let (list, reason) = match verdict {
+                Some(Verdict::Error(reason)) => (&mut broken_links, reason),
+                Some(Verdict::Warning(reason)) => (&mut warnings, reason),
+                _ => continue,
+            };
+            list.extend(
+                links
+                    .into_iter()
+                    .map(|link| BrokenLink::new(link, reason.clone())),
+            );
+        }
+        sort_by_location(&mut broken_links);
+    }
+
+    let mut truncated_files = findings.truncated_files;
+    let mut aborted = findings.aborted;
+    // Tags, security and sequence findings and URL and package checks were
+    // collected without the limits; cap everything together.
+    if !limits.is_unlimited() {
+        let limited = limits.apply(broken_links);
+        broken_links = limited.broken_links;
+        truncated_files.extend(limited.truncated_files);
+        truncated_files.sort();
+        truncated_files.dedup();
+        aborted |= limited.aborted;
+    }
+
+    if let Some(database) = &run.database {
+        let findings = Findings {
+            broken: &broken_links,
+            warnings: &warnings,
+            flaky: &flaky,
+            ignored: &ignored,
+        };
+        database
+            .lock()
+            .unwrap()
+            .record_run(&analyzer, findings)
+            .map_err(|e| format!("Could not write to --output-db: {}", e))?;
+    }
+
+    let titles = broken_links
+        .iter()
+        .filter_map(|broken| {
+            let path = &broken.link.file_path;
+            Some((path.clone(), analyzer.document_title(path)?.to_string()))
+        })
+        .collect();
+    let owners = broken_links
+        .iter()
+        .filter_map(|broken| {
+            let path = &broken.link.file_path;
+            let fields: Vec<(&'static str, String)> = [("Owner:", "owner"), ("Team:", "team")]
+                .into_iter()
+                .filter_map(|(label, field)| {
+                    Some((label, analyzer.front_matter_field(path, field)?.to_string()))
+                })
+                .collect();
+            (!fields.is_empty()).then(|| (path.clone(), fields))
+        })
+        .collect();
+    Ok(CheckOutcome {
+        root,
+        causes: if args.group_by_cause {
+            analyzer.group_by_cause(&broken_links)
+        } else {
+            Vec::new()
+        },
+        groups: args.group_by.as_ref().map(|field| {
+            (
+                field.clone(),
+                analyzer.group_by_front_matter(&broken_links, field),
+            )
+        }),
+        broken_links,
+        warnings,
+        ignored,
+        flaky,
+        assumed_links: analyzer.find_assumed_links(),
+        comment_only: if args.commented {
+            analyzer.find_comment_only_links()
+        } else {
+            Vec::new()
+        },
+        ignored_targets: analyzer.ignored_target_count(),
+        skipped_files: analyzer.skipped_files().to_vec(),
+        truncated_files: truncated_files.len(),
+        aborted,
+        incomplete,
+        titles,
+        owners,
+        documents: analyzer.document_paths(),
+        links: analyzer.link_count(),
+        orphans: analyzer.find_orphaned_documents().len(),
+    })
+}
+
+/// Why network checks were left undone: the request budget ran out, or else
+/// the deadline passed.
+fn cut_short_by(budget: &RequestBudget, args: &CheckArgs) -> String {
+    match args.max_requests {
+        Some(max) if budget.is_exceeded() => {
+            format!("request budget exceeded (--max-requests {})", max)
+        }
+        _ => "timed out".to_string(),
+    }
+}
+
+fn sort_by_location(findings: &mut [BrokenLink]) {
+    findings.sort_by(|a, b| {
+        (&a.link.file_path, a.link.line_number).cmp(&(&b.link.file_path, b.link.line_number))
+    });
+}
+
+/// Answers from `cache` while the last result for `key` is fresh, and
+/// otherwise runs `check` and caches what it found. Network failures are not
+/// cached, so they are retried next run; `None` means the deadline or the
+/// request budget cut the check off.
+fn check_cached(
+    key: &str,
+    cache: &Mutex<CheckCache>,
+    budget: &RequestBudget,
+    cancel: &CancellationToken,
+    check: impl FnOnce() -> (ExternalResponse, Verdict),
+) -> Option<Verdict> {
+    let now = SystemTime::now();
+    if let Some(cached) = cache.lock().unwrap().get(key, now) {
+        return Some(match &cached.error {
+            Some(error) => Verdict::Error(error.clone()),
+            None => Verdict::Ok,
+        });
+    }
+
+    if !budget.try_take() {
+        return None;
+    }
+    let (response, verdict) = check();
+    if response.error.is_some() && cancel.is_cancelled() {
+        return None;
+    }
+    match &verdict {
+        Verdict::Ok => cache.lock().unwrap().record(key, None, now),
+        Verdict::Error(reason) if response.status.is_some() => {
+            cache.lock().unwrap().record(key, Some(reason.clone()), now)
+        }
+        _ => {}
+    }
+    Some(verdict)
+}
+
+/// Checks a badge image by reading the error it draws, if any.
+fn check_badge(
+    checker: &ExternalChecker,
+    url: &str,
+    run: &CheckRun,
+    settings: &Config,
+    cancel: &CancellationToken,
+) -> Option<Verdict> {
+    check_cached(url, &run.badges, &run.budget, cancel, || {
+        let (response, error) = checker.check_badge_before(url, cancel.deadline());
+        let verdict = match error {
+            Some(error) => Verdict::Error(error),
+            None => response
+                .verdict_accepting(settings.external_status_rules, &settings.accepted_status),
+        };
+        (response, verdict)
+    })
+}
+
+/// Checks a DOI or RFC with its authority instead of its landing page.
+fn check_identifier(
+    checker: &ExternalChecker,
+    identifier: &Identifier,
+    run: &CheckRun,
+    cancel: &CancellationToken,
+) -> Option<Verdict> {
+    let url = identifier.api_url();
+    check_cached(&url, &run.identifiers, &run.budget, cancel, || {
+        let response = checker.check_before(&url, cancel.deadline());
+        let verdict = identifier.verdict(&response);
+        (response, verdict)
+    })
+}
+
+/// Looks `name` up in `registry`.
+fn check_package(
+    checker: &ExternalChecker,
+    registry: Registry,
+    name: &str,
+    run: &CheckRun,
+    cancel: &CancellationToken,
+) -> Option<Verdict> {
+    let url = registry.api_url(name);
+    check_cached(&url, &run.packages, &run.budget, cancel, || {
+        let response = checker.check_before(&url, cancel.deadline());
+        let verdict = registry.verdict(name, &response);
+        (response, verdict)
+    })
+}
+
+fn jobs(args: &CheckArgs) -> usize {
+    args.jobs.unwrap_or_else(|| {
+        std::thread::available_parallelism()
+            .map(|n| n.get())
+            .unwrap_or(1)
+    })
+}
+
+fn run_concurrently<T, R>(items: &[T], jobs: usize, f: impl Fn(&T) -> R + Sync) -> Vec<R>
+where
+    T: Sync,
+    R: Send,
+{
+    let next = AtomicUsize::new(0);
+    let results = Mutex::new((0..items.len()).map(|_| None).collect::<Vec<_>>());
+
+    std::thread::scope(|scope| {
+        for _ in 0..jobs.clamp(1, items.len().max(1)) {
+            scope.spawn(|| loop {
+                let index = next.fetch_add(1, Ordering::SeqCst);
+                let Some(item) = items.get(index) else {
+                    break;
+                };
+                let result = f(item);
+                results.lock().unwrap()[index] = Some(result);
+            });
+        }
+    });
+
+    results
+        .into_inner()
+        .unwrap()
+        .into_iter()
+        .map(|result| result.expect("every item is processed"))
+        .collect()
+}
+
+fn print_check_outcome(outcome: &CheckOutcome, verbose: bool) {
+    let path = &outcome.root;
+
+    if verbose && !outcome.assumed_links.is_empty() {
+        println!(
+            "{} {} links point at files assumed present:",
+            "ℹ".blue().bold(),
+            outcome.assumed_links.len()
+        );
+        for link in &outcome.assumed_links {
+            let file_path = link.file_path.strip_prefix(path).unwrap_or(&link.file_path);
+            println!(
+                "  {} {}:{} -> {}",
+                "Assumed:".blue().bold(),
+                file_path.display(),
+                link.line_number,
+                link.target
+            );
+        }
+        println!();
+    }
+
+    if outcome.ignored_targets > 0 {
+        println!(
+            "{} {} links skipped by ignore_targets",
+            "ℹ".blue().bold(),
+            outcome.ignored_targets
+        );
+        println!();
+    }
+
+    if !outcome.skipped_files.is_empty() {
+        println!(
+            "{} {} documents were skipped and not checked:",
+            "⚠".yellow().bold(),
+            outcome.skipped_files.len()
+        );
+        for (file_path, reason) in &outcome.skipped_files {
+            let file_path = file_path.strip_prefix(path).unwrap_or(file_path);
+            println!("  {} ({})", file_path.display(), reason);
+        }
+        println!();
+    }
+
+    if !outcome.comment_only.is_empty() {
+        println!(
+            "{} {} targets are only linked from HTML comments:",
+            "ℹ".blue().bold(),
+            outcome.comment_only.len()
+        );
+        for link in &outcome.comment_only {
+            let file_path = link.file_path.strip_prefix(path).unwrap_or(&link.file_path);
+            println!(
+                "  {}:{} {}",
+                file_path.display(),
+                link.line_number,
+                link.target
+            );
+        }
+        println!();
+    }
+
+    if verbose && !outcome.ignored.is_empty() {
+        println!(
+            "{} {} external links ignored:",
+            "ℹ".blue().bold(),
+            outcome.ignored.len()
+        );
+        for ignored in &outcome.ignored {
+            println!(
+                "  {} {} ({})",
+                "Ignored:".blue().bold(),
+                ignored.link.target,
+                ignored.reason
+            );
+        }
+        println!();
+    }
+
+    if !outcome.warnings.is_empty() {
+        println!(
+            "{} {} links need attention:",
+            "⚠".yellow().bold(),
+            outcome.warnings.len()
+        );
+        for warning in &outcome.warnings {
+            let file_path = warning
+                .link
+                .file_path
+                .strip_prefix(path)
+                .unwrap_or(&warning.link.file_path);
+            println!(
+                "  {}:{} {} ({})",
+                file_path.display(),
+                warning.link.line_number,
+                warning.link.target,
+                warning.reason
+            );
+        }
+        println!();
+    }
+
+    if !outcome.flaky.is_empty() {
+        println!(
+            "{} {} flaky external links (not counted as broken):",
+            "⚠".yellow().bold(),
+            outcome.flaky.len()
+        );
+        for flaky in &outcome.flaky {
+            let file_path = flaky
+                .link
+                .file_path
+                .strip_prefix(path)
+                .unwrap_or(&flaky.link.file_path);
+            println!(
+                "  {}:{} {} ({})",
+                file_path.display(),
+                flaky.link.line_number,
+                flaky.link.target,
+                flaky.reason
+            );
+        }
+        println!();
+    }
+
+    let broken_links = &outcome.broken_links;
+    if broken_links.is_empty() {
+        println!(
+            "{} No broken links found in {} documents!",
+            "✓".green().bold(),
+            outcome.documents.len()
+        );
+        return;
+    }
+
+    if let Some((field, _)) = &outcome.groups {
+        println!(
+            "{} Found {} broken links by {} ({} documents analyzed):",
+            "✗".red().bold(),
+            broken_links.len(),
+            field,
+            outcome.documents.len()
+        );
+    } else if outcome.causes.is_empty() {
+        println!(
+            "{} Found {} broken links ({} documents analyzed):",
+            "✗".red().bold(),
+            broken_links.len(),
+            outcome.documents.len()
+        );
+    } else {
+        println!(
+            "{} Found {} broken links from {} causes ({} documents analyzed):",
+            "✗".red().bold(),
+            broken_links.len(),
+            outcome.causes.len(),
+            outcome.documents.len()
+        );
+    }
+
+    if !outcome.causes.is_empty() {
+        print_causes(outcome, verbose);
+    }
+    if let Some((field, groups)) = &outcome.groups {
+        print_field_groups(outcome, field, groups, verbose);
+    }
+    let ungrouped = outcome.causes.is_empty() && outcome.groups.is_none();
+    for broken_link in broken_links.iter().filter(|_| ungrouped) {
+        let file_path = broken_link
+            .link
+            .file_path
+            .strip_prefix(path)
+            .unwrap_or(&broken_link.link.file_path);
+
+        let location = format!("{}:{}", file_path.display(), broken_link.link.line_number);
+        println!();
+        match outcome.titles.get(&broken_link.link.file_path) {
+            Some(title) => println!("  {} {} ({})", "File:".yellow().bold(), title, location),
+            None => println!("  {} {}", "File:".yellow().bold(), location),
+        }
+        println!("  {} {}", "Link:".cyan().bold(), broken_link.link.text);
+        println!(
+            "  {} {}",
+            "Target:".magenta().bold(),
+            broken_link.link.target
+        );
+        println!("  {} {}", "Reason:".red().bold(), broken_link.reason);
+        if let Some(suggestion) = &broken_link.suggestion {
+            println!(
+                "  {} {}?",
+                "Did you mean:".green().bold(),
+                suggestion
+                    .strip_prefix(path)
+                    .unwrap_or(suggestion)
+                    .display()
+            );
+        }
+        for (label, value) in outcome
+            .owners
+            .get(&broken_link.link.file_path)
+            .into_iter()
+            .flatten()
+        {
+            println!("  {} {}", label.green().bold(), value);
+        }
+
+        if verbose {
+            println!(
+                "  {} [{}]({})",
+                "Markdown:".blue().bold(),
+                broken_link.link.text,
+                broken_link.link.target
+            );
+        }
+    }
+
+    if outcome.truncated_files > 0 {
+        println!();
+        println!(
+            "{} Output truncated: {} files have more broken links than --max-findings-per-file",
+            "⚠".yellow().bold(),
+            outcome.truncated_files
+        );
+    }
+    if let Some(reason) = &outcome.incomplete {
+        println!();
+        println!(
+            "{} Incomplete: {}; results are partial",
+            "⚠".yellow().bold(),
+            reason
+        );
+    }
+    if outcome.aborted {
+        println!();
+        println!(
+            "{} Stopped after {} broken links (--max-findings); the remaining documents were not checked",
+            "⚠".yellow().bold(),
+            broken_links.len()
+        );
+    }
+}
+
+/// One entry per root cause with a few of its links, or all of them with
+/// `verbose`.
+fn print_causes(outcome: &CheckOutcome, verbose: bool) {
+    const EXAMPLES: usize = 3;
+    let relative = |path: &Path| {
+        path.strip_prefix(&outcome.root)
+            .unwrap_or(path)
+            .display()
+            .to_string()
+    };
+
+    for group in &outcome.causes {
+        let cause = match &group.cause {
+            RootCause::MissingDirectory(directory) => {
+                format!("Directory {}/ does not exist", relative(directory))
+            }
+            RootCause::MissingFile(file) => format!("File {} does not exist", relative(file)),
+            RootCause::MissingAnchor { document, anchor } => {
+                format!("{} has no heading #{}", relative(document), anchor)
+            }
+            RootCause::Other(reason) => reason.clone(),
+            _ => "Other".to_string(),
+        };
+        let count = group.broken_links.len();
+        println!();
+        println!(
+            "  {} {} {} {} broken {}",
+            "Cause:".red().bold(),
+            cause,
+            "→".dimmed(),
+            count,
+            if count == 1 { "link" } else { "links" }
+        );
+
+        let shown = if verbose { count } else { EXAMPLES };
+        for broken_link in group.broken_links.iter().take(shown) {
+            println!(
+                "    {}:{} {}",
+                relative(&broken_link.link.file_path),
+                broken_link.link.line_number,
+                broken_link.link.target.dimmed()
+            );
+        }
+        if count > shown {
+            println!("    {}", format!("… and {} more", count - shown).dimmed());
+        }
+    }
+}
+
+/// One entry per value of the --group-by front matter field with a few of
+/// its links, or all of them with `verbose`.
+fn print_field_groups(outcome: &CheckOutcome, field: &str, groups: &[FieldGroup], verbose: bool) {
+    const EXAMPLES: usize = 3;
+    for group in groups {
+        let count = group.broken_links.len();
+        let value = match &group.value {
+            Some(value) => format!("{} {}", format!("{}:", field).green().bold(), value),
+            None => format!("No {}", field).yellow().bold().to_string(),
+        };
+        println!();
+        println!(
+            "  {} {} {} broken {}",
+            value,
+            "→".dimmed(),
+            count,
+            if count == 1 { "link" } else { "links" }
+        );
+
+        let shown = if verbose { count } else { EXAMPLES };
+        for broken_link in group.broken_links.iter().take(shown) {
+            let link = &broken_link.link;
+            println!(
+                "    {}:{} {} ({})",
+                link.file_path
+                    .strip_prefix(&outcome.root)
+                    .unwrap_or(&link.file_path)
+                    .display(),
+                link.line_number,
+                link.target.dimmed(),
+                broken_link.reason
+            );
+        }
+        if count > shown {
+            println!("    {}", format!("… and {} more", count - shown).dimmed());
+        }
+    }
+}
+
+fn show_statistics(
+    args: &StatsArgs,
+    config: &ConfigArgs,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut analyzer = config.analyzer(&args.path)?;
+    analyzer.analyze_directory()?;
+
+    if args.by_dir {
+        let directories = analyzer.get_directory_statistics(args.depth);
+        match args.format.as_str() {
+            "json" => {
+                let report = Report {
+                    metadata: ReportMetadata::collect(&args.path, &config.load(&args.path)?),
+                    body: BTreeMap::from([("directories", &directories)]),
+                };
+                println!("{}", serde_json::to_string_pretty(&report)?);
+            }
+            "csv" => {
+                println!(
+                    "{}",
+                    csv_row(&[
+                        "directory",
+                        "documents",
+                        "total_links",
+                        "internal_links",
+                        "external_links",
+                        "broken_links",
+                        "orphaned_documents",
+                    ])
+                );
+                for (directory, stats) in &directories {
+                    println!(
+                        "{}",
+                        csv_row(&[
+                            directory,
+                            &stats.documents.to_string(),
+                            &stats.total_links.to_string(),
+                            &stats.internal_links.to_string(),
+                            &stats.external_links.to_string(),
+                            &stats.broken_links.to_string(),
+                            &stats.orphaned_documents.to_string(),
+                        ])
+                    );
+                }
+            }
+            _ => print_directory_statistics(&directories),
+        }
+        return Ok(());
+    }
+
+    let mut stats = analyzer.get_statistics();
+    let graph = analyzer.link_graph();
+    if args.detailed {
+        stats.graph = Some(graph.metrics());
+    }
+
+    match args.format.as_str() {
+        "json" => {
+            let report = Report {
+                metadata: ReportMetadata::collect(&args.path, &config.load(&args.path)?),
+                body: &stats,
+            };
+            println!("{}", serde_json::to_string_pretty(&report)?);
+        }
+        "csv" => {
+            let mut header = vec![
+                "document",
+                "total_links",
+                "internal_links",
+                "external_links",
+                "broken_links",
+            ];
+            if args.detailed {
+                header.extend(["in_degree", "out_degree"]);
+            }
+            println!("{}", csv_row(&header));
+            let in_degrees = graph.in_degrees();
+            let mut documents: Vec<_> = stats.document_stats.iter().collect();
+            documents.sort_by(|a, b| a.0.cmp(b.0));
+            for (doc_path, doc_stats) in documents {
+                let mut row = vec![
+                    doc_path
+                        .strip_prefix(&args.path)
+                        .unwrap_or(doc_path)
+                        .display()
+                        .to_string(),
+                    doc_stats.total_links.to_string(),
+                    doc_stats.internal_links.to_string(),
+                    doc_stats.external_links.to_string(),
+                    doc_stats.broken_links.to_string(),
+                ];
+                if args.detailed {
+                    row.push(in_degrees.get(doc_path).copied().unwrap_or(0).to_string());
+                    row.push(graph.successors(doc_path).count().to_string());
+                }
+                let row: Vec<&str> = row.iter().map(String::as_str).collect();
+                println!("{}", csv_row(&row));
+            }
+        }
+        _ => {
+            print_text_statistics(&stats);
+        }
+    }
+
+    if let Some(min_score) = args.min_score {
+        if stats.health.score < min_score {
+            return Err(format!(
+                "Health score {:.1} is below the minimum of {:.1}",
+                stats.health.score, min_score
+            )
+            .into());
+        }
+    }
+
+    Ok(())
+}
+
+/// Appends the job summary GitHub Actions shows on the run page. Outside
+/// Actions there is nowhere to write it, which is only worth a warning.
+fn write_github_summary(
+    targets: &[CheckTarget],
+    outcomes: &[Result<CheckOutcome, String>],
+    if_empty: &str,
+) {
+    let Some(path) = env::var_os("GITHUB_STEP_SUMMARY") else {
+        log(
+            Level::Warn,
+            "--github-summary: GITHUB_STEP_SUMMARY is not set, skipping the job summary",
+        );
+        return;
+    };
+    let summary = github_summary(targets, outcomes, if_empty);
+    let written = fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .and_then(|mut file| file.write_all(summary.as_bytes()));
+    if let Err(e) = written {
+        log(
+            Level::Warn,
+            format!(
+                "Could not write the job summary to {}: {}",
+                PathBuf::from(path).display(),
+                e
+            ),
+        );
+    }
+}
+
+fn github_summary(
+    targets: &[CheckTarget],
+    outcomes: &[Result<CheckOutcome, String>],
+    if_empty: &str,
+) -> String {
+    const MAX_OFFENDERS: usize = 10;
+    const MAX_ROWS: usize = 50;
+    let cell = |text: &str| text.replace('|', "\\|").replace('\n', " ");
+
+    let mut summary = String::from("## Link check\n\n");
+    summary.push_str("| Target | Documents | Broken | Warnings | Ignored | Flaky | Status |\n");
+    summary.push_str("|---|---:|---:|---:|---:|---:|---|\n");
+    for (target, outcome) in targets.iter().zip(outcomes) {
+        let row = match outcome {
+            Ok(outcome) => {
+                let status = if let Some(reason) = &outcome.incomplete {
+                    format!("⚠️ incomplete: {}", reason)
+                } else if is_empty(outcome) && if_empty == "error" {
+                    "❌ no documents".to_string()
+                } else if is_empty(outcome) && if_empty == "warn" {
+                    "⚠️ no documents".to_string()
+                } else if outcome.broken_links.is_empty() {
+                    "✅ passed".to_string()
+                } else {
+                    "❌ failed".to_string()
+                };
+                format!(
+                    "| {} | {} | {} | {} | {} | {} | {} |",
+                    cell(&target.label()),
+                    outcome.documents.len(),
+                    outcome.broken_links.len(),
+                    outcome.warnings.len(),
+                    outcome.ignored.len(),
+                    outcome.flaky.len(),
+                    cell(&status)
+                )
+            }
+            Err(e) => format!(
+                "| {} | | | | | | ❌ error: {} |",
+                cell(&target.label()),
+                cell(e)
+            ),
+        };
+        summary.push_str(&row);
+        summary.push('\n');
+    }
+
+    // (file relative to its target, line, target, reason)
+    let mut rows: Vec<(String, usize, &str, &str)> = Vec::new();
+    for outcome in outcomes.iter().flatten() {
+        for broken_link in &outcome.broken_links {
+            let link = &broken_link.link;
+            let file_path = link
+                .file_path
+                .strip_prefix(&outcome.root)
+                .unwrap_or(&link.file_path);
+            rows.push((
+                file_path.display().to_string(),
+                link.line_number,
+                &link.target,
+                &broken_link.reason,
+            ));
+        }
+    }
+    if rows.is_empty() {
+        return summary + "\n";
+    }
+    rows.sort();
+
+    let mut per_file: BTreeMap<&str, usize> = BTreeMap::new();
+    for (file, ..) in &rows {
+        *per_file.entry(file).or_default() += 1;
+    }
+    let mut offenders: Vec<(&str, usize)> = per_file.into_iter().collect();
+    offenders.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+
+    summary.push_str("\n### Top offenders\n\n| File | Broken links |\n|---|---:|\n");
+    for (file, count) in offenders.iter().take(MAX_OFFENDERS) {
+        summary.push_str(&format!("| {} | {} |\n", cell(file), count));
+    }
+
+    summary.push_str(&format!(
+        "\n<details><summary>{} broken links</summary>\n\n| File | Line | Target | Reason |\n|---|---:|---|---|\n",
+        rows.len()
+    ));
+    for (file, line, target, reason) in rows.iter().take(MAX_ROWS) {
+        summary.push_str(&format!(
+            "| {} | {} | `{}` | {} |\n",
+            cell(file),
+            line,
+            cell(target),
+            cell(reason)
+        ));
+    }
+    if rows.len() > MAX_ROWS {
+        summary.push_str(&format!("\n…and {} more\n", rows.len() - MAX_ROWS));
+    }
+    summary.push_str("\n</details>\n\n");
+    summary
+}
+
+fn csv_row(fields: &[&str]) -> String {
+    fields
+        .iter()
+        .map(|field| {
+            if field.contains([',', '"', '\n', '\r']) {
+                format!("\"{}\"", field.replace('"', "\"\""))
+            } else {
+                field.to_string()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+fn generate_report(
+    args: &ReportArgs,
+    config: &ConfigArgs,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut analyzer = config.analyzer(&args.path)?;
+    analyzer.analyze_directory()?;
+    let directories = analyzer.get_directory_statistics(args.depth);
+
+    let report = match args.format.as_str() {
+        "heatmap-html" => report::heatmap_html(&directories),
+        _ => heatmap_text(&directories, args.output.is_none()),
+    };
+    match &args.output {
+        Some(output) => {
+            fs::write(output, report)?;
+            println!(
+                "{} Wrote the heatmap of {} directories to {}",
+                "✓".green().bold(),
+                directories.len(),
+                output.display()
+            );
+        }
+        None => print!("{}", report),
+    }
+    Ok(())
+}
+
+fn export_graph(args: &GraphArgs, config: &ConfigArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let mut analyzer = config.analyzer(&args.path)?;
+    analyzer.analyze_directory()?;
+    let graph = if args.anchors {
+        analyzer.export_section_graph()
+    } else {
+        analyzer.export_graph()
+    };
+
+    let output = match args.format.as_str() {
+        "graphml" => graph.to_graphml(),
+        "html" => graph.to_html(),
+        _ => serde_json::to_string_pretty(&graph)? + "\n",
+    };
+    match &args.output {
+        Some(path) => {
+            fs::write(path, output)?;
+            println!(
+                "{} Wrote {} documents and {} links to {}",
+                "✓".green().bold(),
+                graph.nodes.len(),
+                graph.links.len(),
+                path.display()
+            );
+        }
+        None => print!("{}", output),
+    }
+    Ok(())
+}
+
+/// One row per directory, worst first, with a shaded cell for its
+/// broken-link density. Colors are left out when writing to a file.
+fn heatmap_text(directories: &BTreeMap<String, DirectoryStats>, color: bool) -> String {
+    let width = directories
+        .keys()
+        .map(|d| d.chars().count())
+        .max()
+        .unwrap_or(0);
+    let mut text = String::from("Broken-link heatmap\n\n");
+
+    for (directory, stats) in report::heatmap_rows(directories) {
+        let density = stats.broken_density();
+        let level = if density == 0.0 {
+            0
+        } else {
+            1 + (density / report::HEATMAP_SATURATION * 3.0).min(3.0) as usize
+        };
+        let cell = ["░░░░", "▒▒▒▒", "▓▓▓▓", "████", "████"][level];
+        let cell = match (color, level) {
+            (false, _) => cell.normal(),
+            (true, 0) => cell.green(),
+            (true, 1) => cell.yellow(),
+            (true, 2) => cell.bright_red(),
+            (true, _) => cell.red(),
+        };
+        text.push_str(&format!(
+            "  {} {:>6.1}%  {:<width$}  {} of {} links broken, {} documents\n",
+            cell,
+            density * 100.0,
+            directory,
+            stats.broken_links,
+            stats.internal_links,
+            stats.documents,
+            width = width
+        ));
+    }
+
+    text.push_str(&format!(
+        "\n  ░ none  ▒ under {:.1}%  ▓ under {:.1}%  █ {:.1}% or more\n",
+        report::HEATMAP_SATURATION * 100.0 / 3.0,
+        report::HEATMAP_SATURATION * 200.0 / 3.0,
+        report::HEATMAP_SATURATION * 200.0 / 3.0
+    ));
+    text
+}
+
+fn print_directory_statistics(directories: &BTreeMap<String, DirectoryStats>) {
+    println!("{}", "Directory Link Statistics".bold().underline());
+    println!();
+
+    for (directory, stats) in directories {
+        let broken = format!("{} broken", stats.broken_links);
+        let orphaned = format!("{} orphaned", stats.orphaned_documents);
+        println!(
+            "  {} {} documents, {} links ({} internal, {} external), {}, {}",
+            format!("{}:", directory).magenta().bold(),
+            stats.documents,
+            stats.total_links,
+            stats.internal_links,
+            stats.external_links,
+            if stats.broken_links > 0 {
+                broken.red()
+            } else {
+                broken.green()
+            },
+            if stats.orphaned_documents > 0 {
+                orphaned.yellow()
+            } else {
+                orphaned.green()
+            }
+        );
+    }
+}
+
+fn print_graph_metrics(graph: &GraphMetrics) {
+    let distribution = |counts: &BTreeMap<usize, usize>| {
+        counts
+            .iter()
+            .map(|(degree, documents)| format!("{}: {}", degree, documents))
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    println!();
+    println!("{}", "Link Graph:".bold().underline());
+    println!(
+        "{} {} (largest has {} of {} documents, {} isolated)",
+        "Components:".cyan().bold(),
+        graph.components,
+        graph.largest_component,
+        graph.documents,
+        graph.isolated_documents
+    );
+    println!(
+        "{} {} documents ({} components in all)",
+        "Largest Strongly Connected Component:".cyan().bold(),
+        graph.largest_strongly_connected_component,
+        graph.strongly_connected_components
+    );
+    println!(
+        "{} {:.2} links in and out per document (max {} in, {} out)",
+        "Average Degree:".cyan().bold(),
+        graph.average_degree,
+        graph.max_in_degree,
+        graph.max_out_degree
+    );
+    println!(
+        "{} {}",
+        "In-Degree Distribution:".cyan().bold(),
+        distribution(&graph.in_degree_distribution)
+    );
+    println!(
+        "{} {}",
+        "Out-Degree Distribution:".cyan().bold(),
+        distribution(&graph.out_degree_distribution)
+    );
+}
+
+fn print_text_statistics(stats: &LinkStatistics) {
+    println!("{}", "Document Link Statistics".bold().underline());
+    println!();
+
+    println!(
+        "{} {}",
+        "Total Documents:".cyan().bold(),
+        stats.total_documents
+    );
+    println!("{} {}", "Total Links:".cyan().bold(), stats.total_links);
+    println!(
+        "{} {} ({}%)",
+        "Internal Links:".green().bold(),
+        stats.internal_links,
+        (stats.internal_links * 100)
+            .checked_div(stats.total_links)
+            .unwrap_or(0)
+    );
+    println!(
+        "{} {} ({}%)",
+        "External Links:".blue().bold(),
+        stats.external_links,
+        (stats.external_links * 100)
+            .checked_div(stats.total_links)
+            .unwrap_or(0)
+    );
+
+    if stats.broken_links > 0 {
+        println!("{} {}", "Broken Links:".red().bold(), stats.broken_links);
+    } else {
+        println!("{} {}", "Broken Links:".green().bold(), stats.broken_links);
+    }
+
+    if stats.orphaned_documents > 0 {
+        println!(
+            "{} {}",
+            "Orphaned Documents:".yellow().bold(),
+            stats.orphaned_documents
+        );
+    } else {
+        println!(
+            "{} {}",
+            "Orphaned Documents:".green().bold(),
+            stats.orphaned_documents
+        );
+    }
+
+    let health = &stats.health;
+    let score = format!("{:.1}/100", health.score);
+    let score = if health.score >= 90.0 {
+        score.green()
+    } else if health.score >= 75.0 {
+        score.yellow()
+    } else {
+        score.red()
+    };
+    println!("{} {}", "Health Score:".cyan().bold(), score.bold());
+    println!(
+        "  {:.1} links per document, {:.1}% broken, {:.1}% orphaned, {:.1}% stale anchors, average click depth {:.1}",
+        health.link_density,
+        health.broken_ratio * 100.0,
+        health.orphan_ratio * 100.0,
+        health.stale_anchor_ratio * 100.0,
+        health.average_click_depth
+    );
+
+    if let Some(graph) = &stats.graph {
+        print_graph_metrics(graph);
+    }
+
+    if !stats.document_stats.is_empty() {
+        println!();
+        println!("{}", "Per-Document Statistics:".bold().underline());
+
+        for (doc_path, doc_stats) in &stats.document_stats {
+            let file_name = doc_path
+                .file_name()
+                .and_then(|n| n.to_str())
+                .unwrap_or("unknown");
+
+            println!(
+                "  {} {} links ({} internal, {} external)",
+                file_name.magenta().bold(),
+                doc_stats.total_links,
+                doc_stats.internal_links,
+                doc_stats.external_links
+            );
+            if !doc_stats.aliases.is_empty() {
+                let aliases: Vec<String> = doc_stats
+                    .aliases
+                    .iter()
+                    .map(|alias| alias.display().to_string())
+                    .collect();
+                println!("    {} {}", "also at".dimmed(), aliases.join(", "));
+            }
+        }
+    }
+}
+
+fn run_query(args: &QueryArgs) -> Result<(), Box<dyn std::error::Error>> {
+    if !args.db.is_file() {
+        return Err(format!("No database at {}", args.db.display()).into());
+    }
+    let database = Database::open(&args.db)?;
+    let run_id = match args.run {
+        Some(run_id) => run_id,
+        None => database
+            .latest_run()?
+            .ok_or_else(|| format!("{} has no recorded runs", args.db.display()))?,
+    };
+    let result = database.query(&args.query, run_id, SystemTime::now())?;
+    let text = |value: &serde_json::Value| match value {
+        serde_json::Value::String(text) => text.clone(),
+        serde_json::Value::Null => String::new(),
+        value => value.to_string(),
+    };
+
+    match args.format.as_str() {
+        "json" => {
+            let records: Vec<serde_json::Map<String, serde_json::Value>> = result
+                .rows
+                .iter()
+                .map(|row| {
+                    result
+                        .columns
+                        .iter()
+                        .cloned()
+                        .zip(row.iter().cloned())
+                        .collect()
+                })
+                .collect();
+            println!("{}", serde_json::to_string_pretty(&records)?);
+        }
+        "csv" => {
+            let columns: Vec<&str> = result.columns.iter().map(String::as_str).collect();
+            println!("{}", csv_row(&columns));
+            for row in &result.rows {
+                let fields: Vec<String> = row.iter().map(text).collect();
+                let fields: Vec<&str> = fields.iter().map(String::as_str).collect();
+                println!("{}", csv_row(&fields));
+            }
+        }
+        _ if result.rows.is_empty() => {
+            println!(
+                "{} No {} match in run {}",
+                "ℹ".blue().bold(),
+                args.query.subject(),
+                run_id
+            );
+        }
+        _ => {
+            let rows: Vec<Vec<String>> = result
+                .rows
+                .iter()
+                .map(|row| row.iter().map(text).collect())
+                .collect();
+            let widths: Vec<usize> = (0..result.columns.len())
+                .map(|i| {
+                    rows.iter()
+                        .map(|row| row[i].chars().count())
+                        .chain([result.columns[i].len()])
+                        .max()
+                        .unwrap_or(0)
+                })
+                .collect();
+            let line = |fields: &[String]| {
+                fields
+                    .iter()
+                    .zip(&widths)
+                    .map(|(field, width)| format!("{:<width$}", field, width = width))
+                    .collect::<Vec<_>>()
+                    .join("  ")
+                    .trim_end()
+                    .to_string()
+            };
+            println!(
+                "{} {} {} in run {}:",
+                "ℹ".blue().bold(),
+                rows.len(),
+                args.query.subject(),
+                run_id
+            );
+            println!("  {}", line(&result.columns).bold());
+            for row in &rows {
+                println!("  {}", line(row));
+            }
+        }
+    }
+    Ok(())
+}
+
+fn list_files(
+    path: &Path,
+    excluded: bool,
+    config: &ConfigArgs,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut analyzer = config.analyzer(path)?;
+    analyzer.analyze_directory()?;
+
+    let relative = |file: &Path| {
+        file.strip_prefix(path)
+            .unwrap_or(file)
+            .display()
+            .to_string()
+    };
+    if excluded {
+        for (file, reason) in analyzer.excluded_files() {
+            println!("{} ({})", relative(&file), reason);
+        }
+    } else {
+        for file in analyzer.document_paths() {
+            println!("{}", relative(&file));
+        }
+    }
+    Ok(())
+}
+
+/// Checks `path`, then waits for files to change and re-checks only the
+/// changed documents and the documents linking to changed files.
+fn watch_links(path: &Path, config: &ConfigArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let mut analyzer = config.analyzer(path)?;
+    analyzer.analyze_directory()?;
+
+    let mut findings: BTreeMap<PathBuf, Vec<BrokenLink>> = BTreeMap::new();
+    for broken_link in analyzer.find_broken_links() {
+        findings
+            .entry(broken_link.link.file_path.clone())
+            .or_default()
+            .push(broken_link);
+    }
+    for broken_links in findings.values_mut() {
+        broken_links.sort_by_key(|broken_link| broken_link.link.line_number);
+        print_watched_findings(path, broken_links);
+    }
+    print_watch_total(&analyzer, &findings);
+
+    let (sender, events) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(sender)?;
+    watcher.watch(path, RecursiveMode::Recursive)?;
+    let base = path.canonicalize()?;
+    println!(
+        "{} Watching {} for changes (Ctrl-C to stop)",
+        "ℹ".blue().bold(),
+        path.display()
+    );
+
+    loop {
+        let mut batch = vec![events.recv()?];
+        // Saving a file is often several events; check them together.
+        while let Ok(event) = events.recv_timeout(Duration::from_millis(100)) {
+            batch.push(event);
+        }
+        let mut changed = Vec::new();
+        for event in batch {
+            let event = match event {
+                Ok(event) => event,
+                Err(e) => {
+                    log(Level::Error, format!("Watch error: {}", e));
+                    continue;
+                }
+            };
+            if event.kind.is_access() {
+                continue;
+            }
+            for changed_path in event.paths {
+                changed.push(match changed_path.strip_prefix(&base) {
+                    Ok(relative) => path.join(relative),
+                    Err(_) => changed_path,
+                });
+            }
+        }
+        changed.sort();
+        changed.dedup();
+
+        // A file caught mid-save may be unreadable for a moment; keep watching
+        // and pick it up on its next event.
+        let affected = match analyzer.reanalyze(&changed) {
+            Ok(affected) => affected,
+            Err(e) => {
+                log(Level::Error, format!("Could not re-check changes: {}", e));
+                continue;
+            }
+        };
+        for removed in changed.iter().filter(|file| !affected.contains(file)) {
+            findings.remove(removed);
+        }
+        if affected.is_empty() {
+            continue;
+        }
+        println!();
+        println!(
+            "{} Re-checked {} documents:",
+            "↻".blue().bold(),
+            affected.len()
+        );
+        for document in affected {
+            let mut broken_links = analyzer.find_broken_links_in(&document);
+            broken_links.sort_by_key(|broken_link| broken_link.link.line_number);
+            if broken_links.is_empty() {
+                let name = document.strip_prefix(path).unwrap_or(&document);
+                println!("  {} {}", "✓".green().bold(), name.display());
+                findings.remove(&document);
+            } else {
+                print_watched_findings(path, &broken_links);
+                findings.insert(document, broken_links);
+            }
+        }
+        print_watch_total(&analyzer, &findings);
+    }
+}
+
+/// One line per broken link, as `file:line target (reason)`.
+fn print_watched_findings(root: &Path, broken_links: &[BrokenLink]) {
+    for broken_link in broken_links {
+        let link = &broken_link.link;
+        let file_path = link.file_path.strip_prefix(root).unwrap_or(&link.file_path);
+        println!(
+            "  {} {}:{} {} ({})",
+            "✗".red().bold(),
+            file_path.display(),
+            link.line_number,
+            link.target,
+            broken_link.reason
+        );
+    }
+}
+
+fn print_watch_total(analyzer: &LinkAnalyzer, findings: &BTreeMap<PathBuf, Vec<BrokenLink>>) {
+    let documents = analyzer.document_paths().len();
+    let broken = findings.values().map(Vec::len).sum::<usize>();
+    if broken == 0 {
+        println!(
+            "{} No broken links found in {} documents!",
+            "✓".green().bold(),
+            documents
+        );
+    } else {
+        println!(
+            "{} {} broken links ({} documents analyzed)",
+            "✗".red().bold(),
+            broken,
+            documents
+        );
+    }
+}
+
+fn find_orphans(path: PathBuf, config: &ConfigArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let mut analyzer = config.analyzer(&path)?;
+    analyzer.analyze_directory()?;
+
+    let orphaned_docs = analyzer.find_orphaned_documents();
+
+    if orphaned_docs.is_empty() {
+        println!("{} No orphaned documents found!", "✓".green().bold());
+        return Ok(());
+    }
+
+    println!(
+        "{} Found {} orphaned documents:",
+        "⚠".yellow().bold(),
+        orphaned_docs.len()
+    );
+
+    for orphaned_doc in orphaned_docs {
+        println!("  {}", analyzer.display_name(&orphaned_doc).red());
+    }
+
+    Ok(())
+}
+
+/// Prints the shortest chain of links from `from` to `to`, and returns
+/// whether there is one.
+fn show_shortest_path(
+    path: PathBuf,
+    config: &ConfigArgs,
+    from: &Path,
+    to: &Path,
+) -> Result<bool, Box<dyn std::error::Error>> {
+    let mut analyzer = config.analyzer(&path)?;
+    analyzer.analyze_directory()?;
+
+    let find = |document: &Path| {
+        analyzer
+            .find_document(document)
+            .or_else(|| analyzer.find_document(&path.join(document)))
+            .ok_or_else(|| {
+                format!(
+                    "{} is not a document in {}",
+                    document.display(),
+                    path.display()
+                )
+            })
+    };
+    let (from, to) = (find(from)?, find(to)?);
+    let relative = |document: &Path| {
+        document
+            .strip_prefix(&path)
+            .unwrap_or(document)
+            .display()
+            .to_string()
+    };
+
+    let Some(links) = analyzer.shortest_path(&from, &to) else {
+        println!(
+            "{} No chain of links leads from {} to {}",
+            "✗".red().bold(),
+            relative(&from),
+            relative(&to)
+        );
+        let mut linked_from: Vec<String> = analyzer
+            .inbound_links(&to)
+            .iter()
+            .map(|inbound| relative(&inbound.source))
+            .collect();
+        linked_from.sort();
+        linked_from.dedup();
+        if linked_from.is_empty() {
+            println!("  No document links to {}", relative(&to));
+        } else {
+            println!(
+                "  {} is only linked from: {}",
+                relative(&to),
+                linked_from.join(", ")
+            );
+        }
+        return Ok(false);
+    };
+
+    println!(
+        "{} {} {} from {} to {}:",
+        "✓".green().bold(),
+        links.len(),
+        if links.len() == 1 { "link" } else { "links" },
+        relative(&from),
+        relative(&to)
+    );
+    for link in &links {
+        println!(
+            "  {}:{} [{}]({})",
+            relative(&link.file_path),
+            link.line_number,
+            link.text,
+            link.target.cyan()
+        );
+    }
+    println!("  {}", relative(&to).green());
+    Ok(true)
+}
+
+fn show_anchor_usage(
+    args: &AnchorsArgs,
+    config: &ConfigArgs,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut analyzer = config.analyzer(&args.path)?;
+    analyzer.analyze_directory()?;
+
+    let usage = analyzer.anchor_usage();
+    // Document titles are linked by the page, not by their anchor.
+    let (linked, unused): (Vec<_>, Vec<_>) = usage
+        .iter()
+        .partition(|anchor| !anchor.references.is_empty());
+    let unused: Vec<_> = unused
+        .into_iter()
+        .filter(|anchor| anchor.level > 1)
+        .collect();
+
+    if args.format == "json" {
+        let mut body = BTreeMap::new();
+        if !args.unused {
+            body.insert(
+                "most_linked",
+                linked.iter().take(args.top).copied().collect(),
+            );
+        }
+        body.insert("unused", unused);
+        let report = Report {
+            metadata: ReportMetadata::collect(&args.path, &config.load(&args.path)?),
+            body,
+        };
+        println!("{}", serde_json::to_string_pretty(&report)?);
+        return Ok(());
+    }
+
+    let location = |anchor: &AnchorUsage| {
+        let document = anchor
+            .document
+            .strip_prefix(&args.path)
+            .unwrap_or(&anchor.document);
+        format!("{}#{}", document.display(), anchor.anchor)
+    };
+
+    if !args.unused {
+        if linked.is_empty() {
+            println!("{} No links to headings found", "ℹ".blue().bold());
+        } else {
+            println!("{}", "Most Linked Anchors".bold());
+            for anchor in linked.iter().take(args.top) {
+                println!(
+                    "  {:>4}  {}  {}",
+                    anchor.references.len().to_string().cyan(),
+                    location(anchor),
+                    anchor.heading.dimmed()
+                );
+            }
+        }
+        println!();
+    }
+
+    if unused.is_empty() {
+        println!("{} Every section heading is linked to", "✓".green().bold());
+    } else {
+        println!(
+            "{} {} section headings are never linked to from these documents:",
+            "ℹ".blue().bold(),
+            unused.len()
+        );
+        for anchor in unused {
+            println!(
+                "  {}  {}",
+                location(anchor).yellow(),
+                format!("line {}", anchor.line_number).dimmed()
+            );
+        }
+    }
+
+    Ok(())
+}
+
+fn find_duplicates(
+    path: &Path,
+    threshold: f64,
+    format: &str,
+    config: &ConfigArgs,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut analyzer = config.analyzer(path)?;
+    analyzer.analyze_directory()?;
+
+    let pairs = analyzer.find_duplicate_documents(threshold);
+
+    if format == "json" {
+        let report = Report {
+            metadata: ReportMetadata::collect(path, &config.load(path)?),
+            body: BTreeMap::from([("duplicates", &pairs)]),
+        };
+        println!("{}", serde_json::to_string_pretty(&report)?);
+        return Ok(());
+    }
+
+    if pairs.is_empty() {
+        println!("{} No near-duplicate documents found!", "✓".green().bold());
+        return Ok(());
+    }
+
+    println!(
+        "{} Found {} pairs of near-duplicate documents:",
+        "⚠".yellow().bold(),
+        pairs.len()
+    );
+    for pair in &pairs {
+        let first = pair.first.strip_prefix(path).unwrap_or(&pair.first);
+        let second = pair.second.strip_prefix(path).unwrap_or(&pair.second);
+        println!(
+            "  {:>6}  {}  {}",
+            format!("{:.1}%", pair.similarity * 100.0).yellow(),
+            first.display(),
+            second.display()
+        );
+    }
+
+    Ok(())
+}
+
+fn generate_sitemap(
+    args: &SitemapArgs,
+    config: &ConfigArgs,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut analyzer = config.analyzer(&args.path)?;
+    analyzer.analyze_directory()?;
+
+    let layout = match args.layout.as_str() {
+        "directory" => SitemapLayout::Directory,
+        _ => SitemapLayout::Graph,
+    };
+    let entries = sitemap::build(
+        &analyzer.link_graph(),
+        &analyzer.root_documents(),
+        &args.path,
+        layout,
+    );
+
+    match args.format.as_str() {
+        "xml" => print!("{}", sitemap::to_xml(&entries, &args.base_url)),
+        _ => print!("{}", sitemap::to_markdown(&entries)),
+    }
+
+    Ok(())
+}
+
+fn cross_check_published(
+    path: PathBuf,
+    config: &ConfigArgs,
+    source: &str,
+    base_url: Option<String>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let configured = config.load(&path)?.base_url;
+    let base_url = match base_url {
+        Some(base_url) => base_url,
+        None if !configured.is_empty() => configured,
+        None if source.starts_with("http://") || source.starts_with("https://") => {
+            source[..source.rfind('/').unwrap_or(source.len())].to_string()
+        }
+        None => return Err("--base-url is required when reading a local file".into()),
+    };
+
+    let mut analyzer = config.analyzer(&path)?;
+    analyzer.analyze_directory()?;
+    let documents: Vec<PathBuf> = analyzer
+        .document_paths()
+        .iter()
+        .map(|doc| doc.strip_prefix(&path).unwrap_or(doc).to_path_buf())
+        .collect();
+
+    let urls = crosscheck::published_urls(&crosscheck::read_source(source)?);
+    let result = crosscheck::cross_check(&urls, &base_url, &documents);
+
+    if result.is_clean() {
+        println!(
+            "{} All {} published pages match the local documents!",
+            "✓".green().bold(),
+            urls.len()
+        );
+        return Ok(());
+    }
+
+    if !result.missing_locally.is_empty() {
+        println!(
+            "{} {} published pages have no local document:",
+            "✗".red().bold(),
+            result.missing_locally.len()
+        );
+        for url in &result.missing_locally {
+            println!("  {}", url.red());
+        }
+        println!();
+    }
+
+    if !result.mismatched.is_empty() {
+        println!(
+            "{} {} published URLs do not map cleanly to a local path:",
+            "✗".red().bold(),
+            result.mismatched.len()
+        );
+        for (url, reason) in &result.mismatched {
+            println!("  {} ({})", url.red(), reason);
+        }
+        println!();
+    }
 
-    match cli.command {
-        Commands::Check { path, verbose } => {
-            if let Err(e) = check_links(path, verbose) {
-                eprintln!("{} {}", "Error:".red().bold(), e);
-                process::exit(1);
-            }
+    if !result.unpublished.is_empty() {
+        println!(
+            "{} {} local documents are not published:",
+            "⚠".yellow().bold(),
+            result.unpublished.len()
+        );
+        for doc in &result.unpublished {
+            println!("  {}", doc.display().to_string().yellow());
         }
-        Commands::Stats { path, format } => {
-            if let Err(e) = show_statistics(path, &format) {
-                eprintln!("{} {}", "Error:".red().bold(), e);
-                process::exit(1);
-            }
+    }
+
+    if !result.missing_locally.is_empty() || !result.mismatched.is_empty() {
+        process::exit(1);
+    }
+    Ok(())
+}
+
+fn import_config(args: &ImportConfigArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let tool = match &args.tool {
+        Some(tool) => SourceTool::parse(tool)?,
+        None => SourceTool::detect(&args.from).ok_or_else(|| {
+            format!(
+                "Can't tell which tool {} is for; pass --tool lychee or --tool markdown-link-check",
+                args.from.display()
+            )
+        })?,
+    };
+    let content =
+        fs::read_to_string(&args.from).map_err(|e| format!("{}: {}", args.from.display(), e))?;
+    let imported = ImportedConfig::parse(tool, &content)
+        .map_err(|e| format!("{}: {}", args.from.display(), e))?;
+    let toml = format!(
+        "# Translated from {} by `doclink-checker import-config`.\n{}",
+        args.from.display(),
+        imported.to_toml()?
+    );
+
+    if args.dry_run {
+        print!("{}", toml);
+    } else {
+        if args.output.exists() {
+            return Err(format!("{} already exists", args.output.display()).into());
         }
-        Commands::Orphans { path } => {
-            if let Err(e) = find_orphans(path) {
-                eprintln!("{} {}", "Error:".red().bold(), e);
-                process::exit(1);
-            }
+        fs::write(&args.output, toml)?;
+        println!(
+            "{} Wrote {} from {}",
+            "✓".green().bold(),
+            args.output.display(),
+            args.from.display()
+        );
+    }
+    for skipped in &imported.skipped {
+        log(Level::Warn, format!("Not translated: {}", skipped));
+    }
+    Ok(())
+}
+
+fn create_document(args: &NewArgs) -> Result<(), Box<dyn std::error::Error>> {
+    if args.path.exists() {
+        return Err(format!("{} already exists", args.path.display()).into());
+    }
+    let template = match &args.from {
+        Some(template) => Some(
+            fs::read_to_string(template).map_err(|e| format!("{}: {}", template.display(), e))?,
+        ),
+        None => None,
+    };
+    // Read the index up front so a missing one leaves nothing half done.
+    let index = match &args.link_from {
+        Some(index) => Some((
+            index,
+            fs::read_to_string(index).map_err(|e| format!("{}: {}", index.display(), e))?,
+        )),
+        None => None,
+    };
+
+    let title = args
+        .title
+        .clone()
+        .unwrap_or_else(|| scaffold::title_from_path(&args.path));
+    if let Some(parent) = args.path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(&args.path, scaffold::render(template.as_deref(), &title))?;
+    println!("{} Created {}", "✓".green().bold(), args.path.display());
+
+    match index {
+        Some((index, content)) => {
+            let target = scaffold::link_target(index, &args.path);
+            let (content, line) = scaffold::insert_link(&content, &title, &target);
+            fs::write(index, content)?;
+            println!(
+                "{} Linked from {}:{}",
+                "✓".green().bold(),
+                index.display(),
+                line
+            );
         }
+        None => println!(
+            "{} Nothing links to it yet; pass --link-from to add it to an index",
+            "ℹ".blue().bold()
+        ),
     }
+    Ok(())
 }
 
-fn check_links(path: PathBuf, verbose: bool) -> Result<(), Box<dyn std::error::Error>> {
-    let mut analyzer = LinkAnalyzer::new(path.clone());
+fn fix_links(args: &FixArgs, config: &ConfigArgs) -> Result<(), Box<dyn std::error::Error>> {
+    if let Some(style) = &args.style {
+        return convert_link_style(args, style, config);
+    }
+    let path = &args.path;
+    let mut analyzer = config.analyzer(path)?;
     analyzer.analyze_directory()?;
 
-    let broken_links = analyzer.find_broken_links();
+    let fixes = match args.relative_style.as_deref() {
+        Some("dot") => analyzer.normalize_fixes(fix::PathStyle::DotPrefixed),
+        Some("plain") => analyzer.normalize_fixes(fix::PathStyle::Plain),
+        Some(_) => analyzer.normalize_fixes(fix::PathStyle::RootRelative),
+        None if args.sync_anchors => analyzer.anchor_fixes(|document| {
+            args.since
+                .as_deref()
+                .and_then(|git_ref| git::show_file(document, git_ref))
+        }),
+        None => analyzer.suggested_fixes(),
+    };
+    let json = args.format == "json";
+    if fixes.is_empty() && !json {
+        println!("{} Nothing to fix!", "✓".green().bold());
+        return Ok(());
+    }
 
-    if broken_links.is_empty() {
-        println!("{} No broken links found!", "✓".green().bold());
+    let mut selected = Vec::new();
+    for fix in &fixes {
+        let file_path = fix.file_path.strip_prefix(path).unwrap_or(&fix.file_path);
+        let risky = fix.confidence == Confidence::Risky;
+        if !json {
+            println!(
+                "  {}:{} {} -> {}{}",
+                file_path.display(),
+                fix.line_number,
+                fix.target.red(),
+                fix.replacement.green(),
+                if risky {
+                    " (risky)".yellow()
+                } else {
+                    "".normal()
+                }
+            );
+        }
+        let apply = if !risky || args.apply_risky {
+            true
+        } else if args.interactive && !args.dry_run {
+            confirm(&format!(
+                "Apply {} -> {} in {}:{}?",
+                fix.target,
+                fix.replacement,
+                file_path.display(),
+                fix.line_number
+            ))?
+        } else {
+            false
+        };
+        selected.push(apply);
+    }
+
+    let chosen: Vec<Fix> = fixes
+        .iter()
+        .zip(&selected)
+        .filter(|(_, apply)| **apply)
+        .map(|(fix, _)| fix.clone())
+        .collect();
+    let skipped = fixes.len() - chosen.len();
+
+    let changes = fix::plan_fixes(&chosen)?;
+    let patch = fix::patch(&changes, path);
+    if let Some(patch_path) = &args.patch {
+        fs::write(patch_path, &patch)?;
+        if !json {
+            println!(
+                "{} Wrote patch to {}",
+                "ℹ".blue().bold(),
+                patch_path.display()
+            );
+        }
+    }
+
+    if !args.dry_run {
+        fix::write_changes(
+            &changes,
+            &fix::FixOptions {
+                backup: args.backup,
+            },
+        )?;
+        record_fix_session("fix", &changes);
+    }
+
+    if json {
+        let entries: Vec<FixReportEntry> = fixes
+            .iter()
+            .zip(&selected)
+            .map(|(fix, apply)| FixReportEntry {
+                fix,
+                applied: *apply && !args.dry_run,
+            })
+            .collect();
+        let report = Report {
+            metadata: ReportMetadata::collect(path, &config.load(path)?),
+            body: BTreeMap::from([("fixes", entries)]),
+        };
+        println!("{}", serde_json::to_string_pretty(&report)?);
         return Ok(());
     }
 
-    println!(
-        "{} Found {} broken links:",
-        "✗".red().bold(),
-        broken_links.len()
-    );
+    println!();
+    if args.dry_run {
+        if !patch.is_empty() {
+            print_patch(&patch);
+            println!();
+        }
+        println!(
+            "{} {} fixes available (dry run, no files changed)",
+            "ℹ".blue().bold(),
+            chosen.len()
+        );
+    } else {
+        let applied: usize = changes.iter().map(|change| change.edits.len()).sum();
+        println!("{} Applied {} fixes", "✓".green().bold(), applied);
+    }
+    if skipped > 0 {
+        println!(
+            "{} Skipped {} risky fixes; review them and rerun with --apply-risky or --interactive",
+            "⚠".yellow().bold(),
+            skipped
+        );
+    }
+    Ok(())
+}
 
-    for broken_link in &broken_links {
-        let file_path = broken_link
-            .link
-            .file_path
-            .strip_prefix(&path)
-            .unwrap_or(&broken_link.link.file_path);
+/// Converts every document to `--style`. Like `rewrite`, a dry run prints
+/// the diff.
+fn convert_link_style(
+    args: &FixArgs,
+    style: &str,
+    config: &ConfigArgs,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let style = match style {
+        "reference" => fix::LinkStyle::Reference,
+        _ => fix::LinkStyle::Inline,
+    };
+    let path = &args.path;
+    let mut analyzer = config.analyzer(path)?;
+    analyzer.analyze_directory()?;
+
+    let changes = fix::plan_style(&analyzer.document_paths(), style)?;
+    if changes.is_empty() {
+        println!("{} Every link already uses that style", "✓".green().bold());
+        return Ok(());
+    }
+
+    let patch = fix::patch(&changes, path);
+    if let Some(patch_path) = &args.patch {
+        fs::write(patch_path, &patch)?;
+    }
 
+    if args.dry_run {
+        print_patch(&patch);
         println!();
         println!(
-            "  {} {}:{}",
-            "File:".yellow().bold(),
-            file_path.display(),
-            broken_link.link.line_number
+            "{} {} files would be converted (dry run, no files changed)",
+            "ℹ".blue().bold(),
+            changes.len()
         );
-        println!("  {} {}", "Link:".cyan().bold(), broken_link.link.text);
+        return Ok(());
+    }
+
+    fix::write_changes(
+        &changes,
+        &fix::FixOptions {
+            backup: args.backup,
+        },
+    )?;
+    record_fix_session("fix --style", &changes);
+    println!("{} Converted {} files", "✓".green().bold(), changes.len());
+    Ok(())
+}
+
+fn print_patch(patch: &str) {
+    for line in patch.lines() {
+        if line.starts_with("+++") || line.starts_with("---") {
+            println!("{}", line.bold());
+        } else if line.starts_with('+') {
+            println!("{}", line.green());
+        } else if line.starts_with('-') {
+            println!("{}", line.red());
+        } else if line.starts_with("@@") {
+            println!("{}", line.cyan());
+        } else {
+            println!("{}", line);
+        }
+    }
+}
+
+fn rewrite_links(
+    args: &RewriteArgs,
+    config: &ConfigArgs,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let from = Regex::new(&args.from).map_err(|e| format!("Invalid --from pattern: {}", e))?;
+    let path = &args.path;
+    let mut analyzer = config.analyzer(path)?;
+    analyzer.analyze_directory()?;
+
+    let fixes = analyzer.rewrite_fixes(&from, &args.to)?;
+    let changes = fix::plan_fixes(&fixes)?;
+    if changes.is_empty() {
+        println!("{} No link target matches {}", "ℹ".blue().bold(), args.from);
+        return Ok(());
+    }
+
+    let patch = fix::patch(&changes, path);
+    if let Some(patch_path) = &args.patch {
+        fs::write(patch_path, &patch)?;
+    }
+    let rewritten: usize = changes.iter().map(|change| change.edits.len()).sum();
+
+    if args.dry_run {
+        print_patch(&patch);
+        println!();
         println!(
-            "  {} {}",
-            "Target:".magenta().bold(),
-            broken_link.link.target
+            "{} {} links in {} files would be rewritten (dry run, no files changed)",
+            "ℹ".blue().bold(),
+            rewritten,
+            changes.len()
         );
-        println!("  {} {}", "Reason:".red().bold(), broken_link.reason);
+        return Ok(());
+    }
 
-        if verbose {
-            println!(
-                "  {} [{}]({})",
-                "Markdown:".blue().bold(),
-                broken_link.link.text,
-                broken_link.link.target
-            );
-        }
+    fix::write_changes(
+        &changes,
+        &fix::FixOptions {
+            backup: args.backup,
+        },
+    )?;
+    record_fix_session("rewrite", &changes);
+    println!(
+        "{} Rewrote {} links in {} files",
+        "✓".green().bold(),
+        rewritten,
+        changes.len()
+    );
+    Ok(())
+}
+
+/// Asks a yes/no question on the terminal; anything but `y` means no.
+fn confirm(question: &str) -> Result<bool, Box<dyn std::error::Error>> {
+    eprint!("{} {} [y/N] ", "?".cyan().bold(), question);
+    std::io::stderr().flush()?;
+    let mut answer = String::new();
+    std::io::stdin().read_line(&mut answer)?;
+    Ok(matches!(answer.trim(), "y" | "Y" | "yes"))
+}
+
+/// Adds applied changes to the journal read by `undo`. A journal that cannot
+/// be saved only costs the ability to undo, so it is reported as a warning.
+fn record_fix_session(command: &str, changes: &[fix::FileChange]) {
+    if changes.is_empty() {
+        return;
+    }
+    let journal_path = cache::cache_dir().join(Journal::FILE_NAME);
+    let mut journal = Journal::load(&journal_path);
+    journal.record(FixSession::new(command, changes));
+    if let Err(e) = journal.save(&journal_path) {
+        log(
+            Level::Warn,
+            format!("Could not record the fix session for undo: {}", e),
+        );
+    }
+}
+
+/// The field of a `--group-by frontmatter:<field>` key.
+fn parse_group_by(key: &str) -> Result<String, String> {
+    match key.strip_prefix("frontmatter:") {
+        Some(field) if !field.trim().is_empty() => Ok(field.trim().to_string()),
+        _ => Err(format!(
+            "`{}` is not a front matter field (use e.g. frontmatter:owner)",
+            key
+        )),
     }
+}
 
-    process::exit(1);
+fn parse_ratio(value: &str) -> Result<f64, String> {
+    match value.parse::<f64>() {
+        Ok(ratio) if (0.0..=1.0).contains(&ratio) => Ok(ratio),
+        _ => Err(format!("`{}` is not a number between 0.0 and 1.0", value)),
+    }
 }
 
-fn show_statistics(path: PathBuf, format: &str) -> Result<(), Box<dyn std::error::Error>> {
-    let mut analyzer = LinkAnalyzer::new(path);
-    analyzer.analyze_directory()?;
+fn generate_fixture(args: &FixtureArgs) -> Result<(), Box<dyn std::error::Error>> {
+    if args.path.exists() && fs::read_dir(&args.path)?.next().is_some() {
+        return Err(format!("{} is not empty", args.path.display()).into());
+    }
 
-    let stats = analyzer.get_statistics();
+    let fixture = Fixture::generate(&FixtureSpec {
+        documents: args.documents,
+        links_per_document: args.links_per_document,
+        broken_ratio: args.broken_ratio,
+        documents_per_directory: args.documents_per_directory,
+        sections: args.sections,
+        seed: args.seed,
+    });
+    fixture.write_to(&args.path)?;
 
-    match format {
-        "json" => {
-            println!("{}", serde_json::to_string_pretty(&stats)?);
-        }
-        "text" | _ => {
-            print_text_statistics(&stats);
+    println!(
+        "{} Generated {} files with {} links ({} broken) in {}",
+        "✓".green().bold(),
+        fixture.files.len(),
+        fixture.links,
+        fixture.broken,
+        args.path.display()
+    );
+    Ok(())
+}
+
+fn undo_fixes(dry_run: bool) -> Result<(), Box<dyn std::error::Error>> {
+    let journal_path = cache::cache_dir().join(Journal::FILE_NAME);
+    let mut journal = Journal::load(&journal_path);
+    let Some(session) = journal.last() else {
+        println!("{} No fix session to undo", "ℹ".blue().bold());
+        return Ok(());
+    };
+
+    for file in &session.files {
+        for edit in &file.edits {
+            println!(
+                "  {} {} -> {}",
+                file.path.display(),
+                edit.after.red(),
+                edit.before.green()
+            );
         }
     }
+    println!();
+
+    if dry_run {
+        session.plan_undo()?;
+        println!(
+            "{} {} edits from `{}` can be reverted (dry run, no files changed)",
+            "ℹ".blue().bold(),
+            session.edit_count(),
+            session.command
+        );
+        return Ok(());
+    }
 
+    if let Some(session) = journal.undo_last()? {
+        journal.save(&journal_path)?;
+        println!(
+            "{} Reverted {} edits from `{}`",
+            "✓".green().bold(),
+            session.edit_count(),
+            session.command
+        );
+    }
     Ok(())
 }
 
-fn print_text_statistics(stats: &LinkStatistics) {
-    println!("{}", "Document Link Statistics".bold().underline());
-    println!();
+fn explain_resolution(
+    path: PathBuf,
+    config: &ConfigArgs,
+    file: &Path,
+    target: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let analyzer = config.analyzer(&path)?;
+    let resolution = analyzer.resolve(file, target);
 
     println!(
-        "{} {}",
-        "Total Documents:".cyan().bold(),
-        stats.total_documents
+        "{} {} from {}",
+        "Resolving".bold(),
+        target.cyan(),
+        file.display()
     );
-    println!("{} {}", "Total Links:".cyan().bold(), stats.total_links);
+    println!();
+
+    let kind = match resolution.kind {
+        LinkKind::External if resolution.published => "link to the published site",
+        LinkKind::External => "external URL (not checked)",
+        LinkKind::Internal => "internal link",
+        LinkKind::SameDocument => "anchor in the same document",
+        LinkKind::AbsolutePath => "absolute filesystem path (not portable)",
+        LinkKind::FileUrl => "file:// URL",
+        LinkKind::WikiLink => "wiki link",
+        LinkKind::Include => "include directive",
+        LinkKind::Other => "URL of another scheme (not checked)",
+        _ => "link",
+    };
+    println!("  {} {}", "Classification:".yellow().bold(), kind);
     println!(
-        "{} {} ({}%)",
-        "Internal Links:".green().bold(),
-        stats.internal_links,
-        if stats.total_links > 0 {
-            (stats.internal_links * 100) / stats.total_links
+        "  {} {}",
+        "Path:".yellow().bold(),
+        if resolution.path.is_empty() {
+            "(none)"
         } else {
-            0
+            &resolution.path
         }
     );
     println!(
-        "{} {} ({}%)",
-        "External Links:".blue().bold(),
-        stats.external_links,
-        if stats.total_links > 0 {
-            (stats.external_links * 100) / stats.total_links
-        } else {
-            0
-        }
+        "  {} {}",
+        "Fragment:".yellow().bold(),
+        resolution.fragment.as_deref().unwrap_or("(none)")
     );
-
-    if stats.broken_links > 0 {
-        println!("{} {}", "Broken Links:".red().bold(), stats.broken_links);
-    } else {
-        println!("{} {}", "Broken Links:".green().bold(), stats.broken_links);
+    if let Some(base_dir) = &resolution.base_dir {
+        println!(
+            "  {} {}",
+            "Base directory:".yellow().bold(),
+            base_dir.display()
+        );
     }
-
-    if stats.orphaned_documents > 0 {
+    for candidate in &resolution.candidates {
+        println!("  {} {}", "Candidate:".yellow().bold(), candidate.display());
+    }
+    if let Some(resolved) = &resolution.resolved {
+        println!("  {} {}", "Resolved:".yellow().bold(), resolved.display());
+    }
+    if !matches!(resolution.kind, LinkKind::External | LinkKind::Other) || resolution.published {
         println!(
-            "{} {}",
-            "Orphaned Documents:".yellow().bold(),
-            stats.orphaned_documents
+            "  {} {}",
+            "Exists:".yellow().bold(),
+            if resolution.resolved.is_some() {
+                "yes"
+            } else if resolution.assumed_present {
+                "no (assumed present by configuration)"
+            } else {
+                "no"
+            }
         );
-    } else {
+    }
+    match (&resolution.anchors, resolution.anchor_found) {
+        (Some(anchors), Some(found)) => {
+            println!(
+                "  {} {} ({} anchors in target)",
+                "Anchor:".yellow().bold(),
+                if found { "found" } else { "not found" },
+                anchors.len()
+            );
+            if !found && !anchors.is_empty() {
+                println!("  {} {}", "Available:".yellow().bold(), anchors.join(", "));
+            }
+        }
+        _ if resolution.fragment.is_some() && resolution.resolved.is_some() => {
+            println!(
+                "  {} not checked (target is not a markdown document)",
+                "Anchor:".yellow().bold()
+            );
+        }
+        _ => {}
+    }
+
+    if let Some(suggestion) = &resolution.suggestion {
+        println!("  {} {}", "Suggestion:".yellow().bold(), suggestion);
+    }
+
+    println!();
+    match &resolution.error {
+        Some(reason) => {
+            println!("{} {}", "✗".red().bold(), reason);
+            process::exit(1);
+        }
+        None => println!("{} Link resolves", "✓".green().bold()),
+    }
+
+    Ok(())
+}
+
+fn run_doctor(config_path: &Path, profile: Option<&str>) {
+    let diagnostics = doctor::run(config_path, profile, &cache::cache_dir());
+
+    for diagnostic in &diagnostics {
+        let marker = match diagnostic.severity {
+            Severity::Ok => "✓".green().bold(),
+            Severity::Warning => "⚠".yellow().bold(),
+            Severity::Error => "✗".red().bold(),
+            _ => "ℹ".blue().bold(),
+        };
         println!(
-            "{} {}",
-            "Orphaned Documents:".green().bold(),
-            stats.orphaned_documents
+            "{} {} {}",
+            marker,
+            format!("[{}]", diagnostic.check).dimmed(),
+            diagnostic.message
         );
     }
 
-    if !stats.document_stats.is_empty() {
-        println!();
-        println!("{}", "Per-Document Statistics:".bold().underline());
+    if diagnostics
+        .iter()
+        .any(|diagnostic| diagnostic.severity == Severity::Error)
+    {
+        process::exit(1);
+    }
+}
 
-        for (doc_path, doc_stats) in &stats.document_stats {
-            let file_name = doc_path
-                .file_name()
-                .and_then(|n| n.to_str())
-                .unwrap_or("unknown");
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
 
-            println!(
-                "  {} {} links ({} internal, {} external)",
-                file_name.magenta().bold(),
-                doc_stats.total_links,
-                doc_stats.internal_links,
-                doc_stats.external_links
-            );
+    fn env(vars: &[(&str, &str)]) -> Vec<(String, String)> {
+        vars.iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect()
+    }
+
+    fn check_args(cli: &Cli) -> &CheckArgs {
+        match &cli.command {
+            Commands::Check(args) => args,
+            _ => panic!("expected the check command"),
         }
     }
-}
 
-fn find_orphans(path: PathBuf) -> Result<(), Box<dyn std::error::Error>> {
-    let mut analyzer = LinkAnalyzer::new(path.clone());
-    analyzer.analyze_directory()?;
+    #[test]
+    fn test_env_overrides_check_flags() {
+        let mut cli = Cli::try_parse_from([
+            "doclink-checker",
+            "check",
+            "--external",
+            "--jobs",
+            "2",
+            "--timeout",
+            "1m",
+        ])
+        .unwrap();
+        cli.apply_env(env(&[
+            ("DOCLINK_EXTERNAL", "0"),
+            ("DOCLINK_OFFLINE", "yes"),
+            ("DOCLINK_JOBS", "8"),
+            ("DOCLINK_TIMEOUT", "5s"),
+            ("DOCLINK_REQUEST_TIMEOUT", "500ms"),
+            ("PATH", "/usr/bin"),
+        ]))
+        .unwrap();
 
-    let orphaned_docs = analyzer.find_orphaned_documents();
+        let args = check_args(&cli);
+        assert!(!args.external);
+        assert!(args.offline);
+        assert_eq!(args.jobs, Some(8));
+        assert_eq!(args.timeout, Some(Duration::from_secs(5)));
+        assert_eq!(args.request_timeout, Some(Duration::from_millis(500)));
+        assert_eq!(
+            cli.config.env,
+            env(&[
+                ("DOCLINK_EXTERNAL", "0"),
+                ("DOCLINK_OFFLINE", "yes"),
+                ("DOCLINK_JOBS", "8"),
+                ("DOCLINK_TIMEOUT", "5s"),
+                ("DOCLINK_REQUEST_TIMEOUT", "500ms")
+            ])
+        );
+    }
 
-    if orphaned_docs.is_empty() {
-        println!("{} No orphaned documents found!", "✓".green().bold());
-        return Ok(());
+    #[test]
+    fn test_invalid_env_switch_is_an_error() {
+        let mut cli = Cli::try_parse_from(["doclink-checker", "check"]).unwrap();
+        let error = cli.apply_env(env(&[("DOCLINK_JOBS", "many")])).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "DOCLINK_JOBS: expected an integer, got `many`"
+        );
+
+        let error = cli
+            .apply_env(env(&[("DOCLINK_PACKAGES", "maybe")]))
+            .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "DOCLINK_PACKAGES: expected a boolean, got `maybe`"
+        );
     }
 
-    println!(
-        "{} Found {} orphaned documents:",
-        "⚠".yellow().bold(),
-        orphaned_docs.len()
-    );
+    #[test]
+    fn test_env_selects_config_file_and_profile() {
+        let temp_dir = TempDir::new().unwrap();
+        let flag_config = temp_dir.path().join("flag.toml");
+        let env_config = temp_dir.path().join("env.toml");
+        fs::write(&flag_config, "flavor = \"github-wiki\"\n").unwrap();
+        fs::write(
+            &env_config,
+            "[profile.ci]\nflavor = \"azure-devops-wiki\"\n[profile.local]\nflavor = \"markdown\"\n",
+        )
+        .unwrap();
 
-    for orphaned_doc in orphaned_docs {
-        let file_path = orphaned_doc.strip_prefix(&path).unwrap_or(&orphaned_doc);
-        println!("  {}", file_path.display().to_string().red());
+        let mut cli = Cli::try_parse_from([
+            "doclink-checker",
+            "--config",
+            flag_config.to_str().unwrap(),
+            "--profile",
+            "local",
+            "check",
+        ])
+        .unwrap();
+        cli.apply_env(env(&[
+            ("DOCLINK_CONFIG", env_config.to_str().unwrap()),
+            ("DOCLINK_PROFILE", "ci"),
+        ]))
+        .unwrap();
+
+        assert_eq!(cli.config.config.as_deref(), Some(env_config.as_path()));
+        assert_eq!(cli.config.profile.as_deref(), Some("ci"));
+        let config = cli.config.load(temp_dir.path()).unwrap();
+        assert_eq!(config.flavor, "azure-devops-wiki");
     }
 
-    Ok(())
+    #[test]
+    fn test_env_overrides_config_file_and_flags() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(
+            temp_dir.path().join(Config::FILE_NAME),
+            "flavor = \"github-wiki\"\nexclude = [\"drafts\"]\n",
+        )
+        .unwrap();
+
+        let mut cli = Cli::try_parse_from([
+            "doclink-checker",
+            "--flavor",
+            "markdown",
+            "--exclude",
+            "vendor",
+            "--follow-symlinks",
+            "check",
+        ])
+        .unwrap();
+        let config = cli.config.load(temp_dir.path()).unwrap();
+        assert_eq!(config.flavor, "markdown");
+        assert_eq!(config.exclude, vec!["drafts", "vendor"]);
+        assert!(config.follow_symlinks);
+
+        cli.apply_env(env(&[
+            ("DOCLINK_FLAVOR", "azure-devops-wiki"),
+            ("DOCLINK_EXCLUDE", "archive"),
+            ("DOCLINK_FOLLOW_SYMLINKS", "off"),
+        ]))
+        .unwrap();
+        let config = cli.config.load(temp_dir.path()).unwrap();
+        assert_eq!(config.flavor, "azure-devops-wiki");
+        assert_eq!(config.exclude, vec!["archive"]);
+        assert!(!config.follow_symlinks);
+    }
 }
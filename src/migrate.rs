@@ -0,0 +1,428 @@
+use crate::Config;
+use regex::Regex;
+use std::path::Path;
+
+/// URLs lychee's `exclude_loopback`, `exclude_private` and
+/// `exclude_link_local` skip, as `ignore_targets` patterns.
+const LOOPBACK: &str = r"^https?://(localhost|127\.\d+\.\d+\.\d+|\[::1\])([:/?#]|$)";
+const PRIVATE: &str = r"^https?://(10\.\d+|192\.168|172\.(1[6-9]|2\d|3[01]))\.\d+\.\d+([:/?#]|$)";
+const LINK_LOCAL: &str = r"^https?://169\.254\.\d+\.\d+([:/?#]|$)";
+
+/// Another link checker whose configuration can be translated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum SourceTool {
+    /// lychee, configured by a `lychee.toml`.
+    Lychee,
+    /// markdown-link-check, configured by a JSON file.
+    MarkdownLinkCheck,
+}
+
+impl SourceTool {
+    pub fn parse(name: &str) -> Result<Self, String> {
+        match name {
+            "lychee" => Ok(SourceTool::Lychee),
+            "markdown-link-check" => Ok(SourceTool::MarkdownLinkCheck),
+            _ => Err(format!(
+                "Unknown tool `{}` (expected lychee or markdown-link-check)",
+                name
+            )),
+        }
+    }
+
+    /// The tool a configuration file is for, by its extension: `.toml` for
+    /// lychee and `.json` for markdown-link-check.
+    pub fn detect(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()? {
+            "toml" => Some(SourceTool::Lychee),
+            "json" => Some(SourceTool::MarkdownLinkCheck),
+            _ => None,
+        }
+    }
+}
+
+/// A configuration translated from another tool's, with the settings that
+/// could not be carried over.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[non_exhaustive]
+pub struct ImportedConfig {
+    pub config: Config,
+    /// One `` `key`: why `` note per setting left out.
+    pub skipped: Vec<String>,
+}
+
+impl ImportedConfig {
+    pub fn parse(tool: SourceTool, content: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        match tool {
+            SourceTool::Lychee => from_lychee(content),
+            SourceTool::MarkdownLinkCheck => from_markdown_link_check(content),
+        }
+    }
+
+    /// The configuration as TOML, with only the keys that differ from the
+    /// defaults.
+    pub fn to_toml(&self) -> Result<String, Box<dyn std::error::Error>> {
+        let defaults = toml::Table::try_from(Config::default())?;
+        let mut table = toml::Table::try_from(&self.config)?;
+        table.retain(|key, value| defaults.get(key) != Some(value));
+        Ok(toml::to_string(&table)?)
+    }
+
+    fn ignore_target(&mut self, key: &str, pattern: &str) {
+        if Regex::new(pattern).is_err() {
+            self.skipped.push(format!(
+                "`{}`: `{}` is not a valid regular expression",
+                key, pattern
+            ));
+        } else if !self
+            .config
+            .ignore_targets
+            .iter()
+            .any(|known| known == pattern)
+        {
+            self.config.ignore_targets.push(pattern.to_string());
+        }
+    }
+
+    fn header(&mut self, prefix: &str, name: &str, value: &str) {
+        self.config
+            .external_headers
+            .entry(prefix.to_string())
+            .or_default()
+            .insert(name.to_string(), value.to_string());
+    }
+
+    /// Accepts `code` unless it passes anyway, as 2xx codes do.
+    fn accept(&mut self, code: u16) {
+        if !(200..300).contains(&code) && !self.config.accepted_status.contains(&code) {
+            self.config.accepted_status.push(code);
+        }
+    }
+
+    fn unsupported(&mut self, key: &str) {
+        self.skipped
+            .push(format!("`{}`: doclink-checker has no such setting", key));
+    }
+
+    fn malformed(&mut self, key: &str) {
+        self.skipped
+            .push(format!("`{}`: value not understood", key));
+    }
+}
+
+/// Translates a `lychee.toml`: URL excludes become `ignore_targets`,
+/// `exclude_path` entries that are plain paths become `exclude` globs, and
+/// headers, the user agent, `timeout` and `accept` carry over.
+pub fn from_lychee(content: &str) -> Result<ImportedConfig, Box<dyn std::error::Error>> {
+    let table: toml::Table = toml::from_str(content)?;
+    let mut imported = ImportedConfig::default();
+
+    for (key, value) in &table {
+        match (key.as_str(), value) {
+            ("exclude", toml::Value::Array(patterns)) => {
+                for pattern in patterns {
+                    match pattern.as_str() {
+                        Some(pattern) => imported.ignore_target(key, pattern),
+                        None => imported.malformed(key),
+                    }
+                }
+            }
+            ("exclude_path", toml::Value::Array(paths)) => {
+                for path in paths {
+                    match path.as_str().and_then(exclude_glob) {
+                        Some(glob) => imported.config.exclude.push(glob),
+                        None => imported.skipped.push(format!(
+                            "`exclude_path`: `{}` is a pattern, not a path",
+                            path.as_str().unwrap_or_default()
+                        )),
+                    }
+                }
+            }
+            ("exclude_loopback", toml::Value::Boolean(true)) => {
+                imported.ignore_target(key, LOOPBACK);
+            }
+            ("exclude_private", toml::Value::Boolean(true)) => {
+                imported.ignore_target(key, PRIVATE);
+            }
+            ("exclude_link_local", toml::Value::Boolean(true)) => {
+                imported.ignore_target(key, LINK_LOCAL);
+            }
+            ("exclude_all_private", toml::Value::Boolean(true)) => {
+                for pattern in [LOOPBACK, PRIVATE, LINK_LOCAL] {
+                    imported.ignore_target(key, pattern);
+                }
+            }
+            (
+                "exclude_loopback"
+                | "exclude_private"
+                | "exclude_link_local"
+                | "exclude_all_private",
+                toml::Value::Boolean(false),
+            ) => {}
+            ("timeout", toml::Value::Integer(seconds)) if *seconds >= 0 => {
+                imported.config.external_timeout = *seconds as u64;
+            }
+            ("header", toml::Value::Table(headers)) => {
+                for (name, value) in headers {
+                    match value.as_str() {
+                        Some(value) => imported.header("", name, value),
+                        None => imported.malformed(key),
+                    }
+                }
+            }
+            ("header" | "headers", toml::Value::Array(headers)) => {
+                for header in headers {
+                    match header
+                        .as_str()
+                        .and_then(|header| header.split_once(['=', ':']))
+                    {
+                        Some((name, value)) => imported.header("", name.trim(), value.trim()),
+                        None => imported.malformed(key),
+                    }
+                }
+            }
+            ("user_agent", toml::Value::String(user_agent)) => {
+                imported.header("", "User-Agent", user_agent);
+            }
+            ("accept", accept) => {
+                let codes: Option<Vec<u16>> = match accept {
+                    toml::Value::String(text) => status_ranges(text),
+                    toml::Value::Array(items) => items
+                        .iter()
+                        .map(|item| match item {
+                            toml::Value::Integer(code) => Some(vec![u16::try_from(*code).ok()?]),
+                            toml::Value::String(text) => status_ranges(text),
+                            _ => None,
+                        })
+                        .collect::<Option<Vec<_>>>()
+                        .map(|ranges| ranges.concat()),
+                    _ => None,
+                };
+                match codes {
+                    Some(codes) => codes.into_iter().for_each(|code| imported.accept(code)),
+                    None => imported.malformed(key),
+                }
+            }
+            (
+                "exclude"
+                | "exclude_path"
+                | "exclude_loopback"
+                | "exclude_private"
+                | "exclude_link_local"
+                | "exclude_all_private"
+                | "timeout"
+                | "header"
+                | "headers"
+                | "user_agent",
+                _,
+            ) => imported.malformed(key),
+            _ => imported.unsupported(key),
+        }
+    }
+    Ok(imported)
+}
+
+/// Translates a markdown-link-check JSON configuration: `ignorePatterns`
+/// become `ignore_targets`, `httpHeaders` become `external_headers`, and
+/// `timeout` and `aliveStatusCodes` carry over.
+pub fn from_markdown_link_check(
+    content: &str,
+) -> Result<ImportedConfig, Box<dyn std::error::Error>> {
+    let object: serde_json::Map<String, serde_json::Value> = serde_json::from_str(content)?;
+    let mut imported = ImportedConfig::default();
+
+    for (key, value) in &object {
+        match key.as_str() {
+            "ignorePatterns" => {
+                for entry in value.as_array().into_iter().flatten() {
+                    match entry["pattern"].as_str() {
+                        Some(pattern) => imported.ignore_target(key, pattern),
+                        None => imported.malformed(key),
+                    }
+                }
+            }
+            "httpHeaders" => {
+                for entry in value.as_array().into_iter().flatten() {
+                    let (Some(urls), Some(headers)) =
+                        (entry["urls"].as_array(), entry["headers"].as_object())
+                    else {
+                        imported.malformed(key);
+                        continue;
+                    };
+                    for url in urls.iter().filter_map(|url| url.as_str()) {
+                        for (name, value) in headers {
+                            match value.as_str() {
+                                Some(value) => imported.header(url, name, value),
+                                None => imported.malformed(key),
+                            }
+                        }
+                    }
+                }
+            }
+            "timeout" => match mlc_timeout_seconds(value) {
+                Some(seconds) => imported.config.external_timeout = seconds,
+                None => imported.malformed(key),
+            },
+            "aliveStatusCodes" => {
+                for code in value.as_array().into_iter().flatten() {
+                    match code.as_u64().and_then(|code| u16::try_from(code).ok()) {
+                        Some(code) => imported.accept(code),
+                        None => imported.malformed(key),
+                    }
+                }
+            }
+            _ => imported.unsupported(key),
+        }
+    }
+    Ok(imported)
+}
+
+/// An `exclude` glob for a plain file or directory path, e.g. `docs/old/**`
+/// for `./docs/old/`. `None` for regular expressions.
+fn exclude_glob(path: &str) -> Option<String> {
+    let path = path.trim_start_matches("./").trim_end_matches('/');
+    let plain = path.split('/').all(|part| {
+        !part.is_empty()
+            && part
+                .chars()
+                .all(|c| c.is_alphanumeric() || matches!(c, '.' | '_' | '-'))
+    });
+    match Path::new(path).extension() {
+        _ if !plain => None,
+        Some(_) => Some(path.to_string()),
+        None => Some(format!("{}/**", path)),
+    }
+}
+
+/// Status codes in lychee's `accept` syntax, e.g. `200..=204, 429`.
+fn status_ranges(text: &str) -> Option<Vec<u16>> {
+    let mut codes = Vec::new();
+    for part in text
+        .split(',')
+        .map(str::trim)
+        .filter(|part| !part.is_empty())
+    {
+        if let Some((start, end)) = part.split_once("..=") {
+            codes.extend(start.trim().parse::<u16>().ok()?..=end.trim().parse().ok()?);
+        } else if let Some((start, end)) = part.split_once("..") {
+            codes.extend(start.trim().parse::<u16>().ok()?..end.trim().parse().ok()?);
+        } else {
+            codes.push(part.parse().ok()?);
+        }
+    }
+    Some(codes)
+}
+
+/// markdown-link-check's `timeout`, e.g. `"20s"`, `"1m"` or milliseconds,
+/// in whole seconds, rounded up.
+fn mlc_timeout_seconds(value: &serde_json::Value) -> Option<u64> {
+    let text = match value {
+        serde_json::Value::Number(number) => number.to_string(),
+        serde_json::Value::String(text) => text.trim().to_string(),
+        _ => return None,
+    };
+    let split = text
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(text.len());
+    let (number, unit) = text.split_at(split);
+    let number: f64 = number.parse().ok()?;
+    let seconds = match unit.trim() {
+        "" | "ms" => number / 1000.0,
+        "s" => number,
+        "m" => number * 60.0,
+        "h" => number * 3600.0,
+        _ => return None,
+    };
+    Some(seconds.ceil() as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn test_from_lychee() {
+        let imported = from_lychee(
+            r#"
+exclude = ['^https://twitter\.com', "(unclosed"]
+exclude_path = ["./docs/archive/", "CHANGELOG.md", ".*\\.bak$"]
+exclude_loopback = true
+timeout = 20
+max_retries = 3
+user_agent = "curl/8.0"
+accept = ["200..=204", "403", 429]
+
+[header]
+Accept = "text/html"
+"#,
+        )
+        .unwrap();
+        let config = &imported.config;
+        assert_eq!(
+            config.ignore_targets,
+            vec![r"^https://twitter\.com".to_string(), LOOPBACK.to_string()]
+        );
+        assert_eq!(config.exclude, vec!["docs/archive/**", "CHANGELOG.md"]);
+        assert_eq!(config.external_timeout, 20);
+        assert_eq!(config.accepted_status, vec![403, 429]);
+        assert_eq!(
+            config.external_headers[""],
+            BTreeMap::from([
+                ("Accept".to_string(), "text/html".to_string()),
+                ("User-Agent".to_string(), "curl/8.0".to_string()),
+            ])
+        );
+        assert_eq!(
+            imported.skipped,
+            vec![
+                "`exclude`: `(unclosed` is not a valid regular expression",
+                "`exclude_path`: `.*\\.bak$` is a pattern, not a path",
+                "`max_retries`: doclink-checker has no such setting",
+            ]
+        );
+
+        // The translation is a valid configuration holding only what was set.
+        let toml = imported.to_toml().unwrap();
+        assert!(!toml.contains("wiki_links"));
+        assert_eq!(Config::parse(&toml).unwrap(), imported.config);
+    }
+
+    #[test]
+    fn test_from_markdown_link_check() {
+        let imported = from_markdown_link_check(
+            r#"{
+  "ignorePatterns": [{"pattern": "^http://localhost"}],
+  "httpHeaders": [
+    {"urls": ["https://api.github.com/"], "headers": {"Authorization": "token x"}}
+  ],
+  "timeout": "1500ms",
+  "retryOn429": true,
+  "aliveStatusCodes": [200, 206, 403]
+}"#,
+        )
+        .unwrap();
+        let config = &imported.config;
+        assert_eq!(config.ignore_targets, vec!["^http://localhost"]);
+        assert_eq!(
+            config.external_headers["https://api.github.com/"]["Authorization"],
+            "token x"
+        );
+        assert_eq!(config.external_timeout, 2);
+        assert_eq!(config.accepted_status, vec![403]);
+        assert_eq!(
+            imported.skipped,
+            vec!["`retryOn429`: doclink-checker has no such setting"]
+        );
+
+        assert_eq!(
+            SourceTool::detect(Path::new(".markdown-link-check.json")),
+            Some(SourceTool::MarkdownLinkCheck)
+        );
+        assert_eq!(
+            SourceTool::detect(Path::new("lychee.toml")),
+            Some(SourceTool::Lychee)
+        );
+        assert!(from_markdown_link_check("[]").is_err());
+    }
+}
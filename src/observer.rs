@@ -0,0 +1,27 @@
+use std::fmt::Debug;
+use std::path::Path;
+use std::sync::Arc;
+
+use crate::{BrokenLink, MarkdownLink};
+
+/// Receives findings while an analysis runs, e.g. to stream them elsewhere
+/// or drive a progress display. Every method defaults to doing nothing.
+pub trait AnalysisObserver: Debug + Send + Sync {
+    fn on_file_scanned(&self, _path: &Path, _links: &[MarkdownLink]) {}
+    fn on_link_found(&self, _link: &MarkdownLink) {}
+    fn on_broken_link(&self, _broken_link: &BrokenLink) {}
+}
+
+impl<T: AnalysisObserver + ?Sized> AnalysisObserver for Arc<T> {
+    fn on_file_scanned(&self, path: &Path, links: &[MarkdownLink]) {
+        (**self).on_file_scanned(path, links)
+    }
+
+    fn on_link_found(&self, link: &MarkdownLink) {
+        (**self).on_link_found(link)
+    }
+
+    fn on_broken_link(&self, broken_link: &BrokenLink) {
+        (**self).on_broken_link(broken_link)
+    }
+}
@@ -0,0 +1,388 @@
+use crate::badges::host_and_path;
+use crate::database::{unix_seconds, Database};
+use crate::report::rfc3339;
+use globset::{Glob, GlobMatcher};
+use rusqlite::types::Value as SqlValue;
+use serde_json::Value;
+use std::fmt;
+use std::str::FromStr;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// What a query lists, one row each.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Subject {
+    Links,
+    Documents,
+    /// Documents nothing links to.
+    Orphans,
+    Findings,
+    /// Pairs of documents where the first links to the second.
+    Edges,
+}
+
+impl Subject {
+    const ALL: [Subject; 5] = [
+        Subject::Links,
+        Subject::Documents,
+        Subject::Orphans,
+        Subject::Findings,
+        Subject::Edges,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Subject::Links => "links",
+            Subject::Documents => "documents",
+            Subject::Orphans => "orphans",
+            Subject::Findings => "findings",
+            Subject::Edges => "edges",
+        }
+    }
+
+    fn filters(self) -> &'static [&'static str] {
+        match self {
+            Subject::Links => &["from", "to", "kind", "domain"],
+            Subject::Documents | Subject::Orphans => &["path", "older", "newer"],
+            Subject::Findings => &["from", "to", "kind", "domain", "reason"],
+            Subject::Edges => &["from", "to"],
+        }
+    }
+
+    fn sql(self) -> &'static str {
+        match self {
+            Subject::Links => {
+                "SELECT source, line, text, target, kind FROM links WHERE run_id = ?1
+                 ORDER BY source, line"
+            }
+            Subject::Documents => {
+                "SELECT path, orphan, modified_at FROM documents WHERE run_id = ?1 ORDER BY path"
+            }
+            Subject::Orphans => {
+                "SELECT path, modified_at FROM documents WHERE run_id = ?1 AND orphan
+                 ORDER BY path"
+            }
+            Subject::Findings => {
+                "SELECT kind, source, line, text, target, reason FROM findings WHERE run_id = ?1
+                 ORDER BY source, line"
+            }
+            Subject::Edges => {
+                "SELECT source, target FROM edges WHERE run_id = ?1 ORDER BY source, target"
+            }
+        }
+    }
+}
+
+impl fmt::Display for Subject {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Filter {
+    Glob(&'static str, GlobMatcher),
+    Equals(&'static str, String),
+    Contains(&'static str, String),
+    /// The target's host is this domain or one of its subdomains.
+    Domain(String),
+    OlderThan(Duration),
+    NewerThan(Duration),
+}
+
+impl Filter {
+    fn matches(&self, columns: &[String], row: &[SqlValue], now: SystemTime) -> bool {
+        let get = |column: &str| {
+            columns
+                .iter()
+                .position(|name| name == column)
+                .map(|i| &row[i])
+        };
+        let text = |column| match get(column) {
+            Some(SqlValue::Text(text)) => Some(text.as_str()),
+            _ => None,
+        };
+        let age = || match get("modified_at") {
+            Some(SqlValue::Integer(modified_at)) => Some(Duration::from_secs(
+                (unix_seconds(now) - modified_at).max(0) as u64,
+            )),
+            _ => None,
+        };
+        match self {
+            Filter::Glob(column, matcher) => {
+                text(column).is_some_and(|text| matcher.is_match(text))
+            }
+            Filter::Equals(column, value) => text(column) == Some(value.as_str()),
+            Filter::Contains(column, value) => {
+                text(column).is_some_and(|text| text.to_lowercase().contains(value))
+            }
+            Filter::Domain(domain) => {
+                text("target")
+                    .and_then(host_and_path)
+                    .is_some_and(|(host, _)| {
+                        host == *domain || host.ends_with(&format!(".{}", domain))
+                    })
+            }
+            Filter::OlderThan(max) => age().is_some_and(|age| age > *max),
+            Filter::NewerThan(max) => age().is_some_and(|age| age <= *max),
+        }
+    }
+}
+
+/// A query over a database written by `check --output-db`: a subject
+/// followed by `key:value` filters that must all match, e.g.
+/// `links from:docs/api/** kind:external` or `orphans older:90d`.
+#[derive(Debug, Clone)]
+pub struct Query {
+    subject: Subject,
+    filters: Vec<Filter>,
+}
+
+impl Query {
+    pub fn subject(&self) -> Subject {
+        self.subject
+    }
+}
+
+impl FromStr for Query {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let mut words = text.split_whitespace();
+        let subject = words.next().ok_or("Empty query")?;
+        let subject = Subject::ALL
+            .into_iter()
+            .find(|known| known.name() == subject)
+            .ok_or_else(|| {
+                format!(
+                    "Unknown query `{}` (use {})",
+                    subject,
+                    Subject::ALL.map(Subject::name).join(", ")
+                )
+            })?;
+
+        let mut filters = Vec::new();
+        for word in words {
+            let (key, value) = word
+                .split_once(':')
+                .filter(|(_, value)| !value.is_empty())
+                .ok_or_else(|| format!("Expected a `key:value` filter, found `{}`", word))?;
+            let Some(&key) = subject.filters().iter().find(|known| **known == key) else {
+                return Err(format!(
+                    "`{}` has no `{}:` filter (use {})",
+                    subject,
+                    key,
+                    subject
+                        .filters()
+                        .iter()
+                        .map(|key| format!("{}:", key))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ));
+            };
+            let glob = |column| {
+                Glob::new(value)
+                    .map(|glob| Filter::Glob(column, glob.compile_matcher()))
+                    .map_err(|e| format!("Invalid pattern `{}`: {}", value, e))
+            };
+            filters.push(match key {
+                "from" => glob("source")?,
+                "to" => glob("target")?,
+                "path" => glob("path")?,
+                "kind" => Filter::Equals("kind", value.to_string()),
+                "reason" => Filter::Contains("reason", value.to_lowercase()),
+                "domain" => Filter::Domain(value.trim_start_matches("*.").to_lowercase()),
+                "older" => Filter::OlderThan(parse_age(value)?),
+                _ => Filter::NewerThan(parse_age(value)?),
+            });
+        }
+        Ok(Self { subject, filters })
+    }
+}
+
+/// An age such as `12h`, `90d` or `2w`.
+fn parse_age(text: &str) -> Result<Duration, String> {
+    let split = text
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(text.len());
+    let (number, unit) = text.split_at(split);
+    let number: u64 = number
+        .parse()
+        .map_err(|_| format!("Invalid age `{}`", text))?;
+    let hours = match unit {
+        "h" => number,
+        "d" => number * 24,
+        "w" => number * 24 * 7,
+        _ => return Err(format!("Invalid age `{}` (use h, d or w, e.g. 90d)", text)),
+    };
+    Ok(Duration::from_secs(hours * 3600))
+}
+
+/// The rows a query matched, in a stable order.
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct QueryResult {
+    pub columns: Vec<String>,
+    pub rows: Vec<Vec<Value>>,
+}
+
+impl Database {
+    /// The id of the most recently recorded run, if any.
+    pub fn latest_run(&self) -> Result<Option<i64>, Box<dyn std::error::Error>> {
+        Ok(self
+            .conn
+            .query_row("SELECT MAX(id) FROM runs", [], |row| row.get(0))?)
+    }
+
+    /// Runs `query` against run `run_id`. `now` is what `older:` and
+    /// `newer:` count from.
+    pub fn query(
+        &self,
+        query: &Query,
+        run_id: i64,
+        now: SystemTime,
+    ) -> Result<QueryResult, Box<dyn std::error::Error>> {
+        let mut statement = self.conn.prepare(query.subject.sql())?;
+        let columns: Vec<String> = statement
+            .column_names()
+            .into_iter()
+            .map(String::from)
+            .collect();
+        let rows = statement.query_map([run_id], |row| {
+            (0..columns.len())
+                .map(|i| row.get::<_, SqlValue>(i))
+                .collect::<Result<Vec<_>, _>>()
+        })?;
+
+        let mut result = QueryResult {
+            columns: columns.clone(),
+            rows: Vec::new(),
+        };
+        for row in rows {
+            let row = row?;
+            if query
+                .filters
+                .iter()
+                .all(|filter| filter.matches(&columns, &row, now))
+            {
+                result.rows.push(
+                    columns
+                        .iter()
+                        .zip(row.iter())
+                        .map(|(column, value)| json_value(column, value))
+                        .collect(),
+                );
+            }
+        }
+        Ok(result)
+    }
+}
+
+/// Flags become booleans and Unix times RFC 3339 timestamps.
+fn json_value(column: &str, value: &SqlValue) -> Value {
+    match (column, value) {
+        ("orphan", SqlValue::Integer(flag)) => Value::Bool(*flag != 0),
+        ("modified_at", SqlValue::Integer(seconds)) => {
+            Value::String(rfc3339(UNIX_EPOCH + Duration::from_secs(*seconds as u64)))
+        }
+        (_, SqlValue::Integer(number)) => Value::from(*number),
+        (_, SqlValue::Real(number)) => Value::from(*number),
+        (_, SqlValue::Text(text)) => Value::String(text.clone()),
+        (_, SqlValue::Null | SqlValue::Blob(_)) => Value::Null,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::Findings;
+    use crate::LinkAnalyzer;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_parse_query() {
+        assert!("links from:docs/api/** kind:external"
+            .parse::<Query>()
+            .is_ok());
+        assert!("orphans older:90d".parse::<Query>().is_ok());
+        assert_eq!(
+            "pages".parse::<Query>().unwrap_err(),
+            "Unknown query `pages` (use links, documents, orphans, findings, edges)"
+        );
+        assert_eq!(
+            "links older:90d".parse::<Query>().unwrap_err(),
+            "`links` has no `older:` filter (use from:, to:, kind:, domain:)"
+        );
+        assert_eq!(
+            "orphans older:3mo".parse::<Query>().unwrap_err(),
+            "Invalid age `3mo` (use h, d or w, e.g. 90d)"
+        );
+        assert!("links from".parse::<Query>().is_err());
+    }
+
+    #[test]
+    fn test_query() {
+        let temp_dir = TempDir::new().unwrap();
+        let docs = temp_dir.path().join("docs");
+        fs::create_dir_all(docs.join("api")).unwrap();
+        fs::write(docs.join("README.md"), "# Docs\n\n[API](api/index.md)\n").unwrap();
+        fs::write(
+            docs.join("api/index.md"),
+            "# API\n\n[Spec](https://spec.example.com/v1)\n[Home](../README.md)\n",
+        )
+        .unwrap();
+        fs::write(
+            docs.join("guide.md"),
+            "# Guide\n\n[Repo](https://github.com/org/repo)\n",
+        )
+        .unwrap();
+        fs::write(docs.join("stale.md"), "# Stale\n").unwrap();
+
+        let mut analyzer = LinkAnalyzer::new(docs);
+        analyzer.analyze_directory().unwrap();
+        let mut database = Database::open(&temp_dir.path().join("db.sqlite")).unwrap();
+        let run_id = database.record_run(&analyzer, Findings::default()).unwrap();
+        assert_eq!(database.latest_run().unwrap(), Some(run_id));
+
+        let now = SystemTime::now();
+        let run = |query: &str| {
+            database
+                .query(&query.parse().unwrap(), run_id, now)
+                .unwrap()
+        };
+
+        let external = run("links from:api/** kind:external");
+        assert_eq!(
+            external.columns,
+            vec!["source", "line", "text", "target", "kind"]
+        );
+        assert_eq!(
+            external.rows,
+            vec![vec![
+                Value::from("api/index.md"),
+                Value::from(3),
+                Value::from("Spec"),
+                Value::from("https://spec.example.com/v1"),
+                Value::from("external"),
+            ]]
+        );
+        assert_eq!(run("links domain:example.com").rows.len(), 1);
+        assert_eq!(run("links domain:github.com").rows[0][0], "guide.md");
+
+        let orphans = run("orphans");
+        let paths: Vec<&Value> = orphans.rows.iter().map(|row| &row[0]).collect();
+        assert_eq!(paths, vec!["guide.md", "stale.md"]);
+        assert!(run("orphans older:90d").rows.is_empty());
+        let later = now + Duration::from_secs(91 * 24 * 3600);
+        let old = database
+            .query(&"orphans older:90d".parse().unwrap(), run_id, later)
+            .unwrap();
+        assert_eq!(old.rows.len(), 2);
+
+        assert_eq!(
+            run("edges to:README.md").rows,
+            vec![vec![Value::from("api/index.md"), Value::from("README.md")]]
+        );
+    }
+}
@@ -0,0 +1,180 @@
+use crate::external::{ExternalResponse, Verdict};
+use crate::LinkAnalyzer;
+use crate::MarkdownLink;
+use std::collections::BTreeMap;
+
+/// File name of the package `CheckCache` in the cache directory.
+pub const CACHE_FILE_NAME: &str = "packages.json";
+
+/// A package registry whose package pages can be linked to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[non_exhaustive]
+pub enum Registry {
+    /// crates.io, also behind docs.rs links.
+    Crates,
+    Npm,
+    PyPi,
+}
+
+impl Registry {
+    pub fn name(self) -> &'static str {
+        match self {
+            Registry::Crates => "crates.io",
+            Registry::Npm => "npm",
+            Registry::PyPi => "PyPI",
+        }
+    }
+
+    /// API endpoint that answers 404 for a package that does not exist; the
+    /// package pages themselves often answer 200 either way.
+    pub fn api_url(self, package: &str) -> String {
+        match self {
+            Registry::Crates => format!("https://crates.io/api/v1/crates/{}", package),
+            Registry::Npm => format!("https://registry.npmjs.org/{}", package.replace('/', "%2F")),
+            Registry::PyPi => format!("https://pypi.org/pypi/{}/json", package),
+        }
+    }
+
+    /// Judges the API's answer for `package`.
+    pub fn verdict(self, package: &str, response: &ExternalResponse) -> Verdict {
+        match response.status {
+            Some(404) => Verdict::Error(format!(
+                "Package `{}` does not exist on {}",
+                package,
+                self.name()
+            )),
+            _ => response.verdict(false),
+        }
+    }
+}
+
+/// Docs.rs paths that are site pages rather than crates.
+const DOCS_RS_PAGES: &[&str] = &["about", "releases", "crate", "-", "search"];
+
+/// The registry and package a package page URL points at, such as
+/// `https://crates.io/crates/serde`, `https://docs.rs/serde/latest/serde/`,
+/// `https://www.npmjs.com/package/@scope/name` or
+/// `https://pypi.org/project/requests/2.0/`.
+pub fn package_link(url: &str) -> Option<(Registry, String)> {
+    let rest = url
+        .strip_prefix("https://")
+        .or_else(|| url.strip_prefix("http://"))?;
+    let rest = rest.split(['?', '#']).next().unwrap_or(rest);
+    let mut segments = rest.split('/').filter(|segment| !segment.is_empty());
+    let host = segments.next()?.to_lowercase();
+    let segments: Vec<&str> = segments.collect();
+
+    let (registry, package) = match (host.as_str(), segments.as_slice()) {
+        ("crates.io", ["crates", name, ..]) => (Registry::Crates, name.to_string()),
+        ("docs.rs", ["crate", name, ..]) => (Registry::Crates, name.to_string()),
+        ("docs.rs", [name, ..]) if !DOCS_RS_PAGES.contains(name) => {
+            (Registry::Crates, name.to_string())
+        }
+        ("www.npmjs.com" | "npmjs.com", ["package", scope, name, ..]) if scope.starts_with('@') => {
+            (Registry::Npm, format!("{}/{}", scope, name))
+        }
+        ("www.npmjs.com" | "npmjs.com", ["package", name, ..]) => (Registry::Npm, name.to_string()),
+        ("pypi.org" | "pypi.python.org", ["project", name, ..]) => {
+            (Registry::PyPi, name.to_string())
+        }
+        _ => return None,
+    };
+    Some((registry, package))
+}
+
+impl LinkAnalyzer {
+    /// External links to package pages, grouped by the package they name so
+    /// each one is looked up once.
+    pub fn package_links(&self) -> BTreeMap<(Registry, String), Vec<MarkdownLink>> {
+        let mut packages: BTreeMap<(Registry, String), Vec<MarkdownLink>> = BTreeMap::new();
+        for link in self.external_links() {
+            if let Some(package) = package_link(&link.target) {
+                packages.entry(package).or_default().push(link);
+            }
+        }
+        packages
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vfs::MemoryFileSystem;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_package_link() {
+        let package = package_link;
+        assert_eq!(
+            package("https://crates.io/crates/serde"),
+            Some((Registry::Crates, "serde".to_string()))
+        );
+        assert_eq!(
+            package("https://docs.rs/serde_json/latest/serde_json/fn.from_str.html"),
+            Some((Registry::Crates, "serde_json".to_string()))
+        );
+        assert_eq!(
+            package("https://docs.rs/crate/tokio/1.0.0"),
+            Some((Registry::Crates, "tokio".to_string()))
+        );
+        assert_eq!(
+            package("https://www.npmjs.com/package/@types/node?activeTab=versions"),
+            Some((Registry::Npm, "@types/node".to_string()))
+        );
+        assert_eq!(
+            package("https://pypi.org/project/requests/2.31.0/"),
+            Some((Registry::PyPi, "requests".to_string()))
+        );
+        assert_eq!(package("https://docs.rs/releases"), None);
+        assert_eq!(package("https://crates.io/search?q=serde"), None);
+        assert_eq!(package("https://example.com/crates/serde"), None);
+
+        assert_eq!(
+            Registry::Npm.api_url("@types/node"),
+            "https://registry.npmjs.org/@types%2Fnode"
+        );
+    }
+
+    #[test]
+    fn test_verdict() {
+        let response = |status| ExternalResponse {
+            url: Registry::Crates.api_url("serde"),
+            status: Some(status),
+            location: None,
+            error: None,
+        };
+        assert_eq!(
+            Registry::Crates.verdict("serde", &response(200)),
+            Verdict::Ok
+        );
+        assert_eq!(
+            Registry::Crates.verdict("serde", &response(404)),
+            Verdict::Error("Package `serde` does not exist on crates.io".to_string())
+        );
+    }
+
+    #[test]
+    fn test_package_links() {
+        let mut fs = MemoryFileSystem::new();
+        fs.insert(
+            "docs/README.md",
+            "[serde](https://crates.io/crates/serde) [docs](https://docs.rs/serde)\n\
+             [left-pad](https://www.npmjs.com/package/left-pad) [home](https://serde.rs/)",
+        );
+        let mut analyzer = LinkAnalyzer::new(PathBuf::from("docs")).with_filesystem(fs);
+        analyzer.analyze_directory().unwrap();
+
+        let packages = analyzer.package_links();
+        let summary: Vec<(Registry, &str, usize)> = packages
+            .iter()
+            .map(|((registry, name), links)| (*registry, name.as_str(), links.len()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (Registry::Crates, "serde", 2),
+                (Registry::Npm, "left-pad", 1)
+            ]
+        );
+    }
+}
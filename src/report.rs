@@ -0,0 +1,759 @@
+use crate::graph::GraphMetrics;
+use crate::{anchors, BrokenLink, Config, LinkAnalyzer, LinkKind, MarkdownLink};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::env;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Where and how a report was produced, so archived reports can be audited
+/// and compared across tool versions and configurations.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct ReportMetadata {
+    pub tool_version: String,
+    /// Hash of the effective configuration (file, profile and environment).
+    pub config_hash: String,
+    /// Branch checked out in the analyzed directory, if any.
+    pub git_ref: Option<String>,
+    pub git_sha: Option<String>,
+    pub hostname: Option<String>,
+    /// UTC time the report was generated, e.g. `2024-05-01T12:30:00Z`.
+    pub generated_at: String,
+}
+
+/// A serialized report: its own fields with `metadata` alongside.
+#[derive(Debug, Clone, Serialize)]
+pub struct Report<T> {
+    pub metadata: ReportMetadata,
+    #[serde(flatten)]
+    pub body: T,
+}
+
+impl ReportMetadata {
+    /// Metadata for a report on the documents in `path` analyzed with `config`.
+    pub fn collect(path: &Path, config: &Config) -> Self {
+        #[cfg(feature = "git")]
+        let head = crate::git::head(path);
+        #[cfg(not(feature = "git"))]
+        let head: Option<(String, Option<String>)> = {
+            let _ = path;
+            None
+        };
+        Self {
+            tool_version: env!("CARGO_PKG_VERSION").to_string(),
+            config_hash: config_hash(config),
+            git_ref: head.as_ref().and_then(|(_, branch)| branch.clone()),
+            git_sha: head.map(|(sha, _)| sha),
+            hostname: hostname(),
+            generated_at: rfc3339(SystemTime::now()),
+        }
+    }
+}
+
+/// FNV-1a hash of the configuration's JSON form, which lists keys in a fixed
+/// order, so equal settings give equal hashes on every platform and release.
+pub fn config_hash(config: &Config) -> String {
+    let json = serde_json::to_string(config).unwrap_or_default();
+    let hash = json.bytes().fold(0xcbf2_9ce4_8422_2325u64, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0000_0100_0000_01b3)
+    });
+    format!("{:016x}", hash)
+}
+
+fn hostname() -> Option<String> {
+    env::var("HOSTNAME")
+        .or_else(|_| env::var("COMPUTERNAME"))
+        .ok()
+        .or_else(|| fs::read_to_string("/etc/hostname").ok())
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty())
+}
+
+/// UTC time as `2024-05-01T12:30:00Z`.
+pub fn rfc3339(time: SystemTime) -> String {
+    let seconds = time.duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs());
+    let (days, rest) = (seconds / 86_400, seconds % 86_400);
+    // Civil date from days since the epoch (Howard Hinnant's algorithm).
+    let z = days + 719_468;
+    let era = z / 146_097;
+    let day_of_era = z % 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + u64::from(month <= 2);
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year,
+        month,
+        day,
+        rest / 3600,
+        rest % 3600 / 60,
+        rest % 60
+    )
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct DocumentStats {
+    pub total_links: usize,
+    pub internal_links: usize,
+    pub external_links: usize,
+    pub broken_links: usize,
+    /// Other paths the document was found under, through symlinks.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub aliases: Vec<PathBuf>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct DirectoryStats {
+    pub documents: usize,
+    pub total_links: usize,
+    pub internal_links: usize,
+    pub external_links: usize,
+    pub broken_links: usize,
+    pub orphaned_documents: usize,
+}
+
+impl DirectoryStats {
+    /// Broken links among the directory's internal links.
+    pub fn broken_density(&self) -> f64 {
+        if self.internal_links == 0 {
+            0.0
+        } else {
+            self.broken_links as f64 / self.internal_links as f64
+        }
+    }
+}
+
+/// Broken-link density at which a heatmap cell is fully red.
+pub const HEATMAP_SATURATION: f64 = 0.1;
+
+/// Directories ordered for a heatmap: highest broken-link density first,
+/// then most broken links, then by name.
+pub fn heatmap_rows(
+    directories: &BTreeMap<String, DirectoryStats>,
+) -> Vec<(&String, &DirectoryStats)> {
+    let mut rows: Vec<_> = directories.iter().collect();
+    rows.sort_by(|a, b| {
+        b.1.broken_density()
+            .total_cmp(&a.1.broken_density())
+            .then(b.1.broken_links.cmp(&a.1.broken_links))
+            .then(a.0.cmp(b.0))
+    });
+    rows
+}
+
+/// A self-contained HTML page showing `directories` as a heatmap, each
+/// colored from green to red by broken-link density.
+#[cfg(feature = "html-report")]
+pub fn heatmap_html(directories: &BTreeMap<String, DirectoryStats>) -> String {
+    let escape = |text: &str| {
+        text.replace('&', "&amp;")
+            .replace('<', "&lt;")
+            .replace('>', "&gt;")
+            .replace('"', "&quot;")
+    };
+
+    let mut html = String::from(
+        "<!DOCTYPE html>
+<html lang=\"en\">
+<head>
+<meta charset=\"utf-8\">
+<title>Broken-link heatmap</title>
+<style>
+body { font-family: system-ui, sans-serif; margin: 2rem; }
+.grid { display: grid; grid-template-columns: repeat(auto-fill, minmax(14rem, 1fr)); gap: 0.5rem; }
+.cell { border-radius: 0.4rem; padding: 0.6rem 0.8rem; }
+.cell strong { display: block; overflow-wrap: anywhere; }
+.cell span { font-size: 0.85rem; }
+</style>
+</head>
+<body>
+<h1>Broken-link heatmap</h1>
+<div class=\"grid\">
+",
+    );
+    for (directory, stats) in heatmap_rows(directories) {
+        let density = stats.broken_density();
+        let hue = 120.0 * (1.0 - (density / HEATMAP_SATURATION).min(1.0));
+        html.push_str(&format!(
+            "<div class=\"cell\" style=\"background: hsl({:.0}, 70%, 80%)\" title=\"{} documents, {} orphaned\"><strong>{}</strong><span>{:.1}% broken ({} of {} links)</span></div>\n",
+            hue,
+            stats.documents,
+            stats.orphaned_documents,
+            escape(directory),
+            density * 100.0,
+            stats.broken_links,
+            stats.internal_links
+        ));
+    }
+    html.push_str("</div>\n</body>\n</html>\n");
+    html
+}
+
+/// A heading anchor and the links that point at it, from
+/// `LinkAnalyzer::anchor_usage`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[non_exhaustive]
+pub struct AnchorUsage {
+    pub document: PathBuf,
+    pub anchor: String,
+    pub heading: String,
+    pub level: usize,
+    pub line_number: usize,
+    /// Links to the anchor, including ones from its own document.
+    pub references: Vec<AnchorReference>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[non_exhaustive]
+pub struct AnchorReference {
+    pub file_path: PathBuf,
+    pub line_number: usize,
+}
+
+/// A single 0-100 number summarizing documentation health, for dashboards
+/// and `stats --min-score` gates, with the ratios it is computed from.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct HealthScore {
+    pub score: f64,
+    /// Links per document.
+    pub link_density: f64,
+    /// Broken links among internal links.
+    pub broken_ratio: f64,
+    /// Orphaned documents among all documents.
+    pub orphan_ratio: f64,
+    /// Links to missing headings among links with a `#fragment`.
+    pub stale_anchor_ratio: f64,
+    /// Mean number of links to follow from the README to each document
+    /// reachable from it; 0 without a README.
+    pub average_click_depth: f64,
+}
+
+impl HealthScore {
+    /// Points each ratio takes off the score when it reaches 1.
+    pub const BROKEN_WEIGHT: f64 = 50.0;
+    pub const ORPHAN_WEIGHT: f64 = 20.0;
+    pub const STALE_ANCHOR_WEIGHT: f64 = 15.0;
+    /// Points taken off for deep navigation: nothing up to an average click
+    /// depth of 2, all of them from a depth of 10.
+    pub const DEPTH_WEIGHT: f64 = 15.0;
+
+    fn compute(mut self) -> Self {
+        let depth_penalty = ((self.average_click_depth - 2.0) / 8.0).clamp(0.0, 1.0);
+        let score = 100.0
+            - Self::BROKEN_WEIGHT * self.broken_ratio
+            - Self::ORPHAN_WEIGHT * self.orphan_ratio
+            - Self::STALE_ANCHOR_WEIGHT * self.stale_anchor_ratio
+            - Self::DEPTH_WEIGHT * depth_penalty;
+        self.score = (score.clamp(0.0, 100.0) * 10.0).round() / 10.0;
+        self
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct LinkStatistics {
+    pub total_documents: usize,
+    pub total_links: usize,
+    pub internal_links: usize,
+    pub external_links: usize,
+    pub broken_links: usize,
+    pub orphaned_documents: usize,
+    pub health: HealthScore,
+    pub document_stats: HashMap<PathBuf, DocumentStats>,
+    /// Shape of the link graph, with `stats --detailed`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub graph: Option<GraphMetrics>,
+}
+
+/// Link counts of some documents, gathered in one pass over their links.
+#[derive(Debug, Clone, Copy, Default)]
+struct Counters {
+    total: usize,
+    internal: usize,
+    external: usize,
+    /// Internal links with a `#fragment`.
+    with_fragment: usize,
+}
+
+impl Counters {
+    /// Counts `links`, classifying each target once across calls sharing
+    /// `kinds`: classifying tries the include directive patterns, and trees
+    /// link to the same targets over and over.
+    fn of<'a>(links: &'a [MarkdownLink], kinds: &mut HashMap<&'a str, LinkKind>) -> Self {
+        let mut counters = Self::default();
+        for link in links {
+            counters.total += 1;
+            let kind = *kinds
+                .entry(&link.target)
+                .or_insert_with_key(|target| LinkKind::classify(target));
+            if kind == LinkKind::External {
+                counters.external += 1;
+            } else {
+                counters.internal += 1;
+                if link.target.contains('#') {
+                    counters.with_fragment += 1;
+                }
+            }
+        }
+        counters
+    }
+
+    fn add(&mut self, other: Self) {
+        self.total += other.total;
+        self.internal += other.internal;
+        self.external += other.external;
+        self.with_fragment += other.with_fragment;
+    }
+}
+
+impl LinkAnalyzer {
+    pub fn get_statistics(&self) -> LinkStatistics {
+        let mut stats = LinkStatistics {
+            total_documents: self.documents.len(),
+            ..Default::default()
+        };
+
+        let mut kinds = HashMap::new();
+        let mut totals = Counters::default();
+        for (doc_path, links) in &self.documents {
+            let counters = Counters::of(links, &mut kinds);
+            totals.add(counters);
+            stats.document_stats.insert(
+                doc_path.clone(),
+                DocumentStats {
+                    total_links: counters.total,
+                    internal_links: counters.internal,
+                    external_links: counters.external,
+                    broken_links: 0,
+                    aliases: self.aliases(doc_path).to_vec(),
+                },
+            );
+        }
+        stats.total_links = totals.total;
+        stats.internal_links = totals.internal;
+        stats.external_links = totals.external;
+
+        let broken_links = self.find_broken_links();
+        for broken_link in &broken_links {
+            if let Some(doc_stats) = stats.document_stats.get_mut(&broken_link.link.file_path) {
+                doc_stats.broken_links += 1;
+            }
+        }
+
+        stats.broken_links = broken_links.len();
+        stats.orphaned_documents = self.find_orphaned_documents().len();
+        stats.health = self.health_score(&stats, totals.with_fragment, &broken_links);
+
+        stats
+    }
+
+    /// Every heading anchor of the analyzed documents with the internal links
+    /// that reach it, most linked first; never-linked anchors come last.
+    pub fn anchor_usage(&self) -> Vec<AnchorUsage> {
+        let mut documents: Vec<&PathBuf> = self.documents.keys().collect();
+        documents.sort();
+
+        let mut usage = Vec::new();
+        // (document key, anchor) to its entry in `usage`.
+        let mut index: HashMap<(PathBuf, String), usize> = HashMap::new();
+        for document in &documents {
+            let key = self.document_key(document);
+            let Some(headings) = self.document_headings(&key) else {
+                continue;
+            };
+            for (heading, anchor) in headings.headings.iter().zip(&headings.anchors) {
+                index
+                    .entry((key.clone(), anchor.clone()))
+                    .or_insert(usage.len());
+                usage.push(AnchorUsage {
+                    document: document.to_path_buf(),
+                    anchor: anchor.clone(),
+                    heading: heading.text.clone(),
+                    level: heading.level,
+                    line_number: heading.line_number,
+                    references: Vec::new(),
+                });
+            }
+        }
+
+        for document in &documents {
+            for link in &self.documents[*document] {
+                if LinkKind::classify(&link.target) == LinkKind::External {
+                    continue;
+                }
+                let resolution = self.resolve(document, &link.target);
+                let (Some(resolved), Some(fragment), Some(true)) = (
+                    resolution.resolved,
+                    resolution.fragment,
+                    resolution.anchor_found,
+                ) else {
+                    continue;
+                };
+                let entry = [
+                    fragment.clone(),
+                    fragment.to_lowercase(),
+                    anchors::github_slug(&fragment),
+                ]
+                .into_iter()
+                .find_map(|anchor| index.get(&(resolved.clone(), anchor)));
+                if let Some(&entry) = entry {
+                    usage[entry].references.push(AnchorReference {
+                        file_path: link.file_path.clone(),
+                        line_number: link.line_number,
+                    });
+                }
+            }
+        }
+
+        // Stable, so equally linked anchors stay in document order.
+        usage.sort_by_key(|usage| std::cmp::Reverse(usage.references.len()));
+        usage
+    }
+
+    fn health_score(
+        &self,
+        stats: &LinkStatistics,
+        with_fragment: usize,
+        broken_links: &[BrokenLink],
+    ) -> HealthScore {
+        let ratio = |part: usize, whole: usize| {
+            if whole == 0 {
+                0.0
+            } else {
+                part as f64 / whole as f64
+            }
+        };
+
+        let stale_anchors = broken_links
+            .iter()
+            .filter(|broken| {
+                self.resolve(&broken.link.file_path, &broken.link.target)
+                    .anchor_found
+                    == Some(false)
+            })
+            .count();
+
+        let roots = self.root_documents();
+        let depths: Vec<usize> = self
+            .link_graph()
+            .depths(&roots)
+            .into_values()
+            .filter(|depth| *depth > 0)
+            .collect();
+        let average_click_depth = if depths.is_empty() {
+            0.0
+        } else {
+            depths.iter().sum::<usize>() as f64 / depths.len() as f64
+        };
+
+        HealthScore {
+            link_density: ratio(stats.total_links, stats.total_documents),
+            broken_ratio: ratio(stats.broken_links, stats.internal_links),
+            orphan_ratio: ratio(stats.orphaned_documents, stats.total_documents),
+            stale_anchor_ratio: ratio(stale_anchors, with_fragment),
+            average_click_depth,
+            ..HealthScore::default()
+        }
+        .compute()
+    }
+
+    /// Rolls statistics up per directory, keyed by the first `depth` components
+    /// of each document's directory relative to the base path (`.` for the root).
+    pub fn get_directory_statistics(&self, depth: usize) -> BTreeMap<String, DirectoryStats> {
+        let mut directories: BTreeMap<String, DirectoryStats> = BTreeMap::new();
+
+        let mut kinds = HashMap::new();
+        for (doc_path, links) in &self.documents {
+            let dir_stats = directories
+                .entry(self.directory_key(doc_path, depth))
+                .or_default();
+            let counters = Counters::of(links, &mut kinds);
+            dir_stats.documents += 1;
+            dir_stats.total_links += counters.total;
+            dir_stats.internal_links += counters.internal;
+            dir_stats.external_links += counters.external;
+        }
+
+        for broken_link in self.find_broken_links() {
+            let key = self.directory_key(&broken_link.link.file_path, depth);
+            directories.entry(key).or_default().broken_links += 1;
+        }
+        for orphan in self.find_orphaned_documents() {
+            let key = self.directory_key(&orphan, depth);
+            directories.entry(key).or_default().orphaned_documents += 1;
+        }
+
+        directories
+    }
+
+    pub(crate) fn directory_key(&self, doc_path: &Path, depth: usize) -> String {
+        let relative = doc_path.strip_prefix(&self.base_path).unwrap_or(doc_path);
+        let components: Vec<String> = relative
+            .parent()
+            .map(|parent| {
+                parent
+                    .components()
+                    .filter_map(|c| match c {
+                        Component::Normal(name) => Some(name.to_string_lossy().into_owned()),
+                        _ => None,
+                    })
+                    .take(depth)
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        if components.is_empty() {
+            ".".to_string()
+        } else {
+            components.join("/")
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vfs;
+    use std::io::Write;
+    use std::time::Duration;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_rfc3339() {
+        assert_eq!(rfc3339(UNIX_EPOCH), "1970-01-01T00:00:00Z");
+        let leap_day = UNIX_EPOCH + Duration::from_secs(1_709_210_096);
+        assert_eq!(rfc3339(leap_day), "2024-02-29T12:34:56Z");
+    }
+
+    #[test]
+    fn test_config_hash() {
+        let default = config_hash(&Config::default());
+        assert_eq!(default.len(), 16);
+        assert_eq!(default, config_hash(&Config::default()));
+
+        let config = Config {
+            wiki_links: true,
+            ..Config::default()
+        };
+        assert_ne!(default, config_hash(&config));
+    }
+
+    #[test]
+    fn test_report_flattens_body() {
+        let report = Report {
+            metadata: ReportMetadata::collect(Path::new("."), &Config::default()),
+            body: std::collections::BTreeMap::from([("fixes", Vec::<String>::new())]),
+        };
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["fixes"], serde_json::json!([]));
+        assert_eq!(json["metadata"]["tool_version"], env!("CARGO_PKG_VERSION"));
+    }
+
+    #[test]
+    fn test_get_directory_statistics() {
+        let temp_dir = TempDir::new().unwrap();
+        let base_path = temp_dir.path();
+
+        let guides_dir = base_path.join("docs").join("guides");
+        fs::create_dir_all(&guides_dir).unwrap();
+
+        let readme_path = base_path.join("README.md");
+        let mut readme = fs::File::create(&readme_path).unwrap();
+        writeln!(readme, "[Guide](./docs/guides/a.md)").unwrap();
+        writeln!(readme, "[Rust](https://www.rust-lang.org)").unwrap();
+
+        let a_path = guides_dir.join("a.md");
+        let mut a = fs::File::create(&a_path).unwrap();
+        writeln!(a, "[Broken](./missing.md)").unwrap();
+
+        let b_path = guides_dir.join("b.md");
+        let mut b = fs::File::create(&b_path).unwrap();
+        writeln!(b, "# Orphaned").unwrap();
+
+        let mut analyzer = LinkAnalyzer::new(base_path.to_path_buf());
+        analyzer.analyze_directory().unwrap();
+
+        let by_dir = analyzer.get_directory_statistics(1);
+        assert_eq!(by_dir.keys().collect::<Vec<_>>(), vec![".", "docs"]);
+        assert_eq!(by_dir["."].external_links, 1);
+        assert_eq!(
+            by_dir["docs"],
+            DirectoryStats {
+                documents: 2,
+                total_links: 1,
+                internal_links: 1,
+                external_links: 0,
+                broken_links: 1,
+                orphaned_documents: 1,
+            }
+        );
+
+        let by_dir = analyzer.get_directory_statistics(2);
+        assert!(by_dir.contains_key("docs/guides"));
+    }
+
+    #[test]
+    fn test_get_statistics() {
+        let temp_dir = TempDir::new().unwrap();
+        let base_path = temp_dir.path();
+
+        let doc1_path = base_path.join("doc1.md");
+        let mut doc1 = fs::File::create(&doc1_path).unwrap();
+        writeln!(doc1, "[Internal link](./doc2.md)").unwrap();
+        writeln!(doc1, "[External link](https://example.com)").unwrap();
+
+        let doc2_path = base_path.join("doc2.md");
+        let mut doc2 = fs::File::create(&doc2_path).unwrap();
+        writeln!(doc2, "[Another internal](./doc1.md)").unwrap();
+        writeln!(doc2, "[Broken link](./missing.md)").unwrap();
+
+        let orphaned_path = base_path.join("orphaned.md");
+        let mut orphaned = fs::File::create(&orphaned_path).unwrap();
+        writeln!(orphaned, "# Orphaned").unwrap();
+
+        let mut analyzer = LinkAnalyzer::new(base_path.to_path_buf());
+        analyzer.analyze_directory().unwrap();
+
+        let stats = analyzer.get_statistics();
+
+        assert_eq!(stats.total_documents, 3);
+        assert_eq!(stats.total_links, 4);
+        assert_eq!(stats.internal_links, 3);
+        assert_eq!(stats.external_links, 1);
+        assert_eq!(stats.broken_links, 1);
+        assert_eq!(stats.orphaned_documents, 1);
+
+        assert_eq!(stats.document_stats.len(), 3);
+        assert_eq!(stats.document_stats[&doc1_path].internal_links, 1);
+        assert_eq!(stats.document_stats[&doc1_path].external_links, 1);
+        assert_eq!(stats.document_stats[&doc2_path].broken_links, 1);
+    }
+
+    #[test]
+    fn test_heatmap() {
+        let stats = |internal_links, broken_links| DirectoryStats {
+            documents: 1,
+            internal_links,
+            broken_links,
+            ..DirectoryStats::default()
+        };
+        let directories = BTreeMap::from([
+            (".".to_string(), stats(10, 0)),
+            ("api".to_string(), stats(10, 1)),
+            ("guides".to_string(), stats(4, 2)),
+            ("<empty>".to_string(), stats(0, 0)),
+        ]);
+
+        let order: Vec<&str> = heatmap_rows(&directories)
+            .into_iter()
+            .map(|(directory, _)| directory.as_str())
+            .collect();
+        assert_eq!(order, vec!["guides", "api", ".", "<empty>"]);
+    }
+
+    #[cfg(feature = "html-report")]
+    #[test]
+    fn test_heatmap_html() {
+        let stats = |internal_links, broken_links| DirectoryStats {
+            documents: 1,
+            internal_links,
+            broken_links,
+            ..DirectoryStats::default()
+        };
+        let directories = BTreeMap::from([
+            (".".to_string(), stats(10, 0)),
+            ("api".to_string(), stats(10, 1)),
+            ("guides".to_string(), stats(4, 2)),
+            ("<empty>".to_string(), stats(0, 0)),
+        ]);
+
+        let html = heatmap_html(&directories);
+        assert!(html.contains("hsl(0, 70%, 80%)\" title=\"1 documents, 0 orphaned\"><strong>guides</strong><span>50.0% broken (2 of 4 links)"));
+        assert!(html
+            .contains("hsl(0, 70%, 80%)\" title=\"1 documents, 0 orphaned\"><strong>api</strong>"));
+        assert!(html.contains(
+            "hsl(120, 70%, 80%)\" title=\"1 documents, 0 orphaned\"><strong>&lt;empty&gt;</strong>"
+        ));
+    }
+
+    #[test]
+    fn test_anchor_usage() {
+        let mut vfs = vfs::MemoryFileSystem::new();
+        vfs.insert(
+            "docs/README.md",
+            "# Home\n\n[Install](./guide.md#install)\n[Again](guide.md#Install)\n[Top](#home)\n[Gone](./guide.md#removed)",
+        );
+        vfs.insert(
+            "docs/guide.md",
+            "# Guide\n\n## Install\n\n## Legacy setup\n\nSee [install](#install).",
+        );
+
+        let mut analyzer = LinkAnalyzer::new(PathBuf::from("docs")).with_filesystem(vfs);
+        analyzer.analyze_directory().unwrap();
+        let usage = analyzer.anchor_usage();
+
+        let summary: Vec<(&str, usize)> = usage
+            .iter()
+            .map(|usage| (usage.anchor.as_str(), usage.references.len()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("install", 3),
+                ("home", 1),
+                ("guide", 0),
+                ("legacy-setup", 0)
+            ]
+        );
+        assert_eq!(usage[0].document, PathBuf::from("docs/guide.md"));
+        assert_eq!(usage[0].heading, "Install");
+        assert_eq!(usage[0].line_number, 3);
+        assert_eq!(
+            usage[0].references[0],
+            AnchorReference {
+                file_path: PathBuf::from("docs/README.md"),
+                line_number: 3,
+            }
+        );
+    }
+
+    #[test]
+    fn test_health_score() {
+        let mut vfs = vfs::MemoryFileSystem::new();
+        vfs.insert(
+            "docs/README.md",
+            "[Guide](./guide.md#install)\n[Gone](./guide.md#removed)\n[Ext](https://example.com)",
+        );
+        vfs.insert(
+            "docs/guide.md",
+            "# Guide\n\n## Install\n\n[Deep](./deep/page.md)\n[Missing](./missing.md)",
+        );
+        vfs.insert("docs/deep/page.md", "# Page");
+        vfs.insert("docs/orphan.md", "# Orphan");
+
+        let mut analyzer = LinkAnalyzer::new(PathBuf::from("docs")).with_filesystem(vfs);
+        analyzer.analyze_directory().unwrap();
+        let health = analyzer.get_statistics().health;
+
+        assert_eq!(health.link_density, 5.0 / 4.0);
+        assert_eq!(health.broken_ratio, 2.0 / 4.0);
+        assert_eq!(health.orphan_ratio, 1.0 / 4.0);
+        assert_eq!(health.stale_anchor_ratio, 1.0 / 2.0);
+        assert_eq!(health.average_click_depth, 1.5);
+        // 100 - 50 * 0.5 - 20 * 0.25 - 15 * 0.5
+        assert_eq!(health.score, 62.5);
+
+        let empty =
+            LinkAnalyzer::new(PathBuf::from("docs")).with_filesystem(vfs::MemoryFileSystem::new());
+        assert_eq!(empty.get_statistics().health.score, 100.0);
+    }
+}
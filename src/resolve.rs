@@ -0,0 +1,1460 @@
+use crate::anchors::{self, DocumentHeadings, FileStamp};
+use crate::{crosscheck, fix, include, LinkAnalyzer};
+use serde::{Deserialize, Serialize};
+use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
+use unicode_normalization::UnicodeNormalization as _;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[non_exhaustive]
+#[serde(rename_all = "kebab-case")]
+pub enum LinkKind {
+    External,
+    Internal,
+    SameDocument,
+    /// A path on the author's machine, e.g. `/home/alice/docs/a.md` or `C:\docs\a.md`.
+    AbsolutePath,
+    /// A `file://` URL.
+    FileUrl,
+    /// An Obsidian-style `[[note]]` link or `![[file]]` embed.
+    WikiLink,
+    /// An include directive such as `{{#include file.rs:anchor}}`.
+    Include,
+    /// A URL of another scheme, e.g. `mailto:`, `tel:` or `ftp://`, which
+    /// is not checked.
+    Other,
+}
+
+impl LinkKind {
+    pub fn classify(target: &str) -> Self {
+        if include::is_directive(target) {
+            LinkKind::Include
+        } else if target.starts_with("http://") || target.starts_with("https://") {
+            LinkKind::External
+        } else if target.starts_with("[[") {
+            LinkKind::WikiLink
+        } else if target
+            .get(..7)
+            .is_some_and(|scheme| scheme.eq_ignore_ascii_case("file://"))
+        {
+            LinkKind::FileUrl
+        } else if is_absolute_os_path(target) {
+            LinkKind::AbsolutePath
+        } else if target.starts_with('#') {
+            LinkKind::SameDocument
+        } else if has_scheme(target) {
+            LinkKind::Other
+        } else {
+            LinkKind::Internal
+        }
+    }
+}
+
+/// Whether `target` starts with a URI scheme followed by `:` (RFC 3986),
+/// e.g. `mailto:` or `ftp:`.
+fn has_scheme(target: &str) -> bool {
+    let Some((scheme, _)) = target.split_once(':') else {
+        return false;
+    };
+    let mut chars = scheme.chars();
+    chars.next().is_some_and(|c| c.is_ascii_alphabetic())
+        && chars.all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'))
+}
+
+/// Every step taken to resolve a link target, as reported by `LinkAnalyzer::resolve`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[non_exhaustive]
+pub struct Resolution {
+    pub target: String,
+    pub kind: LinkKind,
+    /// The target is a URL on the project's own published site (`base_url`)
+    /// and was mapped back to local files.
+    pub published: bool,
+    pub path: String,
+    pub fragment: Option<String>,
+    pub base_dir: Option<PathBuf>,
+    pub candidates: Vec<PathBuf>,
+    pub resolved: Option<PathBuf>,
+    pub assumed_present: bool,
+    pub anchors: Option<Vec<String>>,
+    pub anchor_found: Option<bool>,
+    pub error: Option<String>,
+    /// Portable replacement for the target, when one is known.
+    pub suggestion: Option<String>,
+    /// How sure the suggestion is.
+    pub confidence: Option<fix::Confidence>,
+}
+
+/// How file names are Unicode-normalized before link targets are compared
+/// with them, from the `unicode_normalization` setting. macOS writes names
+/// decomposed (NFD) while editors write links composed (NFC), so `café.md`
+/// may be spelled two ways that look the same.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+#[non_exhaustive]
+pub enum UnicodeNormalization {
+    /// Names must match byte for byte.
+    #[default]
+    None,
+    /// Canonically equivalent names match, e.g. NFC and NFD spellings.
+    Nfc,
+    /// Compatibility equivalent names match too, e.g. `ﬁ` and `fi`.
+    Nfkc,
+}
+
+impl UnicodeNormalization {
+    /// Parses the `unicode_normalization` setting; empty means none.
+    pub fn parse(name: &str) -> Result<Self, String> {
+        match name {
+            "" | "none" => Ok(UnicodeNormalization::None),
+            "nfc" => Ok(UnicodeNormalization::Nfc),
+            "nfkc" => Ok(UnicodeNormalization::Nfkc),
+            _ => Err(format!(
+                "Unknown Unicode normalization `{}` (expected none, nfc or nfkc)",
+                name
+            )),
+        }
+    }
+
+    pub fn apply(self, text: &str) -> String {
+        match self {
+            UnicodeNormalization::None => text.to_string(),
+            UnicodeNormalization::Nfc => text.nfc().collect(),
+            UnicodeNormalization::Nfkc => text.nfkc().collect(),
+        }
+    }
+
+    /// `path` normalized, or unchanged if it isn't valid UTF-8.
+    pub(crate) fn apply_path(self, path: &Path) -> PathBuf {
+        match path.to_str() {
+            Some(text) if self != UnicodeNormalization::None => PathBuf::from(self.apply(text)),
+            _ => path.to_path_buf(),
+        }
+    }
+}
+
+/// Conventions of where documents are published, from the `flavor` setting.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+#[non_exhaustive]
+pub enum Flavor {
+    /// Plain Markdown files linking to each other by path.
+    #[default]
+    Markdown,
+    /// A GitHub wiki: `[[Page Title]]` and `[[text|Page Title]]` name
+    /// `Page-Title.md` anywhere in the wiki, ignoring case, and `_Sidebar.md`
+    /// and `_Footer.md` are shown on every page.
+    GithubWiki,
+    /// An Azure DevOps wiki: links name pages without `.md`, from the wiki
+    /// root as in `/Parent/Child-Page`, page titles are stored with spaces
+    /// as dashes and dashes as `%2D`, and each folder's `.order` file lists
+    /// the pages shown in the navigation.
+    AzureDevopsWiki,
+}
+
+impl Flavor {
+    /// Parses the `flavor` setting; empty means plain Markdown.
+    pub fn parse(name: &str) -> Result<Self, String> {
+        match name {
+            "" | "markdown" => Ok(Flavor::Markdown),
+            "github-wiki" => Ok(Flavor::GithubWiki),
+            "azure-devops-wiki" => Ok(Flavor::AzureDevopsWiki),
+            _ => Err(format!(
+                "Unknown flavor `{}` (expected markdown, github-wiki or azure-devops-wiki)",
+                name
+            )),
+        }
+    }
+}
+
+impl LinkAnalyzer {
+    pub fn resolve(&self, file_path: &Path, target: &str) -> Resolution {
+        let (path, fragment) = split_fragment(target);
+        let path = strip_query(path);
+        let mut resolution = Resolution {
+            target: target.to_string(),
+            kind: LinkKind::classify(target),
+            published: false,
+            path: path.to_string(),
+            fragment: fragment.map(str::to_string),
+            base_dir: None,
+            candidates: Vec::new(),
+            resolved: None,
+            assumed_present: false,
+            anchors: None,
+            anchor_found: None,
+            error: None,
+            suggestion: None,
+            confidence: None,
+        };
+
+        if resolution.kind == LinkKind::AbsolutePath {
+            resolution.suggestion =
+                self.portable_suggestion(file_path, path)
+                    .map(|relative| match fragment {
+                        Some(fragment) => format!("{}#{}", relative, fragment),
+                        None => relative,
+                    });
+            resolution.error = Some(match &resolution.suggestion {
+                Some(suggestion) => format!(
+                    "Absolute filesystem path only works on the author's machine; use {}",
+                    suggestion
+                ),
+                None => "Absolute filesystem path only works on the author's machine".to_string(),
+            });
+            return resolution;
+        }
+
+        if resolution.kind == LinkKind::Include {
+            self.resolve_include(file_path, &mut resolution);
+            return resolution;
+        }
+
+        if resolution.kind == LinkKind::WikiLink {
+            self.resolve_wiki_link(file_path, &mut resolution);
+            return resolution;
+        }
+
+        if resolution.kind == LinkKind::Other {
+            return resolution;
+        }
+
+        if resolution.kind == LinkKind::FileUrl {
+            let Some(local) = file_url_path(path) else {
+                resolution.error = Some(format!("Malformed file:// URL: {}", path));
+                return resolution;
+            };
+            let base = self
+                .fs
+                .canonicalize(&self.base_path)
+                .unwrap_or_else(|_| normalize_path(&self.base_path));
+            let Ok(relative) = normalize_path(&local)
+                .strip_prefix(&base)
+                .map(Path::to_path_buf)
+            else {
+                resolution.error = Some(format!(
+                    "file:// URL points outside the repository: {}",
+                    local.display()
+                ));
+                return resolution;
+            };
+            resolution.base_dir = Some(self.base_path.clone());
+            resolution.candidates.push(self.base_path.join(relative));
+        } else if resolution.kind == LinkKind::External {
+            let Some(url_path) = self.published_path(path) else {
+                return resolution;
+            };
+            resolution.published = true;
+            resolution.base_dir = Some(self.base_path.clone());
+            resolution.candidates = crosscheck::candidate_paths(url_path)
+                .into_iter()
+                .map(|candidate| self.base_path.join(candidate))
+                .collect();
+        } else {
+            let Some(candidate) = self.resolve_target(file_path, target) else {
+                return resolution;
+            };
+            resolution.base_dir = Some(if path.starts_with('/') {
+                self.base_path.clone()
+            } else {
+                self.document_dir(file_path).to_path_buf()
+            });
+            let bare = path.trim_end_matches('/');
+            let extensionless = !bare.is_empty() && Path::new(bare).extension().is_none();
+            if extensionless && self.flavor == Flavor::AzureDevopsWiki {
+                let pages = azure_page_path(bare).into_iter().chain([bare.to_string()]);
+                for page in pages {
+                    if let Some(candidate) = self.resolve_target(file_path, &format!("{}.md", page))
+                    {
+                        if !resolution.candidates.contains(&candidate) {
+                            resolution.candidates.push(candidate);
+                        }
+                    }
+                }
+            } else {
+                if extensionless {
+                    let bare = candidate.to_string_lossy();
+                    let bare = bare.trim_end_matches(['/', '\\']);
+                    for suffix in &self.extensionless_candidates {
+                        resolution
+                            .candidates
+                            .push(PathBuf::from(format!("{}{}", bare, suffix)));
+                    }
+                }
+                // `My%20Page.md` names `My Page.md`, as exports and renderers write it.
+                if let Some(decoded) = percent_decode(path)
+                    .filter(|decoded| decoded != path)
+                    .and_then(|decoded| self.resolve_target(file_path, &decoded))
+                {
+                    resolution.candidates.push(decoded);
+                }
+                resolution.candidates.push(candidate);
+            }
+        }
+
+        let Some(candidate) = resolution
+            .candidates
+            .iter()
+            .find(|candidate| self.fs.exists(&self.document_key(candidate)))
+            .cloned()
+        else {
+            if resolution.published {
+                let tried: Vec<String> = resolution
+                    .candidates
+                    .iter()
+                    .map(|candidate| normalize_path(candidate).display().to_string())
+                    .collect();
+                resolution.error = Some(format!(
+                    "No local document for published URL (tried {})",
+                    tried.join(", ")
+                ));
+                return resolution;
+            }
+            let normalized = normalize_path(resolution.candidates.last().unwrap());
+            if self.is_assumed_present(&normalized) {
+                resolution.assumed_present = true;
+            } else {
+                resolution.error = Some(format!("File not found: {}", normalized.display()));
+                if let Some((moved, confidence)) = self.find_moved(file_path, &normalized) {
+                    let relative = self.relative_target(file_path, &moved);
+                    resolution.suggestion = Some(match fragment {
+                        Some(fragment) => format!("{}#{}", relative, fragment),
+                        None => relative,
+                    });
+                    resolution.confidence = Some(confidence);
+                }
+            }
+            return resolution;
+        };
+
+        if resolution.published || resolution.kind == LinkKind::FileUrl {
+            let relative = self.relative_target(file_path, &candidate);
+            resolution.suggestion = Some(match fragment {
+                Some(fragment) => format!("{}#{}", relative, fragment),
+                None => relative,
+            });
+            resolution.confidence = Some(fix::Confidence::Safe);
+        }
+
+        let resolved = self.document_key(&candidate);
+        resolution.resolved = Some(resolved.clone());
+
+        let Some(fragment) = fragment.filter(|f| !f.is_empty()) else {
+            return resolution;
+        };
+        if resolved.extension().and_then(|s| s.to_str()) != Some("md") {
+            return resolution;
+        }
+        let Some(headings) = self.document_headings(&resolved) else {
+            return resolution;
+        };
+
+        let anchors = headings.anchors.clone();
+        let mut found = anchors
+            .iter()
+            .any(|anchor| anchor == fragment || *anchor == fragment.to_lowercase());
+        let version = anchors::version_anchor(fragment).filter(|_| self.version_anchors);
+        if let (false, Some(version)) = (found, &version) {
+            found = headings
+                .headings
+                .iter()
+                .any(|heading| anchors::heading_version(&heading.text).as_ref() == Some(version));
+        }
+        if !found {
+            resolution.error = Some(match version {
+                Some(version) => format!(
+                    "No heading for version {} (#{}) in {}",
+                    version,
+                    fragment,
+                    resolved.display()
+                ),
+                None => format!("Anchor not found: #{} in {}", fragment, resolved.display()),
+            });
+        }
+        resolution.anchor_found = Some(found);
+        resolution.anchors = Some(anchors);
+
+        resolution
+    }
+
+    pub(crate) fn resolve_target(&self, file_path: &Path, target: &str) -> Option<PathBuf> {
+        if matches!(
+            LinkKind::classify(target),
+            LinkKind::External | LinkKind::Other
+        ) {
+            return None;
+        }
+
+        let (path, _) = split_fragment(target);
+        let path = strip_query(path);
+        let resolved_path = if path.is_empty() {
+            file_path.to_path_buf()
+        } else if let Some(stripped) = path.strip_prefix('/') {
+            self.base_path.join(stripped)
+        } else {
+            self.document_dir(file_path).join(path)
+        };
+
+        Some(resolved_path)
+    }
+
+    /// Headings of the document at `document_key` `path`, from the scan or
+    /// read on first use, until the next scan. `None` if it can't be read.
+    pub(crate) fn document_headings(&self, path: &Path) -> Option<Arc<DocumentHeadings>> {
+        self.headings.get_or_parse(
+            path,
+            || self.file_stamp(path),
+            || {
+                let content = self.read_document(path).ok()?;
+                Some(DocumentHeadings::parse_with(
+                    &content,
+                    self.heading_styles,
+                    self.slugger,
+                ))
+            },
+        )
+    }
+
+    /// Size and modification time of `path`, if the file system knows both.
+    pub(crate) fn file_stamp(&self, path: &Path) -> Option<FileStamp> {
+        Some(FileStamp {
+            size: self.fs.file_size(path).ok()?,
+            modified: self.fs.modified(path).ok()?,
+        })
+    }
+
+    /// Path part of `url` when it points into the published site, e.g.
+    /// `/guide/` for `https://docs.example.com/guide/?tab=1`.
+    pub(crate) fn published_path<'a>(&self, url: &'a str) -> Option<&'a str> {
+        if self.base_url.is_empty() {
+            return None;
+        }
+        let rest = url.strip_prefix(self.base_url.as_str())?;
+        if !rest.is_empty() && !rest.starts_with('/') {
+            return None;
+        }
+        rest.split('?').next()
+    }
+
+    /// Resolves an include directive relative to the including document,
+    /// falling back to the analyzed directory as snippets tools do, and checks
+    /// the anchor or line range it selects.
+    pub(crate) fn resolve_include(&self, file_path: &Path, resolution: &mut Resolution) {
+        let Some(include) = include::parse(&resolution.target) else {
+            return;
+        };
+        resolution.path = include.path.clone();
+        resolution.fragment = None;
+
+        let base_dir = self.document_dir(file_path).to_path_buf();
+        resolution.candidates.push(base_dir.join(&include.path));
+        resolution
+            .candidates
+            .push(self.base_path.join(&include.path));
+        resolution.candidates.dedup();
+        resolution.base_dir = Some(base_dir);
+
+        let Some(candidate) = resolution
+            .candidates
+            .iter()
+            .find(|candidate| self.fs.exists(&self.document_key(candidate)))
+            .cloned()
+        else {
+            let candidate = normalize_path(&resolution.candidates[0]);
+            if self.is_assumed_present(&candidate) {
+                resolution.assumed_present = true;
+            } else {
+                resolution.error =
+                    Some(format!("Included file not found: {}", candidate.display()));
+            }
+            return;
+        };
+
+        let resolved = self.document_key(&candidate);
+        resolution.resolved = Some(resolved.clone());
+
+        let Some(selector) = include.selector else {
+            return;
+        };
+        let Ok(source) = self.read_document(&resolved) else {
+            return;
+        };
+        if let Err(e) = include::check_selector(&source, &selector) {
+            resolution.error = Some(format!("{} in {}", e, resolved.display()));
+        }
+    }
+
+    /// Resolves `[[note#fragment|alias]]` the way Obsidian does: by path
+    /// from the vault root, else by file name anywhere, shortest path first.
+    pub(crate) fn resolve_wiki_link(&self, file_path: &Path, resolution: &mut Resolution) {
+        let inner = resolution
+            .target
+            .trim_start_matches("[[")
+            .trim_end_matches("]]");
+        // Obsidian puts the note before the `|`, GitHub wikis after it.
+        let inner = match self.flavor {
+            Flavor::GithubWiki => inner.rsplit('|').next(),
+            _ => inner.split('|').next(),
+        }
+        .unwrap_or_default();
+        let (note, fragment) = match inner.split_once('#') {
+            Some((note, fragment)) => (note.trim(), Some(fragment.trim())),
+            None => (inner.trim(), None),
+        };
+        resolution.path = note.to_string();
+        resolution.fragment = fragment.map(str::to_string);
+        if self.flavor == Flavor::GithubWiki && note.contains("://") {
+            // `[[text|https://example.com]]` is an external link.
+            return;
+        }
+
+        let target = if note.is_empty() {
+            file_path.to_path_buf()
+        } else {
+            let page = match self.flavor {
+                Flavor::GithubWiki => note.replace(' ', "-"),
+                _ => note.to_string(),
+            };
+            let name = if Path::new(&page).extension().is_some() {
+                page
+            } else {
+                format!("{}.md", page)
+            };
+            let from_root = self.base_path.join(&name);
+            resolution.base_dir = Some(self.base_path.clone());
+            resolution.candidates.push(from_root.clone());
+
+            let fold = |text: &str| self.unicode_normalization.apply(text).to_lowercase();
+            let file_name = fold(name.rsplit('/').next().unwrap_or(&name));
+            let suffix = format!("/{}", fold(&name));
+            let by_name = self.file_index.get(&file_name).and_then(|paths| {
+                paths.iter().find(|path| {
+                    let path = format!("/{}", path.to_string_lossy().replace('\\', "/"));
+                    fold(&path).ends_with(&suffix)
+                })
+            });
+
+            if self.fs.exists(&from_root) {
+                from_root
+            } else if let Some(path) = by_name {
+                resolution.candidates.push(path.clone());
+                path.clone()
+            } else {
+                resolution.error = Some(format!("Note not found: {}", note));
+                return;
+            }
+        };
+
+        let resolved = self.document_key(&target);
+        resolution.resolved = Some(resolved.clone());
+
+        // Nested heading references (`#Part#Section`) name the last heading.
+        let Some(fragment) = fragment
+            .and_then(|f| f.rsplit('#').next())
+            .filter(|f| !f.is_empty())
+        else {
+            return;
+        };
+        let (found, available) = if let Some(block) = fragment.strip_prefix('^') {
+            let Ok(content) = self.read_document(&resolved) else {
+                return;
+            };
+            let blocks = anchors::block_ids(&content);
+            (blocks.iter().any(|id| id == block), blocks)
+        } else {
+            let Some(headings) = self.document_headings(&resolved) else {
+                return;
+            };
+            let slug = anchors::github_slug(fragment);
+            let found = headings.headings.iter().any(|heading| {
+                heading.text.eq_ignore_ascii_case(fragment)
+                    || anchors::github_slug(&heading.text) == slug
+            });
+            (
+                found,
+                headings
+                    .headings
+                    .iter()
+                    .map(|heading| heading.text.clone())
+                    .collect(),
+            )
+        };
+
+        if !found {
+            let what = if fragment.starts_with('^') {
+                "Block"
+            } else {
+                "Heading"
+            };
+            resolution.error = Some(format!(
+                "{} not found: #{} in {}",
+                what,
+                fragment,
+                resolved.display()
+            ));
+        }
+        resolution.anchor_found = Some(found);
+        resolution.anchors = Some(available);
+    }
+
+    /// Link target leading from `file_path` to `target`, with `/` separators.
+    pub(crate) fn relative_target(&self, file_path: &Path, target: &Path) -> String {
+        relative_path(self.document_dir(file_path), target)
+            .to_string_lossy()
+            .replace('\\', "/")
+    }
+
+    /// Where a missing file most likely is or moved to: the file at the same
+    /// path but for case or a missing `.md`, or the only file with the same
+    /// name, is a safe guess; one of several, or a similarly named file (a
+    /// typo or a rename), is a risky one. The closest candidate wins.
+    pub(crate) fn find_moved(
+        &self,
+        file_path: &Path,
+        missing: &Path,
+    ) -> Option<(PathBuf, fix::Confidence)> {
+        let mut missing = missing.to_path_buf();
+        if missing.extension().is_none() {
+            missing.as_mut_os_string().push(".md");
+        }
+        let name = missing.file_name()?.to_string_lossy();
+        let closest = |paths: Vec<&PathBuf>| {
+            paths
+                .into_iter()
+                .min_by_key(|path| (self.relative_target(file_path, path).len(), *path))
+                .cloned()
+        };
+        let fold = |path: &Path| {
+            self.unicode_normalization
+                .apply(&normalize_path(path).to_string_lossy())
+                .to_lowercase()
+        };
+
+        let name = self.unicode_normalization.apply(&name);
+        let same_names = self.file_index.get(&name.to_lowercase());
+        let in_place: Vec<&PathBuf> = same_names
+            .into_iter()
+            .flatten()
+            .filter(|path| fold(path) == fold(&missing))
+            .collect();
+        if let [path] = in_place[..] {
+            return Some((path.clone(), fix::Confidence::Safe));
+        }
+
+        let exact: Vec<&PathBuf> = same_names
+            .into_iter()
+            .flatten()
+            .filter(|path| {
+                path.file_name()
+                    .is_some_and(|n| self.unicode_normalization.apply(&n.to_string_lossy()) == name)
+            })
+            .collect();
+        match exact.len() {
+            0 => {}
+            1 => return Some((exact[0].clone(), fix::Confidence::Safe)),
+            _ => return closest(exact).map(|path| (path, fix::Confidence::Risky)),
+        }
+
+        let stem = missing.file_stem()?.to_string_lossy().to_lowercase();
+        let extension = missing.extension();
+        let mut similar: Vec<(usize, &PathBuf)> = self
+            .file_index
+            .values()
+            .flatten()
+            .filter(|path| path.extension() == extension)
+            .filter_map(|path| {
+                let other = path.file_stem()?.to_string_lossy().to_lowercase();
+                let distance = strsim::levenshtein(&stem, &other);
+                (distance <= 2 && distance * 4 <= stem.len()).then_some((distance, path))
+            })
+            .collect();
+        let best = similar.iter().map(|(distance, _)| *distance).min()?;
+        similar.retain(|(distance, _)| *distance == best);
+        closest(similar.into_iter().map(|(_, path)| path).collect())
+            .map(|path| (path, fix::Confidence::Risky))
+    }
+
+    /// Finds the analyzed file an absolute filesystem path most likely meant,
+    /// by dropping leading components until the rest exists under the base.
+    pub(crate) fn portable_suggestion(&self, file_path: &Path, path: &str) -> Option<String> {
+        let components: Vec<&str> = path
+            .split(['/', '\\'])
+            .filter(|c| !c.is_empty() && *c != "~" && !c.ends_with(':'))
+            .collect();
+
+        (0..components.len()).find_map(|start| {
+            let candidate = self.base_path.join(components[start..].join("/"));
+            self.fs
+                .exists(&candidate)
+                .then(|| self.relative_target(file_path, &candidate))
+        })
+    }
+
+    pub(crate) fn document_dir<'a>(&'a self, file_path: &'a Path) -> &'a Path {
+        file_path
+            .parent()
+            .filter(|parent| !parent.as_os_str().is_empty())
+            .unwrap_or(&self.base_path)
+    }
+
+    /// Whether a file or directory matches `exclude` and is left out of the scan.
+    pub(crate) fn is_excluded(&self, path: &Path) -> bool {
+        normalize_path(path)
+            .strip_prefix(normalize_path(&self.base_path))
+            .map(|relative| self.exclude.is_match(relative))
+            .unwrap_or(false)
+    }
+
+    /// What a path is compared by: `.` and `..` resolved lexically, the way
+    /// browsers resolve links, and symlinked aliases mapped to the document
+    /// the scan analyzed. With `unicode_normalization`, other spellings of a
+    /// walked file's name map to the one it has on disk. Unlike
+    /// `canonicalize` it never touches the file system, so it is cheap and
+    /// works for paths that don't exist.
+    pub(crate) fn document_key(&self, path: &Path) -> PathBuf {
+        let mut normalized = normalize_path(path);
+        if self.unicode_normalization != UnicodeNormalization::None {
+            let folded = self.unicode_normalization.apply_path(&normalized);
+            normalized = self.spellings.get(&folded).cloned().unwrap_or(normalized);
+        }
+        match self.alias_of.get(&normalized) {
+            Some(primary) => normalize_path(primary),
+            None => normalized,
+        }
+    }
+
+    pub(crate) fn is_assumed_present(&self, resolved_path: &Path) -> bool {
+        normalize_path(resolved_path)
+            .strip_prefix(normalize_path(&self.base_path))
+            .map(|relative| self.assumed_present.is_match(relative))
+            .unwrap_or(false)
+    }
+}
+
+/// Whether `target` is a path that only exists on one machine: a path under a
+/// common home or system directory, `~/...`, a Windows drive path or a UNC share.
+pub fn is_absolute_os_path(target: &str) -> bool {
+    const UNIX_ROOTS: [&str; 9] = [
+        "/home/",
+        "/Users/",
+        "/root/",
+        "/tmp/",
+        "/var/",
+        "/mnt/",
+        "/opt/",
+        "/private/",
+        "/Volumes/",
+    ];
+
+    let bytes = target.as_bytes();
+    let windows_drive = bytes.len() >= 3
+        && bytes[0].is_ascii_alphabetic()
+        && bytes[1] == b':'
+        && (bytes[2] == b'\\' || bytes[2] == b'/');
+
+    windows_drive
+        || target.starts_with("\\\\")
+        || target.starts_with("~/")
+        || UNIX_ROOTS.iter().any(|root| target.starts_with(root))
+}
+
+/// Local path of a `file://` URL, e.g. `/home/a/docs/x y.md` for
+/// `file:///home/a/docs/x%20y.md` and `C:/docs/a.md` for `file:///C:/docs/a.md`.
+pub fn file_url_path(url: &str) -> Option<PathBuf> {
+    let rest = url.get(7..).filter(|_| {
+        url.get(..7)
+            .is_some_and(|scheme| scheme.eq_ignore_ascii_case("file://"))
+    })?;
+    let rest = rest.split('?').next().unwrap_or_default();
+    let (host, path) = match rest.find('/') {
+        Some(index) => rest.split_at(index),
+        None => return None,
+    };
+
+    let path = percent_decode(path)?;
+    let bytes = path.as_bytes();
+    let windows_drive = bytes.len() >= 3 && bytes[1].is_ascii_alphabetic() && bytes[2] == b':';
+    Some(match host {
+        "" | "localhost" if windows_drive => PathBuf::from(&path[1..]),
+        "" | "localhost" => PathBuf::from(path),
+        host => PathBuf::from(format!("//{}{}", host, path)),
+    })
+}
+
+/// The file an Azure DevOps wiki link path names: spaces, written as `-`
+/// or `%20`, are stored as dashes, and dashes and characters file names
+/// can't hold stay percent-encoded, e.g. `/Q%26A%2DArchive` is stored as
+/// `Q&A%2DArchive.md`. `None` if the path is malformed.
+fn azure_page_path(path: &str) -> Option<String> {
+    let mut bytes = Vec::with_capacity(path.len());
+    let mut iter = path.bytes();
+    while let Some(byte) = iter.next() {
+        if byte != b'%' {
+            bytes.push(if byte == b' ' { b'-' } else { byte });
+            continue;
+        }
+        let hex = [iter.next()?, iter.next()?];
+        match u8::from_str_radix(std::str::from_utf8(&hex).ok()?, 16).ok()? {
+            b' ' => bytes.push(b'-'),
+            byte @ (b'-' | b':' | b'<' | b'>' | b'*' | b'?' | b'|' | b'"') => {
+                bytes.extend(format!("%{:02X}", byte).bytes())
+            }
+            byte => bytes.push(byte),
+        }
+    }
+    String::from_utf8(bytes).ok()
+}
+
+pub(crate) fn percent_decode(text: &str) -> Option<String> {
+    let mut bytes = Vec::with_capacity(text.len());
+    let mut iter = text.bytes();
+    while let Some(byte) = iter.next() {
+        if byte == b'%' {
+            let hex = [iter.next()?, iter.next()?];
+            bytes.push(u8::from_str_radix(std::str::from_utf8(&hex).ok()?, 16).ok()?);
+        } else {
+            bytes.push(byte);
+        }
+    }
+    String::from_utf8(bytes).ok()
+}
+
+/// Splits `guide.md#install` into `("guide.md", Some("install"))`.
+pub fn split_fragment(target: &str) -> (&str, Option<&str>) {
+    match target.split_once('#') {
+        Some((path, fragment)) => (path, Some(fragment)),
+        None => (target, None),
+    }
+}
+
+/// `path` without its `?query`, which names no part of a local file.
+fn strip_query(path: &str) -> &str {
+    path.split_once('?').map_or(path, |(path, _)| path)
+}
+
+pub(crate) fn normalize_path(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                if !normalized.pop() {
+                    normalized.push("..");
+                }
+            }
+            other => normalized.push(other),
+        }
+    }
+    normalized
+}
+
+/// Path from directory `from` to `to`, both taken relative to the same root.
+pub(crate) fn relative_path(from: &Path, to: &Path) -> PathBuf {
+    let from = normalize_path(from);
+    let to = normalize_path(to);
+    let common = from
+        .components()
+        .zip(to.components())
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    let mut relative = PathBuf::new();
+    for _ in from.components().skip(common) {
+        relative.push("..");
+    }
+    for component in to.components().skip(common) {
+        relative.push(component);
+    }
+    relative
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fix::Confidence;
+    use crate::{vfs, Config};
+    use std::fs;
+    use std::io::Write;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_fragment_links() {
+        let temp_dir = TempDir::new().unwrap();
+        let base_path = temp_dir.path();
+
+        let doc1_path = base_path.join("doc1.md");
+        let mut doc1 = fs::File::create(&doc1_path).unwrap();
+        writeln!(doc1, "# Introduction").unwrap();
+        writeln!(doc1, "[Top](#introduction)").unwrap();
+        writeln!(doc1, "[Setup](./doc2.md#setup-guide)").unwrap();
+        writeln!(doc1, "[Missing section](./doc2.md#uninstall)").unwrap();
+        writeln!(doc1, "[Missing here](#outro)").unwrap();
+
+        let doc2_path = base_path.join("doc2.md");
+        let mut doc2 = fs::File::create(&doc2_path).unwrap();
+        writeln!(doc2, "## Setup Guide").unwrap();
+
+        let mut analyzer = LinkAnalyzer::new(base_path.to_path_buf());
+        analyzer.analyze_directory().unwrap();
+
+        let mut broken_links = analyzer.find_broken_links();
+        broken_links.sort_by(|a, b| a.link.target.cmp(&b.link.target));
+        assert_eq!(broken_links.len(), 2);
+        assert_eq!(broken_links[0].link.target, "#outro");
+        assert_eq!(broken_links[1].link.target, "./doc2.md#uninstall");
+        assert!(broken_links[1].reason.starts_with("Anchor not found"));
+
+        let resolution = analyzer.resolve(&doc1_path, "./doc2.md#setup-guide");
+        assert_eq!(resolution.kind, LinkKind::Internal);
+        assert_eq!(resolution.path, "./doc2.md");
+        assert_eq!(resolution.fragment.as_deref(), Some("setup-guide"));
+        assert_eq!(resolution.anchor_found, Some(true));
+        assert_eq!(resolution.error, None);
+    }
+
+    #[test]
+    fn test_other_schemes_and_queries() {
+        for target in [
+            "mailto:team@example.com",
+            "tel:+1-555-0100",
+            "ftp://example.com/file",
+            "javascript:void(0)",
+            "urn:isbn:0451450523",
+            "vscode://file/a.md",
+        ] {
+            assert_eq!(LinkKind::classify(target), LinkKind::Other, "{}", target);
+        }
+        for target in ["guide.md", "docs/a:b.md", "./a:b.md", "C:/docs/a.md"] {
+            assert_ne!(LinkKind::classify(target), LinkKind::Other, "{}", target);
+        }
+
+        let mut vfs = vfs::MemoryFileSystem::new();
+        vfs.insert(
+            "docs/README.md",
+            "[Mail](mailto:team@example.com)\n[FTP](ftp://example.com/file)\n[Plain](guide.md?plain=1)\n[Setup](guide.md?plain=1#setup)\n[Gone](gone.md?plain=1)",
+        );
+        vfs.insert("docs/guide.md", "# Setup");
+        let mut analyzer = LinkAnalyzer::new(PathBuf::from("docs")).with_filesystem(vfs);
+        analyzer.analyze_directory().unwrap();
+
+        let broken = analyzer.find_broken_links();
+        assert_eq!(broken.len(), 1);
+        assert_eq!(broken[0].link.target, "gone.md?plain=1");
+        assert_eq!(broken[0].reason, "File not found: docs/gone.md");
+
+        let readme = Path::new("docs/README.md");
+        let resolution = analyzer.resolve(readme, "ftp://example.com/file");
+        assert_eq!(resolution.kind, LinkKind::Other);
+        assert!(resolution.candidates.is_empty());
+        assert_eq!(resolution.error, None);
+
+        let resolution = analyzer.resolve(readme, "guide.md?plain=1#setup");
+        assert_eq!(resolution.kind, LinkKind::Internal);
+        assert_eq!(resolution.path, "guide.md");
+        assert_eq!(resolution.resolved, Some(PathBuf::from("docs/guide.md")));
+        assert_eq!(resolution.anchor_found, Some(true));
+    }
+
+    #[test]
+    fn test_published_self_links() {
+        let mut vfs = vfs::MemoryFileSystem::new();
+        vfs.insert(
+            "docs/guide/setup.md",
+            "[Home](https://docs.example.com/)\n[API](https://docs.example.com/api/#client)\n[Old](https://docs.example.com/old/)\n[Other](https://example.org/)",
+        );
+        vfs.insert("docs/README.md", "# Home");
+        vfs.insert("docs/api/index.md", "## Client");
+
+        let config = Config {
+            base_url: "https://docs.example.com/".to_string(),
+            ..Default::default()
+        };
+        let mut analyzer = LinkAnalyzer::with_config(PathBuf::from("docs"), &config)
+            .unwrap()
+            .with_filesystem(vfs);
+        analyzer.analyze_directory().unwrap();
+
+        let broken_links = analyzer.find_broken_links();
+        assert_eq!(broken_links.len(), 1);
+        assert_eq!(broken_links[0].link.target, "https://docs.example.com/old/");
+        assert!(broken_links[0]
+            .reason
+            .starts_with("No local document for published URL"));
+
+        let fixes = analyzer.suggested_fixes();
+        let replacements: Vec<&str> = fixes.iter().map(|f| f.replacement.as_str()).collect();
+        assert_eq!(replacements, vec!["../README.md", "../api/index.md#client"]);
+    }
+
+    #[test]
+    fn test_extensionless_candidates() {
+        let mut vfs = vfs::MemoryFileSystem::new();
+        vfs.insert(
+            "docs/README.md",
+            "[Guide](./guide)\n[API](api/#client)\n[Setup](setup)\n[License](LICENSE)",
+        );
+        vfs.insert("docs/guide.md", "# Guide");
+        vfs.insert("docs/api/README.md", "## Client");
+        vfs.insert("docs/LICENSE", "MIT");
+
+        let config = Config {
+            extensionless_candidates: vec![
+                ".md".to_string(),
+                "/index.md".to_string(),
+                "/README.md".to_string(),
+            ],
+            ..Default::default()
+        };
+        let mut analyzer = LinkAnalyzer::with_config(PathBuf::from("docs"), &config)
+            .unwrap()
+            .with_filesystem(vfs);
+        analyzer.analyze_directory().unwrap();
+
+        let resolution = analyzer.resolve(Path::new("docs/README.md"), "api/#client");
+        assert_eq!(
+            resolution.resolved,
+            Some(PathBuf::from("docs/api/README.md"))
+        );
+        assert_eq!(resolution.anchor_found, Some(true));
+        assert_eq!(resolution.candidates.len(), 4);
+
+        let broken_links = analyzer.find_broken_links();
+        assert_eq!(broken_links.len(), 1);
+        assert_eq!(broken_links[0].link.target, "setup");
+        assert_eq!(broken_links[0].reason, "File not found: docs/setup");
+        assert!(analyzer.find_orphaned_documents().is_empty());
+    }
+
+    #[test]
+    fn test_percent_encoded_targets() {
+        let mut vfs = vfs::MemoryFileSystem::new();
+        vfs.insert(
+            "README.md",
+            "[Page](My%20Page.md#setup)\n[Missing](No%20Page.md)",
+        );
+        vfs.insert("My Page.md", "## Setup");
+
+        let mut analyzer = LinkAnalyzer::new(PathBuf::new()).with_filesystem(vfs);
+        analyzer.analyze_directory().unwrap();
+
+        let resolution = analyzer.resolve(Path::new("README.md"), "My%20Page.md#setup");
+        assert_eq!(resolution.resolved, Some(PathBuf::from("My Page.md")));
+        assert_eq!(resolution.anchor_found, Some(true));
+
+        let broken_links = analyzer.find_broken_links();
+        assert_eq!(broken_links.len(), 1);
+        assert_eq!(broken_links[0].reason, "File not found: No%20Page.md");
+    }
+
+    #[test]
+    fn test_absolute_filesystem_paths() {
+        assert!(is_absolute_os_path("/home/alice/docs/a.md"));
+        assert!(is_absolute_os_path("C:\\docs\\a.md"));
+        assert!(is_absolute_os_path("c:/docs/a.md"));
+        assert!(is_absolute_os_path("\\\\server\\share\\a.md"));
+        assert!(is_absolute_os_path("~/docs/a.md"));
+        assert!(!is_absolute_os_path("/docs/a.md"));
+        assert!(!is_absolute_os_path("./a.md"));
+
+        let mut vfs = vfs::MemoryFileSystem::new();
+        vfs.insert(
+            "docs/guide/setup.md",
+            "[A](/home/alice/projects/docs/api/a.md#usage)\n[B](C:\\Users\\bob\\notes.md)",
+        );
+        vfs.insert("docs/api/a.md", "## Usage");
+
+        let mut analyzer = LinkAnalyzer::new(PathBuf::from("docs")).with_filesystem(vfs);
+        analyzer.analyze_directory().unwrap();
+
+        let mut broken_links = analyzer.find_broken_links();
+        broken_links.sort_by_key(|b| b.link.line_number);
+        assert_eq!(
+            broken_links[0].reason,
+            "Absolute filesystem path only works on the author's machine; use ../api/a.md#usage"
+        );
+        assert_eq!(
+            broken_links[1].reason,
+            "Absolute filesystem path only works on the author's machine"
+        );
+
+        let fixes = analyzer.suggested_fixes();
+        assert_eq!(fixes.len(), 1);
+        assert_eq!(fixes[0].replacement, "../api/a.md#usage");
+    }
+
+    #[test]
+    fn test_moved_file_suggestions() {
+        let mut vfs = vfs::MemoryFileSystem::new();
+        vfs.insert(
+            "docs/README.md",
+            "[A](setup.md#install)\n[B](faq.md)\n[C](intro.md)\n[D](nothing.md)\n\
+             [E](Guide/FAQ.md)\n[F](guide/introduction)",
+        );
+        vfs.insert("docs/guide/setup.md", "## Install");
+        vfs.insert("docs/guide/faq.md", "");
+        vfs.insert("docs/archive/faq.md", "");
+        vfs.insert("docs/guide/introduction.md", "");
+        vfs.insert("docs/guide/intro.md.bak", "");
+        vfs.insert("docs/guide/intros.md", "");
+
+        let mut analyzer = LinkAnalyzer::new(PathBuf::from("docs")).with_filesystem(vfs);
+        analyzer.analyze_directory().unwrap();
+
+        let resolution = analyzer.resolve(Path::new("docs/README.md"), "setup.md#install");
+        assert_eq!(
+            resolution.suggestion.as_deref(),
+            Some("guide/setup.md#install")
+        );
+        assert_eq!(resolution.confidence, Some(Confidence::Safe));
+
+        let mut suggestions: Vec<_> = analyzer
+            .find_broken_links()
+            .into_iter()
+            .map(|broken| (broken.link.line_number, broken.suggestion))
+            .collect();
+        suggestions.sort();
+        assert_eq!(
+            suggestions,
+            vec![
+                (1, Some(PathBuf::from("docs/guide/setup.md"))),
+                (2, Some(PathBuf::from("docs/guide/faq.md"))),
+                (3, Some(PathBuf::from("docs/guide/intros.md"))),
+                (4, None),
+                (5, Some(PathBuf::from("docs/guide/faq.md"))),
+                (6, Some(PathBuf::from("docs/guide/introduction.md"))),
+            ]
+        );
+
+        let fixes: Vec<_> = analyzer
+            .suggested_fixes()
+            .into_iter()
+            .map(|fix| (fix.target, fix.replacement, fix.confidence))
+            .collect();
+        assert_eq!(
+            fixes,
+            vec![
+                (
+                    "setup.md#install".to_string(),
+                    "guide/setup.md#install".to_string(),
+                    Confidence::Safe
+                ),
+                (
+                    "faq.md".to_string(),
+                    "guide/faq.md".to_string(),
+                    Confidence::Risky
+                ),
+                (
+                    "intro.md".to_string(),
+                    "guide/intros.md".to_string(),
+                    Confidence::Risky
+                ),
+                (
+                    "Guide/FAQ.md".to_string(),
+                    "guide/faq.md".to_string(),
+                    Confidence::Safe
+                ),
+                (
+                    "guide/introduction".to_string(),
+                    "guide/introduction.md".to_string(),
+                    Confidence::Safe
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_file_urls() {
+        assert_eq!(
+            file_url_path("file:///home/a/docs/x%20y.md"),
+            Some(PathBuf::from("/home/a/docs/x y.md"))
+        );
+        assert_eq!(
+            file_url_path("file:///C:/docs/a.md"),
+            Some(PathBuf::from("C:/docs/a.md"))
+        );
+        assert_eq!(
+            file_url_path("file://server/share/a.md"),
+            Some(PathBuf::from("//server/share/a.md"))
+        );
+        assert_eq!(file_url_path("file://%zz"), None);
+
+        let temp_dir = TempDir::new().unwrap();
+        let base_path = temp_dir.path().canonicalize().unwrap();
+        fs::create_dir(base_path.join("guide")).unwrap();
+        let mut doc = fs::File::create(base_path.join("guide/setup.md")).unwrap();
+        writeln!(doc, "# Setup").unwrap();
+
+        let url = |path: &Path| format!("file://{}", path.display());
+        let mut readme = fs::File::create(base_path.join("README.md")).unwrap();
+        writeln!(
+            readme,
+            "[Setup]({}#setup)",
+            url(&base_path.join("guide/setup.md"))
+        )
+        .unwrap();
+        writeln!(readme, "[Missing]({})", url(&base_path.join("missing.md"))).unwrap();
+        writeln!(readme, "[Outside](file:///etc/hosts)").unwrap();
+
+        let mut analyzer = LinkAnalyzer::new(base_path.clone());
+        analyzer.analyze_directory().unwrap();
+
+        let mut broken_links = analyzer.find_broken_links();
+        broken_links.sort_by_key(|b| b.link.line_number);
+        assert_eq!(broken_links.len(), 2);
+        assert!(broken_links[0].reason.starts_with("File not found"));
+        assert_eq!(
+            broken_links[1].reason,
+            "file:// URL points outside the repository: /etc/hosts"
+        );
+
+        let fixes = analyzer.suggested_fixes();
+        assert_eq!(fixes.len(), 1);
+        assert_eq!(fixes[0].replacement, "guide/setup.md#setup");
+    }
+
+    #[test]
+    fn test_include_directives() {
+        let mut vfs = vfs::MemoryFileSystem::new();
+        vfs.insert(
+            "book/src/guide.md",
+            "```rust\n{{#include ../examples/demo.rs:setup}}\n{{#include ../examples/demo.rs:teardown}}\n{{#include ../examples/demo.rs:2:9}}\n```\n--8<-- \"snippets/note.md\"\n<!-- include: missing.md -->",
+        );
+        vfs.insert(
+            "book/examples/demo.rs",
+            "// ANCHOR: setup\nfn main() {}\n// ANCHOR_END: setup",
+        );
+        vfs.insert("book/snippets/note.md", "Note");
+        let mut analyzer = LinkAnalyzer::new(PathBuf::from("book")).with_filesystem(vfs);
+        analyzer.analyze_directory().unwrap();
+
+        let broken = analyzer.find_broken_links();
+        let mut reasons: Vec<(usize, &str)> = broken
+            .iter()
+            .map(|broken| (broken.link.line_number, broken.reason.as_str()))
+            .collect();
+        reasons.sort();
+        assert_eq!(
+            reasons,
+            vec![
+                (3, "Anchor `teardown` not found in book/examples/demo.rs"),
+                (
+                    4,
+                    "Lines 2:9 are outside the file (3 lines) in book/examples/demo.rs"
+                ),
+                (7, "Included file not found: book/src/missing.md"),
+            ]
+        );
+        assert_eq!(
+            analyzer.find_orphaned_documents(),
+            vec![PathBuf::from("book/src/guide.md")]
+        );
+    }
+
+    #[test]
+    fn test_wiki_links() {
+        let content = "See [[Guide]] and [[guide#Install|installing]].\n![[diagram.png]] [[notes/Ideas#^idea-1]]\n[[Guide#Missing]] [[Nowhere]] [[#Local]]\n<!-- [[Ignored]] -->";
+        let links = LinkAnalyzer::extract_wiki_links(content);
+        let targets: Vec<(&str, &str)> = links
+            .iter()
+            .map(|(text, target, _)| (text.as_str(), target.as_str()))
+            .collect();
+        assert_eq!(
+            targets,
+            vec![
+                ("Guide", "[[Guide]]"),
+                ("installing", "[[guide#Install|installing]]"),
+                ("diagram.png", "[[diagram.png]]"),
+                ("notes/Ideas#^idea-1", "[[notes/Ideas#^idea-1]]"),
+                ("Guide#Missing", "[[Guide#Missing]]"),
+                ("Nowhere", "[[Nowhere]]"),
+                ("#Local", "[[#Local]]"),
+            ]
+        );
+
+        let mut vfs = vfs::MemoryFileSystem::new();
+        vfs.insert("vault/README.md", content);
+        vfs.insert("vault/guides/guide.md", "# Guide\n## Install");
+        vfs.insert("vault/attachments/diagram.png", "");
+        vfs.insert("vault/notes/ideas.md", "First idea ^idea-2");
+
+        let config = Config {
+            wiki_links: true,
+            ..Default::default()
+        };
+        let mut analyzer = LinkAnalyzer::with_config(PathBuf::from("vault"), &config)
+            .unwrap()
+            .with_filesystem(vfs);
+        analyzer.analyze_directory().unwrap();
+
+        let mut reasons: Vec<String> = analyzer
+            .find_broken_links()
+            .into_iter()
+            .map(|broken| broken.reason)
+            .collect();
+        reasons.sort();
+        assert_eq!(
+            reasons,
+            vec![
+                "Block not found: #^idea-1 in vault/notes/ideas.md",
+                "Heading not found: #Local in vault/README.md",
+                "Heading not found: #Missing in vault/guides/guide.md",
+                "Note not found: Nowhere",
+            ]
+        );
+        assert!(analyzer.find_orphaned_documents().is_empty());
+    }
+
+    #[test]
+    fn test_github_wiki_flavor() {
+        let mut vfs = vfs::MemoryFileSystem::new();
+        vfs.insert(
+            "wiki/Home.md",
+            "[[Getting Started]] [[the FAQ|faq]] [[Docs|https://example.com/docs]]\n\
+             [[Missing Page]]",
+        );
+        vfs.insert("wiki/guides/Getting-Started.md", "# Getting Started");
+        vfs.insert("wiki/FAQ.md", "");
+        vfs.insert("wiki/_Sidebar.md", "[[Reference]]");
+        vfs.insert("wiki/_Footer.md", "");
+        vfs.insert("wiki/Reference.md", "");
+
+        let config = Config {
+            flavor: "github-wiki".to_string(),
+            ..Default::default()
+        };
+        let mut analyzer = LinkAnalyzer::with_config(PathBuf::from("wiki"), &config)
+            .unwrap()
+            .with_filesystem(vfs);
+        analyzer.analyze_directory().unwrap();
+
+        let broken: Vec<String> = analyzer
+            .find_broken_links()
+            .into_iter()
+            .map(|broken| broken.reason)
+            .collect();
+        assert_eq!(broken, vec!["Note not found: Missing Page"]);
+        assert_eq!(
+            analyzer
+                .resolve(Path::new("wiki/Home.md"), "[[the FAQ|faq]]")
+                .resolved,
+            Some(PathBuf::from("wiki/FAQ.md"))
+        );
+        assert!(analyzer.find_orphaned_documents().is_empty());
+        assert_eq!(
+            analyzer.root_documents(),
+            vec![
+                PathBuf::from("wiki/Home.md"),
+                PathBuf::from("wiki/_Footer.md"),
+                PathBuf::from("wiki/_Sidebar.md"),
+            ]
+        );
+
+        assert!(Flavor::parse("gitlab-wiki").is_err());
+    }
+
+    #[test]
+    fn test_azure_devops_wiki_flavor() {
+        let mut vfs = vfs::MemoryFileSystem::new();
+        vfs.insert(".order", "Home\nGetting-Started\nRetired-Page\n");
+        vfs.insert(
+            "Home.md",
+            "[a](/Getting-Started) [b](/Getting%20Started/Install-Steps#setup)\n\
+             [c](/Q%26A%2DArchive) ![d](/.attachments/diagram-1a2b.png) [e](/Missing-Page)",
+        );
+        vfs.insert("Getting-Started.md", "");
+        vfs.insert("Getting-Started/.order", "Install-Steps");
+        vfs.insert("Getting-Started/Install-Steps.md", "# Setup");
+        vfs.insert("Q&A%2DArchive.md", "");
+        vfs.insert(".attachments/diagram-1a2b.png", "");
+        vfs.insert("Drafts.md", "");
+
+        let config = Config {
+            flavor: "azure-devops-wiki".to_string(),
+            ..Default::default()
+        };
+        let mut analyzer = LinkAnalyzer::with_config(PathBuf::from(""), &config)
+            .unwrap()
+            .with_filesystem(vfs);
+        analyzer.analyze_directory().unwrap();
+
+        let mut broken: Vec<(String, usize, String)> = analyzer
+            .find_broken_links()
+            .into_iter()
+            .map(|broken| {
+                let link = broken.link;
+                (
+                    link.file_path.display().to_string(),
+                    link.line_number,
+                    broken.reason,
+                )
+            })
+            .collect();
+        broken.sort();
+        assert_eq!(
+            broken,
+            vec![
+                (
+                    ".order".to_string(),
+                    3,
+                    "File not found: Retired-Page.md".to_string()
+                ),
+                (
+                    "Home.md".to_string(),
+                    2,
+                    "File not found: Missing-Page.md".to_string()
+                ),
+            ]
+        );
+        assert_eq!(
+            analyzer
+                .resolve(Path::new("Home.md"), "/Q%26A%2DArchive")
+                .resolved,
+            Some(PathBuf::from("Q&A%2DArchive.md"))
+        );
+
+        // Pages listed in `.order` are in the navigation; others must be linked.
+        assert_eq!(
+            analyzer.find_orphaned_documents(),
+            vec![PathBuf::from("Drafts.md")]
+        );
+        // `.order` files are checked, but aren't documents.
+        assert_eq!(analyzer.document_paths().len(), 5);
+    }
+
+    #[test]
+    fn test_version_anchors() {
+        let mut vfs = vfs::MemoryFileSystem::new();
+        vfs.insert(
+            "docs/README.md",
+            "[a](CHANGELOG.md#v1-2-3) [b](CHANGELOG.md#v1-1-0) [c](CHANGELOG.md#v2-0-0-rc-1) \
+             [d](CHANGELOG.md#unreleased) [e](CHANGELOG.md#setup)",
+        );
+        vfs.insert(
+            "docs/CHANGELOG.md",
+            "# Changelog\n\n## Unreleased\n\n## [2.0.0-rc.1] - 2024-06-01\n\n## [1.2.3] - 2024-05-01\n",
+        );
+
+        let config = Config {
+            version_anchors: true,
+            ..Default::default()
+        };
+        let mut analyzer = LinkAnalyzer::with_config(PathBuf::from("docs"), &config)
+            .unwrap()
+            .with_filesystem(vfs);
+        analyzer.analyze_directory().unwrap();
+
+        let mut reasons: Vec<String> = analyzer
+            .find_broken_links()
+            .into_iter()
+            .map(|broken| broken.reason)
+            .collect();
+        reasons.sort();
+        assert_eq!(
+            reasons,
+            vec![
+                "Anchor not found: #setup in docs/CHANGELOG.md",
+                "No heading for version 1.1.0 (#v1-1-0) in docs/CHANGELOG.md",
+            ]
+        );
+    }
+}
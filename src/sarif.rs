@@ -0,0 +1,344 @@
+use crate::{normalize_path, BrokenLink, LinkKind};
+use serde::Serialize;
+use std::path::Path;
+
+const SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+
+/// Rule ids with what they report, in the order they are listed.
+const RULES: [(&str, &str); 3] = [
+    ("broken-link", "Link to a file that does not exist"),
+    (
+        "missing-anchor",
+        "Link to a heading or block that does not exist",
+    ),
+    ("external-link", "External link that does not work"),
+];
+
+/// A SARIF 2.1.0 log of broken links, for GitHub Code Scanning and other
+/// static analysis dashboards.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[non_exhaustive]
+pub struct SarifLog {
+    #[serde(rename = "$schema")]
+    pub schema: String,
+    pub version: String,
+    pub runs: Vec<SarifRun>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[non_exhaustive]
+pub struct SarifRun {
+    pub tool: SarifTool,
+    pub results: Vec<SarifResult>,
+    /// Present once something kept the run from checking everything.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub invocations: Vec<SarifInvocation>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[non_exhaustive]
+#[serde(rename_all = "camelCase")]
+pub struct SarifInvocation {
+    pub execution_successful: bool,
+    pub tool_execution_notifications: Vec<SarifNotification>,
+}
+
+/// A problem with the run itself rather than with a document, such as a
+/// target that could not be checked.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[non_exhaustive]
+pub struct SarifNotification {
+    pub level: SarifLevel,
+    pub message: SarifMessage,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[non_exhaustive]
+pub struct SarifTool {
+    pub driver: SarifDriver,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[non_exhaustive]
+#[serde(rename_all = "camelCase")]
+pub struct SarifDriver {
+    pub name: String,
+    pub version: String,
+    pub information_uri: String,
+    pub rules: Vec<SarifRule>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[non_exhaustive]
+#[serde(rename_all = "camelCase")]
+pub struct SarifRule {
+    pub id: String,
+    pub short_description: SarifMessage,
+}
+
+/// One finding, at a line of a document.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[non_exhaustive]
+#[serde(rename_all = "camelCase")]
+pub struct SarifResult {
+    pub rule_id: String,
+    pub level: SarifLevel,
+    pub message: SarifMessage,
+    pub locations: Vec<SarifLocation>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[non_exhaustive]
+#[serde(rename_all = "lowercase")]
+pub enum SarifLevel {
+    Error,
+    Warning,
+    Note,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[non_exhaustive]
+pub struct SarifMessage {
+    pub text: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[non_exhaustive]
+#[serde(rename_all = "camelCase")]
+pub struct SarifLocation {
+    pub physical_location: SarifPhysicalLocation,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[non_exhaustive]
+#[serde(rename_all = "camelCase")]
+pub struct SarifPhysicalLocation {
+    pub artifact_location: SarifArtifactLocation,
+    pub region: SarifRegion,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[non_exhaustive]
+pub struct SarifArtifactLocation {
+    /// Path relative to the working directory, with `/` separators, or a
+    /// `file://` URL for documents outside it.
+    pub uri: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[non_exhaustive]
+#[serde(rename_all = "camelCase")]
+pub struct SarifRegion {
+    pub start_line: usize,
+}
+
+impl Default for SarifLog {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SarifLog {
+    /// A log with one run of this tool and no results yet.
+    pub fn new() -> Self {
+        let rules = RULES
+            .iter()
+            .map(|(id, description)| SarifRule {
+                id: id.to_string(),
+                short_description: SarifMessage {
+                    text: description.to_string(),
+                },
+            })
+            .collect();
+        Self {
+            schema: SCHEMA.to_string(),
+            version: "2.1.0".to_string(),
+            runs: vec![SarifRun {
+                tool: SarifTool {
+                    driver: SarifDriver {
+                        name: env!("CARGO_PKG_NAME").to_string(),
+                        version: env!("CARGO_PKG_VERSION").to_string(),
+                        information_uri: env!("CARGO_PKG_REPOSITORY").to_string(),
+                        rules,
+                    },
+                },
+                results: Vec::new(),
+                invocations: Vec::new(),
+            }],
+        }
+    }
+
+    /// Adds `broken_link` as a result at `level`.
+    pub fn push(&mut self, broken_link: &BrokenLink, level: SarifLevel) {
+        let link = &broken_link.link;
+        self.runs[0].results.push(SarifResult {
+            rule_id: rule_id(broken_link).to_string(),
+            level,
+            message: SarifMessage {
+                text: format!("{}: {}", link.target, broken_link.reason),
+            },
+            locations: vec![SarifLocation {
+                physical_location: SarifPhysicalLocation {
+                    artifact_location: SarifArtifactLocation {
+                        uri: artifact_uri(&link.file_path),
+                    },
+                    region: SarifRegion {
+                        start_line: link.line_number,
+                    },
+                },
+            }],
+        });
+    }
+
+    /// Records an error that kept part of the run from being checked, and
+    /// marks the run as unsuccessful.
+    pub fn push_error(&mut self, message: &str) {
+        let run = &mut self.runs[0];
+        if run.invocations.is_empty() {
+            run.invocations.push(SarifInvocation {
+                execution_successful: false,
+                tool_execution_notifications: Vec::new(),
+            });
+        }
+        run.invocations[0]
+            .tool_execution_notifications
+            .push(SarifNotification {
+                level: SarifLevel::Error,
+                message: SarifMessage {
+                    text: message.to_string(),
+                },
+            });
+    }
+
+    pub fn results(&self) -> &[SarifResult] {
+        &self.runs[0].results
+    }
+}
+
+/// The rule a broken link breaks: a missing anchor, an external link that
+/// failed its request, or else a missing file.
+pub fn rule_id(broken_link: &BrokenLink) -> &'static str {
+    let reason = &broken_link.reason;
+    let anchor = [
+        "Anchor not found",
+        "Heading not found",
+        "Block not found",
+        "No heading for version",
+    ];
+    if anchor.iter().any(|prefix| reason.starts_with(prefix)) {
+        "missing-anchor"
+    } else if LinkKind::classify(&broken_link.link.target) == LinkKind::External
+        && !reason.starts_with("No local document")
+    {
+        "external-link"
+    } else {
+        "broken-link"
+    }
+}
+
+fn artifact_uri(path: &Path) -> String {
+    let path = normalize_path(path).to_string_lossy().replace('\\', "/");
+    let mut uri = String::with_capacity(path.len());
+    for byte in path.bytes() {
+        if byte.is_ascii_alphanumeric() || b"-._~/:".contains(&byte) {
+            uri.push(byte as char);
+        } else {
+            uri.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    if uri.starts_with('/') {
+        format!("file://{}", uri)
+    } else if uri.as_bytes().get(1) == Some(&b':') {
+        format!("file:///{}", uri)
+    } else {
+        uri
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MarkdownLink;
+    use std::path::PathBuf;
+
+    fn broken(file_path: &str, target: &str, reason: &str) -> BrokenLink {
+        BrokenLink {
+            link: MarkdownLink {
+                text: "Link".to_string(),
+                target: target.to_string(),
+                line_number: 3,
+                file_path: PathBuf::from(file_path),
+            },
+            reason: reason.to_string(),
+            suggestion: None,
+        }
+    }
+
+    #[test]
+    fn test_sarif_log() {
+        let mut log = SarifLog::new();
+        log.push(
+            &broken(
+                "./docs/my guide.md",
+                "./gone.md",
+                "File not found: docs/gone.md",
+            ),
+            SarifLevel::Error,
+        );
+        log.push(
+            &broken(
+                "/abs/README.md",
+                "#usage",
+                "Anchor not found: #usage in README.md",
+            ),
+            SarifLevel::Error,
+        );
+        log.push(
+            &broken("docs/a.md", "https://example.com/x", "HTTP 404 Not Found"),
+            SarifLevel::Warning,
+        );
+
+        let json = serde_json::to_value(&log).unwrap();
+        assert_eq!(json["version"], "2.1.0");
+        assert_eq!(
+            json["runs"][0]["tool"]["driver"]["rules"][1]["id"],
+            "missing-anchor"
+        );
+        let results = &json["runs"][0]["results"];
+        assert_eq!(
+            results[0],
+            serde_json::json!({
+                "ruleId": "broken-link",
+                "level": "error",
+                "message": {"text": "./gone.md: File not found: docs/gone.md"},
+                "locations": [{
+                    "physicalLocation": {
+                        "artifactLocation": {"uri": "docs/my%20guide.md"},
+                        "region": {"startLine": 3}
+                    }
+                }]
+            })
+        );
+        assert_eq!(results[1]["ruleId"], "missing-anchor");
+        assert_eq!(
+            results[1]["locations"][0]["physicalLocation"]["artifactLocation"]["uri"],
+            "file:///abs/README.md"
+        );
+        assert_eq!(results[2]["ruleId"], "external-link");
+        assert_eq!(results[2]["level"], "warning");
+        assert!(json["runs"][0].get("invocations").is_none());
+
+        log.push_error("https://example.com/repo.git: could not clone");
+        let json = serde_json::to_value(&log).unwrap();
+        assert_eq!(
+            json["runs"][0]["invocations"],
+            serde_json::json!([{
+                "executionSuccessful": false,
+                "toolExecutionNotifications": [{
+                    "level": "error",
+                    "message": {"text": "https://example.com/repo.git: could not clone"}
+                }]
+            }])
+        );
+    }
+}
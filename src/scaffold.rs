@@ -0,0 +1,168 @@
+use crate::resolve::relative_path;
+use regex::Regex;
+use std::path::Path;
+use std::sync::LazyLock;
+
+/// A list item starting with a link, e.g. `- [Setup](./setup.md)` or
+/// `2. [Usage](usage.md)`: indentation, marker, and ordered item number.
+static LINK_ITEM: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^(\s*)(?:([-*+])|(\d+)([.)]))\s+\[").unwrap());
+
+/// Placeholder in templates replaced by the new document's title.
+pub const TITLE_PLACEHOLDER: &str = "{{title}}";
+
+/// Title for a new document named after its file, e.g. `Getting started`
+/// for `getting-started.md`.
+pub fn title_from_path(path: &Path) -> String {
+    let stem = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().replace(['-', '_'], " "))
+        .unwrap_or_default();
+    let mut chars = stem.trim().chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+/// Content of a new document: `template` with its placeholders filled in,
+/// or just a heading without one.
+pub fn render(template: Option<&str>, title: &str) -> String {
+    match template {
+        Some(template) => template.replace(TITLE_PLACEHOLDER, title),
+        None => format!("# {}\n", title),
+    }
+}
+
+/// Target of a link from `index` to `document`, both relative to the same
+/// directory, e.g. `./guides/setup.md`.
+pub fn link_target(index: &Path, document: &Path) -> String {
+    let from = index.parent().unwrap_or(Path::new(""));
+    let target = relative_path(from, document)
+        .to_string_lossy()
+        .replace('\\', "/");
+    if target.starts_with("../") {
+        target
+    } else {
+        format!("./{}", target)
+    }
+}
+
+/// `index` with a `[text](target)` list item added after its last list item
+/// that starts with a link, matching its indentation and marker, or at the
+/// end if it has none. Returns the new content and the item's line number.
+pub fn insert_link(index: &str, text: &str, target: &str) -> (String, usize) {
+    let lines: Vec<&str> = index.lines().collect();
+    let mut in_fence = false;
+    let mut last_item = None;
+    for (number, line) in lines.iter().enumerate() {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_fence = !in_fence;
+        } else if !in_fence {
+            if let Some(caps) = LINK_ITEM.captures(line) {
+                last_item = Some((number, caps));
+            }
+        }
+    }
+
+    let mut content = String::new();
+    let line_number = match last_item {
+        Some((number, caps)) => {
+            let marker = match (caps.get(2), caps.get(3), caps.get(4)) {
+                (Some(bullet), _, _) => bullet.as_str().to_string(),
+                (None, Some(item), Some(delimiter)) => format!(
+                    "{}{}",
+                    item.as_str().parse::<u64>().unwrap_or(0) + 1,
+                    delimiter.as_str()
+                ),
+                _ => "-".to_string(),
+            };
+            let item = format!("{}{} [{}]({})", &caps[1], marker, text, target);
+            for line in &lines[..=number] {
+                content.push_str(line);
+                content.push('\n');
+            }
+            content.push_str(&item);
+            content.push('\n');
+            for line in &lines[number + 1..] {
+                content.push_str(line);
+                content.push('\n');
+            }
+            if !index.ends_with('\n') {
+                content.pop();
+            }
+            number + 2
+        }
+        None => {
+            content.push_str(index);
+            if !index.is_empty() && !index.ends_with('\n') {
+                content.push('\n');
+            }
+            let mut line_number = lines.len() + 1;
+            if lines.last().is_some_and(|line| !line.trim().is_empty()) {
+                content.push('\n');
+                line_number += 1;
+            }
+            content.push_str(&format!("- [{}]({})\n", text, target));
+            line_number
+        }
+    };
+    (content, line_number)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_title_and_target() {
+        assert_eq!(
+            title_from_path(Path::new("docs/getting-started.md")),
+            "Getting started"
+        );
+        assert_eq!(
+            link_target(
+                Path::new("docs/guides/index.md"),
+                Path::new("docs/guides/foo.md")
+            ),
+            "./foo.md"
+        );
+        assert_eq!(
+            link_target(Path::new("docs/README.md"), Path::new("./docs/api/x.md")),
+            "./api/x.md"
+        );
+        assert_eq!(
+            link_target(Path::new("docs/api/index.md"), Path::new("docs/faq.md")),
+            "../faq.md"
+        );
+        assert_eq!(
+            render(Some("# {{title}}\n\nSee also {{title}}.\n"), "Foo"),
+            "# Foo\n\nSee also Foo.\n"
+        );
+        assert_eq!(render(None, "Foo"), "# Foo\n");
+    }
+
+    #[test]
+    fn test_insert_link() {
+        let index = "# Guides\n\n- [Setup](./setup.md)\n  * [Proxy](./proxy.md)\n\n```\n- [Not](./a.md)\n```\n\nMore text\n";
+        let (content, line) = insert_link(index, "Foo", "./foo.md");
+        assert_eq!(
+            content,
+            "# Guides\n\n- [Setup](./setup.md)\n  * [Proxy](./proxy.md)\n  * [Foo](./foo.md)\n\n```\n- [Not](./a.md)\n```\n\nMore text\n"
+        );
+        assert_eq!(line, 5);
+
+        let (content, line) = insert_link("1. [One](1.md)\n2. [Two](2.md)", "Three", "3.md");
+        assert_eq!(content, "1. [One](1.md)\n2. [Two](2.md)\n3. [Three](3.md)");
+        assert_eq!(line, 3);
+
+        let (content, line) = insert_link("# Index\n\nNo list yet.", "Foo", "./foo.md");
+        assert_eq!(content, "# Index\n\nNo list yet.\n\n- [Foo](./foo.md)\n");
+        assert_eq!(line, 5);
+
+        let (content, line) = insert_link("", "Foo", "./foo.md");
+        assert_eq!(content, "- [Foo](./foo.md)\n");
+        assert_eq!(line, 1);
+    }
+}
@@ -0,0 +1,227 @@
+use crate::{BrokenLink, LinkAnalyzer, LinkKind, MarkdownLink};
+use std::path::PathBuf;
+
+/// Which way a navigation link leads through a sequence.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    Next,
+    Previous,
+}
+
+impl Direction {
+    /// The direction a link text such as `Next: Setup →` or `« Previous`
+    /// names by its first word, if any.
+    pub fn of(text: &str) -> Option<Self> {
+        let first_word = text
+            .split(|c: char| !c.is_alphanumeric())
+            .find(|word| !word.is_empty())?
+            .to_lowercase();
+        match first_word.as_str() {
+            "next" => Some(Self::Next),
+            "previous" | "prev" => Some(Self::Previous),
+            _ => None,
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            Self::Next => "Next",
+            Self::Previous => "Previous",
+        }
+    }
+}
+
+impl LinkAnalyzer {
+    /// Navigation links that break the configured `sequences`: every page
+    /// but the last needs a "Next" link to the following page, every page
+    /// but the first a "Previous" link to the one before, and none of them
+    /// may lead anywhere else in the sequence. A missing navigation link is
+    /// reported at the end of its page, with the page it should lead to as
+    /// its target. Pages that don't exist are left to the broken link check.
+    pub fn find_sequence_breaks(&self) -> Vec<BrokenLink> {
+        let mut breaks = Vec::new();
+        for (name, pages) in &self.sequences {
+            let documents: Vec<Option<PathBuf>> = pages
+                .iter()
+                .map(|page| self.find_document(&self.base_path.join(page)))
+                .collect();
+            let keys: Vec<Option<PathBuf>> = documents
+                .iter()
+                .map(|doc| Some(self.document_key(doc.as_ref()?)))
+                .collect();
+
+            for (index, document) in documents.iter().enumerate() {
+                let Some(document) = document else {
+                    continue;
+                };
+                let links = self.documents.get(document).map_or(&[][..], Vec::as_slice);
+                for direction in [Direction::Next, Direction::Previous] {
+                    let expected = match direction {
+                        Direction::Next => Some(index + 1).filter(|&next| next < pages.len()),
+                        Direction::Previous => index.checked_sub(1),
+                    };
+                    let navigation: Vec<&MarkdownLink> = links
+                        .iter()
+                        .filter(|link| Direction::of(&link.text) == Some(direction))
+                        .filter(|link| LinkKind::classify(&link.target) != LinkKind::External)
+                        .collect();
+
+                    let Some(expected) = expected else {
+                        // The first or last page: leaving the sequence is fine,
+                        // looping back into it is not.
+                        for link in navigation {
+                            let resolved = self.resolve(document, &link.target).resolved;
+                            if resolved.is_some() && keys.contains(&resolved) {
+                                breaks.push(BrokenLink {
+                                    link: link.clone(),
+                                    reason: format!(
+                                        "{} leads back into sequence `{}`, which {} here",
+                                        direction.label(),
+                                        name,
+                                        match direction {
+                                            Direction::Next => "ends",
+                                            Direction::Previous => "starts",
+                                        }
+                                    ),
+                                    suggestion: None,
+                                });
+                            }
+                        }
+                        continue;
+                    };
+                    let Some(expected_path) = &keys[expected] else {
+                        continue;
+                    };
+
+                    if navigation.is_empty() {
+                        let last_line = self
+                            .read_document(document)
+                            .map_or(1, |content| content.lines().count().max(1));
+                        breaks.push(BrokenLink {
+                            link: MarkdownLink {
+                                text: direction.label().to_string(),
+                                target: self.relative_target(document, expected_path),
+                                line_number: last_line,
+                                file_path: document.clone(),
+                            },
+                            reason: format!(
+                                "No {} link to {} in sequence `{}`",
+                                direction.label(),
+                                pages[expected],
+                                name
+                            ),
+                            suggestion: None,
+                        });
+                        continue;
+                    }
+                    for link in navigation {
+                        let resolved = self.resolve(document, &link.target).resolved;
+                        // Unresolvable targets are reported as broken links.
+                        if resolved.is_some() && resolved.as_ref() != Some(expected_path) {
+                            breaks.push(BrokenLink {
+                                link: link.clone(),
+                                reason: format!(
+                                    "{} should lead to {} in sequence `{}`",
+                                    direction.label(),
+                                    pages[expected],
+                                    name
+                                ),
+                                suggestion: None,
+                            });
+                        }
+                    }
+                }
+            }
+        }
+
+        breaks.sort_by(|a, b| {
+            (&a.link.file_path, a.link.line_number).cmp(&(&b.link.file_path, b.link.line_number))
+        });
+        breaks
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vfs::MemoryFileSystem;
+    use crate::Config;
+
+    #[test]
+    fn test_direction() {
+        assert_eq!(Direction::of("Next: Setup →"), Some(Direction::Next));
+        assert_eq!(Direction::of("« Previous"), Some(Direction::Previous));
+        assert_eq!(Direction::of("prev"), Some(Direction::Previous));
+        assert_eq!(Direction::of("Nextcloud setup"), None);
+        assert_eq!(Direction::of("Preview"), None);
+    }
+
+    #[test]
+    fn test_find_sequence_breaks() {
+        let mut vfs = MemoryFileSystem::new();
+        vfs.insert("docs/tutorial/1.md", "# One\n\n[Next →](./2.md)\n");
+        // Skips page 3 and is missing its Previous link.
+        vfs.insert("docs/tutorial/2.md", "# Two\n\n[Next](./4.md)\n");
+        vfs.insert(
+            "docs/tutorial/3.md",
+            "# Three\n\n[Previous](./2.md) | [Next](./4.md)\n",
+        );
+        vfs.insert(
+            "docs/tutorial/4.md",
+            "# Four\n\n[Previous](3.md) [Next: Reference](../reference.md) [Next](./1.md)\n",
+        );
+        vfs.insert("docs/reference.md", "# Reference\n");
+        let config = Config {
+            sequences: [(
+                "tutorial".to_string(),
+                ["1", "2", "3", "4"]
+                    .iter()
+                    .map(|page| format!("tutorial/{}.md", page))
+                    .collect(),
+            )]
+            .into(),
+            ..Default::default()
+        };
+        let mut analyzer = LinkAnalyzer::with_config(PathBuf::from("docs"), &config)
+            .unwrap()
+            .with_filesystem(vfs);
+        analyzer.analyze_directory().unwrap();
+
+        let breaks: Vec<(String, usize, String, String)> = analyzer
+            .find_sequence_breaks()
+            .into_iter()
+            .map(|broken| {
+                let link = broken.link;
+                (
+                    link.file_path.to_string_lossy().into_owned(),
+                    link.line_number,
+                    link.target,
+                    broken.reason,
+                )
+            })
+            .collect();
+        assert_eq!(
+            breaks,
+            vec![
+                (
+                    "docs/tutorial/2.md".to_string(),
+                    3,
+                    "./4.md".to_string(),
+                    "Next should lead to tutorial/3.md in sequence `tutorial`".to_string()
+                ),
+                (
+                    "docs/tutorial/2.md".to_string(),
+                    3,
+                    "1.md".to_string(),
+                    "No Previous link to tutorial/1.md in sequence `tutorial`".to_string()
+                ),
+                (
+                    "docs/tutorial/4.md".to_string(),
+                    3,
+                    "./1.md".to_string(),
+                    "Next leads back into sequence `tutorial`, which ends here".to_string()
+                ),
+            ]
+        );
+    }
+}
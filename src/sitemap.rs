@@ -0,0 +1,224 @@
+use crate::graph::LinkGraph;
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum SitemapLayout {
+    /// Documents nested under the page that first links to them.
+    Graph,
+    /// Documents nested under their directories.
+    Directory,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SitemapEntry {
+    /// Path relative to the analyzed directory.
+    pub path: PathBuf,
+    pub depth: usize,
+    pub is_directory: bool,
+    pub reachable: bool,
+}
+
+pub fn build(
+    graph: &LinkGraph,
+    roots: &[PathBuf],
+    base_path: &Path,
+    layout: SitemapLayout,
+) -> Vec<SitemapEntry> {
+    let tree = graph.spanning_tree(roots);
+    let relative = |path: &Path| path.strip_prefix(base_path).unwrap_or(path).to_path_buf();
+
+    let mut entries = Vec::new();
+    match layout {
+        SitemapLayout::Graph => {
+            let mut children: BTreeMap<&PathBuf, Vec<&PathBuf>> = BTreeMap::new();
+            for (node, parent) in &tree {
+                if let Some(parent) = parent {
+                    children.entry(parent).or_default().push(node);
+                }
+            }
+
+            let mut stack: Vec<(&PathBuf, usize)> = roots
+                .iter()
+                .filter(|root| tree.contains_key(*root))
+                .rev()
+                .map(|root| (root, 0))
+                .collect();
+            while let Some((node, depth)) = stack.pop() {
+                entries.push(SitemapEntry {
+                    path: relative(node),
+                    depth,
+                    is_directory: false,
+                    reachable: true,
+                });
+                if let Some(nodes) = children.get(node) {
+                    stack.extend(nodes.iter().rev().map(|child| (*child, depth + 1)));
+                }
+            }
+
+            for node in &graph.nodes {
+                if !tree.contains_key(node) {
+                    entries.push(SitemapEntry {
+                        path: relative(node),
+                        depth: 0,
+                        is_directory: false,
+                        reachable: false,
+                    });
+                }
+            }
+        }
+        SitemapLayout::Directory => {
+            let documents: BTreeSet<PathBuf> = graph.nodes.iter().map(|n| relative(n)).collect();
+            let mut listed_dirs: BTreeSet<PathBuf> = BTreeSet::new();
+
+            for document in &documents {
+                let mut dir = PathBuf::new();
+                let parent = document.parent().unwrap_or(Path::new(""));
+                for (depth, component) in parent.components().enumerate() {
+                    dir.push(component);
+                    if listed_dirs.insert(dir.clone()) {
+                        entries.push(SitemapEntry {
+                            path: dir.clone(),
+                            depth,
+                            is_directory: true,
+                            reachable: true,
+                        });
+                    }
+                }
+                entries.push(SitemapEntry {
+                    path: document.clone(),
+                    depth: parent.components().count(),
+                    is_directory: false,
+                    reachable: tree.contains_key(&base_path.join(document)),
+                });
+            }
+        }
+    }
+
+    entries
+}
+
+pub fn to_markdown(entries: &[SitemapEntry]) -> String {
+    let mut output = String::from("# Sitemap\n\n");
+    // Under a directory heading the file name is enough; in the graph layout
+    // the full path is the only hint of where a page lives.
+    let by_directory = entries.iter().any(|e| e.is_directory);
+
+    for entry in entries {
+        let indent = "  ".repeat(entry.depth);
+        let path = entry.path.to_string_lossy().replace('\\', "/");
+        let name = match entry.path.file_name() {
+            Some(name) if by_directory => name.to_string_lossy().into_owned(),
+            _ => path.clone(),
+        };
+        if entry.is_directory {
+            output.push_str(&format!("{}- {}/\n", indent, name));
+        } else {
+            output.push_str(&format!("{}- [{}]({})", indent, name, path));
+            if !entry.reachable {
+                output.push_str(" ⚠ unreachable");
+            }
+            output.push('\n');
+        }
+    }
+
+    output
+}
+
+pub fn to_xml(entries: &[SitemapEntry], base_url: &str) -> String {
+    let mut output = String::from(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<urlset xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\">\n",
+    );
+    let base_url = base_url.trim_end_matches('/');
+
+    for entry in entries.iter().filter(|e| !e.is_directory) {
+        let path = entry.path.to_string_lossy().replace('\\', "/");
+        let loc = if base_url.is_empty() {
+            path
+        } else {
+            format!("{}/{}", base_url, path)
+        };
+        if !entry.reachable {
+            output.push_str("  <!-- unreachable from the entry documents -->\n");
+        }
+        output.push_str(&format!("  <url><loc>{}</loc></url>\n", xml_escape(&loc)));
+    }
+
+    output.push_str("</urlset>\n");
+    output
+}
+
+pub(crate) fn xml_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_graph() -> LinkGraph {
+        let mut graph = LinkGraph::default();
+        graph.add_edge("README.md".into(), "docs/guide.md".into());
+        graph.add_edge("docs/guide.md".into(), "docs/api/client.md".into());
+        graph.add_node("notes.md".into());
+        graph
+    }
+
+    #[test]
+    fn test_graph_layout_markdown() {
+        let entries = build(
+            &sample_graph(),
+            &["README.md".into()],
+            Path::new(""),
+            SitemapLayout::Graph,
+        );
+        assert_eq!(
+            to_markdown(&entries),
+            "# Sitemap\n\n\
+             - [README.md](README.md)\n\
+             \x20 - [docs/guide.md](docs/guide.md)\n\
+             \x20   - [docs/api/client.md](docs/api/client.md)\n\
+             - [notes.md](notes.md) ⚠ unreachable\n"
+        );
+    }
+
+    #[test]
+    fn test_directory_layout_markdown() {
+        let entries = build(
+            &sample_graph(),
+            &["README.md".into()],
+            Path::new(""),
+            SitemapLayout::Directory,
+        );
+        assert_eq!(
+            to_markdown(&entries),
+            "# Sitemap\n\n\
+             - [README.md](README.md)\n\
+             - docs/\n\
+             \x20 - api/\n\
+             \x20   - [client.md](docs/api/client.md)\n\
+             \x20 - [guide.md](docs/guide.md)\n\
+             - [notes.md](notes.md) ⚠ unreachable\n"
+        );
+    }
+
+    #[test]
+    fn test_xml() {
+        let entries = build(
+            &sample_graph(),
+            &["README.md".into()],
+            Path::new(""),
+            SitemapLayout::Graph,
+        );
+        let xml = to_xml(&entries, "https://docs.example.com/");
+        assert!(xml.contains("<url><loc>https://docs.example.com/docs/guide.md</loc></url>"));
+        assert!(xml.contains(
+            "<!-- unreachable from the entry documents -->\n  <url><loc>https://docs.example.com/notes.md</loc></url>"
+        ));
+    }
+}
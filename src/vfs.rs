@@ -0,0 +1,392 @@
+use std::collections::BTreeMap;
+use std::fmt::Debug;
+use std::fs;
+use std::io;
+#[cfg(feature = "archive")]
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use walkdir::WalkDir;
+
+use crate::normalize_path;
+
+pub trait FileSystem: Debug + Send + Sync {
+    fn files(&self, root: &Path) -> Result<Vec<PathBuf>, Box<dyn std::error::Error>>;
+    fn read_to_string(&self, path: &Path) -> io::Result<String>;
+    fn exists(&self, path: &Path) -> bool;
+    fn canonicalize(&self, path: &Path) -> io::Result<PathBuf>;
+
+    /// Like `files`, but leaves out files `skip` returns true for and
+    /// everything under directories it returns true for. Its second
+    /// argument says whether the path is a directory.
+    fn files_skipping(
+        &self,
+        root: &Path,
+        skip: &dyn Fn(&Path, bool) -> bool,
+    ) -> Result<Vec<PathBuf>, Box<dyn std::error::Error>> {
+        let normalized = normalize_path(root);
+        let mut files = self.files(root)?;
+        files.retain(|path| {
+            let relative = path
+                .strip_prefix(&normalized)
+                .or_else(|_| path.strip_prefix(root))
+                .unwrap_or(path);
+            !relative
+                .ancestors()
+                .filter(|ancestor| !ancestor.as_os_str().is_empty())
+                .any(|ancestor| skip(&root.join(ancestor), ancestor != relative))
+        });
+        Ok(files)
+    }
+
+    /// Size in bytes, checked before reading against `max_file_bytes`.
+    fn file_size(&self, path: &Path) -> io::Result<u64> {
+        self.read_to_string(path)
+            .map(|content| content.len() as u64)
+    }
+
+    /// When the file was last changed, if the file system knows.
+    fn modified(&self, path: &Path) -> io::Result<SystemTime> {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            path.display().to_string(),
+        ))
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct OsFileSystem;
+
+impl FileSystem for OsFileSystem {
+    fn files(&self, root: &Path) -> Result<Vec<PathBuf>, Box<dyn std::error::Error>> {
+        walk(root, &|_, _| false, false)
+    }
+
+    fn files_skipping(
+        &self,
+        root: &Path,
+        skip: &dyn Fn(&Path, bool) -> bool,
+    ) -> Result<Vec<PathBuf>, Box<dyn std::error::Error>> {
+        walk(root, skip, false)
+    }
+
+    fn read_to_string(&self, path: &Path) -> io::Result<String> {
+        fs::read_to_string(path)
+    }
+
+    fn exists(&self, path: &Path) -> bool {
+        path.exists()
+    }
+
+    fn canonicalize(&self, path: &Path) -> io::Result<PathBuf> {
+        path.canonicalize()
+    }
+
+    fn file_size(&self, path: &Path) -> io::Result<u64> {
+        fs::metadata(path).map(|metadata| metadata.len())
+    }
+
+    fn modified(&self, path: &Path) -> io::Result<SystemTime> {
+        fs::metadata(path)?.modified()
+    }
+}
+
+/// The local file system, walked into symlinked files and directories. A
+/// directory linked from inside itself is walked once, and dangling links
+/// are passed over.
+#[derive(Debug, Default)]
+pub struct FollowingFileSystem;
+
+impl FileSystem for FollowingFileSystem {
+    fn files(&self, root: &Path) -> Result<Vec<PathBuf>, Box<dyn std::error::Error>> {
+        walk(root, &|_, _| false, true)
+    }
+
+    fn files_skipping(
+        &self,
+        root: &Path,
+        skip: &dyn Fn(&Path, bool) -> bool,
+    ) -> Result<Vec<PathBuf>, Box<dyn std::error::Error>> {
+        walk(root, skip, true)
+    }
+
+    fn read_to_string(&self, path: &Path) -> io::Result<String> {
+        OsFileSystem.read_to_string(path)
+    }
+
+    fn exists(&self, path: &Path) -> bool {
+        OsFileSystem.exists(path)
+    }
+
+    fn canonicalize(&self, path: &Path) -> io::Result<PathBuf> {
+        OsFileSystem.canonicalize(path)
+    }
+
+    fn file_size(&self, path: &Path) -> io::Result<u64> {
+        OsFileSystem.file_size(path)
+    }
+
+    fn modified(&self, path: &Path) -> io::Result<SystemTime> {
+        OsFileSystem.modified(path)
+    }
+}
+
+fn walk(
+    root: &Path,
+    skip: &dyn Fn(&Path, bool) -> bool,
+    follow_links: bool,
+) -> Result<Vec<PathBuf>, Box<dyn std::error::Error>> {
+    let mut files = Vec::new();
+    let walk = WalkDir::new(root)
+        .follow_links(follow_links)
+        .into_iter()
+        .filter_entry(|entry| {
+            entry.depth() == 0 || !skip(entry.path(), entry.file_type().is_dir())
+        });
+    for entry in walk {
+        let entry = match entry {
+            Ok(entry) => entry,
+            Err(e)
+                if follow_links
+                    && (e.loop_ancestor().is_some()
+                        || e.io_error()
+                            .is_some_and(|e| e.kind() == io::ErrorKind::NotFound)) =>
+            {
+                continue
+            }
+            Err(e) => return Err(e.into()),
+        };
+        if entry.file_type().is_file() {
+            files.push(entry.into_path());
+        }
+    }
+    Ok(files)
+}
+
+/// An in-memory file tree, e.g. the contents of a documentation archive.
+/// Paths are stored lexically normalized; directories exist implicitly.
+#[derive(Debug, Default)]
+pub struct MemoryFileSystem {
+    files: BTreeMap<PathBuf, Vec<u8>>,
+}
+
+impl MemoryFileSystem {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn insert(&mut self, path: impl AsRef<Path>, contents: impl Into<Vec<u8>>) {
+        self.files
+            .insert(normalize_path(path.as_ref()), contents.into());
+    }
+
+    pub fn len(&self) -> usize {
+        self.files.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+
+    /// Loads a `.tar`, `.tar.gz`/`.tgz` or `.zip` archive, chosen by file extension.
+    #[cfg(feature = "archive")]
+    pub fn from_archive(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        let name = path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or_default()
+            .to_lowercase();
+        let file = fs::File::open(path)?;
+
+        if name.ends_with(".zip") {
+            Self::from_zip(file)
+        } else if name.ends_with(".tar.gz") || name.ends_with(".tgz") {
+            Self::from_tar(flate2::read::GzDecoder::new(file))
+        } else if name.ends_with(".tar") {
+            Self::from_tar(file)
+        } else {
+            Err(format!("Unsupported archive format: {}", path.display()).into())
+        }
+    }
+
+    #[cfg(feature = "archive")]
+    pub fn from_tar(reader: impl Read) -> Result<Self, Box<dyn std::error::Error>> {
+        let mut vfs = Self::new();
+        let mut archive = tar::Archive::new(reader);
+
+        for entry in archive.entries()? {
+            let mut entry = entry?;
+            if !entry.header().entry_type().is_file() {
+                continue;
+            }
+            let path = entry.path()?.into_owned();
+            let mut contents = Vec::new();
+            entry.read_to_end(&mut contents)?;
+            vfs.insert(path, contents);
+        }
+
+        Ok(vfs)
+    }
+
+    #[cfg(feature = "archive")]
+    pub fn from_zip(reader: impl Read + io::Seek) -> Result<Self, Box<dyn std::error::Error>> {
+        let mut vfs = Self::new();
+        let mut archive = zip::ZipArchive::new(reader)?;
+
+        for index in 0..archive.len() {
+            let mut entry = archive.by_index(index)?;
+            if !entry.is_file() {
+                continue;
+            }
+            let Some(path) = entry.enclosed_name() else {
+                continue;
+            };
+            let mut contents = Vec::new();
+            entry.read_to_end(&mut contents)?;
+            vfs.insert(path, contents);
+        }
+
+        Ok(vfs)
+    }
+}
+
+impl IntoIterator for MemoryFileSystem {
+    type Item = (PathBuf, Vec<u8>);
+    type IntoIter = std::collections::btree_map::IntoIter<PathBuf, Vec<u8>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.files.into_iter()
+    }
+}
+
+impl FileSystem for MemoryFileSystem {
+    fn files(&self, root: &Path) -> Result<Vec<PathBuf>, Box<dyn std::error::Error>> {
+        let root = normalize_path(root);
+        Ok(self
+            .files
+            .keys()
+            .filter(|path| path.starts_with(&root))
+            .cloned()
+            .collect())
+    }
+
+    fn read_to_string(&self, path: &Path) -> io::Result<String> {
+        let contents = self
+            .files
+            .get(&normalize_path(path))
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, path.display().to_string()))?;
+        String::from_utf8(contents.clone())
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    fn file_size(&self, path: &Path) -> io::Result<u64> {
+        self.files
+            .get(&normalize_path(path))
+            .map(|contents| contents.len() as u64)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, path.display().to_string()))
+    }
+
+    fn exists(&self, path: &Path) -> bool {
+        let path = normalize_path(path);
+        self.files.contains_key(&path) || self.files.keys().any(|file| file.starts_with(&path))
+    }
+
+    fn canonicalize(&self, path: &Path) -> io::Result<PathBuf> {
+        if self.exists(path) {
+            Ok(normalize_path(path))
+        } else {
+            Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                path.display().to_string(),
+            ))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_memory_file_system() {
+        let mut vfs = MemoryFileSystem::new();
+        vfs.insert("./docs/guide.md", "# Guide");
+        vfs.insert("README.md", "[Guide](docs/guide.md)");
+
+        assert!(vfs.exists(Path::new("docs/../README.md")));
+        assert!(vfs.exists(Path::new("docs")));
+        assert!(!vfs.exists(Path::new("doc")));
+        assert_eq!(
+            vfs.read_to_string(Path::new("docs/guide.md")).unwrap(),
+            "# Guide"
+        );
+        assert_eq!(vfs.files(Path::new("docs")).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_files_skipping() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let root = temp_dir.path();
+        fs::create_dir_all(root.join("node_modules/pkg")).unwrap();
+        fs::write(root.join("node_modules/pkg/README.md"), "").unwrap();
+        fs::write(root.join("README.md"), "").unwrap();
+        let skip = |path: &Path, is_dir: bool| is_dir && path.ends_with("node_modules");
+
+        assert_eq!(
+            OsFileSystem.files_skipping(root, &skip).unwrap(),
+            vec![root.join("README.md")]
+        );
+
+        let mut vfs = MemoryFileSystem::new();
+        vfs.insert("docs/node_modules/pkg/README.md", "");
+        vfs.insert("docs/README.md", "");
+        assert_eq!(
+            vfs.files_skipping(Path::new("./docs"), &skip).unwrap(),
+            vec![PathBuf::from("docs/README.md")]
+        );
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_symlink_loops() {
+        use std::os::unix::fs::symlink;
+
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let root = temp_dir.path();
+        fs::write(
+            root.join("README.md"),
+            "[Loop](./a.md) [Dir](./loop/README.md)",
+        )
+        .unwrap();
+        symlink(root.join("b.md"), root.join("a.md")).unwrap();
+        symlink(root.join("a.md"), root.join("b.md")).unwrap();
+        symlink(root, root.join("loop")).unwrap();
+
+        // Symlinks are not followed while walking, so loops end the walk
+        // instead of hanging it.
+        assert_eq!(
+            OsFileSystem.files(root).unwrap(),
+            vec![root.join("README.md")]
+        );
+        assert!(!OsFileSystem.exists(&root.join("a.md")));
+        assert!(OsFileSystem.canonicalize(&root.join("a.md")).is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "archive")]
+    fn test_from_tar() {
+        let mut builder = tar::Builder::new(Vec::new());
+        let contents = b"[Missing](./missing.md)";
+        let mut header = tar::Header::new_gnu();
+        header.set_size(contents.len() as u64);
+        header.set_cksum();
+        builder
+            .append_data(&mut header, "docs/index.md", &contents[..])
+            .unwrap();
+        let archive = builder.into_inner().unwrap();
+
+        let vfs = MemoryFileSystem::from_tar(&archive[..]).unwrap();
+        assert_eq!(vfs.len(), 1);
+        assert!(vfs.exists(Path::new("docs/index.md")));
+    }
+}
@@ -0,0 +1,34 @@
+use std::fs;
+use std::process::{Command, Output};
+use tempfile::TempDir;
+
+fn check(dir: &TempDir, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_doclink-checker"))
+        .current_dir(dir.path())
+        .args(["check", "-p", ".", "--summary-line", "off"])
+        .args(args)
+        .env("XDG_CACHE_HOME", dir.path().join(".cache"))
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn max_findings_caps_security_findings() {
+    let temp_dir = TempDir::new().unwrap();
+    fs::write(
+        temp_dir.path().join("README.md"),
+        "[a](javascript:alert(1))\n[b](missing.md)\n[c](https://user:pw@example.com/)\n",
+    )
+    .unwrap();
+
+    let output = check(
+        &temp_dir,
+        &["--security", "--max-findings", "1", "--format", "csv"],
+    );
+    assert_eq!(output.status.code(), Some(1));
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let rows: Vec<&str> = stdout.lines().skip(1).collect();
+    assert_eq!(rows.len(), 1, "{}", stdout);
+    assert!(rows[0].contains("javascript:alert(1)"), "{}", stdout);
+}
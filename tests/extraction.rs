@@ -0,0 +1,277 @@
+use doclink_checker::{LinkAnalyzer, LinkSyntax};
+use proptest::prelude::*;
+use std::fmt::Write;
+
+/// `(line, syntax, target, title)` of a link.
+type Link = (usize, &'static str, String, Option<String>);
+
+/// Generated Markdown with the links it was written with, so what the
+/// extractor should find is known without parsing the Markdown again.
+#[derive(Debug, Clone)]
+struct Document {
+    markdown: String,
+    links: Vec<Link>,
+}
+
+/// A piece of a generated paragraph line.
+#[derive(Debug, Clone)]
+enum Segment {
+    Word(String),
+    Inline {
+        image: bool,
+        text: String,
+        destination: String,
+        title: Option<String>,
+    },
+    /// `[text][ref<n>]`, written in `uppercase` to exercise case folding.
+    Reference {
+        text: String,
+        label: usize,
+        uppercase: bool,
+    },
+    /// `[ref<n>][]`
+    Collapsed(usize),
+    /// An inline `<!-- -->` comment hiding a link.
+    Comment(String),
+}
+
+fn word() -> impl Strategy<Value = String> {
+    "[a-z][a-z0-9]{0,7}"
+}
+
+/// Link text, sometimes hard-wrapped onto the next line.
+fn text() -> impl Strategy<Value = String> {
+    prop::collection::vec((word(), any::<bool>()), 1..4).prop_map(|words| {
+        let mut text = "w".to_string();
+        for (i, (word, wrap)) in words.iter().enumerate() {
+            if i > 0 {
+                text.push(if *wrap { '\n' } else { ' ' });
+            }
+            text.push_str(word);
+        }
+        text
+    })
+}
+
+fn destination() -> impl Strategy<Value = String> {
+    prop_oneof![
+        "(\\.\\./|\\./)?[a-z0-9_-]{1,8}(/[a-z0-9_-]{1,8}){0,2}\\.md(#[a-z0-9-]{1,8})?",
+        "#[a-z0-9-]{1,10}",
+        "https://[a-z]{1,8}\\.com(/[a-z0-9_-]{1,8}){0,2}",
+        "[a-z]{1,6} [a-z]{1,6}\\.md",
+    ]
+}
+
+fn title() -> impl Strategy<Value = Option<String>> {
+    prop::option::of("[A-Za-z][A-Za-z ]{0,10}")
+}
+
+fn segment(labels: usize) -> impl Strategy<Value = Segment> {
+    // Labels past `labels` are undefined, which neither parser treats as a link.
+    let label = 0..labels + 2;
+    prop_oneof![
+        3 => word().prop_map(Segment::Word),
+        3 => (any::<bool>(), text(), destination(), title()).prop_map(
+            |(image, text, destination, title)| Segment::Inline {
+                image,
+                text,
+                destination,
+                title,
+            }
+        ),
+        2 => (text(), label.clone(), any::<bool>()).prop_map(|(text, label, uppercase)| {
+            Segment::Reference {
+                text,
+                label,
+                uppercase,
+            }
+        }),
+        1 => label.prop_map(Segment::Collapsed),
+        1 => destination().prop_map(Segment::Comment),
+    ]
+}
+
+/// Writes a destination the way an author would: in `<>` if it has spaces.
+fn written(destination: &str) -> String {
+    if destination.contains(' ') {
+        format!("<{}>", destination)
+    } else {
+        destination.to_string()
+    }
+}
+
+fn render(segment: &Segment) -> String {
+    match segment {
+        Segment::Word(word) => word.clone(),
+        Segment::Inline {
+            image,
+            text,
+            destination,
+            title,
+        } => format!(
+            "{}[{}]({}{})",
+            if *image { "!" } else { "" },
+            text,
+            written(destination),
+            title
+                .as_ref()
+                .map(|title| format!(" \"{}\"", title))
+                .unwrap_or_default()
+        ),
+        Segment::Reference {
+            text,
+            label,
+            uppercase,
+        } => {
+            let label = format!("ref{}", label);
+            if *uppercase {
+                format!("[{}][{}]", text, label.to_uppercase())
+            } else {
+                format!("[{}][{}]", text, label)
+            }
+        }
+        Segment::Collapsed(label) => format!("[ref{}][]", label),
+        Segment::Comment(destination) => format!("<!-- [hidden]({}) -->", written(destination)),
+    }
+}
+
+/// Markdown made of paragraphs of links and words followed by reference
+/// definitions, restricted to syntax the extractor claims to support.
+fn document() -> impl Strategy<Value = Document> {
+    prop::collection::vec((destination(), title()), 0..4).prop_flat_map(|definitions| {
+        let labels = definitions.len();
+        let line = (word(), prop::collection::vec(segment(labels), 0..5));
+        prop::collection::vec(line, 1..6).prop_map(move |lines| {
+            let mut markdown = String::new();
+            let mut links = Vec::new();
+            let mut line_number = 1;
+            for (first, segments) in lines {
+                // Lines start with a word so a comment never opens an HTML block.
+                markdown.push_str(&first);
+                for segment in &segments {
+                    if let Some((syntax, target, title)) = expected(segment, &definitions) {
+                        links.push((line_number, syntax, target, title));
+                    }
+                    let rendered = render(segment);
+                    line_number += rendered.matches('\n').count();
+                    write!(markdown, " {}", rendered).unwrap();
+                }
+                markdown.push_str("\n\n");
+                line_number += 2;
+            }
+            for (label, (destination, title)) in definitions.iter().enumerate() {
+                write!(markdown, "[ref{}]: {}", label, written(destination)).unwrap();
+                if let Some(title) = title {
+                    write!(markdown, " \"{}\"", title).unwrap();
+                }
+                markdown.push_str("\n\n");
+            }
+            links.sort();
+            Document { markdown, links }
+        })
+    })
+}
+
+/// `(syntax, target, title)` of the link `segment` writes, if it is one:
+/// references to undefined labels and links inside comments are not.
+fn expected(
+    segment: &Segment,
+    definitions: &[(String, Option<String>)],
+) -> Option<(&'static str, String, Option<String>)> {
+    match segment {
+        Segment::Inline {
+            image,
+            destination,
+            title,
+            ..
+        } => Some((
+            if *image { "image" } else { "inline" },
+            destination.clone(),
+            title.clone(),
+        )),
+        Segment::Reference { label, .. } | Segment::Collapsed(label) => {
+            let (destination, title) = definitions.get(*label)?;
+            Some(("reference", destination.clone(), title.clone()))
+        }
+        Segment::Word(_) | Segment::Comment(_) => None,
+    }
+}
+
+fn extracted_links(markdown: &str) -> Vec<Link> {
+    let mut links: Vec<_> = LinkAnalyzer::extract_links_detailed(markdown)
+        .into_iter()
+        .map(|link| {
+            let syntax = match link.syntax {
+                LinkSyntax::Inline => "inline",
+                LinkSyntax::Image => "image",
+                LinkSyntax::Reference => "reference",
+                _ => "other",
+            };
+            (link.line_number, syntax, link.target, link.title)
+        })
+        .collect();
+    links.sort();
+    links
+}
+
+proptest! {
+    #[test]
+    fn extraction_finds_generated_links(document in document()) {
+        prop_assert_eq!(
+            extracted_links(&document.markdown),
+            document.links,
+            "{}",
+            document.markdown
+        );
+    }
+
+    #[test]
+    fn extraction_never_panics(markdown in "\\PC{0,200}") {
+        for link in LinkAnalyzer::extract_links_detailed(&markdown) {
+            prop_assert!(markdown.get(link.span.clone()).is_some());
+        }
+    }
+}
+
+/// Every link extracted from the files in `tests/corpus`, one per line, so
+/// any change in what the parser finds shows up as a snapshot diff. Run
+/// `cargo insta review` (or `INSTA_UPDATE=always cargo test`) to accept one.
+#[test]
+fn corpus_snapshots() {
+    insta::glob!("corpus/*.md", |path| {
+        let content = std::fs::read_to_string(path).unwrap();
+        let mut snapshot = String::new();
+
+        for link in LinkAnalyzer::extract_links_detailed(&content) {
+            write!(
+                snapshot,
+                "{}:{}:{:?} {:?} {:?} -> {:?}",
+                link.line_number, link.column, link.span, link.syntax, link.text, link.target
+            )
+            .unwrap();
+            if let Some(title) = &link.title {
+                write!(snapshot, " title={:?}", title).unwrap();
+            }
+            if let Some(label) = &link.reference_label {
+                write!(snapshot, " label={:?}", label).unwrap();
+            }
+            snapshot.push('\n');
+        }
+        for (text, target, line_number) in LinkAnalyzer::extract_commented_links(&content) {
+            writeln!(
+                snapshot,
+                "{}: commented {:?} -> {:?}",
+                line_number, text, target
+            )
+            .unwrap();
+        }
+        for (text, target, line_number) in LinkAnalyzer::extract_code_links(&content) {
+            writeln!(snapshot, "{}: code {:?} -> {:?}", line_number, text, target).unwrap();
+        }
+        for (text, target, line_number) in LinkAnalyzer::extract_wiki_links(&content) {
+            writeln!(snapshot, "{}: wiki {:?} -> {:?}", line_number, text, target).unwrap();
+        }
+
+        insta::assert_snapshot!(snapshot);
+    });
+}
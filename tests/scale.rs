@@ -0,0 +1,102 @@
+use doclink_checker::fixture::{Fixture, FixtureSpec};
+use doclink_checker::LinkAnalyzer;
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+
+fn spec(documents: usize, seed: u64) -> FixtureSpec {
+    FixtureSpec {
+        documents,
+        broken_ratio: 0.1,
+        seed,
+        ..FixtureSpec::default()
+    }
+}
+
+/// `(document relative to root, line, target)` of every broken link.
+fn broken_links(analyzer: &LinkAnalyzer, root: &Path) -> Vec<(PathBuf, usize, String)> {
+    let mut broken: Vec<_> = analyzer
+        .find_broken_links()
+        .into_iter()
+        .map(|broken| {
+            let link = broken.link;
+            let path = link.file_path.strip_prefix(root).unwrap().to_path_buf();
+            (path, link.line_number, link.target)
+        })
+        .collect();
+    broken.sort();
+    broken
+}
+
+#[test]
+fn disk_and_memory_file_systems_agree() {
+    let fixture = Fixture::generate(&spec(300, 1));
+    let temp_dir = TempDir::new().unwrap();
+    fixture.write_to(temp_dir.path()).unwrap();
+
+    let mut on_disk = LinkAnalyzer::new(temp_dir.path().to_path_buf());
+    on_disk.analyze_directory().unwrap();
+    let root = PathBuf::from("docs");
+    let mut in_memory =
+        LinkAnalyzer::new(root.clone()).with_filesystem(fixture.to_memory_file_system(&root));
+    in_memory.analyze_directory().unwrap();
+
+    let broken = broken_links(&on_disk, temp_dir.path());
+    assert_eq!(broken.len(), fixture.broken);
+    assert_eq!(broken, broken_links(&in_memory, &root));
+    assert_eq!(on_disk.link_count(), fixture.links);
+    assert_eq!(in_memory.link_count(), fixture.links);
+}
+
+#[cfg(feature = "cli")]
+#[test]
+fn parallel_check_reports_every_target() {
+    let temp_dir = TempDir::new().unwrap();
+    let fixtures: Vec<_> = (1..=3)
+        .map(|seed| {
+            let fixture = Fixture::generate(&spec(150, seed));
+            fixture
+                .write_to(&temp_dir.path().join(format!("tree-{}", seed)))
+                .unwrap();
+            fixture
+        })
+        .collect();
+
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_doclink-checker"))
+        .current_dir(temp_dir.path())
+        .args(["check", "-p", "tree-1", "-p", "tree-2", "-p", "tree-3"])
+        .args(["--jobs", "3", "--summary-line", "stdout", "--format", "csv"])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(1));
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let summary = stdout.lines().last().unwrap();
+    let files: usize = fixtures.iter().map(|fixture| fixture.files.len()).sum();
+    let links: usize = fixtures.iter().map(|fixture| fixture.links).sum();
+    let broken: usize = fixtures.iter().map(|fixture| fixture.broken).sum();
+    assert!(
+        summary.starts_with(&format!(
+            "doclink: files={} links={} broken={} ",
+            files, links, broken
+        )),
+        "{}",
+        summary
+    );
+}
+
+/// A realistic-size tree; run with `cargo test --release -- --ignored`.
+#[test]
+#[ignore]
+fn large_tree() {
+    let fixture = Fixture::generate(&FixtureSpec {
+        documents: 10_000,
+        ..FixtureSpec::default()
+    });
+    let root = PathBuf::from("docs");
+    let mut analyzer =
+        LinkAnalyzer::new(root.clone()).with_filesystem(fixture.to_memory_file_system(&root));
+    analyzer.analyze_directory().unwrap();
+
+    assert_eq!(analyzer.link_count(), fixture.links);
+    assert_eq!(analyzer.find_broken_links().len(), fixture.broken);
+}